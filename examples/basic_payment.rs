@@ -18,13 +18,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let verifier = PaymentVerifier::new(client);
 
     // Create a payment request for 0.1 ETH
-    let payment_request = PaymentRequest {
-        amount: Decimal::from_str("0.1")?,
-        currency: Currency::ETH,
-        recipient_address: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb0".to_string(),
-        required_confirmations: 12,
-        timeout_seconds: Some(1800), // 30 minutes
-    };
+    let payment_request = PaymentRequest::new(
+        Decimal::from_str("0.1")?,
+        Currency::Native,
+        "0x742D35CC6634c0532925A3b844BC9E7595F0BEb0".parse()?,
+        12,
+    )
+    .with_timeout(1800); // 30 minutes
 
     println!("Checking for payment to {}", payment_request.recipient_address);
     println!("Expected amount: {} ETH", payment_request.amount);
@@ -36,19 +36,36 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         VerificationResult::Confirmed {
             tx_hash,
             confirmations,
+            details,
         } => {
             println!("✓ Payment confirmed!");
             println!("  Transaction: {}", tx_hash);
             println!("  Confirmations: {}", confirmations);
+            println!("  Received: {} from {}", details.amount, details.from);
         }
         VerificationResult::Pending {
             tx_hash,
             confirmations,
+            ..
         } => {
             println!("⏳ Payment detected but pending confirmations");
             println!("  Transaction: {}", tx_hash);
             println!("  Confirmations: {}/{}", confirmations, payment_request.required_confirmations);
         }
+        VerificationResult::Seen { tx_hash } => {
+            println!("👀 Payment seen in the mempool, not yet mined");
+            println!("  Transaction: {}", tx_hash);
+        }
+        VerificationResult::PartiallyPaid {
+            received,
+            remaining,
+            ..
+        } => {
+            println!(
+                "⏳ Partially paid: received {}, {} remaining",
+                received, remaining
+            );
+        }
         VerificationResult::NotFound => {
             println!("✗ No matching payment found");
         }
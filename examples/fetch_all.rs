@@ -1,5 +1,5 @@
 use cryptopay::client::{AccountEndpoints, TransactionEndpoints, TokenEndpoints, GasEndpoints};
-use cryptopay::{EtherscanClient, ClientConfig};
+use cryptopay::{Address, EtherscanClient, ClientConfig};
 use std::env;
 
 #[tokio::main]
@@ -11,17 +11,17 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let config = ClientConfig::builder().api_key(api_key).build()?;
     let client = EtherscanClient::with_config(config)?;
 
-    let address = "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb0";
-    let usdt = "0xdAC17F958D2ee523a2206206994597C13D831ec7";
+    let address: Address = "0x742D35CC6634c0532925A3b844BC9E7595F0BEb0".parse()?;
+    let usdt: Address = "0xdAC17F958D2ee523a2206206994597C13D831ec7".parse()?;
 
     println!("--- Account Balance ---");
-    let balance = client.get_balance(address).await?;
+    let balance = client.get_balance(&address).await?;
     println!("Balance: {:#?}", balance);
 
     tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
 
     println!("\n--- Transaction List (Last 1) ---");
-    let txs = client.get_transactions(address, 0, 99999999, 1, 1, "desc").await?;
+    let txs = client.get_transactions(&address, 0, 99999999, 1, 1, "desc").await?;
     if let Some(tx) = txs.first() {
         println!("{:#?}", tx);
         
@@ -38,11 +38,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
 
     println!("\n--- Token Balance (USDT) ---");
-    let token_bal = client.get_token_balance(address, usdt).await?;
+    let token_bal = client.get_token_balance(&address, &usdt).await?;
     println!("{:#?}", token_bal);
 
     println!("\n--- Token Transfers (Last 1) ---");
-    let transfers = client.get_token_transfers(address, None, 0, 99999999, 1, 1, "desc").await?;
+    let transfers = client.get_token_transfers(&address, None, 0, 99999999, 1, 1, "desc").await?;
     if let Some(tf) = transfers.first() {
         println!("{:#?}", tf);
     }
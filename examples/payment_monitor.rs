@@ -23,13 +23,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .build();
 
     // Create a payment request
-    let payment_request = PaymentRequest {
-        amount: Decimal::from_str("0.1")?,
-        currency: Currency::ETH,
-        recipient_address: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb0".to_string(),
-        required_confirmations: 12,
-        timeout_seconds: Some(1800), // 30 minutes
-    };
+    let payment_request = PaymentRequest::new(
+        Decimal::from_str("0.1")?,
+        Currency::Native,
+        "0x742D35CC6634c0532925A3b844BC9E7595F0BEb0".parse()?,
+        12,
+    )
+    .with_timeout(1800); // 30 minutes
 
     println!("🔍 Monitoring payment...");
     println!("Send {} ETH to: {}", payment_request.amount, payment_request.recipient_address);
@@ -53,6 +53,15 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     println!("   Transaction: {}", tx_hash);
                     println!("   Final confirmations: {}", confirmations);
                 }
+                PaymentStatus::PartiallyPaid { received, remaining, .. } => {
+                    println!("💸 Partially paid: received {}, {} remaining", received, remaining);
+                }
+                PaymentStatus::Reorged { tx_hash } => {
+                    println!("🔀 Transaction {} was reorganized, waiting again...", tx_hash);
+                }
+                PaymentStatus::Replaced { tx_hash, replacement } => {
+                    println!("🔁 Transaction {} was replaced by {}", tx_hash, replacement);
+                }
                 PaymentStatus::Failed { reason } => {
                     println!("❌ Payment failed: {}", reason);
                 }
@@ -20,16 +20,16 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let verifier = PaymentVerifier::new(client);
 
     // Create a payment request for 100 USDT
-    let payment_request = PaymentRequest {
-        amount: Decimal::from_str("100.0")?,
-        currency: Currency::ERC20 {
-            contract_address: USDT_CONTRACT.to_string(),
+    let payment_request = PaymentRequest::new(
+        Decimal::from_str("100.0")?,
+        Currency::ERC20 {
+            contract_address: USDT_CONTRACT.parse()?,
             decimals: 6, // USDT has 6 decimals on Ethereum
         },
-        recipient_address: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb0".to_string(),
-        required_confirmations: 6, // Fewer confirmations for tokens
-        timeout_seconds: Some(3600), // 1 hour
-    };
+        "0x742D35CC6634c0532925A3b844BC9E7595F0BEb0".parse()?,
+        6, // Fewer confirmations for tokens
+    )
+    .with_timeout(3600); // 1 hour
 
     println!("Checking for USDT payment to {}", payment_request.recipient_address);
     println!("Expected amount: {} USDT", payment_request.amount);
@@ -42,19 +42,33 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         VerificationResult::Confirmed {
             tx_hash,
             confirmations,
+            details,
         } => {
             println!("✓ USDT payment confirmed!");
             println!("  Transaction: {}", tx_hash);
             println!("  Confirmations: {}", confirmations);
+            println!("  Received: {} from {}", details.amount, details.from);
         }
         VerificationResult::Pending {
             tx_hash,
             confirmations,
+            ..
         } => {
             println!("⏳ USDT payment detected but pending confirmations");
             println!("  Transaction: {}", tx_hash);
             println!("  Confirmations: {}/{}", confirmations, payment_request.required_confirmations);
         }
+        VerificationResult::Seen { tx_hash } => {
+            println!("👀 Payment seen in the mempool, not yet mined");
+            println!("  Transaction: {}", tx_hash);
+        }
+        VerificationResult::PartiallyPaid {
+            received,
+            remaining,
+            ..
+        } => {
+            println!("⏳ Partially paid: received {}, {} remaining", received, remaining);
+        }
         VerificationResult::NotFound => {
             println!("✗ No matching USDT payment found");
         }
@@ -64,13 +78,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
 
     // Demonstrate using predefined currency helpers
-    let _usdc_payment = PaymentRequest {
-        amount: Decimal::from_str("50.0")?,
-        currency: Currency::usdc(), // Use predefined USDC
-        recipient_address: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb0".to_string(),
-        required_confirmations: 6,
-        timeout_seconds: Some(3600),
-    };
+    let _usdc_payment = PaymentRequest::new(
+        Decimal::from_str("50.0")?,
+        Currency::usdc(), // Use predefined USDC
+        "0x742D35CC6634c0532925A3b844BC9E7595F0BEb0".parse()?,
+        6,
+    )
+    .with_timeout(3600);
 
     println!("\nYou can also use predefined currencies:");
     println!("- Currency::usdt()");
@@ -0,0 +1,48 @@
+/*
+ * C interface of the cryptopay library (`ffi` feature).
+ *
+ * Build: cargo rustc --release --features ffi --crate-type cdylib
+ *
+ * Payment requests, verification results, and statuses are JSON strings in
+ * the same shape as the Rust types. Functions returning a pointer return NULL
+ * on error; call cryptopay_last_error() on the same thread for the message.
+ */
+
+#ifndef CRYPTOPAY_H
+#define CRYPTOPAY_H
+
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct CryptopayClient CryptopayClient;
+
+/* Create a client; api_key may hold several comma-separated keys.
+ * Free with cryptopay_client_free(). */
+CryptopayClient *cryptopay_client_new(const char *api_key, uint64_t chain_id);
+
+/* Free a client; NULL is ignored. */
+void cryptopay_client_free(CryptopayClient *client);
+
+/* Verify a payment request (JSON) and return the VerificationResult as JSON.
+ * Blocks until the explorer has answered. Free with cryptopay_string_free(). */
+char *cryptopay_verify_payment(const CryptopayClient *client, const char *request_json);
+
+/* Return the PaymentStatus of a payment request (JSON) as JSON; poll this to
+ * follow a payment. Free with cryptopay_string_free(). */
+char *cryptopay_payment_status(const CryptopayClient *client, const char *request_json);
+
+/* Message of the last error on this thread, or NULL.
+ * Free with cryptopay_string_free(). */
+char *cryptopay_last_error(void);
+
+/* Free a string returned by this library; NULL is ignored. */
+void cryptopay_string_free(char *string);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* CRYPTOPAY_H */
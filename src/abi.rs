@@ -0,0 +1,485 @@
+//! ABI decoding of token transfers
+//!
+//! Two sources are covered: `Transfer` events in receipt logs, and the
+//! calldata (`input`) of the contract calls that usually carry payments.
+//!
+//! ERC20 and ERC721 share the `Transfer(address,address,uint256)` event; they
+//! differ only in whether the third parameter is indexed. ERC20 logs carry
+//! the amount in `data` (three topics), ERC721 logs carry the token ID as a
+//! fourth topic.
+//!
+//! Calldata decoding understands ERC20 `transfer`/`transferFrom` and the
+//! Uniswap V2-style router swaps (also used by PancakeSwap and SushiSwap).
+
+use crate::client::types::{Log, RpcReceipt};
+use crate::primitives::{Address, U256};
+use sha3::{Digest, Keccak256};
+use std::fmt;
+
+/// `Transfer(address,address,uint256)` event topic
+pub const TRANSFER_TOPIC: &str =
+    "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+
+/// What a `Transfer` event moved
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferValue {
+    /// ERC20 amount in raw units
+    Amount(U256),
+    /// ERC721 token ID
+    TokenId(U256),
+}
+
+/// A decoded `Transfer` event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedTransfer {
+    /// Contract that emitted the event
+    pub token: Address,
+    /// Sender (zero address for mints)
+    pub from: Address,
+    /// Recipient (zero address for burns)
+    pub to: Address,
+    /// Amount or token ID
+    pub value: TransferValue,
+}
+
+impl DecodedTransfer {
+    /// ERC20 amount, `None` for ERC721 transfers
+    pub fn amount(&self) -> Option<U256> {
+        match self.value {
+            TransferValue::Amount(amount) => Some(amount),
+            TransferValue::TokenId(_) => None,
+        }
+    }
+}
+
+/// Decode a `Transfer` event, `None` for other or malformed logs
+///
+/// Logs removed by a reorg are ignored.
+pub fn decode_transfer(log: &Log) -> Option<DecodedTransfer> {
+    if log.removed || !log.topics.first()?.eq_ignore_ascii_case(TRANSFER_TOPIC) {
+        return None;
+    }
+
+    let value = match log.topics.len() {
+        3 => TransferValue::Amount(word_to_u256(&log.data)?),
+        4 => TransferValue::TokenId(word_to_u256(&log.topics[3])?),
+        _ => return None,
+    };
+
+    Some(DecodedTransfer {
+        token: log.address.parse().ok()?,
+        from: word_to_address(&log.topics[1])?,
+        to: word_to_address(&log.topics[2])?,
+        value,
+    })
+}
+
+/// Decode all `Transfer` events in a receipt, in log order
+pub fn decode_transfers(receipt: &RpcReceipt) -> Vec<DecodedTransfer> {
+    receipt.logs.iter().filter_map(decode_transfer).collect()
+}
+
+/// ERC20 `transfer(address,uint256)` function selector
+pub const TRANSFER_SELECTOR: &str = "a9059cbb";
+
+/// ERC20 `transferFrom(address,address,uint256)` function selector
+pub const TRANSFER_FROM_SELECTOR: &str = "23b872dd";
+
+/// Argument layout of a router swap
+#[derive(Clone, Copy)]
+enum SwapLayout {
+    /// `(amountIn, amountOutMin, path, to, deadline)`
+    ExactInput,
+    /// `(amountOut, amountInMax, path, to, deadline)`
+    ExactOutput,
+    /// `(amountOut[Min], path, to, deadline)`, paid with native value
+    NativeInput,
+}
+
+/// Recognized router swaps: selector, signature, layout
+const SWAPS: &[(&str, &str, SwapLayout)] = &[
+    (
+        "38ed1739",
+        "swapExactTokensForTokens(uint256,uint256,address[],address,uint256)",
+        SwapLayout::ExactInput,
+    ),
+    (
+        "8803dbee",
+        "swapTokensForExactTokens(uint256,uint256,address[],address,uint256)",
+        SwapLayout::ExactOutput,
+    ),
+    (
+        "7ff36ab5",
+        "swapExactETHForTokens(uint256,address[],address,uint256)",
+        SwapLayout::NativeInput,
+    ),
+    (
+        "4a25d94a",
+        "swapTokensForExactETH(uint256,uint256,address[],address,uint256)",
+        SwapLayout::ExactOutput,
+    ),
+    (
+        "18cbafe5",
+        "swapExactTokensForETH(uint256,uint256,address[],address,uint256)",
+        SwapLayout::ExactInput,
+    ),
+    (
+        "fb3bdb41",
+        "swapETHForExactTokens(uint256,address[],address,uint256)",
+        SwapLayout::NativeInput,
+    ),
+];
+
+/// A decoded contract call
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodedCall {
+    /// ERC20 `transfer(to, amount)`; the token is the transaction's `to`
+    Transfer {
+        /// Recipient
+        to: Address,
+        /// Amount in raw units
+        amount: U256,
+    },
+
+    /// ERC20 `transferFrom(from, to, amount)`; the token is the transaction's `to`
+    TransferFrom {
+        /// Address the tokens are taken from
+        from: Address,
+        /// Recipient
+        to: Address,
+        /// Amount in raw units
+        amount: U256,
+    },
+
+    /// Router swap delivering the last token of `path` to `to`
+    Swap {
+        /// Router method name, e.g. `swapExactETHForTokens`
+        method: &'static str,
+        /// Input amount, or the maximum input for exact-output swaps
+        /// (`None` when paid with native value)
+        amount_in: Option<U256>,
+        /// Output amount, or the minimum output for exact-input swaps
+        amount_out: U256,
+        /// Token route; the first element is spent, the last received
+        path: Vec<Address>,
+        /// Recipient of the output tokens
+        to: Address,
+    },
+}
+
+impl DecodedCall {
+    /// Name of the called method
+    pub fn method(&self) -> &'static str {
+        match self {
+            DecodedCall::Transfer { .. } => "transfer",
+            DecodedCall::TransferFrom { .. } => "transferFrom",
+            DecodedCall::Swap { method, .. } => method,
+        }
+    }
+
+    /// Address receiving the tokens
+    pub fn recipient(&self) -> &Address {
+        match self {
+            DecodedCall::Transfer { to, .. }
+            | DecodedCall::TransferFrom { to, .. }
+            | DecodedCall::Swap { to, .. } => to,
+        }
+    }
+}
+
+impl fmt::Display for DecodedCall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodedCall::Transfer { to, amount } => {
+                write!(f, "transfer(to: {}, amount: {})", to, amount)
+            }
+            DecodedCall::TransferFrom { from, to, amount } => write!(
+                f,
+                "transferFrom(from: {}, to: {}, amount: {})",
+                from, to, amount
+            ),
+            DecodedCall::Swap {
+                method,
+                amount_in,
+                amount_out,
+                path,
+                to,
+            } => {
+                write!(f, "{}(", method)?;
+                if let Some(amount_in) = amount_in {
+                    write!(f, "amount_in: {}, ", amount_in)?;
+                }
+                let path: Vec<&str> = path.iter().map(Address::as_str).collect();
+                write!(
+                    f,
+                    "amount_out: {}, path: [{}], to: {})",
+                    amount_out,
+                    path.join(", "),
+                    to
+                )
+            }
+        }
+    }
+}
+
+/// Decode transaction calldata, `None` for unrecognized or malformed input
+///
+/// # Example
+/// ```
+/// # use cryptopay::abi::{decode_input, DecodedCall};
+/// let input = format!(
+///     "0xa9059cbb{:0>64}{:064x}",
+///     "742d35cc6634c0532925a3b844bc9e7595f0beb0", 1_000_000
+/// );
+/// let call = decode_input(&input).unwrap();
+/// assert_eq!(call.method(), "transfer");
+/// assert_eq!(
+///     call.to_string(),
+///     "transfer(to: 0x742d35cc6634c0532925a3b844bc9e7595f0beb0, amount: 1000000)"
+/// );
+/// ```
+pub fn decode_input(input: &str) -> Option<DecodedCall> {
+    let input = input.strip_prefix("0x").unwrap_or(input);
+    let selector = input.get(..8)?.to_ascii_lowercase();
+    let args = Args(&input[8..]);
+
+    match selector.as_str() {
+        TRANSFER_SELECTOR => Some(DecodedCall::Transfer {
+            to: args.address(0)?,
+            amount: args.uint(1)?,
+        }),
+        TRANSFER_FROM_SELECTOR => Some(DecodedCall::TransferFrom {
+            from: args.address(0)?,
+            to: args.address(1)?,
+            amount: args.uint(2)?,
+        }),
+        selector => {
+            let (_, signature, layout) = SWAPS.iter().find(|(s, ..)| *s == selector)?;
+            let method = &signature[..signature.find('(')?];
+            let (amount_in, amount_out, path, to) = match layout {
+                SwapLayout::ExactInput => (Some(args.uint(0)?), args.uint(1)?, 2, 3),
+                SwapLayout::ExactOutput => (Some(args.uint(1)?), args.uint(0)?, 2, 3),
+                SwapLayout::NativeInput => (None, args.uint(0)?, 1, 2),
+            };
+            Some(DecodedCall::Swap {
+                method,
+                amount_in,
+                amount_out,
+                path: args.address_array(path)?,
+                to: args.address(to)?,
+            })
+        }
+    }
+}
+
+/// Four-byte function selector of a signature, as 8 lowercase hex digits
+pub fn selector(signature: &str) -> String {
+    let hash = Keccak256::digest(signature.as_bytes());
+    hash[..4]
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// ABI-encoded call arguments (hex, without selector)
+struct Args<'a>(&'a str);
+
+impl Args<'_> {
+    fn word(&self, index: usize) -> Option<&str> {
+        self.0.get(index * 64..(index + 1) * 64)
+    }
+
+    fn uint(&self, index: usize) -> Option<U256> {
+        word_to_u256(self.word(index)?)
+    }
+
+    fn address(&self, index: usize) -> Option<Address> {
+        word_to_address(self.word(index)?)
+    }
+
+    /// Dynamic `address[]` whose offset is stored at `index`
+    fn address_array(&self, index: usize) -> Option<Vec<Address>> {
+        let offset = self.small_uint(index)?;
+        if offset % 32 != 0 {
+            return None;
+        }
+        let start = offset / 32;
+        let len = self.small_uint(start)?;
+        // Bound the length by the words actually present
+        if len > self.0.len() / 64 {
+            return None;
+        }
+        (0..len).map(|i| self.address(start + 1 + i)).collect()
+    }
+
+    fn small_uint(&self, index: usize) -> Option<usize> {
+        let value = self.uint(index)?;
+        if value.bits() > 32 {
+            return None;
+        }
+        Some(value.as_usize())
+    }
+}
+
+/// Parse a 32-byte ABI word as an unsigned integer
+pub fn word_to_u256(word: &str) -> Option<U256> {
+    let digits = word.strip_prefix("0x").unwrap_or(word);
+    if digits.is_empty() || digits.len() > 64 {
+        return None;
+    }
+    U256::from_str_radix(digits, 16).ok()
+}
+
+/// Decode the return data of a `string` getter such as `name()` or `symbol()`
+///
+/// Besides ABI-encoded strings, accepts the right-padded `bytes32` some older
+/// tokens (e.g. MKR) return. `None` for empty or malformed data.
+pub fn decode_string(output: &str) -> Option<String> {
+    let data = output.strip_prefix("0x").unwrap_or(output);
+    let bytes = if data.len() == 64 {
+        // bytes32: the text padded with zero bytes
+        let bytes = hex::decode(data).ok()?;
+        let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+        bytes[..end].to_vec()
+    } else {
+        let args = Args(data);
+        let offset = args.small_uint(0)?;
+        if offset % 32 != 0 {
+            return None;
+        }
+        let len = args.small_uint(offset / 32)?;
+        let start = (offset + 32) * 2;
+        hex::decode(data.get(start..start.checked_add(len.checked_mul(2)?)?)?).ok()?
+    };
+    String::from_utf8(bytes)
+        .ok()
+        .filter(|text| !text.is_empty())
+}
+
+/// Encode an address as a 32-byte ABI word (64 hex digits, no `0x`)
+pub fn address_to_word(address: &Address) -> String {
+    format!("{:0>64}", address.as_str().trim_start_matches("0x"))
+}
+
+/// Extract the address stored in the low 20 bytes of a 32-byte ABI word
+pub fn word_to_address(word: &str) -> Option<Address> {
+    let digits = word.strip_prefix("0x").unwrap_or(word);
+    if digits.len() != 64 || !digits[..24].bytes().all(|b| b == b'0') {
+        return None;
+    }
+    format!("0x{}", &digits[24..]).parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log(topics: &[String], data: &str) -> Log {
+        Log {
+            address: "0xdAC17F958D2ee523a2206206994597C13D831ec7".to_string(),
+            topics: topics.to_vec(),
+            data: data.to_string(),
+            block_number: "0x10".to_string(),
+            transaction_hash: format!("0x{:064x}", 1),
+            transaction_index: "0x0".to_string(),
+            block_hash: format!("0x{:064x}", 2),
+            log_index: "0x0".to_string(),
+            removed: false,
+        }
+    }
+
+    #[test]
+    fn test_decode_transfer() {
+        let from = format!("0x{:064x}", 0xa);
+        let to = format!("0x{:064x}", 0xb);
+
+        let erc20 = log(
+            &[TRANSFER_TOPIC.to_string(), from.clone(), to.clone()],
+            &format!("0x{:064x}", 1_000_000u64),
+        );
+        let transfer = decode_transfer(&erc20).unwrap();
+        assert_eq!(transfer.token, "0xdac17f958d2ee523a2206206994597c13d831ec7");
+        assert_eq!(transfer.from, format!("0x{:040x}", 0xa));
+        assert_eq!(transfer.to, format!("0x{:040x}", 0xb));
+        assert_eq!(transfer.amount(), Some(U256::from(1_000_000u64)));
+
+        let erc721 = log(
+            &[
+                TRANSFER_TOPIC.to_string(),
+                from.clone(),
+                to.clone(),
+                format!("0x{:064x}", 42),
+            ],
+            "0x",
+        );
+        assert_eq!(
+            decode_transfer(&erc721).unwrap().value,
+            TransferValue::TokenId(U256::from(42))
+        );
+
+        // Other events, reorged logs, and malformed topics are skipped
+        let approval = log(&[format!("0x{:064x}", 3), from.clone(), to.clone()], "0x01");
+        assert!(decode_transfer(&approval).is_none());
+        let mut removed = erc20.clone();
+        removed.removed = true;
+        assert!(decode_transfer(&removed).is_none());
+        let truncated = log(
+            &[TRANSFER_TOPIC.to_string(), from, "0x0b".to_string()],
+            "0x01",
+        );
+        assert!(decode_transfer(&truncated).is_none());
+    }
+
+    #[test]
+    fn test_decode_input() {
+        assert_eq!(selector("transfer(address,uint256)"), TRANSFER_SELECTOR);
+        assert_eq!(
+            selector("transferFrom(address,address,uint256)"),
+            TRANSFER_FROM_SELECTOR
+        );
+        for (selector_hex, signature, _) in SWAPS {
+            assert_eq!(selector(signature), *selector_hex, "{}", signature);
+        }
+
+        let word = |value: u64| format!("{:064x}", value);
+        let from = format!("0x{:040x}", 0xa);
+        let to = format!("0x{:040x}", 0xb);
+
+        let transfer_from = format!("0x23b872dd{}{}{}", word(0xa), word(0xb), word(500));
+        assert_eq!(
+            decode_input(&transfer_from),
+            Some(DecodedCall::TransferFrom {
+                from: from.parse().unwrap(),
+                to: to.parse().unwrap(),
+                amount: U256::from(500),
+            })
+        );
+
+        // swapExactETHForTokens(amountOutMin, path = [0xc, 0xd], to = 0xb, deadline)
+        let swap = format!(
+            "0x7ff36ab5{}{}{}{}{}{}{}",
+            word(900),
+            word(0x80),
+            word(0xb),
+            word(1_700_000_000),
+            word(2),
+            word(0xc),
+            word(0xd)
+        );
+        let call = decode_input(&swap).unwrap();
+        assert_eq!(call.method(), "swapExactETHForTokens");
+        assert_eq!(call.recipient(), &to);
+        assert_eq!(
+            call.to_string(),
+            format!(
+                "swapExactETHForTokens(amount_out: 900, path: [0x{:040x}, 0x{:040x}], to: {})",
+                0xc, 0xd, to
+            )
+        );
+
+        assert!(decode_input("0xa9059cbb").is_none());
+        assert!(decode_input(&format!("0x095ea7b3{}{}", word(0xb), word(1))).is_none());
+        // Array length pointing past the calldata
+        assert!(decode_input(&swap.replace(&word(2), &word(1000))).is_none());
+    }
+}
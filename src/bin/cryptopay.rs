@@ -0,0 +1,367 @@
+//! `cryptopay` command-line tool (`cli` feature)
+//!
+//! ```text
+//! cryptopay verify  --to 0x742d... --amount 0.1 [--currency usdt] [--tx 0xabc...]
+//! cryptopay monitor --to 0x742d... --amount 0.1 --confirmations 12
+//! cryptopay balance 0x742d...
+//! cryptopay txs 0x742d... --limit 20
+//! cryptopay daemon --config cryptopay.toml
+//! ```
+//!
+//! API keys come from `--api-key` or `ETHERSCAN_API_KEYS`.
+
+use clap::{Args, Parser, Subcommand};
+use cryptopay::client::endpoints::AccountEndpoints;
+use cryptopay::gateway::Daemon;
+use cryptopay::{
+    Address, BscScanClient, Chain, ClientConfig, Currency, Error, PaymentMonitor, PaymentRequest,
+    PaymentStatus, PaymentVerifier, Result, TxHash, VerificationResult,
+};
+use rust_decimal::Decimal;
+use std::path::PathBuf;
+use std::time::Duration;
+
+#[derive(Parser)]
+#[command(
+    name = "cryptopay",
+    version,
+    about = "Verify and monitor on-chain payments"
+)]
+struct Cli {
+    /// Explorer API key(s), comma-separated
+    #[arg(
+        long,
+        env = "ETHERSCAN_API_KEYS",
+        global = true,
+        hide_env_values = true
+    )]
+    api_key: Option<String>,
+
+    /// Chain ID
+    #[arg(long, env = "ETHERSCAN_CHAIN_ID", global = true, default_value_t = 1)]
+    chain: u64,
+
+    /// Print JSON instead of text
+    #[arg(long, global = true)]
+    json: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Verify a payment once
+    Verify {
+        #[command(flatten)]
+        request: RequestArgs,
+        /// Check this transaction instead of scanning the recipient's history
+        #[arg(long)]
+        tx: Option<TxHash>,
+    },
+    /// Poll a payment until it is confirmed, failed, or expired
+    Monitor {
+        #[command(flatten)]
+        request: RequestArgs,
+        /// Seconds between checks
+        #[arg(long, default_value_t = 15)]
+        interval: u64,
+    },
+    /// Show the native balance of an address
+    Balance {
+        /// Address to query
+        address: Address,
+    },
+    /// List the latest transactions of an address
+    Txs {
+        /// Address to query
+        address: Address,
+        /// Number of transactions
+        #[arg(long, default_value_t = 10)]
+        limit: u32,
+    },
+    /// Run the payment gateway from a TOML configuration file
+    Daemon {
+        /// TOML configuration file
+        #[arg(long, short)]
+        config: PathBuf,
+    },
+}
+
+/// Payment request given as flags or as a JSON file
+#[derive(Args)]
+struct RequestArgs {
+    /// JSON file containing a `PaymentRequest` (overrides the other flags)
+    #[arg(long, conflicts_with_all = ["to", "amount"])]
+    request: Option<PathBuf>,
+    /// Recipient address
+    #[arg(long, required_unless_present = "request")]
+    to: Option<Address>,
+    /// Amount in whole units (e.g. 0.1)
+    #[arg(long, required_unless_present = "request")]
+    amount: Option<Decimal>,
+    /// `native`, `usdt`, `usdc`, `dai`, or `<contract>:<decimals>`
+    #[arg(long, default_value = "native")]
+    currency: String,
+    /// Required confirmations
+    #[arg(long, default_value_t = 12)]
+    confirmations: u64,
+}
+
+impl RequestArgs {
+    fn into_request(self, chain: Chain) -> Result<PaymentRequest> {
+        if let Some(path) = self.request {
+            let json = std::fs::read_to_string(path)?;
+            return Ok(serde_json::from_str(&json)?);
+        }
+
+        // clap enforces `to` and `amount` when no request file is given
+        let (Some(to), Some(amount)) = (self.to, self.amount) else {
+            return Err(Error::InvalidConfig(
+                "--to and --amount are required".to_string(),
+            ));
+        };
+        let currency = parse_currency(&self.currency, chain)?;
+        Ok(PaymentRequest::new(
+            amount,
+            currency,
+            to,
+            self.confirmations,
+        ))
+    }
+}
+
+/// Parse a currency name or `<contract>:<decimals>` pair
+fn parse_currency(value: &str, chain: Chain) -> Result<Currency> {
+    let stablecoin = match value.to_ascii_lowercase().as_str() {
+        "native" | "eth" | "bnb" | "pol" => return Ok(Currency::Native),
+        "usdt" => Currency::usdt_on(chain),
+        "usdc" => Currency::usdc_on(chain),
+        "dai" => Currency::dai_on(chain),
+        _ => {
+            let (contract, decimals) = value
+                .split_once(':')
+                .ok_or_else(|| Error::InvalidConfig(format!("unknown currency: {}", value)))?;
+            let decimals = decimals
+                .parse()
+                .map_err(|_| Error::InvalidConfig(format!("invalid decimals: {}", decimals)))?;
+            let contract: Address = contract.parse()?;
+            return Ok(Currency::erc20(contract, decimals));
+        }
+    };
+
+    stablecoin.ok_or_else(|| {
+        Error::InvalidConfig(format!(
+            "{} is not available on {}",
+            value.to_uppercase(),
+            chain
+        ))
+    })
+}
+
+#[tokio::main]
+async fn main() {
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info")),
+        )
+        .with_writer(std::io::stderr)
+        .init();
+
+    if let Err(e) = run(Cli::parse()).await {
+        eprintln!("error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+async fn run(cli: Cli) -> Result<()> {
+    let chain = Chain::from(cli.chain);
+    let json = cli.json;
+
+    match cli.command {
+        Command::Verify { request, tx } => {
+            let request = request.into_request(chain)?;
+            let verifier = PaymentVerifier::new(client(cli.api_key, cli.chain)?);
+            let result = match tx {
+                Some(tx) => verifier.verify_tx(&tx, &request).await?,
+                None => verifier.verify_payment(&request).await?,
+            };
+            print(json, &result, || describe_result(&result));
+        }
+        Command::Monitor { request, interval } => {
+            let request = request.into_request(chain)?;
+            let monitor = PaymentMonitor::new(
+                client(cli.api_key, cli.chain)?,
+                Duration::from_secs(interval),
+            );
+            monitor
+                .start_monitoring(request, move |status| {
+                    let now = chrono::Utc::now().format("%H:%M:%S");
+                    print(json, &status, || {
+                        format!("[{}] {}", now, describe_status(&status))
+                    });
+                })
+                .await?;
+        }
+        Command::Balance { address } => {
+            let balance = client(cli.api_key, cli.chain)?
+                .get_balance(&address)
+                .await?;
+            let symbol = chain.native_symbol();
+            print(json, &balance, || format!("{} {}", balance.ether(), symbol));
+        }
+        Command::Txs { address, limit } => {
+            let txs = client(cli.api_key, cli.chain)?
+                .get_transactions(&address, 0, 99_999_999, 1, limit, "desc")
+                .await?;
+            let symbol = chain.native_symbol();
+            print(json, &txs, || {
+                txs.iter()
+                    .map(|tx| {
+                        let to = tx
+                            .to
+                            .as_ref()
+                            .map_or("(contract creation)", |to| to.as_str());
+                        let status = if tx.is_successful() { "" } else { "  FAILED" };
+                        format!(
+                            "{}  #{}  {} -> {}  {} {}{}",
+                            tx.hash,
+                            tx.block_number,
+                            tx.from,
+                            to,
+                            tx.value_native(),
+                            symbol,
+                            status
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            });
+        }
+        Command::Daemon { config } => Daemon::from_file(config)?.run().await?,
+    }
+
+    Ok(())
+}
+
+fn client(api_key: Option<String>, chain_id: u64) -> Result<BscScanClient> {
+    let api_keys: Vec<String> = api_key
+        .unwrap_or_default()
+        .split(',')
+        .map(|key| key.trim().to_string())
+        .filter(|key| !key.is_empty())
+        .collect();
+    if api_keys.is_empty() {
+        return Err(Error::InvalidConfig(
+            "no API key; pass --api-key or set ETHERSCAN_API_KEYS".to_string(),
+        ));
+    }
+
+    BscScanClient::with_config(
+        ClientConfig::builder()
+            .api_keys(api_keys)
+            .chain_id(chain_id)
+            .build()?,
+    )
+}
+
+fn print<T: serde::Serialize>(json: bool, value: &T, text: impl FnOnce() -> String) {
+    if json {
+        match serde_json::to_string(value) {
+            Ok(json) => println!("{}", json),
+            Err(e) => eprintln!("error: {}", e),
+        }
+    } else {
+        println!("{}", text());
+    }
+}
+
+fn describe_result(result: &VerificationResult) -> String {
+    match result {
+        VerificationResult::NotFound => "not found".to_string(),
+        VerificationResult::Seen { tx_hash } => format!("seen in mempool: {}", tx_hash),
+        VerificationResult::Pending {
+            tx_hash,
+            confirmations,
+            details,
+        } => format!(
+            "pending: {} ({} confirmations, {} from {})",
+            tx_hash, confirmations, details.amount, details.from
+        ),
+        VerificationResult::Confirmed {
+            tx_hash,
+            confirmations,
+            details,
+        } => format!(
+            "confirmed: {} ({} confirmations, {} from {})",
+            tx_hash, confirmations, details.amount, details.from
+        ),
+        VerificationResult::PartiallyPaid {
+            received,
+            remaining,
+            ..
+        } => format!(
+            "partially paid: {} received, {} remaining",
+            received, remaining
+        ),
+        VerificationResult::Failed { reason } => format!("failed: {}", reason),
+    }
+}
+
+fn describe_status(status: &PaymentStatus) -> String {
+    match status {
+        PaymentStatus::Pending => "waiting for payment".to_string(),
+        PaymentStatus::Detected {
+            confirmations,
+            tx_hash,
+        } => format!("detected {} ({} confirmations)", tx_hash, confirmations),
+        PaymentStatus::Confirmed {
+            tx_hash,
+            confirmations,
+        } => format!("confirmed {} ({} confirmations)", tx_hash, confirmations),
+        PaymentStatus::PartiallyPaid {
+            received,
+            remaining,
+            ..
+        } => format!(
+            "partially paid: {} received, {} remaining",
+            received, remaining
+        ),
+        PaymentStatus::Reorged { tx_hash } => format!("reorged out: {}", tx_hash),
+        PaymentStatus::Replaced {
+            tx_hash,
+            replacement,
+        } => format!("{} replaced by {}", tx_hash, replacement),
+        PaymentStatus::Failed { reason } => format!("failed: {}", reason),
+        PaymentStatus::Expired => "expired".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_currency() {
+        assert_eq!(
+            parse_currency("ETH", Chain::Ethereum).unwrap(),
+            Currency::Native
+        );
+        assert_eq!(
+            parse_currency("usdt", Chain::Bsc).unwrap(),
+            Currency::usdt_on(Chain::Bsc).unwrap()
+        );
+        assert_eq!(
+            parse_currency(
+                "0x742d35cc6634c0532925a3b844bc9e7595f0beb0:6",
+                Chain::Ethereum
+            )
+            .unwrap()
+            .decimals(),
+            6
+        );
+        assert!(parse_currency("usdt", Chain::Other(999)).is_err());
+        assert!(parse_currency("doge", Chain::Ethereum).is_err());
+    }
+}
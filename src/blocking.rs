@@ -0,0 +1,331 @@
+//! Synchronous client and verifier (`blocking` feature)
+//!
+//! Wrappers for CLI tools, scripts, and codebases without an async runtime.
+//! Each [`Client`] owns a single-threaded tokio runtime that drives the
+//! async client; clones share it.
+//!
+//! These types must not be used from within an async runtime: blocking on a
+//! runtime inside another one panics. Use the async API there instead.
+//!
+//! # Example
+//! ```no_run
+//! use cryptopay::blocking::{Client, PaymentVerifier};
+//! use cryptopay::{PaymentRequest, VerificationResult};
+//!
+//! # fn main() -> cryptopay::Result<()> {
+//! let client = Client::new("api-key")?;
+//! let verifier = PaymentVerifier::new(&client);
+//!
+//! let request = PaymentRequest::eth(
+//!     "0.1".parse().unwrap(),
+//!     "0x742d35cc6634c0532925a3b844bc9e7595f0beb0".parse()?,
+//!     12,
+//! );
+//! if let VerificationResult::Confirmed { tx_hash, .. } = verifier.verify_payment(&request)? {
+//!     println!("paid in {}", tx_hash);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::client::{
+    AccountEndpoints, Balance, BscScanClient, GasEndpoints, GasOracle, InternalTransaction,
+    RpcReceipt, TokenBalance, TokenEndpoints, TokenTransfer, Transaction, TransactionEndpoints,
+};
+use crate::config::ClientConfig;
+use crate::error::Result;
+use crate::payment::{
+    self, MatchDecision, PaymentRequest, PaymentStatus, TransferRef, VerificationResult,
+};
+use crate::primitives::{Address, TxHash};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::runtime::Runtime;
+
+/// Blocking Etherscan client
+#[derive(Clone)]
+pub struct Client {
+    inner: BscScanClient,
+    runtime: Arc<Runtime>,
+}
+
+impl Client {
+    /// Create a client with the given API key
+    pub fn new(api_key: impl Into<String>) -> Result<Self> {
+        Self::with_config(ClientConfig::new(api_key))
+    }
+
+    /// Create a client with custom configuration
+    pub fn with_config(config: ClientConfig) -> Result<Self> {
+        Self::from_async(BscScanClient::with_config(config)?)
+    }
+
+    /// Wrap an async client
+    pub fn from_async(client: BscScanClient) -> Result<Self> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        Ok(Self {
+            inner: client,
+            runtime: Arc::new(runtime),
+        })
+    }
+
+    /// Underlying async client
+    pub fn inner(&self) -> &BscScanClient {
+        &self.inner
+    }
+
+    /// Run any future on the client's runtime, e.g. an async API without a blocking wrapper
+    pub fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.runtime.block_on(future)
+    }
+
+    /// Get the native balance of an address
+    pub fn get_balance(&self, address: &Address) -> Result<Balance> {
+        self.block_on(self.inner.get_balance(address))
+    }
+
+    /// Get transactions of an address, see [`AccountEndpoints::get_transactions`]
+    pub fn get_transactions(
+        &self,
+        address: &Address,
+        start_block: u64,
+        end_block: u64,
+        page: u32,
+        offset: u32,
+        sort: &str,
+    ) -> Result<Vec<Transaction>> {
+        self.block_on(self.inner.get_transactions(
+            address,
+            start_block,
+            end_block,
+            page,
+            offset,
+            sort,
+        ))
+    }
+
+    /// Get internal transactions of an address
+    pub fn get_internal_transactions(
+        &self,
+        address: &Address,
+        start_block: u64,
+        end_block: u64,
+        page: u32,
+        offset: u32,
+        sort: &str,
+    ) -> Result<Vec<InternalTransaction>> {
+        self.block_on(self.inner.get_internal_transactions(
+            address,
+            start_block,
+            end_block,
+            page,
+            offset,
+            sort,
+        ))
+    }
+
+    /// Get token transfers of an address, see [`TokenEndpoints::get_token_transfers`]
+    #[allow(clippy::too_many_arguments)]
+    pub fn get_token_transfers(
+        &self,
+        address: &Address,
+        contract_address: Option<&Address>,
+        start_block: u64,
+        end_block: u64,
+        page: u32,
+        offset: u32,
+        sort: &str,
+    ) -> Result<Vec<TokenTransfer>> {
+        self.block_on(self.inner.get_token_transfers(
+            address,
+            contract_address,
+            start_block,
+            end_block,
+            page,
+            offset,
+            sort,
+        ))
+    }
+
+    /// Get the token balance of an address
+    pub fn get_token_balance(
+        &self,
+        address: &Address,
+        contract_address: &Address,
+    ) -> Result<TokenBalance> {
+        self.block_on(self.inner.get_token_balance(address, contract_address))
+    }
+
+    /// Get a transaction by hash
+    pub fn get_transaction(&self, tx_hash: &TxHash) -> Result<Transaction> {
+        self.block_on(self.inner.get_transaction(tx_hash))
+    }
+
+    /// Get a transaction receipt
+    pub fn get_transaction_receipt(&self, tx_hash: &TxHash) -> Result<RpcReceipt> {
+        self.block_on(self.inner.get_transaction_receipt(tx_hash))
+    }
+
+    /// Get the number of confirmations of a transaction
+    pub fn get_confirmations(&self, tx_hash: &TxHash) -> Result<u64> {
+        self.block_on(self.inner.get_confirmations(tx_hash))
+    }
+
+    /// Get the confirmations of several transactions
+    pub fn get_confirmations_batch(&self, tx_hashes: &[TxHash]) -> Result<HashMap<TxHash, u64>> {
+        self.block_on(self.inner.get_confirmations_batch(tx_hashes))
+    }
+
+    /// Get the latest block number
+    pub fn get_block_number(&self) -> Result<u64> {
+        self.block_on(self.inner.get_block_number())
+    }
+
+    /// Get the gas oracle
+    pub fn get_gas_oracle(&self) -> Result<GasOracle> {
+        self.block_on(self.inner.get_gas_oracle())
+    }
+}
+
+/// Blocking payment verifier
+///
+/// Mirrors [`payment::PaymentVerifier`]; see there for the matching rules.
+pub struct PaymentVerifier {
+    inner: payment::PaymentVerifier,
+    runtime: Arc<Runtime>,
+}
+
+impl PaymentVerifier {
+    /// Create a verifier using `client`
+    pub fn new(client: &Client) -> Self {
+        Self {
+            inner: payment::PaymentVerifier::new(client.inner.clone()),
+            runtime: client.runtime.clone(),
+        }
+    }
+
+    /// Decide with `matcher` which transfers may count towards a request
+    pub fn with_matcher<F>(mut self, matcher: F) -> Self
+    where
+        F: Fn(TransferRef<'_>, &PaymentRequest) -> MatchDecision + Send + Sync + 'static,
+    {
+        self.inner = self.inner.with_matcher(matcher);
+        self
+    }
+
+    /// Report transactions passed to `verify_tx` while still in the mempool
+    pub fn accept_unconfirmed(mut self, accept: bool) -> Self {
+        self.inner = self.inner.accept_unconfirmed(accept);
+        self
+    }
+
+    /// Also scan internal transactions for native payments (default: true)
+    pub fn scan_internal_transactions(mut self, scan: bool) -> Self {
+        self.inner = self.inner.scan_internal_transactions(scan);
+        self
+    }
+
+    /// Underlying async verifier
+    pub fn inner(&self) -> &payment::PaymentVerifier {
+        &self.inner
+    }
+
+    /// Verify a payment request
+    pub fn verify_payment(&self, request: &PaymentRequest) -> Result<VerificationResult> {
+        self.runtime.block_on(self.inner.verify_payment(request))
+    }
+
+    /// Verify several payment requests, sharing explorer fetches between them
+    pub fn verify_batch(&self, requests: &[PaymentRequest]) -> Result<Vec<VerificationResult>> {
+        self.runtime.block_on(self.inner.verify_batch(requests))
+    }
+
+    /// Verify that a specific transaction pays a request
+    pub fn verify_tx(
+        &self,
+        tx_hash: &TxHash,
+        request: &PaymentRequest,
+    ) -> Result<VerificationResult> {
+        self.runtime
+            .block_on(self.inner.verify_tx(tx_hash, request))
+    }
+
+    /// Current status of a payment request
+    pub fn check_payment_status(&self, request: &PaymentRequest) -> Result<PaymentStatus> {
+        self.verify_payment(request).map(PaymentStatus::from)
+    }
+
+    /// Check confirmations for a transaction hash
+    pub fn check_confirmations(&self, tx_hash: &TxHash) -> Result<u64> {
+        self.runtime
+            .block_on(self.inner.check_confirmations(tx_hash))
+    }
+
+    /// Block until a transaction has at least `confirmations` confirmations
+    pub fn wait_for_confirmations(
+        &self,
+        tx_hash: &TxHash,
+        confirmations: u64,
+        poll_interval: Duration,
+    ) -> Result<RpcReceipt> {
+        self.runtime.block_on(self.inner.wait_for_confirmations(
+            tx_hash,
+            confirmations,
+            poll_interval,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::query_param;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    fn test_blocking_calls() {
+        // The server needs a runtime of its own; the client brings its own too
+        let server_runtime = Runtime::new().unwrap();
+        let server = server_runtime.block_on(async {
+            let server = MockServer::start().await;
+            Mock::given(query_param("action", "balance"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "status": "1", "message": "OK", "result": "1000000000000000000"
+                })))
+                .mount(&server)
+                .await;
+            Mock::given(query_param("action", "txlist"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "status": "0", "message": "No transactions found", "result": []
+                })))
+                .mount(&server)
+                .await;
+            server
+        });
+
+        let config = ClientConfig::builder()
+            .api_key("test-key")
+            .base_url(server.uri())
+            .build()
+            .unwrap();
+        let client = Client::with_config(config).unwrap();
+        let address: Address = "0x742d35cc6634c0532925a3b844bc9e7595f0beb0"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            client.get_balance(&address).unwrap().wei,
+            "1000000000000000000"
+        );
+
+        let verifier = PaymentVerifier::new(&client).scan_internal_transactions(false);
+        let request = PaymentRequest::eth(rust_decimal::Decimal::ONE, address, 12);
+        assert_eq!(
+            verifier.check_payment_status(&request).unwrap(),
+            PaymentStatus::Pending
+        );
+    }
+}
@@ -0,0 +1,362 @@
+//! Chains and per-chain stablecoin contracts
+//!
+//! The same stablecoin lives at a different address (and sometimes with
+//! different decimals) on every chain. [`StablecoinRegistry`] maps a
+//! [`Chain`] and symbol to the right [`Currency`], and catches currencies
+//! that belong to another chain than the client's.
+
+use crate::error::{Error, Result};
+use crate::payment::Currency;
+use crate::primitives::Address;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::OnceLock;
+
+/// EVM chain, identified by its chain ID
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "u64", into = "u64")]
+pub enum Chain {
+    /// Ethereum mainnet (1)
+    Ethereum,
+    /// Ethereum Sepolia testnet (11155111)
+    Sepolia,
+    /// BNB Smart Chain (56)
+    Bsc,
+    /// Polygon PoS (137)
+    Polygon,
+    /// Arbitrum One (42161)
+    Arbitrum,
+    /// Base (8453)
+    Base,
+    /// OP Mainnet (10)
+    Optimism,
+    /// Any other chain
+    Other(u64),
+}
+
+impl Chain {
+    /// Chain ID
+    pub fn id(&self) -> u64 {
+        match self {
+            Chain::Ethereum => 1,
+            Chain::Sepolia => 11155111,
+            Chain::Bsc => 56,
+            Chain::Polygon => 137,
+            Chain::Arbitrum => 42161,
+            Chain::Base => 8453,
+            Chain::Optimism => 10,
+            Chain::Other(id) => *id,
+        }
+    }
+
+    /// Human-readable chain name
+    pub fn name(&self) -> String {
+        match self {
+            Chain::Ethereum => "Ethereum".to_string(),
+            Chain::Sepolia => "Sepolia".to_string(),
+            Chain::Bsc => "BNB Smart Chain".to_string(),
+            Chain::Polygon => "Polygon".to_string(),
+            Chain::Arbitrum => "Arbitrum One".to_string(),
+            Chain::Base => "Base".to_string(),
+            Chain::Optimism => "OP Mainnet".to_string(),
+            Chain::Other(id) => format!("chain {}", id),
+        }
+    }
+
+    /// Symbol of the native currency
+    ///
+    /// Unknown chains report "ETH", as most EVM rollups use it.
+    pub fn native_symbol(&self) -> &'static str {
+        match self {
+            Chain::Bsc => "BNB",
+            Chain::Polygon => "POL",
+            _ => "ETH",
+        }
+    }
+
+    /// Decimals of the native currency
+    pub fn native_decimals(&self) -> u8 {
+        18
+    }
+
+    /// Whether validators withdraw to addresses on this chain
+    ///
+    /// True for proof-of-stake Ethereum (mainnet and Sepolia).
+    pub fn has_beacon_withdrawals(&self) -> bool {
+        matches!(self, Chain::Ethereum | Chain::Sepolia)
+    }
+
+    /// Confirmations to wait for before accepting a payment
+    ///
+    /// Reflects each chain's finality: `High` waits for Ethereum's finalized
+    /// checkpoint (two epochs), BNB Smart Chain's fast finality, or Polygon's
+    /// historical reorg depth. Rollups reorder only with their sequencer, so a
+    /// block or two suffices there. Unknown chains get the Ethereum values.
+    pub fn recommended_confirmations(&self, risk: RiskLevel) -> u64 {
+        let (low, medium, high) = match self {
+            Chain::Bsc => (3, 15, 30),
+            Chain::Polygon => (32, 128, 256),
+            Chain::Arbitrum | Chain::Base | Chain::Optimism => (1, 1, 2),
+            Chain::Ethereum | Chain::Sepolia | Chain::Other(_) => (3, 12, 64),
+        };
+
+        match risk {
+            RiskLevel::Low => low,
+            RiskLevel::Medium => medium,
+            RiskLevel::High => high,
+        }
+    }
+}
+
+/// How much a payment's reversal would hurt, for picking confirmation counts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RiskLevel {
+    /// Small amounts; a reorg is an acceptable loss
+    Low,
+    /// Typical payments
+    Medium,
+    /// High-value payments; wait for finality
+    High,
+}
+
+impl From<u64> for Chain {
+    fn from(id: u64) -> Self {
+        match id {
+            1 => Chain::Ethereum,
+            11155111 => Chain::Sepolia,
+            56 => Chain::Bsc,
+            137 => Chain::Polygon,
+            42161 => Chain::Arbitrum,
+            8453 => Chain::Base,
+            10 => Chain::Optimism,
+            id => Chain::Other(id),
+        }
+    }
+}
+
+impl From<Chain> for u64 {
+    fn from(chain: Chain) -> Self {
+        chain.id()
+    }
+}
+
+impl fmt::Display for Chain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name())
+    }
+}
+
+/// Built-in stablecoin deployments: (chain, symbol, contract, decimals)
+#[rustfmt::skip]
+const BUILTIN: &[(Chain, &str, &str, u8)] = &[
+    (Chain::Ethereum, "USDT", "0xdAC17F958D2ee523a2206206994597C13D831ec7", 6),
+    (Chain::Ethereum, "USDC", "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48", 6),
+    (Chain::Ethereum, "DAI", "0x6B175474E89094C44Da98b954EedeAC495271d0F", 18),
+    (Chain::Sepolia, "USDC", "0x1c7D4B196Cb0C7B01d743Fbc6116a902379C7238", 6),
+    (Chain::Bsc, "USDT", "0x55d398326f99059fF775485246999027B3197955", 18),
+    (Chain::Bsc, "USDC", "0x8AC76a51cc950d9822D68b83fE1Ad97B32Cd580d", 18),
+    (Chain::Bsc, "DAI", "0x1AF3F329e8BE154074D8769D1FFa4eE058B1DBc3", 18),
+    (Chain::Polygon, "USDT", "0xc2132D05D31c914a87C6611C10748AEb04B58e8F", 6),
+    (Chain::Polygon, "USDC", "0x3c499c542cEF5E3811e1192ce70d8cC03d5c3359", 6),
+    (Chain::Polygon, "DAI", "0x8f3Cf7ad23Cd3CaDbD9735AFf958023239c6A063", 18),
+    (Chain::Arbitrum, "USDT", "0xFd086bC7CD5C481DCC9C85ebE478A1C0b69FCbb9", 6),
+    (Chain::Arbitrum, "USDC", "0xaf88d065e77c8cC2239327C5EDb3A432268e5831", 6),
+    (Chain::Arbitrum, "DAI", "0xDA10009cBd5D07dd0CeCc66161FC93D7c9000da1", 18),
+    (Chain::Base, "USDT", "0xfde4C96c8593536E31F229EA8f37b2ADa2699bb2", 6),
+    (Chain::Base, "USDC", "0x833589fCD6eDb6E08f4c7C32D4f71b54bdA02913", 6),
+    (Chain::Base, "DAI", "0x50c5725949A6F0c72E6C4a641F24049A917DB0Cb", 18),
+    (Chain::Optimism, "USDT", "0x94b008aA00579c1307B0EF2c499aD98a8ce58e58", 6),
+    (Chain::Optimism, "USDC", "0x0b2C639c533813f4Aa9D7837CAf62653d097Ff85", 6),
+    (Chain::Optimism, "DAI", "0xDA10009cBd5D07dd0CeCc66161FC93D7c9000da1", 18),
+];
+
+/// Stablecoin deployed on a chain
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Stablecoin {
+    /// Chain the contract is deployed on
+    pub chain: Chain,
+    /// Token symbol (e.g. "USDT")
+    pub symbol: String,
+    /// Token contract address
+    pub contract_address: Address,
+    /// Token decimals
+    pub decimals: u8,
+}
+
+impl Stablecoin {
+    /// Currency for this deployment
+    pub fn currency(&self) -> Currency {
+        Currency::erc20(self.contract_address.clone(), self.decimals)
+    }
+}
+
+/// Stablecoin contracts by chain
+///
+/// [`StablecoinRegistry::new`] covers USDT, USDC, and DAI on Ethereum, BNB
+/// Smart Chain, Polygon, Arbitrum, Base, and Optimism (plus USDC on Sepolia);
+/// [`register`](Self::register) adds more.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StablecoinRegistry {
+    entries: Vec<Stablecoin>,
+}
+
+impl Default for StablecoinRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StablecoinRegistry {
+    /// Create a registry with the built-in deployments
+    pub fn new() -> Self {
+        Self {
+            entries: BUILTIN
+                .iter()
+                .map(|&(chain, symbol, contract_address, decimals)| Stablecoin {
+                    chain,
+                    symbol: symbol.to_string(),
+                    contract_address: Address::known(contract_address),
+                    decimals,
+                })
+                .collect(),
+        }
+    }
+
+    /// Create an empty registry
+    pub fn empty() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Shared registry with the built-in deployments
+    pub fn builtin() -> &'static Self {
+        static BUILTIN_REGISTRY: OnceLock<StablecoinRegistry> = OnceLock::new();
+        BUILTIN_REGISTRY.get_or_init(Self::new)
+    }
+
+    /// Add a deployment, replacing any existing one for the same chain and symbol
+    pub fn register(
+        mut self,
+        chain: Chain,
+        symbol: impl Into<String>,
+        contract_address: Address,
+        decimals: u8,
+    ) -> Self {
+        let symbol = symbol.into();
+        self.entries
+            .retain(|entry| !(entry.chain == chain && entry.symbol.eq_ignore_ascii_case(&symbol)));
+        self.entries.push(Stablecoin {
+            chain,
+            symbol,
+            contract_address,
+            decimals,
+        });
+        self
+    }
+
+    /// All registered deployments
+    pub fn entries(&self) -> &[Stablecoin] {
+        &self.entries
+    }
+
+    /// Look up a stablecoin by chain and symbol (case-insensitive)
+    pub fn get(&self, chain: Chain, symbol: &str) -> Option<Currency> {
+        self.entries
+            .iter()
+            .find(|entry| entry.chain == chain && entry.symbol.eq_ignore_ascii_case(symbol))
+            .map(Stablecoin::currency)
+    }
+
+    /// Chains a contract address is registered on
+    pub fn chains_of(&self, contract_address: &Address) -> Vec<Chain> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.contract_address == *contract_address)
+            .map(|entry| entry.chain)
+            .collect()
+    }
+
+    /// Check that a currency can be paid on `chain_id`
+    ///
+    /// Fails for registered contracts that are not registered on this chain
+    /// (e.g. Ethereum USDT used with a BNB Smart Chain client). Native
+    /// currency and unknown tokens always pass.
+    pub fn validate(&self, currency: &Currency, chain_id: u64) -> Result<()> {
+        let Currency::ERC20 {
+            contract_address, ..
+        } = currency
+        else {
+            return Ok(());
+        };
+
+        let chains = self.chains_of(contract_address);
+        if chains.is_empty() || chains.iter().any(|chain| chain.id() == chain_id) {
+            return Ok(());
+        }
+
+        Err(Error::ChainMismatch {
+            currency: contract_address.to_string(),
+            expected: chains
+                .iter()
+                .map(Chain::name)
+                .collect::<Vec<_>>()
+                .join(", "),
+            actual: Chain::from(chain_id).name(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stablecoin_registry() {
+        assert_eq!(Chain::from(8453), Chain::Base);
+        assert_eq!(Chain::from(7777).id(), 7777);
+
+        let registry = StablecoinRegistry::builtin();
+        let usdt = registry.get(Chain::Bsc, "usdt").unwrap();
+        assert_eq!(usdt.decimals(), 18);
+        assert_eq!(
+            registry.get(Chain::Ethereum, "USDT"),
+            Some(Currency::usdt())
+        );
+        assert!(registry.get(Chain::Sepolia, "USDT").is_none());
+
+        assert!(registry.validate(&usdt, 56).is_ok());
+        assert!(matches!(
+            registry.validate(&Currency::usdt(), 56),
+            Err(Error::ChainMismatch { .. })
+        ));
+        // DAI shares an address on Arbitrum and Optimism
+        let dai = registry.get(Chain::Arbitrum, "DAI").unwrap();
+        assert!(registry.validate(&dai, 10).is_ok());
+        assert!(registry.validate(&Currency::Native, 56).is_ok());
+        assert!(registry
+            .validate(
+                &Currency::erc20(
+                    "0x0000000000000000000000000000000000000001"
+                        .parse()
+                        .unwrap(),
+                    18
+                ),
+                56
+            )
+            .is_ok());
+
+        assert_eq!(
+            Chain::Ethereum.recommended_confirmations(RiskLevel::High),
+            64
+        );
+        assert_eq!(Chain::Base.recommended_confirmations(RiskLevel::Medium), 1);
+
+        let usdx: Address = "0x0000000000000000000000000000000000000001"
+            .parse()
+            .unwrap();
+        let custom =
+            StablecoinRegistry::empty().register(Chain::Other(7777), "USDX", usdx.clone(), 6);
+        assert_eq!(custom.chains_of(&usdx), vec![Chain::Other(7777)]);
+    }
+}
@@ -0,0 +1,404 @@
+//! Hosted checkout sessions
+//!
+//! A [`CheckoutSession`] bundles everything a checkout page needs: the
+//! [`Invoice`], a deposit address handed out by a [`DepositAddressSource`],
+//! the EIP-681 payment URI, the expiry, where to send the customer afterwards,
+//! and a signed session token that can be put in a URL.
+//!
+//! ```no_run
+//! # use cryptopay::*;
+//! # use cryptopay::checkout::{AddressPool, Checkout};
+//! # use rust_decimal::Decimal;
+//! # async fn example() -> Result<()> {
+//! let checkout = Checkout::new(
+//!     AddressPool::new(["0x742D35CC6634c0532925A3b844BC9E7595F0BEb0"]),
+//!     "session-signing-secret",
+//! )
+//! .success_url("https://shop.example/thanks")
+//! .cancel_url("https://shop.example/cart");
+//!
+//! let storage = MemoryStorage::new();
+//! let session = checkout
+//!     .create_session(|invoice| {
+//!         invoice
+//!             .currency(Currency::usdc())
+//!             .line_item(LineItem::new("T-shirt", 2, Decimal::from(15)))
+//!     })
+//!     .await?;
+//! session.save(&storage).await?;
+//!
+//! println!("Pay with {}", session.payment_uri);
+//! println!("Session: /checkout/{}", session.token);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::error::{Error, Result};
+use crate::invoice::{Invoice, InvoiceBuilder, InvoiceStatus};
+use crate::payment::PaymentMonitor;
+use crate::primitives::Address;
+use crate::storage::PaymentStorage;
+use async_trait::async_trait;
+use chrono::{DateTime, TimeZone, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+/// Metadata key under which checkout data is stored on the payment
+const METADATA_KEY: &str = "checkout";
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Source of deposit addresses for new checkout sessions
+///
+/// Implement this to derive a fresh address per session, e.g. from an HD
+/// wallet's extended public key.
+#[async_trait]
+pub trait DepositAddressSource: Send + Sync {
+    /// Get the deposit address for the next session
+    async fn next_address(&self) -> Result<String>;
+}
+
+/// Hands out each address of a pre-generated list exactly once
+#[derive(Debug, Default)]
+pub struct AddressPool {
+    addresses: Mutex<VecDeque<String>>,
+}
+
+impl AddressPool {
+    /// Create a pool from a list of addresses
+    pub fn new<I, S>(addresses: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            addresses: Mutex::new(addresses.into_iter().map(Into::into).collect()),
+        }
+    }
+
+    /// Return an address to the pool, e.g. after its session expired unpaid
+    pub fn release(&self, address: impl Into<String>) {
+        self.addresses.lock().unwrap().push_back(address.into());
+    }
+
+    /// Number of addresses left
+    pub fn remaining(&self) -> usize {
+        self.addresses.lock().unwrap().len()
+    }
+}
+
+#[async_trait]
+impl DepositAddressSource for AddressPool {
+    async fn next_address(&self) -> Result<String> {
+        self.addresses
+            .lock()
+            .unwrap()
+            .pop_front()
+            .ok_or_else(|| Error::generic("deposit address pool exhausted"))
+    }
+}
+
+/// Uses the same address for every session
+///
+/// Payments are then told apart only by amount and time, so prefer a pool
+/// or derived addresses when sessions can overlap.
+#[derive(Debug, Clone)]
+pub struct FixedAddress(pub String);
+
+#[async_trait]
+impl DepositAddressSource for FixedAddress {
+    async fn next_address(&self) -> Result<String> {
+        Ok(self.0.clone())
+    }
+}
+
+/// Checkout data stored in the payment metadata
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CheckoutMetadata {
+    success_url: String,
+    cancel_url: String,
+    chain_id: u64,
+}
+
+/// Checkout session for a single invoice
+#[derive(Debug, Clone)]
+pub struct CheckoutSession {
+    /// Session ID (same as the invoice ID)
+    pub id: Uuid,
+    /// Invoice being paid
+    pub invoice: Invoice,
+    /// Address the customer pays to
+    pub deposit_address: Address,
+    /// EIP-681 URI for the invoice's primary currency
+    pub payment_uri: String,
+    /// Time after which the session can no longer be paid
+    pub expires_at: DateTime<Utc>,
+    /// Where to send the customer after a successful payment
+    pub success_url: String,
+    /// Where to send the customer if the session is cancelled or fails
+    pub cancel_url: String,
+    /// Signed token identifying the session, safe to expose in URLs
+    pub token: String,
+}
+
+impl CheckoutSession {
+    /// Current invoice status
+    pub fn status(&self) -> InvoiceStatus {
+        self.invoice.status
+    }
+
+    /// URL to send the customer to, once the session is settled
+    ///
+    /// Returns `None` while payment is still open or processing.
+    pub fn redirect_url(&self) -> Option<&str> {
+        match self.invoice.status {
+            InvoiceStatus::Paid => Some(&self.success_url),
+            InvoiceStatus::Expired | InvoiceStatus::Failed => Some(&self.cancel_url),
+            InvoiceStatus::Open | InvoiceStatus::Processing | InvoiceStatus::PartiallyPaid => None,
+        }
+    }
+
+    /// Persist the session
+    pub async fn save<S: PaymentStorage + ?Sized>(&self, storage: &S) -> Result<()> {
+        self.invoice.save(storage).await
+    }
+
+    /// Monitor the session's invoice until it is paid, fails, or expires
+    ///
+    /// Status changes are written to `storage`; afterwards
+    /// [`redirect_url`](Self::redirect_url) tells where to send the customer.
+    pub async fn monitor<S: PaymentStorage + ?Sized>(
+        &mut self,
+        monitor: &PaymentMonitor,
+        storage: &S,
+    ) -> Result<InvoiceStatus> {
+        self.invoice.monitor(monitor, storage).await
+    }
+}
+
+/// Factory for checkout sessions sharing an address source and signing secret
+#[derive(Clone)]
+pub struct Checkout {
+    addresses: Arc<dyn DepositAddressSource>,
+    secret: Vec<u8>,
+    success_url: String,
+    cancel_url: String,
+    chain_id: u64,
+}
+
+impl Checkout {
+    /// Create a checkout handing out addresses from `addresses` and signing
+    /// session tokens with `secret`
+    pub fn new(addresses: impl DepositAddressSource + 'static, secret: impl AsRef<[u8]>) -> Self {
+        Self {
+            addresses: Arc::new(addresses),
+            secret: secret.as_ref().to_vec(),
+            success_url: String::new(),
+            cancel_url: String::new(),
+            chain_id: 1,
+        }
+    }
+
+    /// Set the URL customers are sent to after paying
+    pub fn success_url(mut self, url: impl Into<String>) -> Self {
+        self.success_url = url.into();
+        self
+    }
+
+    /// Set the URL customers are sent to when a session is cancelled or fails
+    pub fn cancel_url(mut self, url: impl Into<String>) -> Self {
+        self.cancel_url = url.into();
+        self
+    }
+
+    /// Set the chain ID used in payment URIs (default: 1)
+    pub fn chain_id(mut self, chain_id: u64) -> Self {
+        self.chain_id = chain_id;
+        self
+    }
+
+    /// Create a session for a new invoice
+    ///
+    /// `invoice` configures the invoice (line items, currencies, expiry); the
+    /// deposit address is taken from the address source.
+    pub async fn create_session<F>(&self, invoice: F) -> Result<CheckoutSession>
+    where
+        F: FnOnce(InvoiceBuilder) -> InvoiceBuilder,
+    {
+        let address = self.addresses.next_address().await?;
+        let mut invoice = invoice(Invoice::builder(address)).build()?;
+
+        let metadata = CheckoutMetadata {
+            success_url: self.success_url.clone(),
+            cancel_url: self.cancel_url.clone(),
+            chain_id: self.chain_id,
+        };
+        if let Some(fields) = invoice.payment.metadata.as_object_mut() {
+            fields.insert(METADATA_KEY.to_string(), serde_json::to_value(&metadata)?);
+        }
+
+        Ok(self.session(invoice, metadata))
+    }
+
+    /// Load a session by ID
+    pub async fn load_session<S: PaymentStorage + ?Sized>(
+        &self,
+        storage: &S,
+        id: Uuid,
+    ) -> Result<Option<CheckoutSession>> {
+        let Some(invoice) = Invoice::load(storage, id).await? else {
+            return Ok(None);
+        };
+
+        let metadata = invoice
+            .payment
+            .metadata
+            .get(METADATA_KEY)
+            .cloned()
+            .ok_or_else(|| Error::generic(format!("invoice {} has no checkout session", id)))?;
+        let metadata: CheckoutMetadata = serde_json::from_value(metadata)?;
+
+        Ok(Some(self.session(invoice, metadata)))
+    }
+
+    /// Load the session a token refers to, after checking its signature
+    pub async fn session_from_token<S: PaymentStorage + ?Sized>(
+        &self,
+        storage: &S,
+        token: &str,
+    ) -> Result<Option<CheckoutSession>> {
+        let (id, _) = self.verify_token(token)?;
+        self.load_session(storage, id).await
+    }
+
+    /// Check a session token's signature and return the session ID and expiry
+    ///
+    /// Expired tokens are still accepted so that customers returning late can
+    /// be shown the session's final status; compare the expiry if needed.
+    pub fn verify_token(&self, token: &str) -> Result<(Uuid, DateTime<Utc>)> {
+        let invalid = || Error::verification_failed("invalid session token");
+
+        let mut parts = token.splitn(3, '.');
+        let (Some(id), Some(expires), Some(signature)) = (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid());
+        };
+
+        let signature = hex::decode(signature).map_err(|_| invalid())?;
+        self.mac(id, expires)
+            .verify_slice(&signature)
+            .map_err(|_| invalid())?;
+
+        let id = Uuid::parse_str(id).map_err(|_| invalid())?;
+        let expires_at = expires
+            .parse::<i64>()
+            .ok()
+            .and_then(|secs| Utc.timestamp_opt(secs, 0).single())
+            .ok_or_else(invalid)?;
+
+        Ok((id, expires_at))
+    }
+
+    fn session(&self, invoice: Invoice, metadata: CheckoutMetadata) -> CheckoutSession {
+        let token = self.sign(invoice.id, invoice.expires_at);
+        CheckoutSession {
+            id: invoice.id,
+            deposit_address: invoice.deposit_address.clone(),
+            payment_uri: invoice.request().to_eip681_uri_for_chain(metadata.chain_id),
+            expires_at: invoice.expires_at,
+            success_url: metadata.success_url,
+            cancel_url: metadata.cancel_url,
+            token,
+            invoice,
+        }
+    }
+
+    /// Token format: `<session id>.<expiry unix seconds>.<hex HMAC-SHA256>`
+    fn sign(&self, id: Uuid, expires_at: DateTime<Utc>) -> String {
+        let id = id.to_string();
+        let expires = expires_at.timestamp().to_string();
+        let signature = hex::encode(self.mac(&id, &expires).finalize().into_bytes());
+        format!("{}.{}.{}", id, expires, signature)
+    }
+
+    fn mac(&self, id: &str, expires: &str) -> HmacSha256 {
+        let mut mac =
+            HmacSha256::new_from_slice(&self.secret).expect("HMAC accepts keys of any length");
+        mac.update(id.as_bytes());
+        mac.update(b".");
+        mac.update(expires.as_bytes());
+        mac
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::invoice::LineItem;
+    use crate::payment::PaymentStatus;
+    use crate::storage::MemoryStorage;
+    use rust_decimal::Decimal;
+
+    const DEPOSIT: &str = "0x742D35CC6634c0532925A3b844BC9E7595F0BEb0";
+
+    fn checkout() -> Checkout {
+        Checkout::new(AddressPool::new([DEPOSIT]), "secret")
+            .success_url("https://shop.example/thanks")
+            .cancel_url("https://shop.example/cart")
+    }
+
+    #[tokio::test]
+    async fn test_session_roundtrip() {
+        let checkout = checkout();
+        let storage = MemoryStorage::new();
+
+        let mut session = checkout
+            .create_session(|invoice| invoice.line_item(LineItem::new("Widget", 1, Decimal::ONE)))
+            .await
+            .unwrap();
+        session.save(&storage).await.unwrap();
+
+        assert_eq!(session.deposit_address, DEPOSIT);
+        assert!(session.payment_uri.starts_with("ethereum:0x742D"));
+        assert_eq!(session.redirect_url(), None);
+
+        // The pool had a single address
+        assert!(checkout
+            .create_session(|invoice| invoice.line_item(LineItem::new("Widget", 1, Decimal::ONE)))
+            .await
+            .is_err());
+
+        let loaded = checkout
+            .session_from_token(&storage, &session.token)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(loaded.id, session.id);
+        assert_eq!(loaded.token, session.token);
+        assert_eq!(loaded.success_url, "https://shop.example/thanks");
+
+        session.invoice.apply_status(PaymentStatus::Expired);
+        assert_eq!(session.redirect_url(), Some("https://shop.example/cart"));
+    }
+
+    #[test]
+    fn test_token_signature() {
+        let checkout = checkout();
+        let id = Uuid::new_v4();
+        let expires_at = Utc.timestamp_opt(1_900_000_000, 0).unwrap();
+        let token = checkout.sign(id, expires_at);
+
+        assert_eq!(checkout.verify_token(&token).unwrap(), (id, expires_at));
+
+        let tampered = token.replacen("1900000000", "1900000001", 1);
+        assert!(checkout.verify_token(&tampered).is_err());
+
+        let other = Checkout::new(FixedAddress(DEPOSIT.to_string()), "other-secret");
+        assert!(other.verify_token(&token).is_err());
+        assert!(checkout.verify_token("garbage").is_err());
+    }
+}
@@ -0,0 +1,243 @@
+//! Response cache
+//!
+//! Backed by moka on native targets. moka needs threads, so wasm builds use
+//! a small TTL map that evicts the oldest entry when full.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Cache statistics since the client was created
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct CacheStats {
+    /// Lookups answered from the cache
+    pub hits: u64,
+    /// Lookups that went to the explorer or the disk cache
+    pub misses: u64,
+    /// Results served from the disk cache
+    pub disk_hits: u64,
+    /// Entries dropped because they expired or the cache was full
+    pub evictions: u64,
+    /// Entries currently cached (approximate on native targets, where
+    /// removals are applied in batches)
+    pub entry_count: u64,
+    /// Hits and misses per endpoint, keyed by `module/action`
+    pub per_endpoint: HashMap<String, EndpointCacheStats>,
+}
+
+/// Cache hits and misses of one endpoint
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct EndpointCacheStats {
+    /// Lookups answered from the cache
+    pub hits: u64,
+    /// Lookups that went to the explorer
+    pub misses: u64,
+}
+
+/// TTL cache of explorer results keyed by request
+#[derive(Clone)]
+pub(crate) struct ResponseCache {
+    #[cfg(not(target_arch = "wasm32"))]
+    inner: moka::future::Cache<String, Value>,
+    #[cfg(target_arch = "wasm32")]
+    inner: Arc<Mutex<wasm::TtlMap>>,
+    lookups: Arc<Mutex<HashMap<String, EndpointCacheStats>>>,
+    evictions: Arc<AtomicU64>,
+}
+
+impl ResponseCache {
+    /// Cached result of `endpoint` (`module/action`) for `key`, counting the lookup
+    pub async fn get(&self, endpoint: &str, key: &str) -> Option<Value> {
+        let value = self.lookup(key).await;
+        let mut lookups = self
+            .lookups
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let stats = match lookups.get_mut(endpoint) {
+            Some(stats) => stats,
+            None => lookups.entry(endpoint.to_string()).or_default(),
+        };
+        if value.is_some() {
+            stats.hits += 1;
+        } else {
+            stats.misses += 1;
+        }
+        value
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        let per_endpoint = self
+            .lookups
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone();
+        CacheStats {
+            hits: per_endpoint.values().map(|stats| stats.hits).sum(),
+            misses: per_endpoint.values().map(|stats| stats.misses).sum(),
+            disk_hits: 0,
+            evictions: self.evictions.load(Ordering::Relaxed),
+            entry_count: self.entry_count(),
+            per_endpoint,
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl ResponseCache {
+    pub fn new(max_capacity: u64, ttl: Duration) -> Self {
+        let evictions = Arc::new(AtomicU64::new(0));
+        let evicted = evictions.clone();
+        Self {
+            inner: moka::future::Cache::builder()
+                .max_capacity(max_capacity)
+                .time_to_live(ttl)
+                .eviction_listener(move |_key, _value, cause| {
+                    if cause.was_evicted() {
+                        evicted.fetch_add(1, Ordering::Relaxed);
+                    }
+                })
+                .build(),
+            lookups: Arc::default(),
+            evictions,
+        }
+    }
+
+    async fn lookup(&self, key: &str) -> Option<Value> {
+        self.inner.get(key).await
+    }
+
+    pub async fn insert(&self, key: String, value: Value) {
+        self.inner.insert(key, value).await;
+    }
+
+    pub fn invalidate_all(&self) {
+        self.inner.invalidate_all();
+    }
+
+    pub fn entry_count(&self) -> u64 {
+        self.inner.entry_count()
+    }
+
+    pub fn keys(&self) -> Vec<String> {
+        self.inner.iter().map(|(key, _)| key.to_string()).collect()
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl ResponseCache {
+    pub fn new(max_capacity: u64, ttl: Duration) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(wasm::TtlMap::new(max_capacity, ttl))),
+            lookups: Arc::default(),
+            evictions: Arc::default(),
+        }
+    }
+
+    async fn lookup(&self, key: &str) -> Option<Value> {
+        let (value, expired) = self.lock().get(key);
+        self.evictions.fetch_add(expired, Ordering::Relaxed);
+        value
+    }
+
+    pub async fn insert(&self, key: String, value: Value) {
+        let evicted = self.lock().insert(key, value);
+        self.evictions.fetch_add(evicted, Ordering::Relaxed);
+    }
+
+    pub fn invalidate_all(&self) {
+        self.lock().clear();
+    }
+
+    pub fn entry_count(&self) -> u64 {
+        self.lock().len() as u64
+    }
+
+    pub fn keys(&self) -> Vec<String> {
+        self.lock().keys()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, wasm::TtlMap> {
+        self.inner
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod wasm {
+    use crate::rt::Instant;
+    use serde_json::Value;
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    pub struct TtlMap {
+        entries: HashMap<String, (Instant, Value)>,
+        max_capacity: usize,
+        ttl: Duration,
+    }
+
+    impl TtlMap {
+        pub fn new(max_capacity: u64, ttl: Duration) -> Self {
+            Self {
+                entries: HashMap::new(),
+                max_capacity: max_capacity as usize,
+                ttl,
+            }
+        }
+
+        /// Value of `key`, and the number of entries evicted for expiry
+        pub fn get(&mut self, key: &str) -> (Option<Value>, u64) {
+            match self.entries.get(key) {
+                Some((inserted, value)) if inserted.elapsed() < self.ttl => {
+                    (Some(value.clone()), 0)
+                }
+                Some(_) => {
+                    self.entries.remove(key);
+                    (None, 1)
+                }
+                None => (None, 0),
+            }
+        }
+
+        /// Insert `value`, returning the number of entries evicted to make room
+        pub fn insert(&mut self, key: String, value: Value) -> u64 {
+            if self.max_capacity == 0 {
+                return 0;
+            }
+            let before = self.entries.len();
+            if self.entries.len() >= self.max_capacity && !self.entries.contains_key(&key) {
+                let ttl = self.ttl;
+                self.entries
+                    .retain(|_, (inserted, _)| inserted.elapsed() < ttl);
+            }
+            if self.entries.len() >= self.max_capacity {
+                let oldest = self
+                    .entries
+                    .iter()
+                    .min_by_key(|(_, (inserted, _))| *inserted)
+                    .map(|(key, _)| key.clone());
+                if let Some(oldest) = oldest {
+                    self.entries.remove(&oldest);
+                }
+            }
+            let evicted = (before - self.entries.len()) as u64;
+            self.entries.insert(key, (Instant::now(), value));
+            evicted
+        }
+
+        pub fn keys(&self) -> Vec<String> {
+            self.entries.keys().cloned().collect()
+        }
+
+        pub fn clear(&mut self) {
+            self.entries.clear();
+        }
+
+        pub fn len(&self) -> usize {
+            self.entries.len()
+        }
+    }
+}
@@ -0,0 +1,126 @@
+//! File-backed cache of immutable explorer results
+//!
+//! Survives restarts, so a daemon doesn't refetch mined transactions and
+//! receipts it has already seen. Only results of the configured endpoints are
+//! stored, and never empty or still-pending ones (`null`, or objects whose
+//! `blockNumber` is `null`).
+
+use crate::config::DiskCacheConfig;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// One stored result
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    key: String,
+    stored_at: i64,
+    result: Value,
+}
+
+pub(crate) struct DiskCache {
+    dir: PathBuf,
+    ttl_seconds: i64,
+    endpoints: HashSet<String>,
+    hits: AtomicU64,
+}
+
+impl DiskCache {
+    pub fn new(config: &DiskCacheConfig) -> Self {
+        Self {
+            dir: config.dir.clone(),
+            ttl_seconds: i64::try_from(config.ttl_seconds).unwrap_or(i64::MAX),
+            endpoints: config.endpoints.iter().cloned().collect(),
+            hits: AtomicU64::new(0),
+        }
+    }
+
+    /// Whether results of `endpoint` (`module/action`) are stored
+    pub fn covers(&self, endpoint: &str) -> bool {
+        self.endpoints.contains(endpoint)
+    }
+
+    /// Stored result for `key` on `chain_id`, if present and fresh
+    pub fn get(&self, chain_id: u64, key: &str) -> Option<Value> {
+        let contents = std::fs::read_to_string(self.path(chain_id, key)).ok()?;
+        let entry: Entry = serde_json::from_str(&contents).ok()?;
+        let age = Utc::now().timestamp().saturating_sub(entry.stored_at);
+        if entry.key != key || age >= self.ttl_seconds {
+            return None;
+        }
+        self.hits.fetch_add(1, Ordering::Relaxed);
+        Some(entry.result)
+    }
+
+    /// Store `result` unless it is empty or pending; failures are only logged
+    pub fn insert(&self, chain_id: u64, key: &str, result: &Value) {
+        let pending = result.is_null() || result.get("blockNumber").is_some_and(Value::is_null);
+        if pending {
+            return;
+        }
+
+        let entry = Entry {
+            key: key.to_string(),
+            stored_at: Utc::now().timestamp(),
+            result: result.clone(),
+        };
+        let write = std::fs::create_dir_all(&self.dir).and_then(|()| {
+            let contents = serde_json::to_vec(&entry).map_err(std::io::Error::from)?;
+            std::fs::write(self.path(chain_id, key), contents)
+        });
+        if let Err(e) = write {
+            tracing::warn!(error = %e, dir = %self.dir.display(), "failed to write disk cache entry");
+        }
+    }
+
+    /// Results served from disk
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    fn path(&self, chain_id: u64, key: &str) -> PathBuf {
+        let digest = Sha256::digest(format!("{}|{}", chain_id, key));
+        self.dir
+            .join(format!("{}.json", hex::encode(&digest[..16])))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stores_only_final_results() {
+        let dir = std::env::temp_dir().join(format!("cryptopay-disk-{}", uuid::Uuid::new_v4()));
+        let cache = DiskCache::new(&DiskCacheConfig::new(&dir));
+        let mined = serde_json::json!({ "hash": "0x1", "blockNumber": "0x10" });
+        let pending = serde_json::json!({ "hash": "0x2", "blockNumber": null });
+
+        cache.insert(1, "proxy:eth_getTransactionByHash:txhash=0x1", &mined);
+        cache.insert(1, "proxy:eth_getTransactionByHash:txhash=0x2", &pending);
+        cache.insert(1, "proxy:eth_getTransactionByHash:txhash=0x3", &Value::Null);
+
+        assert_eq!(
+            cache.get(1, "proxy:eth_getTransactionByHash:txhash=0x1"),
+            Some(mined)
+        );
+        assert_eq!(
+            cache.get(56, "proxy:eth_getTransactionByHash:txhash=0x1"),
+            None
+        );
+        assert_eq!(
+            cache.get(1, "proxy:eth_getTransactionByHash:txhash=0x2"),
+            None
+        );
+        assert_eq!(
+            cache.get(1, "proxy:eth_getTransactionByHash:txhash=0x3"),
+            None
+        );
+        assert_eq!(cache.hits(), 1);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
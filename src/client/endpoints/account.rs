@@ -1,12 +1,15 @@
 //! Account-related API endpoints
 
-use crate::client::types::{Balance, InternalTransaction, Transaction};
+use crate::client::types::{
+    Balance, BeaconWithdrawal, FundingSource, InternalTransaction, Transaction, ValidatedBlock,
+};
 use crate::client::BscScanClient;
 use crate::error::Result;
+use crate::primitives::Address;
 
 /// Account endpoints
 pub trait AccountEndpoints {
-    /// Get BNB balance for an address
+    /// Get the native-currency balance of an address
     ///
     /// # Example
     /// ```no_run
@@ -14,12 +17,13 @@ pub trait AccountEndpoints {
     /// # use cryptopay::client::AccountEndpoints;
     /// # async fn example() -> Result<()> {
     /// let client = BscScanClient::new("api-key")?;
-    /// let balance = client.get_balance("0x...").await?;
-    /// println!("Balance: {} BNB", balance.bnb());
+    /// let address: Address = "0x742d35cc6634c0532925a3b844bc9e7595f0beb0".parse()?;
+    /// let balance = client.get_balance(&address).await?;
+    /// println!("Balance: {} ETH", balance.ether());
     /// # Ok(())
     /// # }
     /// ```
-    async fn get_balance(&self, address: &str) -> Result<Balance>;
+    async fn get_balance(&self, address: &Address) -> Result<Balance>;
 
     /// Get list of transactions for an address
     ///
@@ -32,7 +36,7 @@ pub trait AccountEndpoints {
     /// - `sort`: "asc" or "desc"
     async fn get_transactions(
         &self,
-        address: &str,
+        address: &Address,
         start_block: u64,
         end_block: u64,
         page: u32,
@@ -43,18 +47,45 @@ pub trait AccountEndpoints {
     /// Get list of internal transactions for an address
     async fn get_internal_transactions(
         &self,
-        address: &str,
+        address: &Address,
         start_block: u64,
         end_block: u64,
         page: u32,
         offset: u32,
         sort: &str,
     ) -> Result<Vec<InternalTransaction>>;
+
+    /// Get the transfer that first funded an address (`fundedby`)
+    ///
+    /// Useful to trace where a paying wallet got its initial funds.
+    async fn get_first_funder(&self, address: &Address) -> Result<FundingSource>;
+
+    /// Get beacon chain withdrawals credited to an address
+    ///
+    /// Parameters as for [`get_transactions`](Self::get_transactions). Only
+    /// proof-of-stake Ethereum chains have withdrawals.
+    async fn get_beacon_withdrawals(
+        &self,
+        address: &Address,
+        start_block: u64,
+        end_block: u64,
+        page: u32,
+        offset: u32,
+        sort: &str,
+    ) -> Result<Vec<BeaconWithdrawal>>;
+
+    /// Get blocks validated by an address, newest first (`getminedblocks`)
+    async fn get_blocks_validated(
+        &self,
+        address: &Address,
+        page: u32,
+        offset: u32,
+    ) -> Result<Vec<ValidatedBlock>>;
 }
 
 impl AccountEndpoints for BscScanClient {
-    async fn get_balance(&self, address: &str) -> Result<Balance> {
-        let params = [("address", address), ("tag", "latest")];
+    async fn get_balance(&self, address: &Address) -> Result<Balance> {
+        let params = [("address", address.as_str()), ("tag", "latest")];
 
         // BscScan returns balance as a simple string, wrap it
         let balance_str: String = self.request_simple("account", "balance", &params).await?;
@@ -64,7 +95,7 @@ impl AccountEndpoints for BscScanClient {
 
     async fn get_transactions(
         &self,
-        address: &str,
+        address: &Address,
         start_block: u64,
         end_block: u64,
         page: u32,
@@ -72,7 +103,7 @@ impl AccountEndpoints for BscScanClient {
         sort: &str,
     ) -> Result<Vec<Transaction>> {
         let params = [
-            ("address", address),
+            ("address", address.as_str()),
             ("startblock", &start_block.to_string()),
             ("endblock", &end_block.to_string()),
             ("page", &page.to_string()),
@@ -85,7 +116,7 @@ impl AccountEndpoints for BscScanClient {
 
     async fn get_internal_transactions(
         &self,
-        address: &str,
+        address: &Address,
         start_block: u64,
         end_block: u64,
         page: u32,
@@ -93,7 +124,7 @@ impl AccountEndpoints for BscScanClient {
         sort: &str,
     ) -> Result<Vec<InternalTransaction>> {
         let params = [
-            ("address", address),
+            ("address", address.as_str()),
             ("startblock", &start_block.to_string()),
             ("endblock", &end_block.to_string()),
             ("page", &page.to_string()),
@@ -103,4 +134,47 @@ impl AccountEndpoints for BscScanClient {
 
         self.request("account", "txlistinternal", &params).await
     }
+
+    async fn get_first_funder(&self, address: &Address) -> Result<FundingSource> {
+        let params = [("address", address.as_str())];
+        self.request("account", "fundedby", &params).await
+    }
+
+    async fn get_beacon_withdrawals(
+        &self,
+        address: &Address,
+        start_block: u64,
+        end_block: u64,
+        page: u32,
+        offset: u32,
+        sort: &str,
+    ) -> Result<Vec<BeaconWithdrawal>> {
+        let params = [
+            ("address", address.as_str()),
+            ("startblock", &start_block.to_string()),
+            ("endblock", &end_block.to_string()),
+            ("page", &page.to_string()),
+            ("offset", &offset.to_string()),
+            ("sort", sort),
+        ];
+
+        self.request("account", "txsBeaconWithdrawal", &params)
+            .await
+    }
+
+    async fn get_blocks_validated(
+        &self,
+        address: &Address,
+        page: u32,
+        offset: u32,
+    ) -> Result<Vec<ValidatedBlock>> {
+        let params = [
+            ("address", address.as_str()),
+            ("blocktype", "blocks"),
+            ("page", &page.to_string()),
+            ("offset", &offset.to_string()),
+        ];
+
+        self.request("account", "getminedblocks", &params).await
+    }
 }
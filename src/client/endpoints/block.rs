@@ -0,0 +1,278 @@
+//! Block-related API endpoints
+
+use crate::client::endpoints::TransactionEndpoints;
+use crate::client::types::RpcBlock;
+use crate::client::BscScanClient;
+use crate::error::{Error, Result};
+use chrono::{DateTime, Utc};
+use std::fmt;
+use std::ops::RangeInclusive;
+
+/// Block to query with [`get_block`](BlockEndpoints::get_block)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockTag {
+    /// Latest mined block
+    Latest,
+    /// Block with the given number
+    Number(u64),
+}
+
+impl From<u64> for BlockTag {
+    fn from(number: u64) -> Self {
+        BlockTag::Number(number)
+    }
+}
+
+impl fmt::Display for BlockTag {
+    /// Formats as the JSON-RPC block parameter (`latest` or a `0x` hex number)
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlockTag::Latest => f.write_str("latest"),
+            BlockTag::Number(number) => write!(f, "0x{:x}", number),
+        }
+    }
+}
+
+/// Which block [`get_block_number_by_time`](BlockEndpoints::get_block_number_by_time)
+/// returns when no block has the exact timestamp
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Closest {
+    /// Last block at or before the time
+    Before,
+    /// First block at or after the time
+    After,
+}
+
+impl Closest {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Closest::Before => "before",
+            Closest::After => "after",
+        }
+    }
+}
+
+/// Block endpoints
+pub trait BlockEndpoints {
+    /// Get the number of the block mined closest to a time (`getblocknobytime`)
+    async fn get_block_number_by_time(&self, time: DateTime<Utc>, closest: Closest) -> Result<u64>;
+
+    /// Get a block (`eth_getBlockByNumber`), with full transactions if `include_txs`
+    ///
+    /// Fails if the block doesn't exist yet.
+    async fn get_block(&self, block: BlockTag, include_txs: bool) -> Result<RpcBlock>;
+}
+
+impl BlockEndpoints for BscScanClient {
+    async fn get_block_number_by_time(&self, time: DateTime<Utc>, closest: Closest) -> Result<u64> {
+        let timestamp = time.timestamp().to_string();
+        let params = [
+            ("timestamp", timestamp.as_str()),
+            ("closest", closest.as_str()),
+        ];
+        let block: String = self.request("block", "getblocknobytime", &params).await?;
+        block
+            .parse()
+            .map_err(|_| Error::generic(format!("Invalid block number: {}", block)))
+    }
+
+    async fn get_block(&self, block: BlockTag, include_txs: bool) -> Result<RpcBlock> {
+        let tag = block.to_string();
+        let params = [
+            ("tag", tag.as_str()),
+            ("boolean", if include_txs { "true" } else { "false" }),
+        ];
+
+        // The node answers `null` for blocks that don't exist yet
+        let rpc_block: Option<RpcBlock> = match block {
+            BlockTag::Latest => {
+                self.request_uncached("proxy", "eth_getBlockByNumber", &params)
+                    .await?
+            }
+            BlockTag::Number(_) => {
+                self.request("proxy", "eth_getBlockByNumber", &params)
+                    .await?
+            }
+        };
+        rpc_block.ok_or_else(|| Error::generic(format!("Block not found: {}", block)))
+    }
+}
+
+/// Inclusive range of blocks
+///
+/// # Example
+/// ```no_run
+/// # use cryptopay::*;
+/// # use cryptopay::client::BlockRange;
+/// # use chrono::{Duration, Utc};
+/// # async fn example(client: BscScanClient) -> Result<()> {
+/// // Blocks mined in the last 24 hours
+/// let now = Utc::now();
+/// let range = BlockRange::for_time_window(&client, now - Duration::days(1), now).await?;
+/// println!("blocks {} to {}", range.start, range.end);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockRange {
+    /// First block
+    pub start: u64,
+    /// Last block
+    pub end: u64,
+}
+
+impl BlockRange {
+    /// Create a range from `start` to `end` (inclusive)
+    pub fn new(start: u64, end: u64) -> Self {
+        Self { start, end }
+    }
+
+    /// Blocks mined between two times (inclusive)
+    ///
+    /// The range starts with the last block before `from`, so a transfer
+    /// made exactly at `from` is always covered; narrow further by timestamp
+    /// if needed. An end time that isn't in the past yet resolves to the
+    /// latest block.
+    pub async fn for_time_window(
+        client: &BscScanClient,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Self> {
+        if to < from {
+            return Err(Error::InvalidConfig(format!(
+                "time window ends ({}) before it starts ({})",
+                to.to_rfc3339(),
+                from.to_rfc3339()
+            )));
+        }
+
+        let start = client
+            .get_block_number_by_time(from, Closest::Before)
+            .await?;
+        let end = if to >= Utc::now() {
+            client.get_block_number().await?
+        } else {
+            client.get_block_number_by_time(to, Closest::Before).await?
+        };
+        Ok(Self::new(start, end.max(start)))
+    }
+
+    /// Check whether a block is in the range
+    pub fn contains(&self, block: u64) -> bool {
+        (self.start..=self.end).contains(&block)
+    }
+
+    /// Range as used by [`Reconciler::run`](crate::reconcile::Reconciler::run)
+    pub fn blocks(&self) -> RangeInclusive<u64> {
+        self.start..=self.end
+    }
+}
+
+impl From<BlockRange> for RangeInclusive<u64> {
+    fn from(range: BlockRange) -> Self {
+        range.blocks()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ClientConfig;
+    use chrono::TimeZone;
+    use wiremock::matchers::query_param;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn ok(result: &str) -> ResponseTemplate {
+        ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "status": "1", "message": "OK", "result": result
+        }))
+    }
+
+    #[tokio::test]
+    async fn test_block_range_for_time_window() {
+        let server = MockServer::start().await;
+        let from = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let to = Utc.with_ymd_and_hms(2024, 1, 2, 0, 0, 0).unwrap();
+        Mock::given(query_param("timestamp", from.timestamp().to_string()))
+            .and(query_param("closest", "before"))
+            .respond_with(ok("18908894"))
+            .mount(&server)
+            .await;
+        Mock::given(query_param("timestamp", to.timestamp().to_string()))
+            .and(query_param("closest", "before"))
+            .respond_with(ok("18916054"))
+            .mount(&server)
+            .await;
+
+        let config = ClientConfig::builder()
+            .api_key("key")
+            .base_url(server.uri())
+            .build()
+            .unwrap();
+        let client = BscScanClient::with_config(config).unwrap();
+
+        let range = BlockRange::for_time_window(&client, from, to)
+            .await
+            .unwrap();
+        assert_eq!(range, BlockRange::new(18908894, 18916054));
+        assert!(range.contains(18910000));
+        assert!(!range.contains(18916055));
+
+        assert!(BlockRange::for_time_window(&client, to, from)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_block_parses_hex_quantities() {
+        let server = MockServer::start().await;
+        let tx_hash = format!("0x{}", "ab".repeat(32));
+        Mock::given(query_param("action", "eth_getBlockByNumber"))
+            .and(query_param("tag", "0x10d4f"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": {
+                    "number": "0x10d4f",
+                    "hash": format!("0x{}", "11".repeat(32)),
+                    "parentHash": format!("0x{}", "22".repeat(32)),
+                    "timestamp": "0x55d21481",
+                    "miner": "0xbb7b8287f3f0a933474a79eae42cbca977791171",
+                    "gasLimit": "0x1c9c380",
+                    "gasUsed": "0x5208",
+                    "baseFeePerGas": "0x3b9aca00",
+                    "size": "0x220",
+                    "transactions": [tx_hash]
+                }
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(query_param("action", "eth_getBlockByNumber"))
+            .and(query_param("tag", "latest"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0", "id": 1, "result": null
+            })))
+            .mount(&server)
+            .await;
+
+        let config = ClientConfig::builder()
+            .api_key("key")
+            .base_url(server.uri())
+            .build()
+            .unwrap();
+        let client = BscScanClient::with_config(config).unwrap();
+
+        let block = client
+            .get_block(BlockTag::from(68943), false)
+            .await
+            .unwrap();
+        assert_eq!(block.number, 68943);
+        assert_eq!(block.timestamp.unwrap().timestamp(), 0x55d21481);
+        assert_eq!(block.gas_used, 21000);
+        assert_eq!(block.base_fee_wei(), Some(1_000_000_000));
+        assert_eq!(block.transaction_count(), 1);
+        assert_eq!(block.transaction_hashes()[0].as_str(), tx_hash);
+
+        assert!(client.get_block(BlockTag::Latest, false).await.is_err());
+    }
+}
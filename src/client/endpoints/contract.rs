@@ -0,0 +1,57 @@
+//! Contract-related API endpoints
+
+use crate::client::types::{ContractCreation, ContractSource};
+use crate::client::BscScanClient;
+use crate::error::{Error, Result};
+use crate::primitives::Address;
+
+/// Contract endpoints
+pub trait ContractEndpoints {
+    /// Get the deployed bytecode at an address (`0x` for accounts without code)
+    async fn get_code(&self, address: &Address) -> Result<String>;
+
+    /// Check whether an address holds contract code
+    async fn is_contract(&self, address: &Address) -> Result<bool>;
+
+    /// Get the verified source of a contract
+    ///
+    /// Unverified contracts are returned with an empty
+    /// [`source_code`](ContractSource::source_code).
+    async fn get_contract_source(&self, address: &Address) -> Result<ContractSource>;
+
+    /// Get the creator and creation transaction of a contract
+    async fn get_contract_creation(&self, address: &Address) -> Result<ContractCreation>;
+}
+
+impl ContractEndpoints for BscScanClient {
+    async fn get_code(&self, address: &Address) -> Result<String> {
+        let params = [("address", address.as_str()), ("tag", "latest")];
+        self.request("proxy", "eth_getCode", &params).await
+    }
+
+    async fn is_contract(&self, address: &Address) -> Result<bool> {
+        let code = self.get_code(address).await?;
+        Ok(!code.trim_start_matches("0x").is_empty())
+    }
+
+    async fn get_contract_source(&self, address: &Address) -> Result<ContractSource> {
+        let params = [("address", address.as_str())];
+        let sources: Vec<ContractSource> =
+            self.request("contract", "getsourcecode", &params).await?;
+        sources
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::api_error(format!("no source entry for {}", address)))
+    }
+
+    async fn get_contract_creation(&self, address: &Address) -> Result<ContractCreation> {
+        let params = [("contractaddresses", address.as_str())];
+        // The explorer answers `null` for addresses that aren't contracts
+        let creations: Option<Vec<ContractCreation>> = self
+            .request("contract", "getcontractcreation", &params)
+            .await?;
+        creations
+            .and_then(|creations| creations.into_iter().next())
+            .ok_or_else(|| Error::api_error(format!("{} is not a contract", address)))
+    }
+}
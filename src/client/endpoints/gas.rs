@@ -1,9 +1,12 @@
 //! Gas-related API endpoints
 
-use crate::client::types::GasOracle;
+use crate::client::types::{GasOracle, RpcFeeHistory};
 use crate::client::BscScanClient;
-use crate::error::Result;
+use crate::error::{Error, Result};
+use crate::payment::utils::{gwei_to_wei, parse_hex_u128, wei_to_ether};
 use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 /// Gas speed options
 #[derive(Debug, Clone, Copy)]
@@ -13,28 +16,189 @@ pub enum GasSpeed {
     Fast,
 }
 
+/// Suggested fees for sending a transaction, in gwei
+///
+/// Combines the gas oracle's legacy price for a [`GasSpeed`] with EIP-1559
+/// values: the priority fee is the part of that price above the base fee, and
+/// the max fee leaves room for the base fee to double.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeeSuggestion {
+    /// Legacy (type 0) gas price
+    pub gas_price: Decimal,
+    /// Current base fee
+    pub base_fee: Decimal,
+    /// Suggested `max_priority_fee_per_gas`
+    pub max_priority_fee: Decimal,
+    /// Suggested `max_fee_per_gas`
+    pub max_fee: Decimal,
+}
+
+impl FeeSuggestion {
+    /// Build a suggestion from gas oracle data
+    pub fn from_oracle(oracle: &GasOracle, speed: GasSpeed) -> Self {
+        let gas_price = oracle.price_gwei(speed);
+        let base_fee = oracle.base_fee_gwei();
+        let max_priority_fee = (gas_price - base_fee).max(Decimal::ZERO);
+
+        Self {
+            gas_price,
+            base_fee,
+            max_priority_fee,
+            max_fee: base_fee * Decimal::TWO + max_priority_fee,
+        }
+    }
+
+    /// Legacy gas price in wei
+    pub fn gas_price_wei(&self) -> u128 {
+        gwei_to_wei(self.gas_price)
+    }
+
+    /// `max_priority_fee_per_gas` in wei
+    pub fn max_priority_fee_wei(&self) -> u128 {
+        gwei_to_wei(self.max_priority_fee)
+    }
+
+    /// `max_fee_per_gas` in wei
+    pub fn max_fee_wei(&self) -> u128 {
+        gwei_to_wei(self.max_fee)
+    }
+
+    /// Upper bound of the fee for `gas_limit` gas, in the native currency
+    pub fn max_cost(&self, gas_limit: u64) -> Decimal {
+        wei_to_ether(self.max_fee_wei().saturating_mul(gas_limit as u128))
+    }
+}
+
 /// Gas endpoints
 pub trait GasEndpoints {
     /// Get gas oracle data
+    ///
+    /// Falls back to the node's `eth_gasPrice` (and `eth_feeHistory`, if
+    /// proxied) on chains where the explorer has no gas tracker.
     async fn get_gas_oracle(&self) -> Result<GasOracle>;
 
     /// Get estimated gas price for a given speed
     async fn estimate_gas_price(&self, speed: GasSpeed) -> Result<Decimal>;
+
+    /// Get the estimated confirmation time for a gas price (in wei)
+    async fn get_gas_estimate(&self, gas_price_wei: u128) -> Result<Duration>;
+
+    /// Get suggested legacy and EIP-1559 fees for a given speed
+    async fn suggest_fees(&self, speed: GasSpeed) -> Result<FeeSuggestion>;
 }
 
 impl GasEndpoints for BscScanClient {
     async fn get_gas_oracle(&self) -> Result<GasOracle> {
         let params: [(&str, &str); 0] = [];
-        self.request("gastracker", "gasoracle", &params).await
+        match self.request("gastracker", "gasoracle", &params).await {
+            // Chains without a gas tracker answer NOTOK
+            Err(Error::ApiError { .. }) => rpc_gas_oracle(self).await,
+            result => result,
+        }
     }
 
     async fn estimate_gas_price(&self, speed: GasSpeed) -> Result<Decimal> {
         let oracle = self.get_gas_oracle().await?;
+        Ok(oracle.price_gwei(speed))
+    }
+
+    async fn get_gas_estimate(&self, gas_price_wei: u128) -> Result<Duration> {
+        let gas_price = gas_price_wei.to_string();
+        let params = [("gasprice", gas_price.as_str())];
+        let seconds: String = self.request("gastracker", "gasestimate", &params).await?;
+
+        seconds
+            .trim()
+            .parse()
+            .map(Duration::from_secs)
+            .map_err(|_| Error::generic(format!("Invalid gas estimate: {}", seconds)))
+    }
+
+    async fn suggest_fees(&self, speed: GasSpeed) -> Result<FeeSuggestion> {
+        let oracle = self.get_gas_oracle().await?;
+        Ok(FeeSuggestion::from_oracle(&oracle, speed))
+    }
+}
+
+/// Gas oracle data derived from the node's JSON-RPC gas price and fee history
+async fn rpc_gas_oracle(client: &BscScanClient) -> Result<GasOracle> {
+    let params: [(&str, &str); 0] = [];
+    let gas_price: String = client.request("proxy", "eth_gasPrice", &params).await?;
+    let gas_price = parse_hex_u128(&gas_price)?;
+
+    // Not every explorer proxies eth_feeHistory; the gas price alone will do
+    let params = [
+        ("blockCount", "0x5"),
+        ("newestBlock", "latest"),
+        ("rewardPercentiles", "10,50,90"),
+    ];
+    let history: Option<RpcFeeHistory> = client
+        .request("proxy", "eth_feeHistory", &params)
+        .await
+        .ok();
+
+    Ok(GasOracle::from_rpc(gas_price, history.as_ref()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fee_suggestion() {
+        let oracle: GasOracle = serde_json::from_value(serde_json::json!({
+            "LastBlock": "19000000",
+            "SafeGasPrice": "12",
+            "ProposeGasPrice": "13.5",
+            "FastGasPrice": "15",
+            "suggestBaseFee": "11.5",
+            "gasUsedRatio": "0.4,0.6"
+        }))
+        .unwrap();
+
+        let fees = FeeSuggestion::from_oracle(&oracle, GasSpeed::Propose);
+        assert_eq!(fees.gas_price, Decimal::new(135, 1));
+        assert_eq!(fees.max_priority_fee, Decimal::from(2));
+        assert_eq!(fees.max_fee, Decimal::from(25));
+        assert_eq!(fees.max_fee_wei(), 25_000_000_000);
+        assert_eq!(fees.max_cost(21_000), Decimal::new(525, 6));
+
+        // Prices at or below the base fee don't go negative
+        let oracle = GasOracle {
+            safe_gas_price: "11".to_string(),
+            ..oracle
+        };
+        let fees = FeeSuggestion::from_oracle(&oracle, GasSpeed::Safe);
+        assert_eq!(fees.max_priority_fee, Decimal::ZERO);
+        assert_eq!(fees.max_fee, Decimal::from(23));
+    }
+
+    #[test]
+    fn test_rpc_gas_oracle() {
+        // Legacy-style fallback: eth_gasPrice only (0.01 gwei)
+        let oracle = GasOracle::from_rpc(10_000_000, None);
+        assert_eq!(oracle.fast_gwei(), Decimal::new(1, 2));
+        assert_eq!(oracle.base_fee_gwei(), Decimal::new(1, 2));
+        assert_eq!(
+            FeeSuggestion::from_oracle(&oracle, GasSpeed::Fast).max_fee,
+            Decimal::new(2, 2)
+        );
 
-        Ok(match speed {
-            GasSpeed::Safe => oracle.safe_gwei(),
-            GasSpeed::Propose => oracle.propose_gwei(),
-            GasSpeed::Fast => oracle.fast_gwei(),
-        })
+        let history: RpcFeeHistory = serde_json::from_value(serde_json::json!({
+            "oldestBlock": "0x10",
+            "baseFeePerGas": ["0x3b9aca00", "0x3b9aca00", "0x77359400"],
+            "gasUsedRatio": [0.5, 0.75],
+            "reward": [
+                ["0x5f5e100", "0x3b9aca00", "0x77359400"],
+                ["0x5f5e100", "0x77359400", "0xb2d05e00"]
+            ]
+        }))
+        .unwrap();
+        let oracle = GasOracle::from_rpc(3_000_000_000, Some(&history));
+        assert_eq!(oracle.suggest_base_fee, "2");
+        assert_eq!(oracle.safe_gwei(), Decimal::new(21, 1));
+        assert_eq!(oracle.propose_gwei(), Decimal::new(35, 1));
+        assert_eq!(oracle.fast_gwei(), Decimal::new(45, 1));
+        assert_eq!(oracle.gas_used_ratio, "0.5,0.75");
     }
 }
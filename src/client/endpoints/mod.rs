@@ -1,11 +1,17 @@
 //! API endpoint implementations
 
 pub mod account;
+pub mod block;
+pub mod contract;
 pub mod gas;
+pub mod stats;
 pub mod token;
 pub mod transaction;
 
 pub use account::AccountEndpoints;
+pub use block::{BlockEndpoints, BlockRange, BlockTag, Closest};
+pub use contract::ContractEndpoints;
 pub use gas::GasEndpoints;
+pub use stats::StatsEndpoints;
 pub use token::TokenEndpoints;
 pub use transaction::TransactionEndpoints;
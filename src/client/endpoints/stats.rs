@@ -0,0 +1,167 @@
+//! Stats API endpoints
+
+use crate::client::types::{DailyGasPrice, DailyNetworkUtilization, DailyTxCount, EthPrice};
+use crate::client::BscScanClient;
+use crate::error::Result;
+use chrono::NaiveDate;
+
+/// Stats endpoints
+///
+/// The daily statistics are API Pro endpoints: they fail with
+/// [`Error::ProEndpointRequired`](crate::Error::ProEndpointRequired) unless
+/// [`ClientConfig::pro_tier`](crate::ClientConfig::pro_tier) is set.
+pub trait StatsEndpoints {
+    /// Get the latest ETH price in USD and BTC
+    async fn get_eth_price(&self) -> Result<EthPrice>;
+
+    /// Get the number of transactions per day between two dates (inclusive)
+    ///
+    /// `sort` is "asc" or "desc".
+    async fn get_daily_tx_count(
+        &self,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+        sort: &str,
+    ) -> Result<Vec<DailyTxCount>>;
+
+    /// Get the highest, lowest, and average gas price per day between two dates (inclusive)
+    async fn get_daily_avg_gas_price(
+        &self,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+        sort: &str,
+    ) -> Result<Vec<DailyGasPrice>>;
+
+    /// Get the network utilization per day between two dates (inclusive)
+    async fn get_daily_network_utilization(
+        &self,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+        sort: &str,
+    ) -> Result<Vec<DailyNetworkUtilization>>;
+}
+
+impl StatsEndpoints for BscScanClient {
+    async fn get_eth_price(&self) -> Result<EthPrice> {
+        let params: [(&str, &str); 0] = [];
+        self.request("stats", "ethprice", &params).await
+    }
+
+    async fn get_daily_tx_count(
+        &self,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+        sort: &str,
+    ) -> Result<Vec<DailyTxCount>> {
+        self.daily_stats("dailytx", start_date, end_date, sort)
+            .await
+    }
+
+    async fn get_daily_avg_gas_price(
+        &self,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+        sort: &str,
+    ) -> Result<Vec<DailyGasPrice>> {
+        self.daily_stats("dailyavggasprice", start_date, end_date, sort)
+            .await
+    }
+
+    async fn get_daily_network_utilization(
+        &self,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+        sort: &str,
+    ) -> Result<Vec<DailyNetworkUtilization>> {
+        self.daily_stats("dailynetutilization", start_date, end_date, sort)
+            .await
+    }
+}
+
+impl BscScanClient {
+    /// Request a daily statistic of the `stats` module
+    async fn daily_stats<T: serde::de::DeserializeOwned>(
+        &self,
+        action: &str,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+        sort: &str,
+    ) -> Result<Vec<T>> {
+        let start = start_date.format("%Y-%m-%d").to_string();
+        let end = end_date.format("%Y-%m-%d").to_string();
+        let params = [
+            ("startdate", start.as_str()),
+            ("enddate", end.as_str()),
+            ("sort", sort),
+        ];
+        self.request_pro("stats", action, &params).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ClientConfig;
+    use crate::error::Error;
+    use wiremock::matchers::query_param;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn client(server: &MockServer, pro_tier: bool) -> BscScanClient {
+        let config = ClientConfig::builder()
+            .api_key("key")
+            .base_url(server.uri())
+            .pro_tier(pro_tier)
+            .build()
+            .unwrap();
+        BscScanClient::with_config(config).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_daily_stats_need_pro_tier() {
+        let server = MockServer::start().await;
+        Mock::given(query_param("action", "dailytx"))
+            .and(query_param("startdate", "2019-02-01"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "status": "1",
+                "message": "OK",
+                "result": [
+                    {"UTCDate": "2019-02-01", "unixTimeStamp": "1548979200", "transactionCount": 498856},
+                    {"UTCDate": "2019-02-02", "unixTimeStamp": "1549065600", "transactionCount": 450314}
+                ]
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(query_param("action", "dailynetutilization"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "status": "0",
+                "message": "NOTOK",
+                "result": "Sorry, it looks like you are trying to access an API Pro endpoint. Contact us to upgrade to API Pro."
+            })))
+            .mount(&server)
+            .await;
+        let start = NaiveDate::from_ymd_opt(2019, 2, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2019, 2, 2).unwrap();
+
+        let free = client(&server, false);
+        let error = free
+            .get_daily_tx_count(start, end, "asc")
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(error, Error::ProEndpointRequired(ref endpoint) if endpoint == "stats/dailytx")
+        );
+        assert!(server.received_requests().await.unwrap().is_empty());
+
+        let pro = client(&server, true);
+        let days = pro.get_daily_tx_count(start, end, "asc").await.unwrap();
+        assert_eq!(days.len(), 2);
+        assert_eq!(days[0].utc_date, start);
+        assert_eq!(days[1].transaction_count, 450314);
+
+        let error = pro
+            .get_daily_network_utilization(start, end, "asc")
+            .await
+            .unwrap_err();
+        assert!(matches!(error, Error::ProEndpointRequired(_)));
+    }
+}
@@ -1,8 +1,27 @@
 //! Token-related API endpoints
 
-use crate::client::types::{TokenBalance, TokenTransfer};
+use crate::abi::{address_to_word, decode_string, selector, word_to_u256};
+use crate::chain::{Chain, StablecoinRegistry};
+use crate::client::types::{TokenBalance, TokenMetadata, TokenTransfer};
 use crate::client::BscScanClient;
-use crate::error::Result;
+use crate::error::{Error, Result};
+use crate::primitives::{Address, TokenAmount, U256};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Page size of `addresstokenbalance` requests
+const HOLDINGS_PAGE_SIZE: usize = 100;
+
+/// Entry of the `addresstokenbalance` response
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct TokenHolding {
+    token_address: String,
+    token_name: String,
+    token_symbol: String,
+    token_quantity: String,
+    token_divisor: String,
+}
 
 /// Token endpoints
 pub trait TokenEndpoints {
@@ -16,10 +35,11 @@ pub trait TokenEndpoints {
     /// - `page`: Page number (1-indexed)
     /// - `offset`: Number of transfers per page (max 10000)
     /// - `sort`: "asc" or "desc"
+    #[allow(clippy::too_many_arguments)]
     async fn get_token_transfers(
         &self,
-        address: &str,
-        contract_address: Option<&str>,
+        address: &Address,
+        contract_address: Option<&Address>,
         start_block: u64,
         end_block: u64,
         page: u32,
@@ -28,14 +48,69 @@ pub trait TokenEndpoints {
     ) -> Result<Vec<TokenTransfer>>;
 
     /// Get BEP20 token balance for an address
-    async fn get_token_balance(&self, address: &str, contract_address: &str) -> Result<TokenBalance>;
+    ///
+    /// The name, symbol, and decimals come from
+    /// [`get_token_metadata`](Self::get_token_metadata).
+    async fn get_token_balance(
+        &self,
+        address: &Address,
+        contract_address: &Address,
+    ) -> Result<TokenBalance>;
+
+    /// Get every ERC20 token an address holds, with a non-zero balance
+    ///
+    /// Uses the `addresstokenbalance` endpoint. Where the explorer doesn't
+    /// offer it (it needs an API Pro plan on Etherscan), falls back to
+    /// [`get_token_balances`](Self::get_token_balances) over the built-in
+    /// stablecoins of the client's chain.
+    async fn get_address_token_holdings(&self, address: &Address) -> Result<Vec<TokenBalance>>;
+
+    /// Get the balances of `address` in each of `contracts` via `eth_call`,
+    /// skipping zero balances
+    async fn get_token_balances(
+        &self,
+        address: &Address,
+        contracts: &[Address],
+    ) -> Result<Vec<TokenBalance>>;
+
+    /// Get a token's name, symbol, and decimals, read from the contract
+    ///
+    /// Metadata never changes, so it is kept for the lifetime of the client.
+    /// Fails if the contract doesn't report its decimals.
+    async fn get_token_metadata(&self, contract: &Address) -> Result<TokenMetadata>;
+
+    /// Call a contract's view function at the latest block (`eth_call`)
+    ///
+    /// `data` is the `0x`-prefixed calldata; returns the hex return data.
+    async fn eth_call(&self, to: &Address, data: &str) -> Result<String>;
+
+    /// Get the decimals of an ERC20 token, read from the contract
+    async fn get_erc20_decimals(&self, contract: &Address) -> Result<u8>;
+
+    /// Get an ERC20 balance with `balanceOf`, scaled by the token's decimals
+    ///
+    /// Unlike [`get_token_balance`](Self::get_token_balance), the decimals
+    /// come from the contract itself.
+    async fn get_erc20_balance_of(
+        &self,
+        contract: &Address,
+        owner: &Address,
+    ) -> Result<TokenAmount>;
+
+    /// Get how much `spender` may transfer from `owner` (`allowance`), scaled by the token's decimals
+    async fn get_erc20_allowance(
+        &self,
+        contract: &Address,
+        owner: &Address,
+        spender: &Address,
+    ) -> Result<TokenAmount>;
 }
 
 impl TokenEndpoints for BscScanClient {
     async fn get_token_transfers(
         &self,
-        address: &str,
-        contract_address: Option<&str>,
+        address: &Address,
+        contract_address: Option<&Address>,
         start_block: u64,
         end_block: u64,
         page: u32,
@@ -55,18 +130,19 @@ impl TokenEndpoints for BscScanClient {
             params.push(("contractaddress", contract.to_string()));
         }
 
-        let params_ref: Vec<(&str, &str)> = params
-            .iter()
-            .map(|(k, v)| (k.as_ref(), v.as_ref()))
-            .collect();
+        let params_ref: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
 
         self.request("account", "tokentx", &params_ref).await
     }
 
-    async fn get_token_balance(&self, address: &str, contract_address: &str) -> Result<TokenBalance> {
+    async fn get_token_balance(
+        &self,
+        address: &Address,
+        contract_address: &Address,
+    ) -> Result<TokenBalance> {
         let params = [
-            ("contractaddress", contract_address),
-            ("address", address),
+            ("contractaddress", contract_address.as_str()),
+            ("address", address.as_str()),
             ("tag", "latest"),
         ];
 
@@ -74,14 +150,361 @@ impl TokenEndpoints for BscScanClient {
             .request_simple("account", "tokenbalance", &params)
             .await?;
 
-        // Note: We don't have token metadata from this endpoint
-        // In real usage, you might want to call another endpoint or maintain a token list
+        // The endpoint reports the raw balance only
+        let metadata = self.get_token_metadata(contract_address).await?;
         Ok(TokenBalance {
             contract_address: contract_address.to_string(),
-            token_name: String::new(),
-            token_symbol: String::new(),
-            token_decimal: "18".to_string(), // Default to 18
+            token_name: metadata.name,
+            token_symbol: metadata.symbol,
+            token_decimal: metadata.decimals.to_string(),
             balance: balance_str,
         })
     }
+
+    async fn get_address_token_holdings(&self, address: &Address) -> Result<Vec<TokenBalance>> {
+        let offset = HOLDINGS_PAGE_SIZE.to_string();
+        let mut holdings = Vec::new();
+        for page in 1.. {
+            let page = page.to_string();
+            let params = [
+                ("address", address.as_str()),
+                ("page", page.as_str()),
+                ("offset", offset.as_str()),
+            ];
+            let batch: Vec<TokenHolding> = match self
+                .request("account", "addresstokenbalance", &params)
+                .await
+            {
+                Ok(batch) => batch,
+                // Endpoint unavailable on this explorer or plan
+                Err(e @ Error::ApiError { .. }) if holdings.is_empty() && !e.is_retryable() => {
+                    let contracts = known_tokens(self.chain_id());
+                    return self.get_token_balances(address, &contracts).await;
+                }
+                Err(e) => return Err(e),
+            };
+
+            let last = batch.len() < HOLDINGS_PAGE_SIZE;
+            holdings.extend(batch.into_iter().map(|holding| TokenBalance {
+                contract_address: holding.token_address,
+                token_name: holding.token_name,
+                token_symbol: holding.token_symbol,
+                token_decimal: holding.token_divisor,
+                balance: holding.token_quantity,
+            }));
+            if last {
+                break;
+            }
+        }
+        Ok(holdings)
+    }
+
+    async fn get_token_balances(
+        &self,
+        address: &Address,
+        contracts: &[Address],
+    ) -> Result<Vec<TokenBalance>> {
+        let mut balances = Vec::new();
+        for contract in contracts {
+            let raw = self
+                .call_uint(contract, "balanceOf(address)", &[address])
+                .await?;
+            if raw.is_zero() {
+                continue;
+            }
+            let metadata = self.get_token_metadata(contract).await?;
+            balances.push(TokenBalance {
+                contract_address: contract.to_string(),
+                token_name: metadata.name,
+                token_symbol: metadata.symbol,
+                token_decimal: metadata.decimals.to_string(),
+                balance: raw.to_string(),
+            });
+        }
+        Ok(balances)
+    }
+
+    async fn get_token_metadata(&self, contract: &Address) -> Result<TokenMetadata> {
+        if let Some(metadata) = self.lock_token_metadata().get(contract) {
+            return Ok(metadata.clone());
+        }
+
+        let decimals = self.get_erc20_decimals(contract).await?;
+        // Both are optional in ERC20; a failed read leaves them empty
+        let name = self
+            .call_string(contract, "name()")
+            .await
+            .unwrap_or_default();
+        let symbol = self
+            .call_string(contract, "symbol()")
+            .await
+            .unwrap_or_default();
+        let metadata = TokenMetadata {
+            contract_address: contract.clone(),
+            name,
+            symbol,
+            decimals,
+        };
+        self.lock_token_metadata()
+            .insert(contract.clone(), metadata.clone());
+        Ok(metadata)
+    }
+
+    async fn eth_call(&self, to: &Address, data: &str) -> Result<String> {
+        let params = [("to", to.as_str()), ("data", data), ("tag", "latest")];
+        self.request("proxy", "eth_call", &params).await
+    }
+
+    async fn get_erc20_decimals(&self, contract: &Address) -> Result<u8> {
+        let decimals = self.call_uint(contract, "decimals()", &[]).await?;
+        if decimals > U256::from(u8::MAX) {
+            return Err(Error::api_error(format!(
+                "{} reports {} decimals",
+                contract, decimals
+            )));
+        }
+        Ok(decimals.as_u32() as u8)
+    }
+
+    async fn get_erc20_balance_of(
+        &self,
+        contract: &Address,
+        owner: &Address,
+    ) -> Result<TokenAmount> {
+        let decimals = self.get_token_metadata(contract).await?.decimals;
+        let raw = self
+            .call_uint(contract, "balanceOf(address)", &[owner])
+            .await?;
+        Ok(TokenAmount::new(raw, decimals))
+    }
+
+    async fn get_erc20_allowance(
+        &self,
+        contract: &Address,
+        owner: &Address,
+        spender: &Address,
+    ) -> Result<TokenAmount> {
+        let decimals = self.get_token_metadata(contract).await?.decimals;
+        let raw = self
+            .call_uint(contract, "allowance(address,address)", &[owner, spender])
+            .await?;
+        Ok(TokenAmount::new(raw, decimals))
+    }
+}
+
+/// Built-in stablecoin contracts on a chain
+fn known_tokens(chain_id: u64) -> Vec<Address> {
+    let chain = Chain::from(chain_id);
+    StablecoinRegistry::builtin()
+        .entries()
+        .iter()
+        .filter(|entry| entry.chain == chain)
+        .map(|entry| entry.contract_address.clone())
+        .collect()
+}
+
+impl BscScanClient {
+    fn lock_token_metadata(&self) -> std::sync::MutexGuard<'_, HashMap<Address, TokenMetadata>> {
+        self.token_metadata
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Call a view function without arguments returning a `string`
+    async fn call_string(&self, contract: &Address, signature: &str) -> Result<String> {
+        let data = format!("0x{}", selector(signature));
+        let output = self.eth_call(contract, &data).await?;
+        decode_string(&output)
+            .ok_or_else(|| Error::api_error(format!("{} returned {}", signature, output)))
+    }
+
+    /// Call a view function taking addresses and returning a `uint`
+    async fn call_uint(
+        &self,
+        contract: &Address,
+        signature: &str,
+        args: &[&Address],
+    ) -> Result<U256> {
+        let mut data = format!("0x{}", selector(signature));
+        for arg in args {
+            data.push_str(&address_to_word(arg));
+        }
+
+        let output = self.eth_call(contract, &data).await?;
+        // Accounts without code answer `0x` instead of reverting
+        let word = output.trim_start_matches("0x").get(..64).ok_or_else(|| {
+            Error::api_error(format!(
+                "{} on {} returned no data; is it an ERC20 contract?",
+                signature, contract
+            ))
+        })?;
+        word_to_u256(word)
+            .ok_or_else(|| Error::api_error(format!("{} returned {}", signature, output)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ClientConfig;
+    use rust_decimal::Decimal;
+    use wiremock::matchers::{query_param, query_param_contains};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn rpc(result: &str) -> ResponseTemplate {
+        ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "jsonrpc": "2.0", "id": 1, "result": result
+        }))
+    }
+
+    /// ABI-encoded `string` return data
+    fn abi_string(text: &str) -> String {
+        format!("0x{:064x}{:064x}{:0<64}", 32, text.len(), hex::encode(text))
+    }
+
+    #[tokio::test]
+    async fn test_token_holdings_fall_back_to_known_tokens() {
+        let server = MockServer::start().await;
+        let owner: Address = "0x742d35cc6634c0532925a3b844bc9e7595f0beb0"
+            .parse()
+            .unwrap();
+
+        Mock::given(query_param("action", "addresstokenbalance"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "status": "0",
+                "message": "NOTOK",
+                "result": "Sorry, it looks like you are trying to access an API Pro endpoint."
+            })))
+            .mount(&server)
+            .await;
+        // Only USDC (0xa0b8...) is held
+        Mock::given(query_param("action", "eth_call"))
+            .and(query_param_contains("data", "0x70a08231"))
+            .and(query_param(
+                "to",
+                "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+            ))
+            .respond_with(rpc(&format!("0x{:064x}", 2_000_000)))
+            .mount(&server)
+            .await;
+        Mock::given(query_param("action", "eth_call"))
+            .and(query_param_contains("data", "0x70a08231"))
+            .respond_with(rpc(&format!("0x{:064x}", 0)))
+            .mount(&server)
+            .await;
+        Mock::given(query_param("action", "eth_call"))
+            .and(query_param("data", "0x313ce567"))
+            .respond_with(rpc(&format!("0x{:064x}", 6)))
+            .mount(&server)
+            .await;
+        Mock::given(query_param("action", "eth_call"))
+            .and(query_param("data", "0x95d89b41"))
+            .respond_with(rpc(&abi_string("USDC")))
+            .mount(&server)
+            .await;
+        Mock::given(query_param("action", "eth_call"))
+            .and(query_param("data", "0x06fdde03"))
+            .respond_with(rpc(&abi_string("USD Coin")))
+            .mount(&server)
+            .await;
+
+        let config = ClientConfig::builder()
+            .api_key("key")
+            .base_url(server.uri())
+            .build()
+            .unwrap();
+        let client = BscScanClient::with_config(config).unwrap();
+
+        let holdings = client.get_address_token_holdings(&owner).await.unwrap();
+        assert_eq!(holdings.len(), 1);
+        assert_eq!(holdings[0].token_symbol, "USDC");
+        assert_eq!(holdings[0].value_tokens(), Decimal::TWO);
+    }
+
+    #[tokio::test]
+    async fn test_erc20_reads_apply_contract_decimals() {
+        let server = MockServer::start().await;
+        let usdt: Address = "0xdac17f958d2ee523a2206206994597c13d831ec7"
+            .parse()
+            .unwrap();
+        let owner: Address = "0x742d35cc6634c0532925a3b844bc9e7595f0beb0"
+            .parse()
+            .unwrap();
+        let spender: Address = "0x7a250d5630b4cf539739df2c5dacb4c659f2488d"
+            .parse()
+            .unwrap();
+
+        Mock::given(query_param("action", "eth_call"))
+            .and(query_param("data", "0x313ce567"))
+            .respond_with(rpc(&format!("0x{:064x}", 6)))
+            .mount(&server)
+            .await;
+        Mock::given(query_param("action", "eth_call"))
+            .and(query_param("data", "0x06fdde03"))
+            .respond_with(rpc(&abi_string("Tether USD")))
+            .mount(&server)
+            .await;
+        // bytes32, like MKR
+        Mock::given(query_param("action", "eth_call"))
+            .and(query_param("data", "0x95d89b41"))
+            .respond_with(rpc(&format!("0x{:0<64}", hex::encode("USDT"))))
+            .mount(&server)
+            .await;
+        Mock::given(query_param("action", "tokenbalance"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "status": "1", "message": "OK", "result": "12500000"
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(query_param("action", "eth_call"))
+            .and(query_param(
+                "data",
+                format!("0x70a08231{}", address_to_word(&owner)),
+            ))
+            .respond_with(rpc(&format!("0x{:064x}", 12_500_000)))
+            .mount(&server)
+            .await;
+        Mock::given(query_param("action", "eth_call"))
+            .and(query_param_contains("data", "0xdd62ed3e"))
+            .respond_with(rpc(&format!("0x{:064x}", 1_000_000)))
+            .mount(&server)
+            .await;
+
+        let config = ClientConfig::builder()
+            .api_key("key")
+            .base_url(server.uri())
+            .build()
+            .unwrap();
+        let client = BscScanClient::with_config(config).unwrap();
+
+        let balance = client.get_erc20_balance_of(&usdt, &owner).await.unwrap();
+        assert_eq!(balance.to_string(), "12.5");
+        assert_eq!(balance.decimals(), 6);
+        let allowance = client
+            .get_erc20_allowance(&usdt, &owner, &spender)
+            .await
+            .unwrap();
+        assert_eq!(allowance.to_string(), "1");
+
+        let balance = client.get_token_balance(&owner, &usdt).await.unwrap();
+        assert_eq!(
+            (balance.token_name.as_str(), balance.token_symbol.as_str()),
+            ("Tether USD", "USDT")
+        );
+        assert_eq!(balance.value_tokens(), Decimal::new(125, 1));
+
+        // Not a contract: the node answers `0x`
+        let server = MockServer::start().await;
+        Mock::given(query_param("action", "eth_call"))
+            .respond_with(rpc("0x"))
+            .mount(&server)
+            .await;
+        let config = ClientConfig::builder()
+            .api_key("key")
+            .base_url(server.uri())
+            .build()
+            .unwrap();
+        let client = BscScanClient::with_config(config).unwrap();
+        assert!(client.get_erc20_decimals(&owner).await.is_err());
+    }
 }
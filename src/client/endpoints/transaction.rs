@@ -1,49 +1,72 @@
 //! Transaction-related API endpoints
 
-use crate::client::types::{Transaction, TransactionReceipt};
+use crate::client::types::{RpcReceipt, RpcTransaction, Transaction};
 use crate::client::BscScanClient;
 use crate::error::{Error, Result};
+use crate::primitives::TxHash;
+use futures::stream::{self, StreamExt, TryStreamExt};
+use std::collections::HashMap;
 
 /// Transaction endpoints
 pub trait TransactionEndpoints {
-    /// Get transaction by hash
-    async fn get_transaction(&self, tx_hash: &str) -> Result<Transaction>;
+    /// Get transaction by hash, converted to the account-module format
+    async fn get_transaction(&self, tx_hash: &TxHash) -> Result<Transaction>;
+
+    /// Get transaction by hash as returned by the node (hex quantities)
+    async fn get_rpc_transaction(&self, tx_hash: &TxHash) -> Result<RpcTransaction>;
 
     /// Get transaction receipt
-    async fn get_transaction_receipt(&self, tx_hash: &str) -> Result<TransactionReceipt>;
+    async fn get_transaction_receipt(&self, tx_hash: &TxHash) -> Result<RpcReceipt>;
 
     /// Get number of confirmations for a transaction
-    async fn get_confirmations(&self, tx_hash: &str) -> Result<u64>;
+    async fn get_confirmations(&self, tx_hash: &TxHash) -> Result<u64>;
+
+    /// Get the confirmations of several transactions
+    ///
+    /// Fetches the current block once and the receipts, bypassing the
+    /// response cache, up to the client's rate limit at a time. Pending and
+    /// unknown transactions have 0 confirmations.
+    async fn get_confirmations_batch(&self, tx_hashes: &[TxHash]) -> Result<HashMap<TxHash, u64>>;
 
     /// Get current block number
+    ///
+    /// Heights are shared between callers for
+    /// [`block_number_ttl_seconds`](crate::ClientConfig::block_number_ttl_seconds).
     async fn get_block_number(&self) -> Result<u64>;
 }
 
 impl TransactionEndpoints for BscScanClient {
-    async fn get_transaction(&self, tx_hash: &str) -> Result<Transaction> {
-        let params = [("txhash", tx_hash)];
+    async fn get_transaction(&self, tx_hash: &TxHash) -> Result<Transaction> {
+        Ok(self.get_rpc_transaction(tx_hash).await?.into())
+    }
 
-        let proxy_tx: crate::client::types::ProxyTransaction = self
+    async fn get_rpc_transaction(&self, tx_hash: &TxHash) -> Result<RpcTransaction> {
+        let params = [("txhash", tx_hash.as_str())];
+
+        // The node answers `null` for unknown transactions
+        let tx: Option<RpcTransaction> = self
             .request("proxy", "eth_getTransactionByHash", &params)
             .await?;
-        
-        Ok(Transaction::from(proxy_tx))
+        tx.ok_or_else(|| Error::TransactionNotFound(tx_hash.to_string()))
     }
 
-    async fn get_transaction_receipt(&self, tx_hash: &str) -> Result<TransactionReceipt> {
-        let params = [("txhash", tx_hash)];
+    async fn get_transaction_receipt(&self, tx_hash: &TxHash) -> Result<RpcReceipt> {
+        let params = [("txhash", tx_hash.as_str())];
 
-        self.request("proxy", "eth_getTransactionReceipt", &params)
-            .await
+        // `null` until the transaction is mined
+        let receipt: Option<RpcReceipt> = self
+            .request("proxy", "eth_getTransactionReceipt", &params)
+            .await?;
+        receipt.ok_or_else(|| Error::TransactionNotFound(tx_hash.to_string()))
     }
 
-    async fn get_confirmations(&self, tx_hash: &str) -> Result<u64> {
-        // Get transaction to find its block number
-        let tx = self.get_transaction(tx_hash).await?;
-        let tx_block: u64 = tx
-            .block_number
-            .parse()
-            .map_err(|_| Error::generic("Invalid block number in transaction"))?;
+    async fn get_confirmations(&self, tx_hash: &TxHash) -> Result<u64> {
+        // Get transaction to find its block number; pending transactions have none
+        let tx = self.get_rpc_transaction(tx_hash).await?;
+        let tx_block = match tx.block_number_u64() {
+            Some(block) => block,
+            None => return Ok(0),
+        };
 
         // Get current block number
         let current_block = self.get_block_number().await?;
@@ -56,16 +79,97 @@ impl TransactionEndpoints for BscScanClient {
         }
     }
 
+    async fn get_confirmations_batch(&self, tx_hashes: &[TxHash]) -> Result<HashMap<TxHash, u64>> {
+        if tx_hashes.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let head = self.latest_block_number().await?;
+        let concurrency = self.config.rate_limit_per_second.max(1) as usize;
+        stream::iter(tx_hashes)
+            .map(|tx_hash| async move {
+                let params = [("txhash", tx_hash.as_str())];
+                // `null` until the transaction is mined
+                let receipt: Option<RpcReceipt> = self
+                    .request_uncached("proxy", "eth_getTransactionReceipt", &params)
+                    .await?;
+                let confirmations = match receipt {
+                    Some(receipt) => {
+                        let block = receipt.block_number_u64()?;
+                        if head >= block {
+                            head - block + 1
+                        } else {
+                            0
+                        }
+                    }
+                    None => 0,
+                };
+                Ok((tx_hash.clone(), confirmations))
+            })
+            .buffer_unordered(concurrency)
+            .try_collect()
+            .await
+    }
+
     async fn get_block_number(&self) -> Result<u64> {
-        let params: [(&str, &str); 0] = [];
-        let block_hex: String = self
-            .request_simple("proxy", "eth_blockNumber", &params)
-            .await?;
+        self.latest_block_number().await
+    }
+}
 
-        // Parse hex string (e.g., "0x1a2b3c")
-        let block_num = u64::from_str_radix(block_hex.trim_start_matches("0x"), 16)
-            .map_err(|_| Error::generic("Invalid block number format"))?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ClientConfig;
+    use wiremock::matchers::query_param;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
 
-        Ok(block_num)
+    fn rpc(result: serde_json::Value) -> ResponseTemplate {
+        ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "jsonrpc": "2.0", "id": 1, "result": result
+        }))
+    }
+
+    #[tokio::test]
+    async fn test_confirmations_batch() {
+        let server = MockServer::start().await;
+        let mined = TxHash::parse(&format!("0x{}", "aa".repeat(32))).unwrap();
+        let pending = TxHash::parse(&format!("0x{}", "bb".repeat(32))).unwrap();
+        Mock::given(query_param("action", "eth_blockNumber"))
+            .respond_with(rpc(serde_json::json!("0x64")))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(query_param("txhash", mined.as_str()))
+            .respond_with(rpc(serde_json::json!({
+                "blockHash": format!("0x{}", "11".repeat(32)),
+                "blockNumber": "0x5a",
+                "contractAddress": null,
+                "cumulativeGasUsed": "0x5208",
+                "gasUsed": "0x5208",
+                "logs": [],
+                "status": "0x1",
+                "transactionHash": mined.as_str(),
+                "transactionIndex": "0x0"
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(query_param("txhash", pending.as_str()))
+            .respond_with(rpc(serde_json::Value::Null))
+            .mount(&server)
+            .await;
+
+        let config = ClientConfig::builder()
+            .api_key("key")
+            .base_url(server.uri())
+            .build()
+            .unwrap();
+        let client = BscScanClient::with_config(config).unwrap();
+
+        let confirmations = client
+            .get_confirmations_batch(&[mined.clone(), pending.clone()])
+            .await
+            .unwrap();
+        assert_eq!(confirmations[&mined], 11);
+        assert_eq!(confirmations[&pending], 0);
     }
 }
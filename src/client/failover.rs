@@ -0,0 +1,110 @@
+//! Failover between explorer base URLs
+//!
+//! Requests go to the active URL, initially the primary `base_url`. After
+//! `failover_threshold` consecutive failures (transport errors or HTTP 5xx)
+//! the next URL becomes active. While a fallback is active, one request per
+//! `failback_interval` probes the primary again and fails back if it succeeds.
+
+use crate::rt::Instant;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Base URLs in order of preference and which one is in use
+pub(crate) struct Failover {
+    urls: Vec<String>,
+    threshold: u32,
+    failback_interval: Duration,
+    state: Mutex<State>,
+}
+
+struct State {
+    active: usize,
+    failures: u32,
+    last_probe: Instant,
+}
+
+/// URL chosen for a request
+pub(crate) struct Target<'a> {
+    pub index: usize,
+    pub url: &'a str,
+    /// Whether this request probes the primary while a fallback is active
+    pub probe: bool,
+}
+
+impl Failover {
+    pub fn new(urls: Vec<String>, threshold: u32, failback_interval: Duration) -> Self {
+        Self {
+            urls,
+            threshold: threshold.max(1),
+            failback_interval,
+            state: Mutex::new(State {
+                active: 0,
+                failures: 0,
+                last_probe: Instant::now(),
+            }),
+        }
+    }
+
+    /// URL currently in use
+    pub fn active_url(&self) -> &str {
+        &self.urls[self.lock().active]
+    }
+
+    /// Whether a fallback URL is in use
+    pub fn is_failed_over(&self) -> bool {
+        self.lock().active != 0
+    }
+
+    /// URL for the next request
+    pub fn select(&self) -> Target<'_> {
+        let mut state = self.lock();
+        if state.active != 0 && state.last_probe.elapsed() >= self.failback_interval {
+            state.last_probe = Instant::now();
+            return self.target(0, true);
+        }
+        self.target(state.active, false)
+    }
+
+    /// URL to retry a failed probe on
+    pub fn fallback(&self) -> Target<'_> {
+        self.target(self.lock().active, false)
+    }
+
+    /// Record the outcome of a request to `index`
+    pub fn report(&self, index: usize, ok: bool) {
+        let mut state = self.lock();
+        if index != state.active {
+            // A probe of the primary
+            if index == 0 && ok {
+                state.active = 0;
+                state.failures = 0;
+            }
+            return;
+        }
+
+        if ok {
+            state.failures = 0;
+            return;
+        }
+        state.failures += 1;
+        if state.failures >= self.threshold && self.urls.len() > 1 {
+            state.active = (state.active + 1) % self.urls.len();
+            state.failures = 0;
+            state.last_probe = Instant::now();
+        }
+    }
+
+    fn target(&self, index: usize, probe: bool) -> Target<'_> {
+        Target {
+            index,
+            url: &self.urls[index],
+            probe,
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
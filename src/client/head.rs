@@ -0,0 +1,106 @@
+//! Short-lived cache of the chain head
+//!
+//! Confirmation checks and monitors ask for the current block number
+//! constantly. [`HeadCache`] keeps the last height for a few seconds and lets
+//! concurrent callers wait for a single lookup instead of each sending one.
+
+use crate::error::Result;
+use crate::rt::Instant;
+use std::future::Future;
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+/// Latest known block number and when it was fetched
+pub(crate) struct HeadCache {
+    ttl: Duration,
+    latest: Mutex<Option<(u64, Instant)>>,
+}
+
+impl HeadCache {
+    /// Keep heights for `ttl`; a zero TTL disables the cache
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            latest: Mutex::new(None),
+        }
+    }
+
+    /// Cached height, or the result of `fetch` if it expired
+    ///
+    /// The lock is held during `fetch`, so callers arriving meanwhile get
+    /// its result.
+    pub async fn get_or_fetch<F, Fut>(&self, fetch: F) -> Result<u64>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<u64>>,
+    {
+        if self.ttl.is_zero() {
+            return fetch().await;
+        }
+
+        let mut latest = self.latest.lock().await;
+        if let Some((block, fetched_at)) = *latest {
+            if fetched_at.elapsed() < self.ttl {
+                return Ok(block);
+            }
+        }
+        let block = fetch().await?;
+        *latest = Some((block, Instant::now()));
+        Ok(block)
+    }
+
+    /// Record a height fetched elsewhere, e.g. by a block watcher
+    pub async fn observe(&self, block: u64) {
+        if self.ttl.is_zero() {
+            return;
+        }
+        let mut latest = self.latest.lock().await;
+        // Lagging backends can briefly report an older head; keep the newer one
+        let block = match *latest {
+            Some((known, fetched_at)) if fetched_at.elapsed() < self.ttl => known.max(block),
+            _ => block,
+        };
+        *latest = Some((block, Instant::now()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_concurrent_callers_share_one_lookup() {
+        let cache = Arc::new(HeadCache::new(Duration::from_secs(60)));
+        let fetches = Arc::new(AtomicU64::new(0));
+
+        let lookups = (0..10).map(|_| {
+            let cache = cache.clone();
+            let fetches = fetches.clone();
+            async move {
+                cache
+                    .get_or_fetch(|| async {
+                        fetches.fetch_add(1, Ordering::SeqCst);
+                        tokio::task::yield_now().await;
+                        Ok(100)
+                    })
+                    .await
+            }
+        });
+        let heights = futures::future::join_all(lookups).await;
+        assert!(heights
+            .iter()
+            .all(|height| *height.as_ref().unwrap() == 100));
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+
+        cache.observe(105).await;
+        assert_eq!(cache.get_or_fetch(|| async { Ok(0) }).await.unwrap(), 105);
+        cache.observe(103).await;
+        assert_eq!(cache.get_or_fetch(|| async { Ok(0) }).await.unwrap(), 105);
+
+        let uncached = HeadCache::new(Duration::ZERO);
+        assert_eq!(uncached.get_or_fetch(|| async { Ok(7) }).await.unwrap(), 7);
+        assert_eq!(uncached.get_or_fetch(|| async { Ok(8) }).await.unwrap(), 8);
+    }
+}
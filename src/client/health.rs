@@ -0,0 +1,106 @@
+//! Request outcome tracking for health checks
+
+use super::rate_limit::LimiterLoad;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+/// Number of recent requests the error rate is computed over
+const WINDOW: usize = 100;
+
+/// Health of a client, e.g. for readiness probes
+///
+/// Computed from requests the client has already made; see
+/// [`BscScanClient::ping`](crate::BscScanClient::ping) for an active check.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ClientHealth {
+    /// Whether fewer than half of the recent requests failed
+    pub healthy: bool,
+    /// Time of the last successful request
+    pub last_success: Option<DateTime<Utc>>,
+    /// Message of the last failed request
+    pub last_error: Option<String>,
+    /// Share of failed requests among the recent ones
+    pub error_rate: f64,
+    /// Number of recent requests `error_rate` is based on (at most 100)
+    pub recent_requests: usize,
+    /// Base URL requests currently go to
+    pub active_base_url: String,
+    /// Whether a fallback base URL is in use
+    pub failed_over: bool,
+    /// Number of API keys configured
+    pub api_keys: usize,
+    /// Number of keys the explorer rejected, skipped until the keys are updated
+    pub quarantined_keys: usize,
+    /// Requests waiting for the rate limiter
+    pub rate_limit_queue: usize,
+    /// Share of the rate limit used in the last second (0.0 to 1.0)
+    pub rate_limit_utilization: f64,
+}
+
+/// Outcomes of the most recent requests
+#[derive(Default)]
+pub(crate) struct HealthTracker {
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    outcomes: VecDeque<bool>,
+    last_success: Option<DateTime<Utc>>,
+    last_error: Option<String>,
+}
+
+impl HealthTracker {
+    /// Record a request that succeeded or failed with `error`
+    pub fn record(&self, error: Option<String>) {
+        let mut state = self.lock();
+        if state.outcomes.len() == WINDOW {
+            state.outcomes.pop_front();
+        }
+        state.outcomes.push_back(error.is_none());
+        match error {
+            None => state.last_success = Some(Utc::now()),
+            Some(error) => state.last_error = Some(error),
+        }
+    }
+
+    /// Health from the recorded outcomes plus the client's failover and key state
+    pub fn snapshot(
+        &self,
+        active_base_url: &str,
+        failed_over: bool,
+        api_keys: usize,
+        quarantined_keys: usize,
+        limiter: LimiterLoad,
+    ) -> ClientHealth {
+        let state = self.lock();
+        let recent_requests = state.outcomes.len();
+        let failures = state.outcomes.iter().filter(|ok| !**ok).count();
+        let error_rate = if recent_requests == 0 {
+            0.0
+        } else {
+            failures as f64 / recent_requests as f64
+        };
+        ClientHealth {
+            healthy: error_rate < 0.5,
+            last_success: state.last_success,
+            last_error: state.last_error.clone(),
+            error_rate,
+            recent_requests,
+            active_base_url: active_base_url.to_string(),
+            failed_over,
+            api_keys,
+            quarantined_keys,
+            rate_limit_queue: limiter.queued,
+            rate_limit_utilization: limiter.utilization,
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
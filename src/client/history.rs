@@ -0,0 +1,105 @@
+//! Complete address history over a block range
+
+use crate::client::types::{BeaconWithdrawal, InternalTransaction, TokenTransfer, Transaction};
+use crate::client::BscScanClient;
+use crate::error::Result;
+use crate::primitives::Address;
+use serde::de::DeserializeOwned;
+
+/// Results requested per page
+const PAGE_SIZE: u32 = 1000;
+
+/// Explorers return at most `page * offset <= 10_000` results per query
+const MAX_PAGES: u32 = 10;
+
+/// List entries that carry the block they were included in
+pub(crate) trait BlockIndexed {
+    fn block(&self) -> u64;
+}
+
+impl BlockIndexed for Transaction {
+    fn block(&self) -> u64 {
+        self.block_number
+    }
+}
+
+impl BlockIndexed for InternalTransaction {
+    fn block(&self) -> u64 {
+        self.block_number
+    }
+}
+
+impl BlockIndexed for TokenTransfer {
+    fn block(&self) -> u64 {
+        self.block_number
+    }
+}
+
+impl BlockIndexed for BeaconWithdrawal {
+    fn block(&self) -> u64 {
+        self.block_number
+    }
+}
+
+impl BscScanClient {
+    /// Fetch every entry of a list endpoint for `address` between two blocks (inclusive)
+    ///
+    /// When a query hits the explorer's result window, the range is narrowed
+    /// to start at the last block returned and queried again, so histories
+    /// longer than 10,000 entries are still fetched completely.
+    pub(crate) async fn fetch_history<T>(
+        &self,
+        action: &str,
+        address: &Address,
+        start_block: u64,
+        end_block: u64,
+    ) -> Result<Vec<T>>
+    where
+        T: DeserializeOwned + BlockIndexed,
+    {
+        let end = end_block.to_string();
+        let offset = PAGE_SIZE.to_string();
+        let mut start_block = start_block;
+        let mut results = Vec::new();
+
+        loop {
+            let start = start_block.to_string();
+            let mut window: Vec<T> = Vec::new();
+            let mut truncated = false;
+
+            for page_number in 1..=MAX_PAGES {
+                let page = page_number.to_string();
+                let params = [
+                    ("address", address.as_str()),
+                    ("startblock", start.as_str()),
+                    ("endblock", end.as_str()),
+                    ("page", page.as_str()),
+                    ("offset", offset.as_str()),
+                    ("sort", "asc"),
+                ];
+
+                let batch: Vec<T> = self.request_uncached("account", action, &params).await?;
+                let full_page = batch.len() as u32 >= PAGE_SIZE;
+                window.extend(batch);
+
+                if !full_page {
+                    break;
+                }
+                truncated = page_number == MAX_PAGES;
+            }
+
+            let last_block = window.last().map(BlockIndexed::block);
+            match last_block {
+                // The last block may be cut off mid-way: drop it and query again from there
+                Some(last) if truncated && last > start_block => {
+                    results.extend(window.into_iter().filter(|entry| entry.block() < last));
+                    start_block = last;
+                }
+                _ => {
+                    results.extend(window);
+                    return Ok(results);
+                }
+            }
+        }
+    }
+}
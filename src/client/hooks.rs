@@ -0,0 +1,131 @@
+//! Request/response hooks
+//!
+//! Hooks see every HTTP request the client sends to the explorer, e.g. to
+//! add headers required by a corporate gateway, log traffic, or collect
+//! metrics. Response hooks see the parsed body of every response that
+//! wasn't served from the cache. Hooks run in the order they were added.
+
+use crate::error::Result;
+use serde_json::Value;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Observer and modifier of explorer requests
+///
+/// Both methods default to doing nothing.
+///
+/// # Example
+/// ```
+/// use cryptopay::client::RequestHook;
+/// use cryptopay::ClientConfig;
+///
+/// struct TenantHeader;
+///
+/// impl RequestHook for TenantHeader {
+///     fn on_request(&self, request: &mut reqwest::Request) -> cryptopay::Result<()> {
+///         request
+///             .headers_mut()
+///             .insert("x-tenant", reqwest::header::HeaderValue::from_static("shop-1"));
+///         Ok(())
+///     }
+/// }
+///
+/// let config = ClientConfig::builder()
+///     .api_key("api-key")
+///     .hook(TenantHeader)
+///     .build()
+///     .unwrap();
+/// ```
+pub trait RequestHook: Send + Sync {
+    /// Called before a request is sent; an error aborts the request
+    ///
+    /// The URL includes the API key in its query string; take care when logging it.
+    fn on_request(&self, request: &mut reqwest::Request) -> Result<()> {
+        let _ = request;
+        Ok(())
+    }
+
+    /// Called when response headers have arrived, before the body is read
+    fn on_response(&self, response: &reqwest::Response, elapsed: Duration) {
+        let _ = (response, elapsed);
+    }
+}
+
+/// Explorer request a response hook is called for
+///
+/// `params` are the endpoint parameters, without the API key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestInfo {
+    /// Chain the request was sent to
+    pub chain_id: u64,
+    /// API module, e.g. `account`
+    pub module: String,
+    /// API action, e.g. `txlist`
+    pub action: String,
+    /// Endpoint parameters
+    pub params: Vec<(String, String)>,
+    /// HTTP status of the response
+    pub status: u16,
+}
+
+/// Observer of explorer response bodies, see
+/// [`ClientConfigBuilder::response_hook`](crate::config::ClientConfigBuilder::response_hook)
+pub type ResponseHook = Arc<dyn Fn(&RequestInfo, &Value) + Send + Sync>;
+
+/// Hooks registered on a [`ClientConfig`](crate::ClientConfig)
+#[derive(Clone, Default)]
+pub struct RequestHooks {
+    request: Vec<Arc<dyn RequestHook>>,
+    response: Vec<ResponseHook>,
+}
+
+impl RequestHooks {
+    /// Add a hook to run after the existing ones
+    pub fn push(&mut self, hook: Arc<dyn RequestHook>) {
+        self.request.push(hook);
+    }
+
+    /// Add a response hook to run after the existing ones
+    pub fn push_response(&mut self, hook: ResponseHook) {
+        self.response.push(hook);
+    }
+
+    /// Check whether no hooks are registered
+    pub fn is_empty(&self) -> bool {
+        self.request.is_empty() && self.response.is_empty()
+    }
+
+    pub(crate) fn on_request(&self, request: &mut reqwest::Request) -> Result<()> {
+        self.request
+            .iter()
+            .try_for_each(|hook| hook.on_request(request))
+    }
+
+    pub(crate) fn on_response(&self, response: &reqwest::Response, elapsed: Duration) {
+        for hook in &self.request {
+            hook.on_response(response, elapsed);
+        }
+    }
+
+    pub(crate) fn has_response_hooks(&self) -> bool {
+        !self.response.is_empty()
+    }
+
+    pub(crate) fn on_response_body(&self, info: &RequestInfo, body: &Value) {
+        for hook in &self.response {
+            hook(info, body);
+        }
+    }
+}
+
+impl fmt::Debug for RequestHooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "RequestHooks({}, responses: {})",
+            self.request.len(),
+            self.response.len()
+        )
+    }
+}
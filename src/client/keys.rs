@@ -0,0 +1,166 @@
+//! API key scheduling
+//!
+//! [`KeyScheduler`] picks the key for each request. Keys are used in
+//! proportion to their weight, so a paid key with five times the quota of a
+//! free one gets five times the requests, and among equally used keys the
+//! least recently used goes first. A key the explorer just rate limited sits
+//! out for a second while other keys are left, and a key it rejected is
+//! quarantined until the keys are replaced.
+
+use crate::config::Secret;
+use crate::rt::Instant;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+/// Time a rate-limited key is skipped for; explorer quotas are per second
+const COOLDOWN: Duration = Duration::from_secs(1);
+
+/// Picks API keys by weight, recent use, and error history
+pub(crate) struct KeyScheduler {
+    slots: Mutex<Vec<Slot>>,
+}
+
+struct Slot {
+    key: Secret<String>,
+    weight: u32,
+    uses: u64,
+    last_used: Option<Instant>,
+    cooling_until: Option<Instant>,
+    quarantined: bool,
+}
+
+impl Slot {
+    fn new((key, weight): (Secret<String>, u32)) -> Self {
+        Self {
+            key,
+            weight: weight.max(1),
+            uses: 0,
+            last_used: None,
+            cooling_until: None,
+            quarantined: false,
+        }
+    }
+
+    /// Requests sent per unit of quota
+    fn load(&self) -> f64 {
+        self.uses as f64 / f64::from(self.weight)
+    }
+}
+
+impl KeyScheduler {
+    /// Schedule `keys` with their weights
+    pub fn new(keys: Vec<(Secret<String>, u32)>) -> Self {
+        Self {
+            slots: Mutex::new(keys.into_iter().map(Slot::new).collect()),
+        }
+    }
+
+    /// Replace the keys, forgetting usage, cooldowns, and quarantines
+    pub fn replace(&self, keys: Vec<(Secret<String>, u32)>) {
+        *self.lock() = keys.into_iter().map(Slot::new).collect();
+    }
+
+    /// Index and value of the key for the next request
+    ///
+    /// Quarantined keys are skipped while any other key is left, and
+    /// cooling-down keys while any usable key isn't cooling down.
+    pub fn next(&self) -> (usize, String) {
+        let now = Instant::now();
+        let mut slots = self.lock();
+        let all_quarantined = slots.iter().all(|slot| slot.quarantined);
+        let usable = |slot: &Slot| all_quarantined || !slot.quarantined;
+        let cooling = |slot: &Slot| slot.cooling_until.is_some_and(|until| until > now);
+        let all_cooling = slots.iter().filter(|slot| usable(slot)).all(cooling);
+
+        let index = slots
+            .iter()
+            .enumerate()
+            .filter(|(_, slot)| usable(slot) && (all_cooling || !cooling(slot)))
+            .min_by(|(_, a), (_, b)| {
+                a.load()
+                    .total_cmp(&b.load())
+                    .then_with(|| a.last_used.cmp(&b.last_used))
+            })
+            .map_or(0, |(index, _)| index);
+
+        let slot = &mut slots[index];
+        slot.uses += 1;
+        slot.last_used = Some(now);
+        (index, slot.key.expose().clone())
+    }
+
+    /// Skip the key for a second after the explorer rate limited it
+    pub fn rate_limited(&self, index: usize) {
+        if let Some(slot) = self.lock().get_mut(index) {
+            slot.cooling_until = Some(Instant::now() + COOLDOWN);
+        }
+    }
+
+    /// Take a key the explorer rejected out of rotation; true if it wasn't already
+    pub fn quarantine(&self, index: usize) -> bool {
+        self.lock()
+            .get_mut(index)
+            .is_some_and(|slot| !std::mem::replace(&mut slot.quarantined, true))
+    }
+
+    /// Number of keys
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Number of quarantined keys
+    pub fn quarantined(&self) -> usize {
+        self.lock().iter().filter(|slot| slot.quarantined).count()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<Slot>> {
+        self.slots
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scheduler(weights: &[u32]) -> KeyScheduler {
+        KeyScheduler::new(
+            weights
+                .iter()
+                .enumerate()
+                .map(|(index, weight)| (Secret::new(format!("key{}", index)), *weight))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_keys_are_used_by_weight() {
+        let keys = scheduler(&[1, 3]);
+        let mut uses = [0; 2];
+        for _ in 0..40 {
+            uses[keys.next().0] += 1;
+        }
+        assert_eq!(uses, [10, 30]);
+    }
+
+    #[test]
+    fn test_rate_limited_and_quarantined_keys_are_skipped() {
+        let keys = scheduler(&[1, 1, 1]);
+        keys.rate_limited(0);
+        assert!(keys.quarantine(1));
+        assert!(!keys.quarantine(1));
+        assert_eq!(keys.quarantined(), 1);
+        for _ in 0..3 {
+            assert_eq!(keys.next(), (2, "key2".to_string()));
+        }
+
+        // With every usable key cooling down, the least used one is picked
+        keys.rate_limited(2);
+        assert_eq!(keys.next().0, 0);
+
+        keys.replace(vec![(Secret::new("new".to_string()), 1)]);
+        assert_eq!((keys.len(), keys.quarantined()), (1, 0));
+        assert_eq!(keys.next(), (0, "new".to_string()));
+    }
+}
@@ -1,30 +1,68 @@
 //! Etherscan API client module
 
-use crate::config::ClientConfig;
-use crate::error::{Error, Result};
-use governor::{DefaultDirectRateLimiter, Quota, RateLimiter};
-use moka::future::Cache;
-use reqwest::Client;
+use crate::config::{validate_api_keys, ClientConfig, Secret, VcrMode};
+use crate::error::{ApiErrorKind, Error, Result};
+use crate::events::Event;
+use crate::primitives::Address;
+use reqwest::{Client, StatusCode};
 use serde::de::DeserializeOwned;
 use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
 use std::num::NonZeroU32;
-use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Semaphore;
 
+mod cache;
+mod disk_cache;
 pub mod endpoints;
+mod failover;
+mod head;
+mod health;
+mod history;
+mod hooks;
+mod keys;
+mod options;
+mod rate_limit;
+mod swap;
 pub mod types;
+mod vcr;
+pub mod watch;
 
+pub use cache::{CacheStats, EndpointCacheStats};
 pub use endpoints::*;
+pub use health::ClientHealth;
+pub use hooks::{RequestHook, RequestHooks, RequestInfo, ResponseHook};
+pub use options::{Priority, RequestOptions};
 pub use types::*;
+pub use watch::AddressEvent;
+
+use cache::ResponseCache;
+use disk_cache::DiskCache;
+use failover::Failover;
+use head::HeadCache;
+use health::HealthTracker;
+use keys::KeyScheduler;
+use rate_limit::RequestLimiter;
+use swap::Swap;
+use vcr::Recording;
 
 /// Etherscan API client with rate limiting and caching
 #[derive(Clone)]
 pub struct BscScanClient {
     config: Arc<ClientConfig>,
     http_client: Client,
-    rate_limiter: Arc<DefaultDirectRateLimiter>,
-    cache: Cache<String, Value>,
-    api_key_index: Arc<AtomicUsize>,
+    failover: Arc<Failover>,
+    health: Arc<HealthTracker>,
+    concurrency: Option<Arc<Semaphore>>,
+    rate_limiter: Arc<Swap<RequestLimiter>>,
+    cache: ResponseCache,
+    disk_cache: Option<Arc<DiskCache>>,
+    keys: Arc<KeyScheduler>,
+    token_metadata: Arc<Mutex<HashMap<Address, TokenMetadata>>>,
+    head: Arc<HeadCache>,
+    options: RequestOptions,
 }
 
 impl BscScanClient {
@@ -44,36 +82,206 @@ impl BscScanClient {
     pub fn with_config(config: ClientConfig) -> Result<Self> {
         config.validate()?;
 
-        let http_client = Client::builder()
-            .timeout(config.timeout())
-            .build()
-            .map_err(|e| Error::InvalidConfig(format!("Failed to create HTTP client: {}", e)))?;
+        let http_client = match &config.http_client {
+            Some(client) => client.clone(),
+            None => {
+                let builder = Client::builder().default_headers(config.headers()?);
+                // reqwest's wasm backend leaves timeouts and proxies to the browser
+                #[cfg(not(target_arch = "wasm32"))]
+                let builder = {
+                    let mut builder = builder
+                        .timeout(config.timeout())
+                        .gzip(config.compression)
+                        .brotli(config.compression);
+                    if let Some(max) = config.pool_max_idle_per_host {
+                        builder = builder.pool_max_idle_per_host(max);
+                    }
+                    if let Some(seconds) = config.pool_idle_timeout_seconds {
+                        builder = builder.pool_idle_timeout(Duration::from_secs(seconds));
+                    }
+                    if let Some(seconds) = config.tcp_keepalive_seconds {
+                        builder = builder.tcp_keepalive(Duration::from_secs(seconds));
+                    }
+                    if let Some(proxy_url) = &config.proxy_url {
+                        let proxy = reqwest::Proxy::all(proxy_url)
+                            .map_err(|e| Error::InvalidConfig(format!("Invalid proxy: {}", e)))?
+                            .no_proxy(
+                                config
+                                    .no_proxy
+                                    .as_deref()
+                                    .and_then(reqwest::NoProxy::from_string),
+                            );
+                        builder = builder.proxy(proxy);
+                    }
+                    builder
+                };
+                builder.build().map_err(|e| {
+                    Error::InvalidConfig(format!("Failed to create HTTP client: {}", e))
+                })?
+            }
+        };
+
+        let base_urls = std::iter::once(&config.base_url)
+            .chain(&config.fallback_urls)
+            .cloned()
+            .collect();
+        let failover = Arc::new(Failover::new(
+            base_urls,
+            config.failover_threshold,
+            Duration::from_secs(config.failback_interval_seconds),
+        ));
 
         // Create rate limiter
-        let rate_limit = NonZeroU32::new(config.rate_limit_per_second)
-            .ok_or_else(|| Error::InvalidConfig("Rate limit must be greater than 0".to_string()))?;
-        let quota = Quota::per_second(rate_limit);
-        let rate_limiter = Arc::new(RateLimiter::direct(quota));
+        let rate_limiter = Arc::new(Swap::new(rate_limiter(config.rate_limit_per_second)?));
 
         // Create cache
-        let cache = Cache::builder()
-            .max_capacity(config.cache_max_size)
-            .time_to_live(config.cache_ttl())
-            .build();
+        let cache = ResponseCache::new(config.cache_max_size, config.cache_ttl());
+        let disk_cache = config
+            .disk_cache
+            .as_ref()
+            .map(|disk_cache| Arc::new(DiskCache::new(disk_cache)));
+        let keys = Arc::new(KeyScheduler::new(config.weighted_api_keys()));
+        let concurrency = (config.max_concurrent_requests > 0)
+            .then(|| Arc::new(Semaphore::new(config.max_concurrent_requests)));
+        let head = Arc::new(HeadCache::new(Duration::from_secs(
+            config.block_number_ttl_seconds,
+        )));
 
         Ok(Self {
             config: Arc::new(config),
             http_client,
+            failover,
+            health: Arc::default(),
+            concurrency,
             rate_limiter,
             cache,
-            api_key_index: Arc::new(AtomicUsize::new(0)),
+            disk_cache,
+            keys,
+            token_metadata: Arc::default(),
+            head,
+            options: RequestOptions::default(),
         })
     }
 
-    /// Get the next API key (round-robin rotation)
-    fn get_api_key(&self) -> &str {
-        let index = self.api_key_index.fetch_add(1, Ordering::Relaxed);
-        &self.config.api_keys[index % self.config.api_keys.len()]
+    /// Client sharing this one's connections, rate limiter, and cache, whose
+    /// requests use `options`
+    pub fn with_options(&self, options: RequestOptions) -> Self {
+        Self {
+            options,
+            ..self.clone()
+        }
+    }
+
+    /// Options applied to this client's requests
+    pub fn options(&self) -> RequestOptions {
+        self.options
+    }
+
+    /// Client configuration
+    pub fn config(&self) -> &ClientConfig {
+        &self.config
+    }
+
+    /// Chain ID the client queries
+    pub fn chain_id(&self) -> u64 {
+        self.config.chain_id
+    }
+
+    /// Base URL requests currently go to: `base_url`, or a fallback after failover
+    pub fn active_base_url(&self) -> &str {
+        self.failover.active_url()
+    }
+
+    /// Check that the explorer answers, with a cheap `eth_blockNumber` call
+    ///
+    /// Bypasses the cache and returns the round-trip time.
+    pub async fn ping(&self) -> Result<Duration> {
+        let started = crate::rt::Instant::now();
+        let _: String = self
+            .request_uncached("proxy", "eth_blockNumber", &[])
+            .await?;
+        Ok(started.elapsed())
+    }
+
+    /// Health from the outcomes of recent requests, without network access
+    pub fn health(&self) -> ClientHealth {
+        self.health.snapshot(
+            self.failover.active_url(),
+            self.failover.is_failed_over(),
+            self.keys.len(),
+            self.keys.quarantined(),
+            self.rate_limiter.load().current_load(),
+        )
+    }
+
+    /// Replace the API keys, e.g. to rotate out a compromised key without a restart
+    ///
+    /// Applies to every clone of this client; requests already being sent
+    /// finish with the old key. Quarantined keys are released. The new keys
+    /// have a weight of 1. [`config`](Self::config) keeps reporting the keys
+    /// the client was created with.
+    pub fn update_keys(&self, keys: Vec<String>) -> Result<()> {
+        self.update_weighted_keys(keys.into_iter().map(|key| (key, 1)).collect())
+    }
+
+    /// Replace the API keys with keys and their weights, see
+    /// [`update_keys`](Self::update_keys) and
+    /// [`ClientConfig::api_key_weights`](crate::ClientConfig::api_key_weights)
+    pub fn update_weighted_keys(&self, keys: Vec<(String, u32)>) -> Result<()> {
+        if keys.is_empty() {
+            return Err(Error::InvalidConfig(
+                "At least one API key required".to_string(),
+            ));
+        }
+        if keys.iter().any(|(_, weight)| *weight == 0) {
+            return Err(Error::InvalidConfig(
+                "API key weights must be greater than 0".to_string(),
+            ));
+        }
+        let (keys, weights): (Vec<_>, Vec<_>) = keys
+            .into_iter()
+            .map(|(key, weight)| (Secret::new(key), weight))
+            .unzip();
+        validate_api_keys(&keys)?;
+        self.keys.replace(keys.into_iter().zip(weights).collect());
+        Ok(())
+    }
+
+    /// Replace the rate limit, e.g. after upgrading the API plan
+    ///
+    /// Applies to every clone of this client. The new limiter starts with a
+    /// full quota.
+    pub fn update_rate_limit(&self, per_second: u32) -> Result<()> {
+        self.rate_limiter.store(rate_limiter(per_second)?);
+        Ok(())
+    }
+
+    /// Get the next API key and its index, see [`KeyScheduler`]
+    fn get_api_key(&self) -> (usize, String) {
+        let (index, key) = self.keys.next();
+        record_span("key_index", index);
+        (index, key)
+    }
+
+    /// Take a key the explorer rejected out of rotation until the keys are updated
+    fn quarantine_key(&self, index: usize) {
+        if self.keys.quarantine(index) {
+            tracing::warn!(
+                chain_id = self.config.chain_id,
+                key_index = index,
+                "API key rejected; quarantined"
+            );
+            self.publish(Event::KeyQuarantined {
+                chain_id: self.config.chain_id,
+                key_index: index,
+            });
+        }
+    }
+
+    fn publish(&self, event: Event) {
+        if let Some(events) = &self.config.events {
+            events.publish(event);
+        }
     }
 
     /// Make a cached API request
@@ -83,66 +291,258 @@ impl BscScanClient {
         action: &str,
         params: &[(&str, &str)],
     ) -> Result<T> {
-        // Create cache key
-        let cache_key = format!(
-            "{}:{}:{}",
-            module,
-            action,
-            params
-                .iter()
-                .map(|(k, v)| format!("{}={}", k, v))
-                .collect::<Vec<_>>()
-                .join("&")
-        );
+        self.send_request(module, action, params, true).await
+    }
 
-        // Check cache if TTL > 0
-        if self.config.cache_ttl_seconds > 0 {
-            if let Some(cached) = self.cache.get(&cache_key).await {
-                return serde_json::from_value(cached)
-                    .map_err(|e| Error::Serialization(e));
+    /// Request any explorer endpoint and get its `result` as untyped JSON
+    ///
+    /// Escape hatch for endpoints the crate doesn't model yet. The request
+    /// goes through the cache, rate limiter, retries, and error handling of
+    /// the typed endpoints.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use cryptopay::*;
+    /// # async fn example(client: BscScanClient) -> Result<()> {
+    /// let supply = client
+    ///     .request_raw("stats", "ethsupply2", &[])
+    ///     .await?;
+    /// println!("burnt fees: {}", supply["BurntFees"]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn request_raw(
+        &self,
+        module: &str,
+        action: &str,
+        params: &[(&str, &str)],
+    ) -> Result<Value> {
+        self.call(module, action, params).await
+    }
+
+    /// Request any explorer endpoint and deserialize its `result` into `T`
+    ///
+    /// For new explorer actions that have no typed method yet. `module`,
+    /// `action`, and `params` are the query parameters of the explorer's
+    /// documentation; the API key and chain ID are added. Responses are
+    /// cached and rate limited like the typed endpoints. Proxy (`proxy`
+    /// module) calls return the JSON-RPC `result`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use cryptopay::*;
+    /// #[derive(serde::Deserialize)]
+    /// #[serde(rename_all = "PascalCase")]
+    /// struct NodeCount {
+    ///     total_node_count: String,
+    /// }
+    ///
+    /// # async fn example(client: BscScanClient) -> Result<()> {
+    /// let nodes: NodeCount = client.call("stats", "nodecount", &[]).await?;
+    /// println!("{} nodes", nodes.total_node_count);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn call<T: DeserializeOwned>(
+        &self,
+        module: &str,
+        action: &str,
+        params: &[(&str, &str)],
+    ) -> Result<T> {
+        self.request(module, action, params).await
+    }
+
+    /// Make a cached request to an endpoint that needs an API Pro key
+    ///
+    /// Fails with [`Error::ProEndpointRequired`] without a request unless
+    /// [`ClientConfig::pro_tier`] is set, and when the explorer rejects the
+    /// key as not Pro.
+    pub(crate) async fn request_pro<T: DeserializeOwned>(
+        &self,
+        module: &str,
+        action: &str,
+        params: &[(&str, &str)],
+    ) -> Result<T> {
+        let endpoint = || format!("{}/{}", module, action);
+        if !self.config.pro_tier {
+            return Err(Error::ProEndpointRequired(endpoint()));
+        }
+        match self.request(module, action, params).await {
+            Err(Error::ApiError { message, .. })
+                if message.to_ascii_lowercase().contains("api pro") =>
+            {
+                Err(Error::ProEndpointRequired(endpoint()))
             }
+            result => result,
         }
+    }
 
-        // Wait for rate limiter
-        self.rate_limiter.until_ready().await;
+    /// Make an API request that always hits the network
+    ///
+    /// Used by polling code paths (watchers, tailers) where a cached response
+    /// would hide new chain data.
+    pub(crate) async fn request_uncached<T: DeserializeOwned>(
+        &self,
+        module: &str,
+        action: &str,
+        params: &[(&str, &str)],
+    ) -> Result<T> {
+        self.send_request(module, action, params, false).await
+    }
 
-        // Build request
-        let api_key = self.get_api_key();
-        let mut url = reqwest::Url::parse(&self.config.base_url)
-            .map_err(|e| Error::InvalidConfig(format!("Invalid base URL: {}", e)))?;
+    async fn send_request<T: DeserializeOwned>(
+        &self,
+        module: &str,
+        action: &str,
+        params: &[(&str, &str)],
+        use_cache: bool,
+    ) -> Result<T> {
+        let result = self
+            .send_request_traced(module, action, params, use_cache)
+            .await;
+        if let Err(e) = &result {
+            self.publish(Event::ApiError {
+                chain_id: self.config.chain_id,
+                endpoint: format!("{}/{}", module, action),
+                code: e.code(),
+                message: e.public_message(),
+            });
+        }
+        result
+    }
 
+    async fn send_request_traced<T: DeserializeOwned>(
+        &self,
+        module: &str,
+        action: &str,
+        params: &[(&str, &str)],
+        use_cache: bool,
+    ) -> Result<T> {
+        #[cfg(feature = "tracing")]
         {
-            let mut query_pairs = url.query_pairs_mut();
-            query_pairs.append_pair("module", module);
-            query_pairs.append_pair("action", action);
-            query_pairs.append_pair("apikey", api_key);
-            query_pairs.append_pair("chainid", &self.config.chain_id.to_string());
+            use tracing::field::Empty;
+            use tracing::Instrument;
 
-            for (key, value) in params {
-                query_pairs.append_pair(key, value);
+            let span = tracing::debug_span!(
+                "api_request",
+                module,
+                action,
+                cache_hit = Empty,
+                key_index = Empty,
+                duration_ms = Empty,
+            );
+            let started = crate::rt::Instant::now();
+            let result = self
+                .within_deadline(self.fetch_retrying(module, action, params, use_cache))
+                .instrument(span.clone())
+                .await;
+            span.record("duration_ms", started.elapsed().as_millis() as u64);
+            if let Err(e) = &result {
+                span.in_scope(|| tracing::debug!(error = %e, "API request failed"));
             }
+            result
         }
 
-        // Make request
-        let response = self
-            .http_client
-            .get(url)
-            .send()
+        #[cfg(not(feature = "tracing"))]
+        self.within_deadline(self.fetch_retrying(module, action, params, use_cache))
             .await
-            .map_err(|e| Error::HttpRequest(e))?;
+    }
 
-        let status = response.status();
-        let body: Value = response.json().await.map_err(|e| Error::HttpRequest(e))?;
+    /// Fail with [`Error::DeadlineExceeded`] once the deadline of the client's options passes
+    async fn within_deadline<T>(&self, request: impl Future<Output = Result<T>>) -> Result<T> {
+        let Some(remaining) = self.options.remaining() else {
+            return request.await;
+        };
+        if remaining.is_zero() {
+            return Err(Error::DeadlineExceeded);
+        }
+        tokio::select! {
+            result = request => result,
+            () = crate::rt::sleep(remaining) => Err(Error::DeadlineExceeded),
+        }
+    }
+
+    /// Fetch, retrying transient errors by the configured [`RetryPolicy`](crate::RetryPolicy)
+    async fn fetch_retrying<T: DeserializeOwned>(
+        &self,
+        module: &str,
+        action: &str,
+        params: &[(&str, &str)],
+        use_cache: bool,
+    ) -> Result<T> {
+        match &self.config.retry {
+            Some(policy) => {
+                policy
+                    .retry(|| self.fetch(module, action, params, use_cache))
+                    .await
+            }
+            None => self.fetch(module, action, params, use_cache).await,
+        }
+    }
+
+    async fn fetch<T: DeserializeOwned>(
+        &self,
+        module: &str,
+        action: &str,
+        params: &[(&str, &str)],
+        use_cache: bool,
+    ) -> Result<T> {
+        let cacheable = use_cache && !self.options.no_cache;
+        let use_cache = cacheable && self.config.cache_ttl_seconds > 0;
+        let endpoint = format!("{}/{}", module, action);
+        let disk_cache = self
+            .disk_cache
+            .as_deref()
+            .filter(|disk_cache| cacheable && disk_cache.covers(&endpoint));
+
+        let cache_key = cache_key(module, action, params);
+
+        // Check cache if TTL > 0
+        if use_cache {
+            if let Some(cached) = self.cache.get(&endpoint, &cache_key).await {
+                record_span("cache_hit", true);
+                return serde_json::from_value(cached).map_err(Error::Serialization);
+            }
+        }
+        if let Some(stored) = disk_cache.and_then(|disk| disk.get(self.config.chain_id, &cache_key))
+        {
+            record_span("cache_hit", true);
+            if use_cache {
+                self.cache.insert(cache_key, stored.clone()).await;
+            }
+            return serde_json::from_value(stored).map_err(Error::Serialization);
+        }
+        record_span("cache_hit", false);
+
+        let (status, body) = self.fetch_response(module, action, params).await?;
+        if self.config.hooks.has_response_hooks() {
+            let info = RequestInfo {
+                chain_id: self.config.chain_id,
+                module: module.to_string(),
+                action: action.to_string(),
+                params: params
+                    .iter()
+                    .map(|(key, value)| (key.to_string(), value.to_string()))
+                    .collect(),
+                status: status.as_u16(),
+            };
+            self.config.hooks.on_response_body(&info, &body);
+        }
 
         // Check for API errors
         if !status.is_success() {
-            return Err(Error::api_error(format!(
+            let message = format!(
                 "HTTP {}: {}",
                 status,
                 body.get("message")
                     .and_then(|v| v.as_str())
                     .unwrap_or("Unknown error")
-            )));
+            );
+            return Err(if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+                Error::api_error_with_kind(ApiErrorKind::MaxRateLimit, message)
+            } else {
+                Error::api_error(message)
+            });
         }
 
         // Handle proxy endpoints (JSON-RPC style)
@@ -150,8 +550,14 @@ impl BscScanClient {
             // Check for JSON-RPC error
             if let Some(error) = body.get("error") {
                 let code = error.get("code").and_then(|v| v.as_i64()).unwrap_or(0);
-                let message = error.get("message").and_then(|v| v.as_str()).unwrap_or("Unknown error");
-                return Err(Error::api_error(format!("JSON-RPC Error {}: {}", code, message)));
+                let message = error
+                    .get("message")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("Unknown error");
+                return Err(Error::api_error_with_kind(
+                    ApiErrorKind::ProxyError,
+                    format!("JSON-RPC Error {}: {}", code, message),
+                ));
             }
 
             // Extract result
@@ -161,7 +567,10 @@ impl BscScanClient {
                 .clone();
 
             // Cache the result
-            if self.config.cache_ttl_seconds > 0 {
+            if let Some(disk_cache) = disk_cache {
+                disk_cache.insert(self.config.chain_id, &cache_key, &result);
+            }
+            if use_cache {
                 self.cache.insert(cache_key, result.clone()).await;
             }
 
@@ -175,19 +584,33 @@ impl BscScanClient {
         }
 
         // Parse Etherscan response format
-        let api_status = body
-            .get("status")
-            .and_then(|v| v.as_str())
-            .unwrap_or("0");
+        let api_status = body.get("status").and_then(|v| v.as_str()).unwrap_or("0");
 
         let message = body
             .get("message")
             .and_then(|v| v.as_str())
             .unwrap_or("Unknown");
 
-        // Status "1" = success, "0" = error
-        if api_status == "0" && message != "No transactions found" && message != "NOTOK" {
-            return Err(Error::api_error(message));
+        // Status "1" = success, "0" = error or an empty result
+        if api_status == "0" {
+            if !is_empty_result(message) {
+                return Err(Error::api_response(
+                    message,
+                    body.get("result").and_then(|v| v.as_str()),
+                ));
+            }
+            // Empty lists are cached like any other result; single-item
+            // endpoints can't represent one and report `NotFound` instead
+            let empty = Value::Array(Vec::new());
+            let found = serde_json::from_value(empty.clone())
+                .map_err(|_| Error::NotFound(endpoint.clone()))?;
+            if let Some(disk_cache) = disk_cache {
+                disk_cache.insert(self.config.chain_id, &cache_key, &empty);
+            }
+            if use_cache {
+                self.cache.insert(cache_key, empty).await;
+            }
+            return Ok(found);
         }
 
         // Extract result
@@ -197,7 +620,10 @@ impl BscScanClient {
             .clone();
 
         // Cache the result
-        if self.config.cache_ttl_seconds > 0 {
+        if let Some(disk_cache) = disk_cache {
+            disk_cache.insert(self.config.chain_id, &cache_key, &result);
+        }
+        if use_cache {
             self.cache.insert(cache_key, result.clone()).await;
         }
 
@@ -210,6 +636,153 @@ impl BscScanClient {
         })
     }
 
+    /// Get the explorer's response, through the record/replay cassettes if configured
+    async fn fetch_response(
+        &self,
+        module: &str,
+        action: &str,
+        params: &[(&str, &str)],
+    ) -> Result<(StatusCode, Value)> {
+        let Some(vcr) = &self.config.vcr else {
+            return self.send_http(module, action, params).await;
+        };
+
+        let mut recording = Recording::request(module, action, self.config.chain_id, params);
+        if vcr.mode != VcrMode::Record {
+            if let Some(recorded) = recording.load(&vcr.dir)? {
+                let status = StatusCode::from_u16(recorded.status).map_err(|_| {
+                    Error::generic(format!("invalid recorded HTTP status: {}", recorded.status))
+                })?;
+                return Ok((status, recorded.body));
+            }
+            if vcr.mode == VcrMode::Replay {
+                return Err(Error::generic(format!(
+                    "no recorded response for {}/{} ({})",
+                    module,
+                    action,
+                    recording.path(&vcr.dir).display()
+                )));
+            }
+        }
+
+        let (status, body) = self.send_http(module, action, params).await?;
+        recording.status = status.as_u16();
+        recording.body = body.clone();
+        recording.save(&vcr.dir)?;
+        Ok((status, body))
+    }
+
+    /// Send a request to the explorer
+    async fn send_http(
+        &self,
+        module: &str,
+        action: &str,
+        params: &[(&str, &str)],
+    ) -> Result<(StatusCode, Value)> {
+        // Wait for a concurrency permit, then for the rate limiter
+        let _permit = match &self.concurrency {
+            Some(semaphore) => Some(
+                semaphore
+                    .acquire()
+                    .await
+                    .map_err(|_| Error::generic("request semaphore closed"))?,
+            ),
+            None => None,
+        };
+        let rate_limiter = self.rate_limiter.load();
+        match self.config.rate_limiter_wait_timeout_seconds {
+            Some(seconds) => {
+                tokio::select! {
+                    () = rate_limiter.until_ready(self.options.priority) => {}
+                    () = crate::rt::sleep(Duration::from_secs(seconds)) => {
+                        return Err(Error::RateLimitExceeded {
+                            retry_after: Some(rate_limiter.estimated_wait()),
+                        });
+                    }
+                }
+            }
+            None => rate_limiter.until_ready(self.options.priority).await,
+        }
+
+        let target = self.failover.select();
+        let mut result = self.send_to(target.url, module, action, params).await;
+        let healthy = is_healthy(&result);
+        self.failover.report(target.index, healthy);
+        if target.probe && !healthy {
+            // The primary is still down; answer from the fallback in use
+            let target = self.failover.fallback();
+            result = self.send_to(target.url, module, action, params).await;
+            self.failover.report(target.index, is_healthy(&result));
+        }
+
+        self.health.record(match &result {
+            Ok((status, _)) if status.is_server_error() => Some(format!("HTTP {}", status)),
+            Ok(_) => None,
+            // reqwest's message includes the URL, and with it the API key
+            Err(Error::HttpRequest(e)) if e.is_timeout() => Some("request timed out".to_string()),
+            Err(Error::HttpRequest(e)) if e.is_decode() => {
+                Some("invalid response body".to_string())
+            }
+            Err(Error::HttpRequest(_)) => Some("HTTP request failed".to_string()),
+            Err(e) => Some(e.to_string()),
+        });
+        result
+    }
+
+    /// Send a request to the explorer at `base_url`
+    async fn send_to(
+        &self,
+        base_url: &str,
+        module: &str,
+        action: &str,
+        params: &[(&str, &str)],
+    ) -> Result<(StatusCode, Value)> {
+        // Build request
+        let (key_index, api_key) = self.get_api_key();
+        let mut url = reqwest::Url::parse(base_url)
+            .map_err(|e| Error::InvalidConfig(format!("Invalid base URL: {}", e)))?;
+
+        {
+            let mut query_pairs = url.query_pairs_mut();
+            query_pairs.append_pair("module", module);
+            query_pairs.append_pair("action", action);
+            query_pairs.append_pair("apikey", &api_key);
+            query_pairs.append_pair("chainid", &self.config.chain_id.to_string());
+
+            for (key, value) in params {
+                query_pairs.append_pair(key, value);
+            }
+        }
+
+        // Make request
+        let mut request = self
+            .http_client
+            .get(url)
+            .build()
+            .map_err(Error::HttpRequest)?;
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(timeout) = self.options.timeout {
+            *request.timeout_mut() = Some(timeout);
+        }
+        self.config.hooks.on_request(&mut request)?;
+        let started = crate::rt::Instant::now();
+        let response = self
+            .http_client
+            .execute(request)
+            .await
+            .map_err(Error::HttpRequest)?;
+        self.config.hooks.on_response(&response, started.elapsed());
+
+        let status = response.status();
+        let body: Value = response.json().await.map_err(Error::HttpRequest)?;
+        if rejects_api_key(&body) {
+            self.quarantine_key(key_index);
+        } else if status == StatusCode::TOO_MANY_REQUESTS || rate_limits_api_key(&body) {
+            self.keys.rate_limited(key_index);
+        }
+        Ok((status, body))
+    }
+
     /// Make a simple request (for endpoints that return single values)
     pub(crate) async fn request_simple<T: DeserializeOwned>(
         &self,
@@ -226,14 +799,100 @@ impl BscScanClient {
     }
 
     /// Get cache statistics
-    pub fn cache_stats(&self) -> (u64, u64) {
-        (self.cache.entry_count(), self.cache.weighted_size())
+    pub fn cache_stats(&self) -> CacheStats {
+        let mut stats = self.cache.stats();
+        stats.disk_hits = self
+            .disk_cache
+            .as_ref()
+            .map_or(0, |disk_cache| disk_cache.hits());
+        stats
+    }
+
+    /// Keys of the cached responses (`module:action:params`), sorted, e.g. to
+    /// see which queries dominate the cache
+    pub fn cache_dump_keys(&self) -> Vec<String> {
+        let mut keys = self.cache.keys();
+        keys.sort();
+        keys
+    }
+}
+
+/// Whether the `message` of a status "0" response reports an empty result
+/// ("No transactions found", "No records found", ...) rather than an error
+fn is_empty_result(message: &str) -> bool {
+    let message = message.trim().to_ascii_lowercase();
+    message.starts_with("no ") && message.ends_with(" found")
+}
+
+/// Whether an explorer response rejects the API key it was sent with
+fn rejects_api_key(body: &Value) -> bool {
+    let field = |name| body.get(name).and_then(|v| v.as_str());
+    field("status") == Some("0")
+        && field("message").is_some_and(|message| {
+            ApiErrorKind::parse(message, field("result")) == ApiErrorKind::InvalidApiKey
+        })
+}
+
+/// Whether an explorer response reports that the key's rate limit was hit
+fn rate_limits_api_key(body: &Value) -> bool {
+    let field = |name| body.get(name).and_then(|v| v.as_str());
+    field("status") == Some("0")
+        && field("message").is_some_and(|message| {
+            ApiErrorKind::parse(message, field("result")) == ApiErrorKind::MaxRateLimit
+        })
+}
+
+/// Cache key of a request: parameters sorted by name, hex values
+/// (addresses, hashes) lowercased, so equivalent requests share an entry
+fn cache_key(module: &str, action: &str, params: &[(&str, &str)]) -> String {
+    let mut params: Vec<String> = params
+        .iter()
+        .map(|(key, value)| {
+            let is_hex = match value.as_bytes() {
+                [b'0', b'x' | b'X', digits @ ..] => {
+                    !digits.is_empty() && digits.iter().all(u8::is_ascii_hexdigit)
+                }
+                _ => false,
+            };
+            if is_hex {
+                format!("{}={}", key, value.to_ascii_lowercase())
+            } else {
+                format!("{}={}", key, value)
+            }
+        })
+        .collect();
+    params.sort();
+    format!("{}:{}:{}", module, action, params.join("&"))
+}
+
+/// Whether a response counts as success of its base URL for failover
+fn is_healthy(result: &Result<(StatusCode, Value)>) -> bool {
+    match result {
+        Ok((status, _)) => !status.is_server_error(),
+        Err(Error::HttpRequest(_) | Error::InvalidConfig(_)) => false,
+        Err(_) => true,
     }
 }
 
+fn rate_limiter(per_second: u32) -> Result<RequestLimiter> {
+    let per_second = NonZeroU32::new(per_second)
+        .ok_or_else(|| Error::InvalidConfig("Rate limit must be greater than 0".to_string()))?;
+    Ok(RequestLimiter::per_second(per_second))
+}
+
+/// Record a field on the current request span (`tracing` feature)
+#[cfg(feature = "tracing")]
+fn record_span(field: &str, value: impl tracing::Value) {
+    tracing::Span::current().record(field, value);
+}
+
+#[cfg(not(feature = "tracing"))]
+fn record_span<V>(_field: &str, _value: V) {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
 
     #[test]
     fn test_client_creation() {
@@ -261,9 +920,778 @@ mod tests {
         let client = BscScanClient::with_config(config).unwrap();
 
         // Test rotation
-        assert_eq!(client.get_api_key(), "key1");
-        assert_eq!(client.get_api_key(), "key2");
-        assert_eq!(client.get_api_key(), "key3");
-        assert_eq!(client.get_api_key(), "key1"); // Should wrap around
+        assert_eq!(client.get_api_key().1, "key1");
+        assert_eq!(client.get_api_key().1, "key2");
+        assert_eq!(client.get_api_key().1, "key3");
+        assert_eq!(client.get_api_key().1, "key1"); // Should wrap around
+    }
+
+    #[test]
+    fn test_update_keys_and_rate_limit() {
+        let client = BscScanClient::new("old-key").unwrap();
+        let scoped = client.with_options(RequestOptions::new().no_cache());
+
+        client
+            .update_keys(vec!["new-a".to_string(), "new-b".to_string()])
+            .unwrap();
+        assert_eq!(scoped.get_api_key().1, "new-a");
+        assert_eq!(client.get_api_key().1, "new-b");
+
+        assert!(client.update_keys(Vec::new()).is_err());
+        assert!(client.update_keys(vec![String::new()]).is_err());
+        assert_eq!(client.get_api_key().1, "new-a");
+
+        assert!(client.update_rate_limit(20).is_ok());
+        assert!(matches!(
+            client.update_rate_limit(0),
+            Err(Error::InvalidConfig(_))
+        ));
+    }
+
+    #[test]
+    fn test_cache_key_normalization() {
+        let checksummed = cache_key(
+            "account",
+            "balance",
+            &[
+                ("tag", "latest"),
+                ("address", "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"),
+            ],
+        );
+        let lowercase = cache_key(
+            "account",
+            "balance",
+            &[
+                ("address", "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed"),
+                ("tag", "latest"),
+            ],
+        );
+        assert_eq!(checksummed, lowercase);
+        assert_eq!(
+            lowercase,
+            "account:balance:address=0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed&tag=latest"
+        );
+        assert_ne!(
+            cache_key("logs", "getLogs", &[("topic0", "Transfer")]),
+            cache_key("logs", "getLogs", &[("topic0", "transfer")])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cache_stats() {
+        use wiremock::matchers::query_param;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(query_param("action", "balance"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "status": "1", "message": "OK", "result": "7"
+            })))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let config = ClientConfig::builder()
+            .api_key("key")
+            .base_url(server.uri())
+            .build()
+            .unwrap();
+        let client = BscScanClient::with_config(config).unwrap();
+        for address in ["0xa", "0xa", "0xb"] {
+            let _: String = client
+                .request("account", "balance", &[("address", address)])
+                .await
+                .unwrap();
+        }
+
+        let stats = client.cache_stats();
+        assert_eq!((stats.hits, stats.misses, stats.evictions), (1, 2, 0));
+        assert_eq!(
+            stats.per_endpoint["account/balance"],
+            EndpointCacheStats { hits: 1, misses: 2 }
+        );
+        assert_eq!(
+            client.cache_dump_keys(),
+            ["account:balance:address=0xa", "account:balance:address=0xb"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_record_then_replay() {
+        use wiremock::matchers::query_param;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(query_param("action", "balance"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "status": "1", "message": "OK", "result": "42"
+            })))
+            .mount(&server)
+            .await;
+
+        let dir = std::env::temp_dir().join(format!("cryptopay-vcr-{}", uuid::Uuid::new_v4()));
+        let params = [("address", "0x742d35cc6634c0532925a3b844bc9e7595f0beb0")];
+
+        let recorder = ClientConfig::builder()
+            .api_key("real-key")
+            .base_url(server.uri())
+            .record_to(&dir)
+            .build()
+            .unwrap();
+        let recorder = BscScanClient::with_config(recorder).unwrap();
+        let balance: String = recorder
+            .request("account", "balance", &params)
+            .await
+            .unwrap();
+        assert_eq!(balance, "42");
+
+        // Replay needs neither the server nor the real key
+        let replayer = ClientConfig::builder()
+            .api_key("other-key")
+            .base_url("http://127.0.0.1:9")
+            .replay_from(&dir)
+            .build()
+            .unwrap();
+        let replayer = BscScanClient::with_config(replayer).unwrap();
+        let balance: String = replayer
+            .request("account", "balance", &params)
+            .await
+            .unwrap();
+        assert_eq!(balance, "42");
+        assert!(replayer
+            .request::<String>("account", "balance", &[("address", "0x0")])
+            .await
+            .is_err());
+
+        let recorded = std::fs::read_dir(&dir).unwrap().next().unwrap().unwrap();
+        assert!(!std::fs::read_to_string(recorded.path())
+            .unwrap()
+            .contains("real-key"));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_custom_http_client_and_hooks() {
+        use reqwest::header::{HeaderMap, HeaderValue};
+        use std::time::Duration;
+        use wiremock::matchers::header;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        struct Tenant(Arc<AtomicUsize>);
+
+        impl RequestHook for Tenant {
+            fn on_request(&self, request: &mut reqwest::Request) -> Result<()> {
+                request
+                    .headers_mut()
+                    .insert("x-tenant", HeaderValue::from_static("shop-1"));
+                Ok(())
+            }
+
+            fn on_response(&self, response: &reqwest::Response, _elapsed: Duration) {
+                assert_eq!(response.status(), StatusCode::OK);
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        let server = MockServer::start().await;
+        Mock::given(header("x-proxy-auth", "secret"))
+            .and(header("x-tenant", "shop-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "status": "1", "message": "OK", "result": "42"
+            })))
+            .mount(&server)
+            .await;
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-proxy-auth", HeaderValue::from_static("secret"));
+        let http_client = Client::builder().default_headers(headers).build().unwrap();
+        let responses = Arc::new(AtomicUsize::new(0));
+        let config = ClientConfig::builder()
+            .api_key("key")
+            .base_url(server.uri())
+            .http_client(http_client)
+            .hook(Tenant(responses.clone()))
+            .build()
+            .unwrap();
+
+        let client = BscScanClient::with_config(config).unwrap();
+        let balance: String = client.request("account", "balance", &[]).await.unwrap();
+        assert_eq!(balance, "42");
+        assert_eq!(responses.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_response_hook_sees_cache_misses() {
+        use std::sync::Mutex;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(wiremock::matchers::any())
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "status": "1", "message": "OK", "result": "42"
+            })))
+            .mount(&server)
+            .await;
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let recorder = seen.clone();
+        let config = ClientConfig::builder()
+            .api_key("key")
+            .base_url(server.uri())
+            .response_hook(Arc::new(move |info: &RequestInfo, body: &Value| {
+                recorder.lock().unwrap().push((info.clone(), body.clone()));
+            }))
+            .build()
+            .unwrap();
+        let client = BscScanClient::with_config(config).unwrap();
+
+        let params = [("address", "0xabc")];
+        for _ in 0..2 {
+            let balance: String = client.request("account", "balance", &params).await.unwrap();
+            assert_eq!(balance, "42");
+        }
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 1);
+        let (info, body) = &seen[0];
+        assert_eq!(
+            (info.module.as_str(), info.action.as_str()),
+            ("account", "balance")
+        );
+        assert_eq!(
+            info.params,
+            vec![("address".to_string(), "0xabc".to_string())]
+        );
+        assert_eq!(info.status, 200);
+        assert_eq!(body["result"], "42");
+    }
+
+    #[tokio::test]
+    async fn test_requests_accept_compressed_responses() {
+        use wiremock::matchers::header_regex;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(header_regex("accept-encoding", "gzip"))
+            .and(header_regex("accept-encoding", "br"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "status": "1", "message": "OK", "result": "7"
+            })))
+            .mount(&server)
+            .await;
+
+        let config = ClientConfig::builder()
+            .api_key("key")
+            .base_url(server.uri())
+            .pool_max_idle_per_host(2)
+            .pool_idle_timeout(30)
+            .tcp_keepalive(60)
+            .build()
+            .unwrap();
+        let client = BscScanClient::with_config(config).unwrap();
+        let balance: String = client.request("account", "balance", &[]).await.unwrap();
+        assert_eq!(balance, "7");
+    }
+
+    #[tokio::test]
+    async fn test_user_agent_and_default_headers() {
+        use wiremock::matchers::header;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(header("user-agent", "shop-backend/2.1"))
+            .and(header("x-gateway-key", "secret"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "status": "1", "message": "OK", "result": "7"
+            })))
+            .mount(&server)
+            .await;
+
+        let config = ClientConfig::builder()
+            .api_key("key")
+            .base_url(server.uri())
+            .user_agent("shop-backend/2.1")
+            .default_header("x-gateway-key", "secret")
+            .build()
+            .unwrap();
+        let client = BscScanClient::with_config(config).unwrap();
+        let balance: String = client.request("account", "balance", &[]).await.unwrap();
+        assert_eq!(balance, "7");
+
+        let invalid = ClientConfig::builder()
+            .api_key("key")
+            .default_header("x-gateway-key", "line\nbreak")
+            .build();
+        assert!(matches!(invalid, Err(Error::InvalidConfig(_))));
+    }
+
+    #[tokio::test]
+    async fn test_rate_limiter_wait_timeout() {
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(wiremock::matchers::any())
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "status": "1", "message": "OK", "result": "7"
+            })))
+            .mount(&server)
+            .await;
+
+        let config = ClientConfig::builder()
+            .api_key("key")
+            .base_url(server.uri())
+            .rate_limit(1)
+            .rate_limiter_wait_timeout(1)
+            .cache_ttl(0)
+            .build()
+            .unwrap();
+        let client = BscScanClient::with_config(config).unwrap();
+
+        // One slot per second: the first goes through, the last ones can't
+        // get a slot within a second
+        let requests = (0..4).map(|_| client.request::<String>("account", "balance", &[]));
+        let results = futures::future::join_all(requests).await;
+        assert!(results[0].is_ok());
+        for result in &results[2..] {
+            let error = result.as_ref().unwrap_err();
+            assert!(matches!(error, Error::RateLimitExceeded { .. }));
+            assert!(error.retry_after().unwrap() >= Duration::from_secs(1));
+        }
+        assert_eq!(client.health().rate_limit_queue, 0);
+    }
+
+    #[tokio::test]
+    async fn test_requests_go_through_proxy() {
+        use wiremock::matchers::query_param;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        // A plain HTTP proxy receives the request with the target as absolute URL
+        let proxy = MockServer::start().await;
+        Mock::given(query_param("action", "balance"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "status": "1", "message": "OK", "result": "7"
+            })))
+            .mount(&proxy)
+            .await;
+
+        let config = ClientConfig::builder()
+            .api_key("key")
+            .base_url("http://explorer.invalid/api")
+            .proxy(proxy.uri())
+            .build()
+            .unwrap();
+        let client = BscScanClient::with_config(config).unwrap();
+        let balance: String = client.request("account", "balance", &[]).await.unwrap();
+        assert_eq!(balance, "7");
+
+        let invalid = ClientConfig::builder()
+            .api_key("key")
+            .proxy("ftp://proxy")
+            .build();
+        assert!(matches!(invalid, Err(Error::InvalidConfig(_))));
+    }
+
+    #[tokio::test]
+    async fn test_failover_and_failback() {
+        use wiremock::matchers::query_param;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let body =
+            |result: &str| serde_json::json!({ "status": "1", "message": "OK", "result": result });
+        let primary = MockServer::start().await;
+        Mock::given(query_param("action", "balance"))
+            .respond_with(
+                ResponseTemplate::new(503).set_body_json(serde_json::json!({ "message": "down" })),
+            )
+            .up_to_n_times(3)
+            .mount(&primary)
+            .await;
+        Mock::given(query_param("action", "balance"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(body("primary")))
+            .mount(&primary)
+            .await;
+        let mirror = MockServer::start().await;
+        Mock::given(query_param("action", "balance"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(body("mirror")))
+            .mount(&mirror)
+            .await;
+
+        let config = ClientConfig::builder()
+            .api_key("key")
+            .base_url(primary.uri())
+            .fallback_url(mirror.uri())
+            .failover_threshold(2)
+            .failback_interval(0)
+            .cache_ttl(0)
+            .build()
+            .unwrap();
+        let client = BscScanClient::with_config(config).unwrap();
+        let balance = || client.request::<String>("account", "balance", &[]);
+
+        assert!(balance().await.is_err());
+        assert_eq!(client.active_base_url(), primary.uri());
+        assert!(balance().await.is_err());
+        assert_eq!(client.active_base_url(), mirror.uri());
+
+        // The probe of the primary fails and the mirror answers
+        assert_eq!(balance().await.unwrap(), "mirror");
+        assert_eq!(client.active_base_url(), mirror.uri());
+
+        // The primary has recovered
+        assert_eq!(balance().await.unwrap(), "primary");
+        assert_eq!(client.active_base_url(), primary.uri());
+    }
+
+    #[tokio::test]
+    async fn test_ping_and_health() {
+        use wiremock::matchers::query_param;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(query_param("action", "eth_blockNumber"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0", "id": 1, "result": "0x10"
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(query_param("action", "balance"))
+            .respond_with(
+                ResponseTemplate::new(503).set_body_json(serde_json::json!({ "message": "down" })),
+            )
+            .mount(&server)
+            .await;
+
+        let config = ClientConfig::builder()
+            .api_key("key")
+            .base_url(server.uri())
+            .build()
+            .unwrap();
+        let client = BscScanClient::with_config(config).unwrap();
+        assert_eq!(client.health().recent_requests, 0);
+        assert!(client.health().healthy);
+
+        client.ping().await.unwrap();
+        let health = client.health();
+        assert!(health.healthy);
+        assert!(health.last_success.is_some());
+        assert_eq!(health.active_base_url, server.uri());
+
+        let result: Result<Vec<Value>> = client.request("account", "balance", &[]).await;
+        assert!(result.is_err());
+        let health = client.health();
+        assert_eq!(health.error_rate, 0.5);
+        assert!(!health.healthy);
+        assert_eq!(
+            health.last_error.as_deref(),
+            Some("HTTP 503 Service Unavailable")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_retry_policy() {
+        use wiremock::matchers::query_param;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(query_param("action", "balance"))
+            .respond_with(
+                ResponseTemplate::new(503).set_body_json(serde_json::json!({ "message": "down" })),
+            )
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(query_param("action", "balance"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "status": "1", "message": "OK", "result": "7"
+            })))
+            .mount(&server)
+            .await;
+
+        let config = ClientConfig::builder()
+            .api_key("key")
+            .base_url(server.uri())
+            .retry(crate::RetryPolicy::new(1).initial_backoff(Duration::from_millis(1)))
+            .build()
+            .unwrap();
+        let client = BscScanClient::with_config(config).unwrap();
+        let balance: String = client.request("account", "balance", &[]).await.unwrap();
+        assert_eq!(balance, "7");
+    }
+
+    #[tokio::test]
+    async fn test_api_error_kinds() {
+        use wiremock::matchers::query_param;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(query_param("action", "balance"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "status": "0", "message": "NOTOK", "result": "Invalid API Key"
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(query_param("action", "tokenbalance"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "status": "0", "message": "Error! Invalid address format", "result": null
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(query_param("action", "eth_getTransactionByHash"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0", "id": 1, "error": { "code": -32602, "message": "invalid argument" }
+            })))
+            .mount(&server)
+            .await;
+
+        let config = ClientConfig::builder()
+            .api_key("key")
+            .base_url(server.uri())
+            .build()
+            .unwrap();
+        let client = BscScanClient::with_config(config).unwrap();
+
+        let kind =
+            |result: Result<Vec<serde_json::Value>>| result.unwrap_err().api_error_kind().cloned();
+        assert_eq!(
+            kind(client.request("account", "balance", &[]).await),
+            Some(ApiErrorKind::InvalidApiKey)
+        );
+        assert_eq!(
+            kind(client.request("account", "tokenbalance", &[]).await),
+            Some(ApiErrorKind::InvalidAddressFormat)
+        );
+        assert_eq!(
+            kind(
+                client
+                    .request("proxy", "eth_getTransactionByHash", &[])
+                    .await
+            ),
+            Some(ApiErrorKind::ProxyError)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_empty_results_and_notok() {
+        use wiremock::matchers::query_param;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(query_param("action", "txlist"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "status": "0", "message": "No transactions found", "result": []
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(query_param("action", "tokeninfo"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "status": "0", "message": "No data found", "result": []
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(query_param("action", "balance"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "status": "0", "message": "NOTOK", "result": "Error! Missing Or invalid Module name"
+            })))
+            .mount(&server)
+            .await;
+
+        let config = ClientConfig::builder()
+            .api_key("key")
+            .base_url(server.uri())
+            .build()
+            .unwrap();
+        let client = BscScanClient::with_config(config).unwrap();
+
+        let transactions: Vec<Value> = client.request("account", "txlist", &[]).await.unwrap();
+        assert!(transactions.is_empty());
+
+        let error = client
+            .request::<serde_json::Map<String, Value>>("token", "tokeninfo", &[])
+            .await
+            .unwrap_err();
+        assert!(matches!(error, Error::NotFound(ref endpoint) if endpoint == "token/tokeninfo"));
+
+        // NOTOK is an error even where the result text would deserialize
+        let error = client
+            .request::<String>("account", "balance", &[])
+            .await
+            .unwrap_err();
+        assert!(matches!(error, Error::ApiError { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_call_unmodeled_action() {
+        use wiremock::matchers::query_param;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        #[derive(serde::Deserialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct NodeCount {
+            total_node_count: String,
+        }
+
+        let server = MockServer::start().await;
+        Mock::given(query_param("module", "stats"))
+            .and(query_param("action", "nodecount"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "status": "1",
+                "message": "OK",
+                "result": { "UTCDate": "2021-05-22", "TotalNodeCount": "6413" }
+            })))
+            .mount(&server)
+            .await;
+
+        let config = ClientConfig::builder()
+            .api_key("key")
+            .base_url(server.uri())
+            .build()
+            .unwrap();
+        let client = BscScanClient::with_config(config).unwrap();
+
+        let nodes: NodeCount = client.call("stats", "nodecount", &[]).await.unwrap();
+        assert_eq!(nodes.total_node_count, "6413");
+        let raw = client.request_raw("stats", "nodecount", &[]).await.unwrap();
+        assert_eq!(raw["UTCDate"], "2021-05-22");
+    }
+
+    #[tokio::test]
+    async fn test_rejected_key_is_quarantined() {
+        use crate::events::{Event, EventBus};
+        use wiremock::matchers::query_param;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(query_param("apikey", "revoked"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "status": "0", "message": "NOTOK", "result": "Invalid API Key"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(query_param("apikey", "good"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "status": "1", "message": "OK", "result": []
+            })))
+            .mount(&server)
+            .await;
+
+        let events = EventBus::new();
+        let mut subscriber = events.subscribe();
+        let config = ClientConfig::builder()
+            .api_keys(vec!["revoked".to_string(), "good".to_string()])
+            .base_url(server.uri())
+            .cache_ttl(0)
+            .events(events)
+            .build()
+            .unwrap();
+        let client = BscScanClient::with_config(config).unwrap();
+
+        let result: Result<Vec<Value>> = client.request("account", "balance", &[]).await;
+        assert!(result.is_err());
+        assert_eq!(
+            subscriber.recv().await.unwrap(),
+            Event::KeyQuarantined {
+                chain_id: 1,
+                key_index: 0
+            }
+        );
+        assert!(matches!(
+            subscriber.recv().await.unwrap(),
+            Event::ApiError { code: "CP1002", ref endpoint, .. } if endpoint == "account/balance"
+        ));
+
+        for _ in 0..3 {
+            let result: Vec<Value> = client.request("account", "balance", &[]).await.unwrap();
+            assert!(result.is_empty());
+        }
+        assert_eq!(client.health().quarantined_keys, 1);
+
+        client.update_keys(vec!["good".to_string()]).unwrap();
+        assert_eq!(client.health().quarantined_keys, 0);
+    }
+
+    #[tokio::test]
+    async fn test_max_concurrent_requests() {
+        use wiremock::matchers::query_param;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(query_param("action", "balance"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({
+                        "status": "1", "message": "OK", "result": "7"
+                    }))
+                    .set_delay(Duration::from_millis(100)),
+            )
+            .mount(&server)
+            .await;
+
+        let config = ClientConfig::builder()
+            .api_key("key")
+            .base_url(server.uri())
+            .rate_limit(100)
+            .cache_ttl(0)
+            .max_concurrent_requests(1)
+            .build()
+            .unwrap();
+        let client = BscScanClient::with_config(config).unwrap();
+        let balance = || client.request::<String>("account", "balance", &[]);
+
+        let started = std::time::Instant::now();
+        let (a, b, c) = tokio::join!(balance(), balance(), balance());
+        assert!(a.is_ok() && b.is_ok() && c.is_ok());
+        assert!(started.elapsed() >= Duration::from_millis(300));
+    }
+
+    #[tokio::test]
+    async fn test_request_options() {
+        use wiremock::matchers::query_param;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(query_param("action", "balance"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({
+                        "status": "1", "message": "OK", "result": "7"
+                    }))
+                    .set_delay(Duration::from_millis(300)),
+            )
+            .expect(4)
+            .mount(&server)
+            .await;
+
+        let config = ClientConfig::builder()
+            .api_key("key")
+            .base_url(server.uri())
+            .build()
+            .unwrap();
+        let client = BscScanClient::with_config(config).unwrap();
+        let balance: String = client.request("account", "balance", &[]).await.unwrap();
+        assert_eq!(balance, "7");
+
+        // Bypasses the cached response
+        let uncached = client.with_options(RequestOptions::new().no_cache());
+        let balance: String = uncached.request("account", "balance", &[]).await.unwrap();
+        assert_eq!(balance, "7");
+
+        let tight = client.with_options(
+            RequestOptions::new()
+                .no_cache()
+                .deadline_in(Duration::from_millis(50)),
+        );
+        let result: Result<String> = tight.request("account", "balance", &[]).await;
+        assert!(matches!(result, Err(Error::DeadlineExceeded)));
+        let expired: Result<String> = tight.request("account", "balance", &[]).await;
+        assert!(matches!(expired, Err(Error::DeadlineExceeded)));
+
+        let short = client.with_options(
+            RequestOptions::new()
+                .no_cache()
+                .timeout(Duration::from_millis(50)),
+        );
+        let result: Result<String> = short.request("account", "balance", &[]).await;
+        assert!(matches!(result, Err(Error::HttpRequest(e)) if e.is_timeout()));
     }
 }
@@ -0,0 +1,96 @@
+//! Per-request options
+
+use crate::rt::Instant;
+use std::time::Duration;
+
+/// Overrides for the requests of a scoped client
+///
+/// Created with [`BscScanClient::with_options`](crate::BscScanClient::with_options),
+/// so the same endpoints and verifier can run with tight limits for
+/// interactive checks and relaxed ones for background jobs.
+///
+/// # Example
+/// ```
+/// use cryptopay::client::RequestOptions;
+/// use cryptopay::{BscScanClient, PaymentVerifier};
+/// use std::time::Duration;
+///
+/// let client = BscScanClient::new("api-key").unwrap();
+///
+/// // Give the whole verification 5 seconds, including rate limiter waits
+/// let interactive = client.with_options(RequestOptions::new().deadline_in(Duration::from_secs(5)));
+/// let verifier = PaymentVerifier::new(interactive);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RequestOptions {
+    /// Timeout of each HTTP request, replacing the configured one
+    pub timeout: Option<Duration>,
+
+    /// Point in time by which every call must have completed, across retries
+    /// and rate limiter waits; later calls fail with
+    /// [`Error::DeadlineExceeded`](crate::Error::DeadlineExceeded)
+    pub deadline: Option<Instant>,
+
+    /// Bypass the response cache for reads and writes
+    pub no_cache: bool,
+
+    /// Place in the queue for the rate limit when requests are waiting for it
+    pub priority: Priority,
+}
+
+/// Scheduling priority of requests waiting for the rate limit
+///
+/// Waiting requests are sent highest priority first, and in arrival order
+/// within a priority, so interactive checks can overtake background polling.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Priority {
+    /// Background work, e.g. monitor pool refreshes and sweeps
+    Low,
+    /// Default
+    #[default]
+    Normal,
+    /// Interactive requests, e.g. a customer waiting on a checkout page
+    High,
+}
+
+impl RequestOptions {
+    /// Options without overrides
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the timeout of each HTTP request
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Set the deadline to `at`
+    pub fn deadline(mut self, at: Instant) -> Self {
+        self.deadline = Some(at);
+        self
+    }
+
+    /// Set the deadline to `budget` from now
+    pub fn deadline_in(self, budget: Duration) -> Self {
+        self.deadline(Instant::now() + budget)
+    }
+
+    /// Bypass the response cache
+    pub fn no_cache(mut self) -> Self {
+        self.no_cache = true;
+        self
+    }
+
+    /// Set the priority for the rate limit queue
+    pub fn priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Time left until the deadline, if one is set
+    pub(crate) fn remaining(&self) -> Option<Duration> {
+        self.deadline
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+}
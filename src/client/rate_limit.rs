@@ -0,0 +1,267 @@
+//! Request rate limiting
+//!
+//! Uses governor on native targets. governor's clock isn't available on
+//! wasm, where requests are instead spaced evenly at the configured rate.
+//!
+//! Requests queue for the limiter by [`Priority`]: only the first request in
+//! the queue waits for the next slot, so a high-priority request arriving
+//! later still goes before queued low-priority ones.
+
+use super::options::Priority;
+use crate::rt::Instant;
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, VecDeque};
+use std::num::NonZeroU32;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+use tokio::sync::Notify;
+
+/// Queue position: highest priority first, then arrival order
+type Ticket = (Reverse<Priority>, u64);
+
+/// Limits requests to a number per second
+pub(crate) struct RequestLimiter {
+    #[cfg(not(target_arch = "wasm32"))]
+    inner: governor::DefaultDirectRateLimiter,
+    #[cfg(target_arch = "wasm32")]
+    inner: wasm::Spacer,
+    per_second: NonZeroU32,
+    queue: Mutex<Queue>,
+    turn: Notify,
+}
+
+#[derive(Default)]
+struct Queue {
+    waiting: BTreeSet<Ticket>,
+    next_seq: u64,
+    /// Whether a request is waiting for a slot of the limiter
+    busy: bool,
+    /// Times of the requests let through in the last second
+    granted: VecDeque<Instant>,
+}
+
+/// Load of a client's rate limiter, see [`ClientHealth`](super::ClientHealth)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct LimiterLoad {
+    /// Requests waiting for a slot
+    pub queued: usize,
+    /// Share of the last second's slots that were used
+    pub utilization: f64,
+}
+
+impl RequestLimiter {
+    /// Allow `per_second` requests per second
+    pub fn per_second(per_second: NonZeroU32) -> Self {
+        #[cfg(not(target_arch = "wasm32"))]
+        let inner = governor::RateLimiter::direct(governor::Quota::per_second(per_second));
+        #[cfg(target_arch = "wasm32")]
+        let inner = wasm::Spacer::new(per_second);
+
+        Self {
+            inner,
+            per_second,
+            queue: Mutex::default(),
+            turn: Notify::new(),
+        }
+    }
+
+    /// Wait until the next request with `priority` may be sent
+    pub async fn until_ready(&self, priority: Priority) {
+        let _turn = self.wait_turn(priority).await;
+        self.inner.until_ready().await;
+
+        let now = Instant::now();
+        let mut queue = self.lock();
+        queue.granted.push_back(now);
+        prune(&mut queue.granted, now);
+    }
+
+    /// Queued requests and recent use of the limiter
+    pub fn current_load(&self) -> LimiterLoad {
+        let mut queue = self.lock();
+        prune(&mut queue.granted, Instant::now());
+        LimiterLoad {
+            queued: queue.waiting.len() + usize::from(queue.busy),
+            utilization: (queue.granted.len() as f64 / f64::from(self.per_second.get())).min(1.0),
+        }
+    }
+
+    /// Time until a request joining the queue now would be sent, at the configured rate
+    pub fn estimated_wait(&self) -> Duration {
+        let ahead = self.current_load().queued as u32 + 1;
+        Duration::from_secs(1) * ahead / self.per_second.get()
+    }
+
+    /// Wait until `priority`'s ticket is first in the queue and the limiter is free
+    async fn wait_turn(&self, priority: Priority) -> Turn<'_> {
+        let ticket = {
+            let mut queue = self.lock();
+            let ticket = (Reverse(priority), queue.next_seq);
+            queue.next_seq += 1;
+            queue.waiting.insert(ticket);
+            ticket
+        };
+        // Leaves the queue if the request is dropped while waiting
+        let mut waiting = Waiting {
+            limiter: self,
+            ticket: Some(ticket),
+        };
+
+        loop {
+            let notified = self.turn.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+            {
+                let mut queue = self.lock();
+                if !queue.busy && queue.waiting.first() == Some(&ticket) {
+                    queue.waiting.remove(&ticket);
+                    queue.busy = true;
+                    waiting.ticket = None;
+                    return Turn { limiter: self };
+                }
+            }
+            notified.await;
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Queue> {
+        self.queue
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Forget grants older than a second
+fn prune(granted: &mut VecDeque<Instant>, now: Instant) {
+    while granted
+        .front()
+        .is_some_and(|granted| now.duration_since(*granted) >= Duration::from_secs(1))
+    {
+        granted.pop_front();
+    }
+}
+
+/// Exclusive right to wait for the next slot; passes it on when dropped
+struct Turn<'a> {
+    limiter: &'a RequestLimiter,
+}
+
+impl Drop for Turn<'_> {
+    fn drop(&mut self) {
+        self.limiter.lock().busy = false;
+        self.limiter.turn.notify_waiters();
+    }
+}
+
+/// Ticket in the queue, removed when dropped before its turn
+struct Waiting<'a> {
+    limiter: &'a RequestLimiter,
+    ticket: Option<Ticket>,
+}
+
+impl Drop for Waiting<'_> {
+    fn drop(&mut self) {
+        if let Some(ticket) = self.ticket.take() {
+            self.limiter.lock().waiting.remove(&ticket);
+            self.limiter.turn.notify_waiters();
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod wasm {
+    use crate::rt::{sleep, Instant};
+    use std::num::NonZeroU32;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    pub struct Spacer {
+        interval: Duration,
+        next: Mutex<Option<Instant>>,
+    }
+
+    impl Spacer {
+        pub fn new(per_second: NonZeroU32) -> Self {
+            Self {
+                interval: Duration::from_secs(1) / per_second.get(),
+                next: Mutex::new(None),
+            }
+        }
+
+        pub async fn until_ready(&self) {
+            let now = Instant::now();
+            let slot = {
+                let mut next = self
+                    .next
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner());
+                let slot = next.map_or(now, |next| next.max(now));
+                *next = Some(slot + self.interval);
+                slot
+            };
+            if slot > now {
+                sleep(slot - now).await;
+            }
+        }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_high_priority_overtakes_queued_requests() {
+        let limiter = Arc::new(RequestLimiter::per_second(NonZeroU32::new(10).unwrap()));
+        // Use up the burst so the next requests have to wait
+        while limiter.inner.check().is_ok() {}
+
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let mut tasks = Vec::new();
+        for (name, priority) in [
+            ("normal", Priority::Normal),
+            ("low", Priority::Low),
+            ("high", Priority::High),
+        ] {
+            let (limiter, order) = (limiter.clone(), order.clone());
+            tasks.push(tokio::spawn(async move {
+                limiter.until_ready(priority).await;
+                order.lock().unwrap().push(name);
+            }));
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        // "normal" already held the limiter when the others arrived
+        assert_eq!(*order.lock().unwrap(), ["normal", "high", "low"]);
+    }
+
+    #[tokio::test]
+    async fn test_load_and_estimated_wait() {
+        let limiter = Arc::new(RequestLimiter::per_second(NonZeroU32::new(4).unwrap()));
+        for _ in 0..4 {
+            limiter.until_ready(Priority::Normal).await;
+        }
+        assert_eq!(limiter.current_load().queued, 0);
+        assert_eq!(limiter.current_load().utilization, 1.0);
+
+        let waiting: Vec<_> = (0..2)
+            .map(|_| {
+                let limiter = limiter.clone();
+                tokio::spawn(async move { limiter.until_ready(Priority::Normal).await })
+            })
+            .collect();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(limiter.current_load().queued, 2);
+        assert_eq!(limiter.estimated_wait(), Duration::from_millis(750));
+
+        // A request that gives up leaves the queue
+        waiting[1].abort();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(limiter.current_load().queued, 1);
+    }
+}
@@ -0,0 +1,30 @@
+//! Settings replaced at runtime
+
+use std::sync::{Arc, RwLock};
+
+/// Value that can be replaced while requests are in flight
+///
+/// Readers take a snapshot, so a request keeps the value it started with.
+pub(crate) struct Swap<T>(RwLock<Arc<T>>);
+
+impl<T> Swap<T> {
+    pub fn new(value: T) -> Self {
+        Self(RwLock::new(Arc::new(value)))
+    }
+
+    /// Current value
+    pub fn load(&self) -> Arc<T> {
+        self.0
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// Replace the value for subsequent readers
+    pub fn store(&self, value: T) {
+        *self
+            .0
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Arc::new(value);
+    }
+}
@@ -1,5 +1,13 @@
 //! Type definitions for Etherscan API responses
 
+use crate::abi::{decode_input, DecodedCall};
+use crate::client::endpoints::gas::GasSpeed;
+use crate::error::Result;
+use crate::payment::utils::{parse_hex_u128, wei_to_gwei};
+use crate::primitives::{
+    empty_as_none, quantity, unix_timestamp, Address, TokenAmount, TxHash, U256,
+};
+use chrono::{DateTime, NaiveDate, Utc};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
@@ -7,99 +15,194 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Transaction {
-    pub block_number: String,
-    pub time_stamp: String,
-    pub hash: String,
-    pub nonce: String,
+    #[serde(default, with = "quantity")]
+    pub block_number: u64,
+    #[serde(default, with = "unix_timestamp")]
+    pub time_stamp: Option<DateTime<Utc>>,
+    pub hash: TxHash,
+    #[serde(default, with = "quantity")]
+    pub nonce: u64,
     pub block_hash: String,
-    pub transaction_index: String,
-    pub from: String,
-    pub to: String,
+    #[serde(default, with = "quantity")]
+    pub transaction_index: u64,
+    pub from: Address,
+    /// Recipient (`None` for contract creation)
+    #[serde(with = "empty_as_none")]
+    pub to: Option<Address>,
     pub value: String,
-    pub gas: String,
-    pub gas_price: String,
+    #[serde(default, with = "quantity")]
+    pub gas: u64,
+    #[serde(default, with = "quantity")]
+    pub gas_price: U256,
     pub is_error: String,
     #[serde(rename = "txreceipt_status")]
     pub txreceipt_status: String,
     pub input: String,
     pub contract_address: String,
-    pub cumulative_gas_used: String,
-    pub gas_used: String,
-    pub confirmations: String,
+    #[serde(default, with = "quantity")]
+    pub cumulative_gas_used: u64,
+    #[serde(default, with = "quantity")]
+    pub gas_used: u64,
+    #[serde(default, with = "quantity")]
+    pub confirmations: u64,
     #[serde(default)]
     pub method_id: String,
     #[serde(default)]
     pub function_name: String,
+    /// Fields of the response the crate doesn't model
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
 impl Transaction {
     /// Get confirmations as u64
+    #[deprecated(note = "`confirmations` is a `u64` field now")]
     pub fn confirmations_u64(&self) -> u64 {
-        self.confirmations.parse().unwrap_or(0)
+        self.confirmations
+    }
+
+    /// Get the exact value (in wei, 18 decimals)
+    pub fn value_amount(&self) -> Result<TokenAmount> {
+        TokenAmount::from_raw_str(&self.value, 18)
+    }
+
+    /// Get value as Decimal, in the chain's native currency
+    ///
+    /// Rounded to `Decimal` precision; use [`value_amount`](Self::value_amount)
+    /// for the exact value.
+    pub fn value_native(&self) -> Decimal {
+        self.value_amount()
+            .map(|amount| amount.to_decimal_lossy())
+            .unwrap_or(Decimal::ZERO)
     }
 
     /// Get value as Decimal (in BNB)
+    #[deprecated(note = "use `value_native`")]
     pub fn value_bnb(&self) -> Decimal {
-        let wei: u128 = self.value.parse().unwrap_or(0);
-        Decimal::from(wei) / Decimal::from(1_000_000_000_000_000_000u128)
+        self.value_native()
     }
 
     /// Check if transaction was successful
     pub fn is_successful(&self) -> bool {
         self.is_error == "0" && self.txreceipt_status == "1"
     }
+
+    /// Gas fee the sender paid (`gas_used * gas_price`, in wei)
+    pub fn gas_cost(&self) -> TokenAmount {
+        gas_cost(self.gas_used, self.gas_price)
+    }
+
+    /// Gas fee the sender paid, in the native currency
+    pub fn gas_cost_eth(&self) -> Decimal {
+        self.gas_cost().to_decimal_lossy()
+    }
+
+    /// Value net of the gas fee, in the native currency
+    ///
+    /// Negative when the fee exceeds the value sent (e.g. zero-value contract
+    /// calls).
+    pub fn effective_amount(&self) -> Decimal {
+        self.value_native() - self.gas_cost_eth()
+    }
+
+    /// Decode the calldata of token transfers and router swaps
+    pub fn decode_input(&self) -> Option<DecodedCall> {
+        decode_input(&self.input)
+    }
 }
 
 /// Internal transaction (contract internal transfers)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct InternalTransaction {
-    pub block_number: String,
-    pub time_stamp: String,
-    pub hash: String,
-    pub from: String,
-    pub to: String,
+    #[serde(default, with = "quantity")]
+    pub block_number: u64,
+    #[serde(default, with = "unix_timestamp")]
+    pub time_stamp: Option<DateTime<Utc>>,
+    pub hash: TxHash,
+    pub from: Address,
+    /// Recipient (`None` for contract creation)
+    #[serde(with = "empty_as_none")]
+    pub to: Option<Address>,
     pub value: String,
     pub contract_address: String,
     pub input: String,
     #[serde(rename = "type")]
     pub tx_type: String,
-    pub gas: String,
-    pub gas_used: String,
+    #[serde(default, with = "quantity")]
+    pub gas: u64,
+    #[serde(default, with = "quantity")]
+    pub gas_used: u64,
     pub trace_id: String,
     pub is_error: String,
     pub err_code: String,
+    /// Fields of the response the crate doesn't model
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl InternalTransaction {
+    /// Get the exact value (in wei, 18 decimals)
+    pub fn value_amount(&self) -> Result<TokenAmount> {
+        TokenAmount::from_raw_str(&self.value, 18)
+    }
+
+    /// Get value as Decimal in the native currency, rounded to `Decimal` precision
+    pub fn value_native(&self) -> Decimal {
+        self.value_amount()
+            .map(|amount| amount.to_decimal_lossy())
+            .unwrap_or(Decimal::ZERO)
+    }
+
+    /// Get value as Decimal (in BNB)
+    #[deprecated(note = "use `value_native`")]
+    pub fn value_bnb(&self) -> Decimal {
+        self.value_native()
+    }
 }
 
 /// ERC20 token transfer
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TokenTransfer {
-    pub block_number: String,
-    pub time_stamp: String,
-    pub hash: String,
-    pub nonce: String,
+    #[serde(default, with = "quantity")]
+    pub block_number: u64,
+    #[serde(default, with = "unix_timestamp")]
+    pub time_stamp: Option<DateTime<Utc>>,
+    pub hash: TxHash,
+    #[serde(default, with = "quantity")]
+    pub nonce: u64,
     pub block_hash: String,
-    pub from: String,
-    pub contract_address: String,
-    pub to: String,
+    pub from: Address,
+    pub contract_address: Address,
+    pub to: Address,
     pub value: String,
     pub token_name: String,
     pub token_symbol: String,
     pub token_decimal: String,
-    pub transaction_index: String,
-    pub gas: String,
-    pub gas_price: String,
-    pub gas_used: String,
-    pub cumulative_gas_used: String,
+    #[serde(default, with = "quantity")]
+    pub transaction_index: u64,
+    #[serde(default, with = "quantity")]
+    pub gas: u64,
+    #[serde(default, with = "quantity")]
+    pub gas_price: U256,
+    #[serde(default, with = "quantity")]
+    pub gas_used: u64,
+    #[serde(default, with = "quantity")]
+    pub cumulative_gas_used: u64,
     pub input: String,
-    pub confirmations: String,
+    #[serde(default, with = "quantity")]
+    pub confirmations: u64,
+    /// Fields of the response the crate doesn't model
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
 impl TokenTransfer {
     /// Get confirmations as u64
+    #[deprecated(note = "`confirmations` is a `u64` field now")]
     pub fn confirmations_u64(&self) -> u64 {
-        self.confirmations.parse().unwrap_or(0)
+        self.confirmations
     }
 
     /// Get token decimals as u8
@@ -107,12 +210,29 @@ impl TokenTransfer {
         self.token_decimal.parse().unwrap_or(18)
     }
 
+    /// Get the exact transferred amount
+    pub fn value_amount(&self) -> Result<TokenAmount> {
+        TokenAmount::from_raw_str(&self.value, self.decimals())
+    }
+
     /// Get value as Decimal (in token units)
+    ///
+    /// Rounded to `Decimal` precision; use [`value_amount`](Self::value_amount)
+    /// for the exact value.
     pub fn value_tokens(&self) -> Decimal {
-        let raw_value: u128 = self.value.parse().unwrap_or(0);
-        let decimals = self.decimals();
-        let divisor = 10u128.pow(decimals as u32);
-        Decimal::from(raw_value) / Decimal::from(divisor)
+        self.value_amount()
+            .map(|amount| amount.to_decimal_lossy())
+            .unwrap_or(Decimal::ZERO)
+    }
+
+    /// Gas fee of the transaction carrying the transfer (in wei)
+    pub fn gas_cost(&self) -> TokenAmount {
+        gas_cost(self.gas_used, self.gas_price)
+    }
+
+    /// Gas fee of the transaction carrying the transfer, in the native currency
+    pub fn gas_cost_eth(&self) -> Decimal {
+        self.gas_cost().to_decimal_lossy()
     }
 }
 
@@ -124,13 +244,38 @@ pub struct Balance {
 }
 
 impl Balance {
+    /// Get the exact balance (in wei, 18 decimals)
+    pub fn amount(&self) -> Result<TokenAmount> {
+        TokenAmount::from_raw_str(&self.wei, 18)
+    }
+
+    /// Get balance as Decimal in the native currency, rounded to `Decimal` precision
+    pub fn ether(&self) -> Decimal {
+        self.amount()
+            .map(|amount| amount.to_decimal_lossy())
+            .unwrap_or(Decimal::ZERO)
+    }
+
     /// Get balance as Decimal (in BNB)
+    #[deprecated(note = "use `ether`")]
     pub fn bnb(&self) -> Decimal {
-        let wei: u128 = self.wei.parse().unwrap_or(0);
-        Decimal::from(wei) / Decimal::from(1_000_000_000_000_000_000u128)
+        self.ether()
     }
 }
 
+/// ERC20 token metadata, read from the contract
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenMetadata {
+    /// Token contract address
+    pub contract_address: Address,
+    /// Token name (empty if the contract doesn't report one)
+    pub name: String,
+    /// Ticker symbol (empty if the contract doesn't report one)
+    pub symbol: String,
+    /// Decimals of the smallest unit
+    pub decimals: u8,
+}
+
 /// Token balance
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokenBalance {
@@ -142,28 +287,78 @@ pub struct TokenBalance {
 }
 
 impl TokenBalance {
-    /// Get balance as Decimal (in token units)
-    pub fn value_tokens(&self) -> Decimal {
-        let raw_value: u128 = self.balance.parse().unwrap_or(0);
+    /// Get the exact balance
+    pub fn amount(&self) -> Result<TokenAmount> {
         let decimals: u8 = self.token_decimal.parse().unwrap_or(18);
-        let divisor = 10u128.pow(decimals as u32);
-        Decimal::from(raw_value) / Decimal::from(divisor)
+        TokenAmount::from_raw_str(&self.balance, decimals)
+    }
+
+    /// Get balance as Decimal (in token units), rounded to `Decimal` precision
+    pub fn value_tokens(&self) -> Decimal {
+        self.amount()
+            .map(|amount| amount.to_decimal_lossy())
+            .unwrap_or(Decimal::ZERO)
     }
 }
 
-/// Transaction receipt
+/// Transaction receipt from the proxy module (`eth_getTransactionReceipt`)
+///
+/// Quantities are `0x`-prefixed hex, as returned by JSON-RPC.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct TransactionReceipt {
+pub struct RpcReceipt {
     pub block_hash: String,
     pub block_number: String,
+    #[serde(default)]
+    pub from: Option<Address>,
+    #[serde(default)]
+    pub to: Option<Address>,
     pub contract_address: Option<String>,
     pub cumulative_gas_used: String,
     pub gas_used: String,
+    #[serde(default)]
+    pub effective_gas_price: Option<String>,
     pub logs: Vec<Log>,
     pub status: String,
     pub transaction_hash: String,
     pub transaction_index: String,
+    #[serde(rename = "type", default)]
+    pub tx_type: Option<String>,
+    /// Fields of the response the crate doesn't model
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Former name of [`RpcReceipt`]
+#[deprecated(note = "renamed to `RpcReceipt`")]
+pub type TransactionReceipt = RpcReceipt;
+
+impl RpcReceipt {
+    /// Block the transaction was included in
+    pub fn block_number_u64(&self) -> Result<u64> {
+        parse_hex_u64(&self.block_number)
+    }
+
+    /// Check if the transaction succeeded (`status == 0x1`)
+    pub fn is_successful(&self) -> bool {
+        matches!(parse_hex_u64(&self.status), Ok(1))
+    }
+
+    /// Gas used by the transaction
+    pub fn gas_used_u64(&self) -> u64 {
+        parse_hex_u64(&self.gas_used).unwrap_or(0)
+    }
+
+    /// Price paid per unit of gas, in wei (`None` for nodes predating EIP-1559)
+    pub fn effective_gas_price_wei(&self) -> Option<u128> {
+        parse_hex_u128(self.effective_gas_price.as_deref()?).ok()
+    }
+
+    /// Gas fee the sender paid (in wei), if the node reports the effective gas price
+    pub fn gas_cost(&self) -> Option<TokenAmount> {
+        let price = self.effective_gas_price_wei()?;
+        Some(gas_cost(self.gas_used_u64(), U256::from(price)))
+    }
 }
 
 /// Transaction log
@@ -178,6 +373,7 @@ pub struct Log {
     pub transaction_index: String,
     pub block_hash: String,
     pub log_index: String,
+    #[serde(default)]
     pub removed: bool,
 }
 
@@ -190,7 +386,11 @@ pub struct GasOracle {
     pub fast_gas_price: String,
     #[serde(rename = "suggestBaseFee")]
     pub suggest_base_fee: String,
+    #[serde(rename = "gasUsedRatio")]
     pub gas_used_ratio: String,
+    /// Fields of the response the crate doesn't model
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
 impl GasOracle {
@@ -208,78 +408,547 @@ impl GasOracle {
     pub fn fast_gwei(&self) -> Decimal {
         self.fast_gas_price.parse().unwrap_or(Decimal::ZERO)
     }
+
+    /// Get gas price in gwei for a given speed
+    pub fn price_gwei(&self, speed: GasSpeed) -> Decimal {
+        match speed {
+            GasSpeed::Safe => self.safe_gwei(),
+            GasSpeed::Propose => self.propose_gwei(),
+            GasSpeed::Fast => self.fast_gwei(),
+        }
+    }
+
+    /// Get suggested base fee in gwei
+    pub fn base_fee_gwei(&self) -> Decimal {
+        self.suggest_base_fee.parse().unwrap_or(Decimal::ZERO)
+    }
+
+    /// Build oracle data from JSON-RPC values, for chains without a gas tracker
+    ///
+    /// With a fee history, the speeds are the next block's base fee plus the
+    /// average 10th, 50th, and 90th percentile priority fee of recent blocks.
+    /// Otherwise every speed is `eth_gasPrice`, which also stands in for the
+    /// base fee.
+    pub fn from_rpc(gas_price_wei: u128, history: Option<&RpcFeeHistory>) -> Self {
+        let base_fee = history
+            .and_then(RpcFeeHistory::base_fee_wei)
+            .unwrap_or(gas_price_wei);
+        let price = |percentile: usize| {
+            let priority = history
+                .and_then(|history| history.average_reward_wei(percentile))
+                .unwrap_or_else(|| gas_price_wei.saturating_sub(base_fee));
+            gwei_string(base_fee.saturating_add(priority))
+        };
+
+        Self {
+            safe_gas_price: price(0),
+            propose_gas_price: price(1),
+            fast_gas_price: price(2),
+            suggest_base_fee: gwei_string(base_fee),
+            gas_used_ratio: history
+                .map(|history| {
+                    let ratios: Vec<String> =
+                        history.gas_used_ratio.iter().map(f64::to_string).collect();
+                    ratios.join(",")
+                })
+                .unwrap_or_default(),
+            extra: Default::default(),
+        }
+    }
+}
+
+fn gwei_string(wei: u128) -> String {
+    wei_to_gwei(wei).normalize().to_string()
+}
+
+/// `eth_feeHistory` response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcFeeHistory {
+    pub oldest_block: String,
+    /// Base fees of the requested blocks, plus the block after the newest one
+    pub base_fee_per_gas: Vec<String>,
+    pub gas_used_ratio: Vec<f64>,
+    /// Priority fees per block at the requested percentiles
+    #[serde(default)]
+    pub reward: Vec<Vec<String>>,
+}
+
+impl RpcFeeHistory {
+    /// Base fee of the next block (in wei)
+    pub fn base_fee_wei(&self) -> Option<u128> {
+        parse_hex_u128(self.base_fee_per_gas.last()?).ok()
+    }
+
+    /// Priority fee at the `index`-th requested percentile, averaged over the blocks (in wei)
+    pub fn average_reward_wei(&self, index: usize) -> Option<u128> {
+        let rewards: Vec<u128> = self
+            .reward
+            .iter()
+            .filter_map(|block| parse_hex_u128(block.get(index)?).ok())
+            .collect();
+        let count = rewards.len() as u128;
+        (count > 0).then(|| rewards.iter().sum::<u128>() / count)
+    }
+}
+
+/// Transfer that first funded an address (`fundedby`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct FundingSource {
+    /// Block of the funding transfer
+    #[serde(default, with = "quantity")]
+    #[cfg_attr(feature = "openapi", schema(value_type = String))]
+    pub block: u64,
+    /// Time of the funding transfer
+    #[serde(default, with = "unix_timestamp")]
+    #[cfg_attr(feature = "openapi", schema(value_type = String))]
+    pub time_stamp: Option<DateTime<Utc>>,
+    /// Address the funds came from
+    pub funding_address: Address,
+    /// Funding transaction hash
+    #[cfg_attr(feature = "openapi", schema(value_type = String))]
+    pub funding_txn: TxHash,
+    /// Funded amount in wei
+    pub value: String,
+}
+
+/// Block validated (mined) by an address (`getminedblocks`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidatedBlock {
+    /// Block number
+    #[serde(default, with = "quantity")]
+    pub block_number: u64,
+    /// Block timestamp
+    #[serde(default, with = "unix_timestamp")]
+    pub time_stamp: Option<DateTime<Utc>>,
+    /// Reward paid to the validator, in wei
+    pub block_reward: String,
+}
+
+impl ValidatedBlock {
+    /// Get the reward as Decimal in the native currency
+    pub fn reward_native(&self) -> Decimal {
+        TokenAmount::from_raw_str(&self.block_reward, 18)
+            .map(|amount| amount.to_decimal_lossy())
+            .unwrap_or(Decimal::ZERO)
+    }
+}
+
+/// Validator withdrawal from the beacon chain (`txsBeaconWithdrawal`)
+///
+/// Withdrawals credit the address without a transaction, so they don't show
+/// up in [`Transaction`] listings.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BeaconWithdrawal {
+    /// Global withdrawal index
+    #[serde(default, with = "quantity")]
+    pub withdrawal_index: u64,
+    /// Index of the withdrawing validator
+    #[serde(default, with = "quantity")]
+    pub validator_index: u64,
+    /// Credited address
+    pub address: Address,
+    /// Withdrawn amount in gwei
+    pub amount: String,
+    /// Block the withdrawal was included in
+    #[serde(default, with = "quantity")]
+    pub block_number: u64,
+    /// Block timestamp
+    #[serde(default, with = "unix_timestamp")]
+    pub timestamp: Option<DateTime<Utc>>,
+}
+
+impl BeaconWithdrawal {
+    /// Get the exact amount (gwei, 9 decimals)
+    pub fn amount_value(&self) -> Result<TokenAmount> {
+        TokenAmount::from_raw_str(&self.amount, 9)
+    }
+
+    /// Get the amount as Decimal in the native currency
+    pub fn amount_native(&self) -> Decimal {
+        self.amount_value()
+            .map(|amount| amount.to_decimal_lossy())
+            .unwrap_or(Decimal::ZERO)
+    }
+}
+
+/// Verified source of a contract (`getsourcecode`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ContractSource {
+    /// Source code; empty if the contract isn't verified
+    #[serde(default)]
+    pub source_code: String,
+    /// Contract ABI as JSON, or an explanation if it isn't verified
+    #[serde(default, rename = "ABI")]
+    pub abi: String,
+    #[serde(default)]
+    pub contract_name: String,
+    #[serde(default)]
+    pub compiler_version: String,
+    /// `"1"` if the contract is a proxy
+    #[serde(default)]
+    pub proxy: String,
+    /// Implementation address of a proxy (empty otherwise)
+    #[serde(default)]
+    pub implementation: String,
+    /// Fields of the response the crate doesn't model
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl ContractSource {
+    /// Check whether the source is verified on the explorer
+    pub fn is_verified(&self) -> bool {
+        !self.source_code.is_empty()
+    }
+
+    /// Check whether the contract is a proxy
+    pub fn is_proxy(&self) -> bool {
+        self.proxy == "1"
+    }
+}
+
+/// Creator and creation transaction of a contract (`getcontractcreation`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractCreation {
+    pub contract_address: Address,
+    pub contract_creator: Address,
+    pub tx_hash: TxHash,
+    /// Creation block (0 if the explorer doesn't report it)
+    #[serde(default, with = "quantity")]
+    pub block_number: u64,
+    /// Creation time, if reported by the explorer
+    #[serde(default, with = "unix_timestamp")]
+    pub timestamp: Option<DateTime<Utc>>,
+}
+
+/// Latest ETH price as reported by the stats module
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EthPrice {
+    pub ethbtc: String,
+    pub ethbtc_timestamp: String,
+    pub ethusd: String,
+    pub ethusd_timestamp: String,
+}
+
+impl EthPrice {
+    /// Get the ETH price in USD
+    pub fn usd(&self) -> Decimal {
+        self.ethusd.parse().unwrap_or(Decimal::ZERO)
+    }
+
+    /// Get the ETH price in BTC
+    pub fn btc(&self) -> Decimal {
+        self.ethbtc.parse().unwrap_or(Decimal::ZERO)
+    }
+}
+
+/// Transactions of one day (`dailytx`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DailyTxCount {
+    /// Day (UTC)
+    #[serde(rename = "UTCDate")]
+    pub utc_date: NaiveDate,
+    /// Start of the day
+    #[serde(rename = "unixTimeStamp", default, with = "unix_timestamp")]
+    pub timestamp: Option<DateTime<Utc>>,
+    /// Transactions mined that day
+    #[serde(rename = "transactionCount", default, with = "quantity")]
+    pub transaction_count: u64,
+}
+
+/// Gas prices paid on one day (`dailyavggasprice`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DailyGasPrice {
+    /// Day (UTC)
+    #[serde(rename = "UTCDate")]
+    pub utc_date: NaiveDate,
+    /// Start of the day
+    #[serde(rename = "unixTimeStamp", default, with = "unix_timestamp")]
+    pub timestamp: Option<DateTime<Utc>>,
+    /// Highest gas price in wei
+    #[serde(rename = "maxGasPrice_Wei")]
+    pub max_gas_price_wei: String,
+    /// Lowest gas price in wei
+    #[serde(rename = "minGasPrice_Wei")]
+    pub min_gas_price_wei: String,
+    /// Average gas price in wei
+    #[serde(rename = "avgGasPrice_Wei")]
+    pub avg_gas_price_wei: String,
+}
+
+impl DailyGasPrice {
+    /// Get the average gas price in gwei
+    pub fn avg_gwei(&self) -> Decimal {
+        wei_to_gwei(self.avg_gas_price_wei.parse().unwrap_or(0))
+    }
+}
+
+/// Network utilization of one day (`dailynetutilization`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DailyNetworkUtilization {
+    /// Day (UTC)
+    #[serde(rename = "UTCDate")]
+    pub utc_date: NaiveDate,
+    /// Start of the day
+    #[serde(rename = "unixTimeStamp", default, with = "unix_timestamp")]
+    pub timestamp: Option<DateTime<Utc>>,
+    /// Share of the block gas limit used, from 0 to 1
+    #[serde(rename = "networkUtilization")]
+    pub network_utilization: String,
+}
+
+impl DailyNetworkUtilization {
+    /// Get the utilization as Decimal (0 to 1)
+    pub fn ratio(&self) -> Decimal {
+        self.network_utilization.parse().unwrap_or(Decimal::ZERO)
+    }
 }
 
 /// Block information
+#[deprecated(note = "doesn't match proxy responses; use `RpcBlock`")]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Block {
-    pub number: String,
-    pub timestamp: String,
+    #[serde(default, with = "quantity")]
+    pub number: u64,
+    #[serde(default, with = "unix_timestamp")]
+    pub timestamp: Option<DateTime<Utc>>,
     pub hash: String,
     pub parent_hash: String,
     pub nonce: String,
     pub miner: String,
-    pub difficulty: String,
-    pub total_difficulty: String,
-    pub size: String,
-    pub gas_limit: String,
-    pub gas_used: String,
+    #[serde(default, with = "quantity")]
+    pub difficulty: U256,
+    #[serde(default, with = "quantity")]
+    pub total_difficulty: U256,
+    #[serde(default, with = "quantity")]
+    pub size: u64,
+    #[serde(default, with = "quantity")]
+    pub gas_limit: u64,
+    #[serde(default, with = "quantity")]
+    pub gas_used: u64,
     pub transaction_count: usize,
 }
 
-/// Proxy transaction (standard JSON-RPC format)
+/// Block from the proxy module (`eth_getBlockByNumber`)
+///
+/// Hex quantities are parsed on deserialization.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct ProxyTransaction {
+pub struct RpcBlock {
+    #[serde(default, with = "quantity")]
+    pub number: u64,
+    pub hash: String,
+    pub parent_hash: String,
+    #[serde(default, with = "unix_timestamp")]
+    pub timestamp: Option<DateTime<Utc>>,
+    pub miner: Address,
+    #[serde(default, with = "quantity")]
+    pub gas_limit: u64,
+    #[serde(default, with = "quantity")]
+    pub gas_used: u64,
+    /// Base fee in wei (`None` before EIP-1559)
+    #[serde(default)]
+    pub base_fee_per_gas: Option<String>,
+    #[serde(default, with = "quantity")]
+    pub size: u64,
+    #[serde(default)]
+    pub transactions: BlockTransactions,
+    /// Fields of the response the crate doesn't model
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl RpcBlock {
+    /// Base fee in wei (`None` before EIP-1559)
+    pub fn base_fee_wei(&self) -> Option<u128> {
+        parse_hex_u128(self.base_fee_per_gas.as_deref()?).ok()
+    }
+
+    /// Number of transactions in the block
+    pub fn transaction_count(&self) -> usize {
+        match &self.transactions {
+            BlockTransactions::Hashes(hashes) => hashes.len(),
+            BlockTransactions::Full(transactions) => transactions.len(),
+        }
+    }
+
+    /// Hashes of the transactions in the block
+    pub fn transaction_hashes(&self) -> Vec<&TxHash> {
+        match &self.transactions {
+            BlockTransactions::Hashes(hashes) => hashes.iter().collect(),
+            BlockTransactions::Full(transactions) => {
+                transactions.iter().map(|tx| &tx.hash).collect()
+            }
+        }
+    }
+}
+
+/// Transactions of an [`RpcBlock`]: hashes, or full transactions if requested
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum BlockTransactions {
+    /// Transaction hashes
+    Hashes(Vec<TxHash>),
+    /// Full transactions
+    Full(Vec<RpcTransaction>),
+}
+
+impl Default for BlockTransactions {
+    fn default() -> Self {
+        BlockTransactions::Hashes(Vec::new())
+    }
+}
+
+/// Transaction from the proxy module (`eth_getTransactionByHash`)
+///
+/// Quantities are `0x`-prefixed hex, as returned by JSON-RPC. Block fields
+/// are `None` while the transaction is pending.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcTransaction {
     pub block_hash: Option<String>,
     pub block_number: Option<String>,
-    pub from: String,
+    pub from: Address,
     pub gas: String,
     pub gas_price: String,
-    pub hash: String,
+    #[serde(default)]
+    pub max_fee_per_gas: Option<String>,
+    #[serde(default)]
+    pub max_priority_fee_per_gas: Option<String>,
+    pub hash: TxHash,
     pub input: String,
     pub nonce: String,
-    pub to: Option<String>,
+    pub to: Option<Address>,
     pub transaction_index: Option<String>,
     pub value: String,
+    #[serde(default)]
+    pub chain_id: Option<String>,
+    #[serde(rename = "type", default)]
+    pub tx_type: Option<String>,
+    /// Fields of the response the crate doesn't model
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
-impl From<ProxyTransaction> for Transaction {
-    fn from(proxy: ProxyTransaction) -> Self {
-        let clean_hex = |s: &str| {
-            if s.starts_with("0x") {
-                u128::from_str_radix(&s[2..], 16).unwrap_or(0).to_string()
-            } else {
-                s.to_string()
-            }
-        };
+/// Former name of [`RpcTransaction`]
+#[deprecated(note = "renamed to `RpcTransaction`")]
+pub type ProxyTransaction = RpcTransaction;
 
-        Self {
-            block_number: proxy.block_number.as_deref().map(clean_hex).unwrap_or_default(),
-            time_stamp: String::new(),
-            hash: proxy.hash,
-            nonce: clean_hex(&proxy.nonce),
-            block_hash: proxy.block_hash.unwrap_or_default(),
-            transaction_index: proxy.transaction_index.as_deref().map(clean_hex).unwrap_or_default(),
-            from: proxy.from,
-            to: proxy.to.unwrap_or_default(),
-            value: clean_hex(&proxy.value),
-            gas: clean_hex(&proxy.gas),
-            gas_price: clean_hex(&proxy.gas_price),
-            is_error: "0".to_string(), // Assume success or unknown
-            txreceipt_status: String::new(), // Unknown
-            input: proxy.input,
-            contract_address: String::new(),
-            cumulative_gas_used: String::new(),
-            gas_used: String::new(), // In receipt
-            confirmations: "0".to_string(),
+impl RpcTransaction {
+    /// Block the transaction was included in (`None` while pending)
+    pub fn block_number_u64(&self) -> Option<u64> {
+        parse_hex_u64(self.block_number.as_deref()?).ok()
+    }
+
+    /// Check if the transaction is still in the mempool
+    pub fn is_pending(&self) -> bool {
+        self.block_number.is_none()
+    }
+
+    /// Sender nonce
+    pub fn nonce_u64(&self) -> u64 {
+        parse_hex_u64(&self.nonce).unwrap_or(0)
+    }
+
+    /// Gas limit
+    pub fn gas_u64(&self) -> u64 {
+        parse_hex_u64(&self.gas).unwrap_or(0)
+    }
+
+    /// Gas price in wei
+    pub fn gas_price_wei(&self) -> u128 {
+        parse_hex_u128(&self.gas_price).unwrap_or(0)
+    }
+
+    /// Get the exact value (in wei, 18 decimals)
+    pub fn value_amount(&self) -> Result<TokenAmount> {
+        TokenAmount::from_hex(&self.value, 18)
+    }
+
+    /// Decode the calldata of token transfers and router swaps
+    pub fn decode_input(&self) -> Option<DecodedCall> {
+        decode_input(&self.input)
+    }
+
+    /// Convert to the account-module [`Transaction`] format
+    ///
+    /// Quantities become decimal strings. Execution results (status, gas
+    /// used) are only known from the receipt; without one the transaction is
+    /// reported as successful with unknown gas usage.
+    pub fn into_transaction(self, receipt: Option<&RpcReceipt>) -> Transaction {
+        let u64_of = |s: &str| quantity::parse::<u64>(s).unwrap_or_default();
+        let u64_opt = |s: &Option<String>| s.as_deref().map(u64_of).unwrap_or_default();
+
+        let (is_error, txreceipt_status, contract_address, cumulative_gas_used, gas_used) =
+            match receipt {
+                Some(receipt) => (
+                    if receipt.is_successful() { "0" } else { "1" }.to_string(),
+                    u64_of(&receipt.status).to_string(),
+                    receipt.contract_address.clone().unwrap_or_default(),
+                    u64_of(&receipt.cumulative_gas_used),
+                    u64_of(&receipt.gas_used),
+                ),
+                None => (
+                    "0".to_string(), // Assume success or unknown
+                    String::new(),
+                    String::new(),
+                    0,
+                    0,
+                ),
+            };
+
+        Transaction {
+            block_number: u64_opt(&self.block_number),
+            time_stamp: None,
+            hash: self.hash,
+            nonce: u64_of(&self.nonce),
+            block_hash: self.block_hash.unwrap_or_default(),
+            transaction_index: u64_opt(&self.transaction_index),
+            from: self.from,
+            to: self.to,
+            value: quantity::parse::<U256>(&self.value)
+                .unwrap_or_default()
+                .to_string(),
+            gas: u64_of(&self.gas),
+            gas_price: quantity::parse(&self.gas_price).unwrap_or_default(),
+            is_error,
+            txreceipt_status,
+            input: self.input,
+            contract_address,
+            cumulative_gas_used,
+            gas_used,
+            confirmations: 0,
             method_id: String::new(),
             function_name: String::new(),
+            extra: Default::default(),
         }
     }
 }
 
+impl From<RpcTransaction> for Transaction {
+    fn from(tx: RpcTransaction) -> Self {
+        tx.into_transaction(None)
+    }
+}
+
+fn gas_cost(gas_used: u64, gas_price: U256) -> TokenAmount {
+    TokenAmount::new(U256::from(gas_used).saturating_mul(gas_price), 18)
+}
+
+fn parse_hex_u64(value: &str) -> Result<u64> {
+    let quantity = parse_hex_u128(value)?;
+    u64::try_from(quantity)
+        .map_err(|_| crate::error::Error::generic(format!("Hex quantity out of range: {}", value)))
+}
+
 /// Block number response (simple string)
 pub type BlockNumber = String;
 
@@ -290,38 +959,136 @@ mod tests {
     #[test]
     fn test_balance_conversion() {
         let balance = Balance {
-            wei: "1000000000000000000".to_string(), // 1 BNB in wei
+            wei: "1000000000000000000".to_string(), // 1 ether in wei
         };
-        assert_eq!(balance.bnb(), Decimal::from(1));
+        assert_eq!(balance.ether(), Decimal::from(1));
     }
 
     #[test]
     fn test_transaction_value_conversion() {
-        let mut tx = Transaction {
-            value: "500000000000000000".to_string(), // 0.5 BNB
-            confirmations: "15".to_string(),
-            is_error: "0".to_string(),
-            txreceipt_status: "1".to_string(),
-            block_number: String::new(),
-            time_stamp: String::new(),
-            hash: String::new(),
-            nonce: String::new(),
-            block_hash: String::new(),
-            transaction_index: String::new(),
-            from: String::new(),
-            to: String::new(),
-            gas: String::new(),
-            gas_price: String::new(),
-            input: String::new(),
-            contract_address: String::new(),
-            cumulative_gas_used: String::new(),
-            gas_used: String::new(),
-            method_id: String::new(),
-            function_name: String::new(),
-        };
+        let json = serde_json::json!({
+            "blockNumber": "23697845",
+            "timeStamp": "1761566423",
+            "hash": format!("0x{}", "ab".repeat(32)),
+            "nonce": "6",
+            "blockHash": "0x3de5aa35a67f35817a9832255ae1bcf3dd61bf87997e38587b77df0bf0311583",
+            "transactionIndex": "166",
+            "from": "0x1234567890123456789012345678901234567890",
+            "to": "",
+            "value": "500000000000000000", // 0.5 ether
+            "gas": "21000",
+            "gasPrice": "2409786389",
+            "isError": "0",
+            "txreceipt_status": "1",
+            "input": "0x",
+            "contractAddress": "",
+            "cumulativeGasUsed": "19314678",
+            "gasUsed": "",
+            "confirmations": "15",
+            "methodId": "0x",
+            "functionName": ""
+        });
+        let tx: Transaction = serde_json::from_value(json.clone()).unwrap();
 
-        assert_eq!(tx.value_bnb(), Decimal::new(5, 1)); // 0.5
-        assert_eq!(tx.confirmations_u64(), 15);
+        assert_eq!(tx.value_native(), Decimal::new(5, 1)); // 0.5
+        assert_eq!(tx.confirmations, 15);
+        assert_eq!(tx.block_number, 23697845);
+        assert_eq!(tx.time_stamp.unwrap().timestamp(), 1761566423);
+        assert_eq!(tx.gas_price, U256::from(2409786389u64));
+        assert_eq!(tx.gas_used, 0); // Malformed quantities fall back to zero
         assert!(tx.is_successful());
+
+        let paid = Transaction {
+            gas_used: 21000,
+            ..tx.clone()
+        };
+        assert_eq!(paid.gas_cost().raw(), U256::from(50_605_514_169_000u64));
+        assert_eq!(paid.gas_cost_eth(), Decimal::new(50_605_514_169, 15));
+        assert_eq!(
+            paid.effective_amount(),
+            Decimal::new(499_949_394_485_831, 15)
+        );
+
+        // Round-trips in the explorer's string format
+        let mut expected = json;
+        expected["gasUsed"] = "0".into();
+        assert_eq!(serde_json::to_value(&tx).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_rpc_transaction_and_receipt() {
+        let tx: RpcTransaction = serde_json::from_value(serde_json::json!({
+            "accessList": [],
+            "blockHash": "0x3de5aa35a67f35817a9832255ae1bcf3dd61bf87997e38587b77df0bf0311583",
+            "blockNumber": "0x16999b5",
+            "chainId": "0x1",
+            "from": "0xd565b3a7166495183a25ad3381f8d01c58c33ab7",
+            "gas": "0x5208",
+            "gasPrice": "0x8fa2ff15",
+            "hash": "0x65a0983ecc0797cfb43cec86c667429f0bf43ee85a8ce09cbeebe3037fc82b46",
+            "input": "0x",
+            "maxFeePerGas": "0xb2d05e00",
+            "maxPriorityFeePerGas": "0x3b9aca00",
+            "nonce": "0x6",
+            "r": "0x1",
+            "s": "0x2",
+            "to": "0x742d35cc6634c0532925a3b844bc9e7595f0beb0",
+            "transactionIndex": "0xa6",
+            "type": "0x2",
+            "v": "0x0",
+            "value": "0xc55c3fea9000",
+            "yParity": "0x0"
+        }))
+        .unwrap();
+        assert_eq!(tx.block_number_u64(), Some(23697845));
+        assert_eq!(tx.gas_u64(), 21000);
+        assert_eq!(tx.gas_price_wei(), 2409824021);
+        assert_eq!(tx.value_amount().unwrap().to_string(), "0.000217");
+        assert_eq!(tx.extra["yParity"], "0x0");
+        assert!(!tx.extra.contains_key("chainId"));
+
+        let receipt: RpcReceipt = serde_json::from_value(serde_json::json!({
+            "blockHash": "0x3de5aa35a67f35817a9832255ae1bcf3dd61bf87997e38587b77df0bf0311583",
+            "blockNumber": "0x16999b5",
+            "contractAddress": null,
+            "cumulativeGasUsed": "0x126b7f6",
+            "effectiveGasPrice": "0x8fa2ff15",
+            "from": "0xd565b3a7166495183a25ad3381f8d01c58c33ab7",
+            "gasUsed": "0x5208",
+            "logs": [],
+            "logsBloom": "0x00",
+            "status": "0x1",
+            "to": "0x742d35cc6634c0532925a3b844bc9e7595f0beb0",
+            "transactionHash": "0x65a0983ecc0797cfb43cec86c667429f0bf43ee85a8ce09cbeebe3037fc82b46",
+            "transactionIndex": "0xa6",
+            "type": "0x2"
+        }))
+        .unwrap();
+        assert!(receipt.is_successful());
+        assert_eq!(receipt.block_number_u64().unwrap(), 23697845);
+        assert_eq!(receipt.effective_gas_price_wei(), Some(2409824021));
+        assert_eq!(receipt.extra["logsBloom"], "0x00");
+
+        let account_tx = tx.into_transaction(Some(&receipt));
+        assert_eq!(account_tx.block_number, 23697845);
+        assert_eq!(account_tx.value, "217000000000000");
+        assert_eq!(account_tx.gas_used, 21000);
+        assert!(account_tx.is_successful());
+    }
+
+    #[test]
+    fn test_beacon_withdrawal_amount() {
+        let json = serde_json::json!({
+            "withdrawalIndex": "13",
+            "validatorIndex": "117823",
+            "address": "0xb9d7934878b5fb9610b3fe8a5e441e8fad7e293f",
+            "amount": "3402931",
+            "blockNumber": "17034877",
+            "timestamp": "1681338599"
+        });
+        let withdrawal: BeaconWithdrawal = serde_json::from_value(json).unwrap();
+        assert_eq!(withdrawal.validator_index, 117823);
+        assert_eq!(withdrawal.block_number, 17034877);
+        assert_eq!(withdrawal.amount_native(), "0.003402931".parse().unwrap());
     }
 }
@@ -0,0 +1,110 @@
+//! Record/replay of explorer responses ("cassettes")
+
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// One recorded explorer response
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct Recording {
+    pub module: String,
+    pub action: String,
+    pub chain_id: u64,
+    pub params: BTreeMap<String, String>,
+    pub status: u16,
+    pub body: Value,
+}
+
+impl Recording {
+    /// Describe a request; the API key is never part of a recording
+    pub fn request(module: &str, action: &str, chain_id: u64, params: &[(&str, &str)]) -> Self {
+        Self {
+            module: module.to_string(),
+            action: action.to_string(),
+            chain_id,
+            params: params
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+            status: 0,
+            body: Value::Null,
+        }
+    }
+
+    /// File of this request in `dir`, stable across runs and parameter order
+    pub fn path(&self, dir: &Path) -> PathBuf {
+        let mut hasher = Sha256::new();
+        hasher.update(format!("{}|{}|{}", self.module, self.action, self.chain_id));
+        for (key, value) in &self.params {
+            hasher.update(format!("|{}={}", key, value));
+        }
+        let digest = hex::encode(&hasher.finalize()[..8]);
+        dir.join(format!("{}_{}_{}.json", self.module, self.action, digest))
+    }
+
+    /// Load the recorded response for this request, if any
+    pub fn load(&self, dir: &Path) -> Result<Option<Recording>> {
+        let path = self.path(dir);
+        match std::fs::read_to_string(&path) {
+            Ok(contents) => Ok(Some(serde_json::from_str(&contents)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(Error::Io(e)),
+        }
+    }
+
+    /// Save the response to `dir`, creating the directory if needed
+    pub fn save(&self, dir: &Path) -> Result<()> {
+        std::fs::create_dir_all(dir)?;
+        std::fs::write(self.path(dir), serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recording_round_trip() {
+        let dir = std::env::temp_dir().join(format!("cryptopay-vcr-{}", uuid::Uuid::new_v4()));
+        let mut recording = Recording::request(
+            "account",
+            "balance",
+            1,
+            &[("address", "0xabc"), ("tag", "latest")],
+        );
+
+        // Parameter order doesn't matter
+        let reordered = Recording::request(
+            "account",
+            "balance",
+            1,
+            &[("tag", "latest"), ("address", "0xabc")],
+        );
+        assert_eq!(recording.path(&dir), reordered.path(&dir));
+        assert_ne!(
+            recording.path(&dir),
+            Recording::request(
+                "account",
+                "balance",
+                56,
+                &[("address", "0xabc"), ("tag", "latest")]
+            )
+            .path(&dir)
+        );
+
+        assert!(recording.load(&dir).unwrap().is_none());
+        recording.status = 200;
+        recording.body = serde_json::json!({ "status": "1", "result": "42" });
+        recording.save(&dir).unwrap();
+
+        let loaded = reordered.load(&dir).unwrap().unwrap();
+        assert_eq!(loaded.status, 200);
+        assert_eq!(loaded.body["result"], "42");
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,397 @@
+//! Polling watchers that turn explorer queries into streams of new chain activity
+
+use crate::client::types::{InternalTransaction, TokenTransfer, Transaction};
+use crate::client::BscScanClient;
+use crate::error::{Error, Result};
+use crate::primitives::Address;
+use crate::rt::sleep;
+use futures::stream::{self, Stream};
+use std::collections::{HashSet, VecDeque};
+use std::time::Duration;
+
+/// Maximum number of results requested per page while tailing
+const WATCH_PAGE_SIZE: u32 = 1000;
+
+/// Maximum number of pages fetched per list in a single poll
+const WATCH_MAX_PAGES: u32 = 10;
+
+/// New activity observed on a watched address
+#[derive(Debug, Clone)]
+pub enum AddressEvent {
+    /// Normal (external) transaction sent from or to the address
+    Transaction(Transaction),
+    /// Internal transaction (contract call value transfer) involving the address
+    InternalTransaction(InternalTransaction),
+    /// ERC20 token transfer involving the address
+    TokenTransfer(TokenTransfer),
+}
+
+impl AddressEvent {
+    /// Transaction hash the event belongs to
+    pub fn tx_hash(&self) -> &str {
+        match self {
+            AddressEvent::Transaction(tx) => tx.hash.as_str(),
+            AddressEvent::InternalTransaction(tx) => tx.hash.as_str(),
+            AddressEvent::TokenTransfer(transfer) => transfer.hash.as_str(),
+        }
+    }
+
+    /// Block number the event was included in
+    pub fn block_number(&self) -> u64 {
+        match self {
+            AddressEvent::Transaction(tx) => tx.block_number,
+            AddressEvent::InternalTransaction(tx) => tx.block_number,
+            AddressEvent::TokenTransfer(transfer) => transfer.block_number,
+        }
+    }
+
+    /// Identity used to de-duplicate events across overlapping polls
+    fn identity(&self) -> String {
+        match self {
+            AddressEvent::Transaction(tx) => format!("tx:{}", tx.hash),
+            AddressEvent::InternalTransaction(tx) => {
+                format!("internal:{}:{}", tx.hash, tx.trace_id)
+            }
+            AddressEvent::TokenTransfer(transfer) => format!(
+                "token:{}:{}:{}:{}:{}",
+                transfer.hash,
+                transfer.contract_address,
+                transfer.from,
+                transfer.to,
+                transfer.value
+            ),
+        }
+    }
+}
+
+/// Block cursor shared by the tailers
+///
+/// Each poll re-queries the cursor block itself (inclusive), so events that
+/// were indexed late within the same block are still picked up. Events already
+/// emitted at the frontier block are remembered and filtered out.
+#[derive(Debug, Default)]
+struct AddressCursor {
+    next_block: u64,
+    frontier: HashSet<String>,
+}
+
+impl AddressCursor {
+    fn new(start_block: u64) -> Self {
+        Self {
+            next_block: start_block,
+            frontier: HashSet::new(),
+        }
+    }
+
+    /// Filter a batch of polled events down to unseen ones and advance the cursor
+    fn accept(&mut self, mut events: Vec<AddressEvent>) -> Vec<AddressEvent> {
+        events.retain(|event| event.block_number() >= self.next_block);
+        events.sort_by_key(|event| event.block_number());
+
+        let mut fresh = Vec::with_capacity(events.len());
+        for event in events {
+            let block = event.block_number();
+            if block > self.next_block {
+                self.next_block = block;
+                self.frontier.clear();
+            }
+            if self.frontier.insert(event.identity()) {
+                fresh.push(event);
+            }
+        }
+
+        fresh
+    }
+}
+
+struct BlockWatchState {
+    client: BscScanClient,
+    interval: Duration,
+    last_emitted: Option<u64>,
+    head: Option<u64>,
+}
+
+impl BlockWatchState {
+    /// Next height to emit, if the known chain head is ahead of what was emitted
+    fn next_height(&mut self) -> Option<u64> {
+        let head = self.head?;
+        let next = match self.last_emitted {
+            Some(last) if last >= head => return None,
+            Some(last) => last + 1,
+            None => head,
+        };
+        self.last_emitted = Some(next);
+        Some(next)
+    }
+}
+
+struct AddressWatchState {
+    client: BscScanClient,
+    address: Address,
+    interval: Duration,
+    cursor: Option<AddressCursor>,
+    buffer: VecDeque<AddressEvent>,
+    first_poll: bool,
+}
+
+impl BscScanClient {
+    /// Stream new activity for an address
+    ///
+    /// The watcher starts at the current chain head and yields every normal
+    /// transaction, internal transaction, and token transfer that appears after
+    /// that, polling the explorer every `interval`. Polling errors are logged
+    /// and retried on the next tick, so the stream never terminates on its own.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use cryptopay::*;
+    /// # use cryptopay::client::AddressEvent;
+    /// # use futures::StreamExt;
+    /// # use std::time::Duration;
+    /// # async fn example() -> Result<()> {
+    /// let client = BscScanClient::new("api-key")?;
+    /// let address: Address = "0x742d35cc6634c0532925a3b844bc9e7595f0beb0".parse()?;
+    /// let mut events = Box::pin(client.watch_address(address, Duration::from_secs(15)));
+    ///
+    /// while let Some(event) = events.next().await {
+    ///     println!("New activity in tx {}", event.tx_hash());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn watch_address(
+        &self,
+        address: Address,
+        interval: Duration,
+    ) -> impl Stream<Item = AddressEvent> + Send + 'static {
+        let state = AddressWatchState {
+            client: self.clone(),
+            address,
+            interval,
+            cursor: None,
+            buffer: VecDeque::new(),
+            first_poll: true,
+        };
+
+        stream::unfold(state, |mut state| async move {
+            loop {
+                if let Some(event) = state.buffer.pop_front() {
+                    return Some((event, state));
+                }
+
+                if !state.first_poll {
+                    sleep(state.interval).await;
+                }
+                state.first_poll = false;
+
+                if let Err(e) = state.poll().await {
+                    tracing::warn!(address = %state.address, error = %e, "address watch poll failed");
+                }
+            }
+        })
+    }
+
+    /// Stream new block heights
+    ///
+    /// Polls `eth_blockNumber` every `interval` and emits each height exactly
+    /// once, in order. The first item is the chain head at subscription time;
+    /// if the chain advances by several blocks between polls, every intermediate
+    /// height is emitted as well. Polling errors are logged and retried.
+    pub fn watch_blocks(&self, interval: Duration) -> impl Stream<Item = u64> + Send + 'static {
+        let state = BlockWatchState {
+            client: self.clone(),
+            interval,
+            last_emitted: None,
+            head: None,
+        };
+
+        stream::unfold(state, |mut state| async move {
+            loop {
+                if let Some(height) = state.next_height() {
+                    return Some((height, state));
+                }
+
+                if state.head.is_some() {
+                    sleep(state.interval).await;
+                }
+
+                match state.client.fetch_block_number().await {
+                    Ok(head) => {
+                        // Share the fresh height with confirmation checks
+                        state.client.head.observe(head).await;
+                        // Lagging backends can briefly report an older head; never go backwards
+                        state.head = Some(state.head.map_or(head, |known| known.max(head)));
+                    }
+                    Err(e) => {
+                        tracing::warn!(error = %e, "block watch poll failed");
+                        sleep(state.interval).await;
+                    }
+                }
+            }
+        })
+    }
+
+    /// Fetch every page of a list endpoint starting at `start_block`
+    async fn fetch_since<T: serde::de::DeserializeOwned>(
+        &self,
+        action: &str,
+        address: &Address,
+        start_block: u64,
+    ) -> Result<Vec<T>> {
+        let start_block = start_block.to_string();
+        let offset = WATCH_PAGE_SIZE.to_string();
+        let mut results = Vec::new();
+
+        for page in 1..=WATCH_MAX_PAGES {
+            let page = page.to_string();
+            let params = [
+                ("address", address.as_str()),
+                ("startblock", start_block.as_str()),
+                ("endblock", "99999999"),
+                ("page", page.as_str()),
+                ("offset", offset.as_str()),
+                ("sort", "asc"),
+            ];
+
+            let batch: Vec<T> = self.request_uncached("account", action, &params).await?;
+            let full_page = batch.len() as u32 >= WATCH_PAGE_SIZE;
+            results.extend(batch);
+
+            if !full_page {
+                break;
+            }
+        }
+
+        Ok(results)
+    }
+
+    /// Get the current block number
+    ///
+    /// Bypasses the response cache; heights are shared for
+    /// [`block_number_ttl_seconds`](crate::ClientConfig::block_number_ttl_seconds)
+    /// instead, and concurrent callers wait for one lookup.
+    pub(crate) async fn latest_block_number(&self) -> Result<u64> {
+        if self.options.no_cache {
+            return self.fetch_block_number().await;
+        }
+        self.head.get_or_fetch(|| self.fetch_block_number()).await
+    }
+
+    /// Fetch the current block number from the explorer
+    async fn fetch_block_number(&self) -> Result<u64> {
+        let params: [(&str, &str); 0] = [];
+        let block_hex: String = self
+            .request_uncached("proxy", "eth_blockNumber", &params)
+            .await?;
+
+        u64::from_str_radix(block_hex.trim_start_matches("0x"), 16)
+            .map_err(|_| Error::generic("Invalid block number format"))
+    }
+}
+
+impl AddressWatchState {
+    async fn poll(&mut self) -> Result<()> {
+        let cursor = match self.cursor.as_mut() {
+            Some(cursor) => cursor,
+            None => {
+                let head = self.client.latest_block_number().await?;
+                self.cursor.insert(AddressCursor::new(head + 1))
+            }
+        };
+
+        let start = cursor.next_block;
+        let transactions: Vec<Transaction> = self
+            .client
+            .fetch_since("txlist", &self.address, start)
+            .await?;
+        let internal: Vec<InternalTransaction> = self
+            .client
+            .fetch_since("txlistinternal", &self.address, start)
+            .await?;
+        let transfers: Vec<TokenTransfer> = self
+            .client
+            .fetch_since("tokentx", &self.address, start)
+            .await?;
+
+        let events = transactions
+            .into_iter()
+            .map(AddressEvent::Transaction)
+            .chain(internal.into_iter().map(AddressEvent::InternalTransaction))
+            .chain(transfers.into_iter().map(AddressEvent::TokenTransfer))
+            .collect();
+
+        self.buffer.extend(cursor.accept(events));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hash(id: u64) -> String {
+        format!("0x{:064x}", id)
+    }
+
+    fn tx(id: u64, block: u64) -> AddressEvent {
+        AddressEvent::Transaction(Transaction {
+            block_number: block,
+            time_stamp: None,
+            hash: hash(id).parse().unwrap(),
+            nonce: 0,
+            block_hash: String::new(),
+            transaction_index: 0,
+            from: "0x0000000000000000000000000000000000000001"
+                .parse()
+                .unwrap(),
+            to: None,
+            value: "0".to_string(),
+            gas: 0,
+            gas_price: Default::default(),
+            is_error: "0".to_string(),
+            txreceipt_status: "1".to_string(),
+            input: String::new(),
+            contract_address: String::new(),
+            cumulative_gas_used: 0,
+            gas_used: 0,
+            confirmations: 1,
+            method_id: String::new(),
+            function_name: String::new(),
+            extra: Default::default(),
+        })
+    }
+
+    #[test]
+    fn test_block_watch_emits_each_height_once() {
+        let mut state = BlockWatchState {
+            client: BscScanClient::new("test-key").unwrap(),
+            interval: Duration::from_secs(1),
+            last_emitted: None,
+            head: Some(100),
+        };
+
+        assert_eq!(state.next_height(), Some(100));
+        assert_eq!(state.next_height(), None);
+
+        state.head = Some(103);
+        assert_eq!(state.next_height(), Some(101));
+        assert_eq!(state.next_height(), Some(102));
+        assert_eq!(state.next_height(), Some(103));
+        assert_eq!(state.next_height(), None);
+    }
+
+    #[test]
+    fn test_cursor_skips_already_seen_events() {
+        let mut cursor = AddressCursor::new(100);
+
+        let first = cursor.accept(vec![tx(0xb, 101), tx(0xa, 100), tx(0x01d, 99)]);
+        let hashes: Vec<_> = first.iter().map(|e| e.tx_hash()).collect();
+        assert_eq!(hashes, vec![hash(0xa), hash(0xb)]);
+
+        // Frontier block is re-queried: only the late-indexed event is new
+        let second = cursor.accept(vec![tx(0xb, 101), tx(0xc, 101), tx(0xd, 102)]);
+        let hashes: Vec<_> = second.iter().map(|e| e.tx_hash()).collect();
+        assert_eq!(hashes, vec![hash(0xc), hash(0xd)]);
+        assert_eq!(cursor.next_block, 102);
+    }
+}
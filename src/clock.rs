@@ -0,0 +1,167 @@
+//! Time source for expiry checks and polling loops
+//!
+//! Components that wait or compare against the current time take a
+//! [`Clock`]. [`SystemClock`] is the default; [`MockClock`] is driven by
+//! hand, so timeout and polling logic can be tested without real delays.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use tokio::sync::Notify;
+
+/// Source of the current time and of delays
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+pub trait Clock: fmt::Debug + Send + Sync {
+    /// Current time
+    fn now(&self) -> DateTime<Utc>;
+
+    /// Wait for `duration` to pass on this clock
+    async fn sleep(&self, duration: Duration);
+}
+
+/// Wall-clock time and the runtime's timers
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+
+    async fn sleep(&self, duration: Duration) {
+        crate::rt::sleep(duration).await;
+    }
+}
+
+/// Manually driven clock for tests and simulations
+///
+/// Time only moves when [`advance`](Self::advance) or [`set`](Self::set) is
+/// called; sleepers wake once the clock has passed their deadline. With
+/// [`auto_advance`](Self::auto_advance), sleeping moves the clock forward
+/// instead, so a single polling loop runs through simulated time at once.
+/// Clones share the same time.
+///
+/// # Example
+/// ```
+/// use cryptopay::clock::{Clock, MockClock};
+/// use std::time::Duration;
+///
+/// let clock = MockClock::new("2024-01-01T00:00:00Z".parse().unwrap());
+/// clock.advance(Duration::from_secs(90));
+/// assert_eq!(clock.now().to_rfc3339(), "2024-01-01T00:01:30+00:00");
+/// ```
+#[derive(Debug, Clone)]
+pub struct MockClock {
+    now: Arc<Mutex<DateTime<Utc>>>,
+    ticks: Arc<Notify>,
+    auto_advance: bool,
+}
+
+impl MockClock {
+    /// Create a clock standing at `now`
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self {
+            now: Arc::new(Mutex::new(now)),
+            ticks: Arc::new(Notify::new()),
+            auto_advance: false,
+        }
+    }
+
+    /// Let [`sleep`](Clock::sleep) advance the clock instead of waiting
+    pub fn auto_advance(mut self, enabled: bool) -> Self {
+        self.auto_advance = enabled;
+        self
+    }
+
+    /// Move the clock forward, waking sleepers whose deadline has passed
+    pub fn advance(&self, duration: Duration) {
+        let step = chrono::Duration::from_std(duration).unwrap_or(chrono::Duration::MAX);
+        {
+            let mut now = self.lock();
+            *now = now
+                .checked_add_signed(step)
+                .unwrap_or(DateTime::<Utc>::MAX_UTC);
+        }
+        self.ticks.notify_waiters();
+    }
+
+    /// Set the clock to `now`, waking sleepers whose deadline has passed
+    pub fn set(&self, now: DateTime<Utc>) {
+        *self.lock() = now;
+        self.ticks.notify_waiters();
+    }
+
+    fn lock(&self) -> MutexGuard<'_, DateTime<Utc>> {
+        self.now
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Default for MockClock {
+    /// Clock standing at the Unix epoch
+    fn default() -> Self {
+        Self::new(DateTime::<Utc>::UNIX_EPOCH)
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl Clock for MockClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.lock()
+    }
+
+    async fn sleep(&self, duration: Duration) {
+        if self.auto_advance {
+            self.advance(duration);
+            tokio::task::yield_now().await;
+            return;
+        }
+
+        let deadline = chrono::Duration::from_std(duration)
+            .ok()
+            .and_then(|duration| self.now().checked_add_signed(duration))
+            .unwrap_or(DateTime::<Utc>::MAX_UTC);
+        loop {
+            // Register before checking so an advance in between isn't missed
+            let tick = self.ticks.notified();
+            if self.now() >= deadline {
+                return;
+            }
+            tick.await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_mock_clock_wakes_sleepers() {
+        let clock = MockClock::default();
+        let sleeper = tokio::spawn({
+            let clock = clock.clone();
+            async move { clock.sleep(Duration::from_secs(10)).await }
+        });
+        tokio::task::yield_now().await;
+
+        clock.advance(Duration::from_secs(9));
+        tokio::task::yield_now().await;
+        assert!(!sleeper.is_finished());
+
+        clock.advance(Duration::from_secs(1));
+        sleeper.await.unwrap();
+        assert_eq!(clock.now().timestamp(), 10);
+
+        let clock = clock.auto_advance(true);
+        clock.sleep(Duration::from_secs(5)).await;
+        assert_eq!(clock.now().timestamp(), 15);
+    }
+}
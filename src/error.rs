@@ -1,5 +1,7 @@
 //! Error types for the CryptoPay library
 
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
 use thiserror::Error;
 
 /// Result type alias for CryptoPay operations
@@ -14,11 +16,15 @@ pub enum Error {
 
     /// BscScan API returned an error
     #[error("BscScan API error: {message}")]
-    ApiError { message: String },
+    ApiError { kind: ApiErrorKind, message: String },
 
-    /// Rate limit exceeded
+    /// Rate limit exceeded, or no rate limiter slot within
+    /// [`ClientConfig::rate_limiter_wait_timeout_seconds`](crate::ClientConfig::rate_limiter_wait_timeout_seconds)
     #[error("Rate limit exceeded. Please retry after some time")]
-    RateLimitExceeded,
+    RateLimitExceeded {
+        /// Estimated wait until a request would be let through, if known
+        retry_after: Option<std::time::Duration>,
+    },
 
     /// Invalid configuration
     #[error("Invalid configuration: {0}")]
@@ -32,6 +38,14 @@ pub enum Error {
     #[error("Invalid transaction hash: {0}")]
     InvalidTxHash(String),
 
+    /// Invalid or unrepresentable amount
+    #[error("Invalid amount: {0}")]
+    InvalidAmount(String),
+
+    /// Payment metadata doesn't match the expected type or fails its validation
+    #[error("Invalid metadata: {0}")]
+    InvalidMetadata(String),
+
     /// Transaction not found
     #[error("Transaction not found: {0}")]
     TransactionNotFound(String),
@@ -52,6 +66,14 @@ pub enum Error {
     #[error("Token contract mismatch: expected {expected}, found {actual}")]
     TokenMismatch { expected: String, actual: String },
 
+    /// Currency is not deployed on the client's chain
+    #[error("Chain mismatch: {currency} is deployed on {expected}, not {actual}")]
+    ChainMismatch {
+        currency: String,
+        expected: String,
+        actual: String,
+    },
+
     /// Insufficient confirmations
     #[error("Insufficient confirmations: {current}/{required}")]
     InsufficientConfirmations { current: u64, required: u64 },
@@ -60,32 +82,282 @@ pub enum Error {
     #[error("Payment timeout: no transaction found within {0} seconds")]
     PaymentTimeout(u64),
 
+    /// Endpoint needs an API Pro key, see [`ClientConfig::pro_tier`](crate::ClientConfig::pro_tier)
+    #[error("API Pro endpoint required: {0}")]
+    ProEndpointRequired(String),
+
+    /// Single-item endpoint had nothing to return, e.g. an account without token info
+    #[error("Not found: {0}")]
+    NotFound(String),
+
+    /// Request did not complete before the deadline of its [`RequestOptions`](crate::client::RequestOptions)
+    #[error("Request deadline exceeded")]
+    DeadlineExceeded,
+
+    /// Payment not found in storage
+    #[error("Payment not found: {0}")]
+    PaymentNotFound(uuid::Uuid),
+
+    /// Illegal change of a payment's status
+    #[error("Invalid payment status transition: {0}")]
+    InvalidTransition(#[from] crate::payment::InvalidTransition),
+
+    /// A monitor callback panicked under [`CallbackErrorPolicy::Abort`](crate::payment::CallbackErrorPolicy::Abort)
+    #[error("Payment callback failed: {0}")]
+    CallbackFailed(String),
+
+    /// Missing or invalid credentials for an HTTP integration
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+
     /// Serialization error
     #[error("Serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
 
+    /// I/O error (e.g. while writing an export)
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
     /// Cache error
     #[error("Cache error: {0}")]
     CacheError(String),
 
-    /// Storage error
-    #[cfg(any(feature = "postgres-storage", feature = "sqlite-storage"))]
-    #[error("Storage error: {0}")]
-    StorageError(#[from] sqlx::Error),
-
     /// Generic error
     #[error("{0}")]
     Generic(String),
 }
 
+/// Kind of an error reported by the explorer API
+///
+/// Parsed from the `message` and `result` fields of the response, so callers
+/// can branch on the cause instead of matching on message strings.
+///
+/// # Example
+/// ```
+/// use cryptopay::{ApiErrorKind, Error};
+///
+/// let error = Error::api_response("NOTOK", Some("Max rate limit reached"));
+/// assert_eq!(error.api_error_kind(), Some(&ApiErrorKind::MaxRateLimit));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiErrorKind {
+    /// The API key is missing, unknown, or revoked
+    InvalidApiKey,
+    /// The rate limit of the API key was hit
+    MaxRateLimit,
+    /// The queried account has no transactions
+    NoTransactionsFound,
+    /// An address parameter was rejected
+    InvalidAddressFormat,
+    /// A `proxy` module call returned a JSON-RPC error
+    ProxyError,
+    /// Any other error, with the explorer's text
+    Unknown { raw: String },
+}
+
+impl ApiErrorKind {
+    /// Classify an error from the `message` and `result` fields of a response
+    pub fn parse(message: &str, result: Option<&str>) -> Self {
+        let text = match result {
+            Some(result) if !result.is_empty() => result,
+            _ => message,
+        };
+        let lower = format!("{} {}", message, text).to_ascii_lowercase();
+
+        if lower.contains("invalid api key") || lower.contains("missing/invalid api key") {
+            Self::InvalidApiKey
+        } else if lower.contains("rate limit") {
+            Self::MaxRateLimit
+        } else if lower.contains("no transactions found") {
+            Self::NoTransactionsFound
+        } else if lower.contains("invalid address format") {
+            Self::InvalidAddressFormat
+        } else {
+            Self::Unknown {
+                raw: text.to_string(),
+            }
+        }
+    }
+}
+
 impl Error {
-    /// Create a new API error
+    /// Create a new API error, classifying it from the message
     pub fn api_error(message: impl Into<String>) -> Self {
+        let message = message.into();
+        Self::ApiError {
+            kind: ApiErrorKind::parse(&message, None),
+            message,
+        }
+    }
+
+    /// Create an API error from the `message` and `result` fields of an explorer response
+    pub fn api_response(message: &str, result: Option<&str>) -> Self {
+        let message = match result {
+            Some(result) if !result.is_empty() && result != message => {
+                format!("{}: {}", message, result)
+            }
+            _ => message.to_string(),
+        };
+        Self::ApiError {
+            kind: ApiErrorKind::parse(&message, result),
+            message,
+        }
+    }
+
+    /// Create an API error of a known kind
+    pub fn api_error_with_kind(kind: ApiErrorKind, message: impl Into<String>) -> Self {
         Self::ApiError {
+            kind,
             message: message.into(),
         }
     }
 
+    /// Stable, machine-readable code of the error, e.g. `CP1002`
+    ///
+    /// Codes are never reused or reassigned; the ranges are `CP1xxx` for
+    /// explorer requests, `CP2xxx` for invalid input, `CP3xxx` for payment
+    /// verification, `CP4xxx` for serialization, I/O and storage, `CP5xxx`
+    /// for integrations, and `CP9999` for generic errors.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::HttpRequest(_) => "CP1001",
+            Self::ApiError { .. } => "CP1002",
+            Self::RateLimitExceeded { .. } => "CP1003",
+            Self::DeadlineExceeded => "CP1004",
+            Self::ProEndpointRequired(_) => "CP1005",
+            Self::NotFound(_) => "CP1006",
+            Self::InvalidConfig(_) => "CP2001",
+            Self::InvalidAddress(_) => "CP2002",
+            Self::InvalidTxHash(_) => "CP2003",
+            Self::InvalidAmount(_) => "CP2004",
+            Self::InvalidMetadata(_) => "CP2005",
+            Self::TransactionNotFound(_) => "CP3001",
+            Self::VerificationFailed(_) => "CP3002",
+            Self::AmountMismatch { .. } => "CP3003",
+            Self::RecipientMismatch { .. } => "CP3004",
+            Self::TokenMismatch { .. } => "CP3005",
+            Self::ChainMismatch { .. } => "CP3006",
+            Self::InsufficientConfirmations { .. } => "CP3007",
+            Self::PaymentTimeout(_) => "CP3008",
+            Self::PaymentNotFound(_) => "CP3009",
+            Self::InvalidTransition(_) => "CP3010",
+            Self::CallbackFailed(_) => "CP3011",
+            Self::Serialization(_) => "CP4001",
+            Self::Io(_) => "CP4002",
+            Self::CacheError(_) => "CP4003",
+            Self::Unauthorized(_) => "CP5001",
+            Self::Generic(_) => "CP9999",
+        }
+    }
+
+    /// Structured fields of the error, `null` for errors with only a message
+    fn details(&self) -> serde_json::Value {
+        use serde_json::json;
+
+        match self {
+            Self::ApiError { kind, .. } => json!({ "kind": kind }),
+            Self::AmountMismatch { expected, actual }
+            | Self::RecipientMismatch { expected, actual }
+            | Self::TokenMismatch { expected, actual } => {
+                json!({ "expected": expected, "actual": actual })
+            }
+            Self::ChainMismatch {
+                currency,
+                expected,
+                actual,
+            } => json!({ "currency": currency, "expected": expected, "actual": actual }),
+            Self::InsufficientConfirmations { current, required } => {
+                json!({ "current": current, "required": required })
+            }
+            Self::RateLimitExceeded {
+                retry_after: Some(retry_after),
+            } => json!({ "retry_after_ms": retry_after.as_millis() as u64 }),
+            Self::PaymentTimeout(seconds) => json!({ "seconds": seconds }),
+            Self::PaymentNotFound(id) => json!({ "id": id }),
+            Self::ProEndpointRequired(endpoint) | Self::NotFound(endpoint) => {
+                json!({ "endpoint": endpoint })
+            }
+            Self::InvalidTransition(transition) => {
+                json!({ "from": transition.from, "to": transition.to })
+            }
+            _ => serde_json::Value::Null,
+        }
+    }
+
+    /// Message safe to show outside the process
+    ///
+    /// reqwest's messages include the request URL, and with it the API key.
+    pub(crate) fn public_message(&self) -> String {
+        match self {
+            Self::HttpRequest(e) if e.is_timeout() => {
+                "HTTP request failed: request timed out".to_string()
+            }
+            Self::HttpRequest(e) if e.is_decode() => {
+                "HTTP request failed: invalid response body".to_string()
+            }
+            Self::HttpRequest(_) => "HTTP request failed".to_string(),
+            _ => self.to_string(),
+        }
+    }
+
+    /// Check whether the error is transient, so the operation may succeed when retried
+    ///
+    /// True for timeouts, connection failures, HTTP 5xx and 429 responses,
+    /// rate limits, and I/O or storage errors of a lost connection.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::HttpRequest(e) => {
+                e.is_timeout()
+                    || e.is_connect()
+                    || e.status().is_some_and(|status| {
+                        status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+                    })
+            }
+            // Non-success statuses of the explorer are reported as "HTTP <status>: ..."
+            Self::ApiError { kind, message } => {
+                *kind == ApiErrorKind::MaxRateLimit || message.starts_with("HTTP 5")
+            }
+            Self::RateLimitExceeded { .. } => true,
+            Self::Io(e) => matches!(
+                e.kind(),
+                std::io::ErrorKind::TimedOut
+                    | std::io::ErrorKind::Interrupted
+                    | std::io::ErrorKind::WouldBlock
+                    | std::io::ErrorKind::ConnectionReset
+                    | std::io::ErrorKind::ConnectionAborted
+                    | std::io::ErrorKind::ConnectionRefused
+            ),
+            _ => false,
+        }
+    }
+
+    /// Minimum delay before retrying, if the error asks for one
+    ///
+    /// Explorer rate limits are per second, so rate limit errors ask for a
+    /// second unless they carry an estimate.
+    pub fn retry_after(&self) -> Option<std::time::Duration> {
+        match self {
+            Self::RateLimitExceeded {
+                retry_after: Some(retry_after),
+            } => Some(*retry_after),
+            Self::RateLimitExceeded { retry_after: None }
+            | Self::ApiError {
+                kind: ApiErrorKind::MaxRateLimit,
+                ..
+            } => Some(std::time::Duration::from_secs(1)),
+            _ => None,
+        }
+    }
+
+    /// Kind of an [`Error::ApiError`], `None` for other errors
+    pub fn api_error_kind(&self) -> Option<&ApiErrorKind> {
+        match self {
+            Self::ApiError { kind, .. } => Some(kind),
+            _ => None,
+        }
+    }
+
     /// Create a new verification failed error
     pub fn verification_failed(message: impl Into<String>) -> Self {
         Self::VerificationFailed(message.into())
@@ -96,3 +368,41 @@ impl Error {
         Self::Generic(message.into())
     }
 }
+
+/// Serializes as `{"code": "CP1002", "message": "...", "details": {...}}`
+impl Serialize for Error {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Error", 3)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.public_message())?;
+        state.serialize_field("details", &self.details())?;
+        state.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_json() {
+        let error = Error::InsufficientConfirmations {
+            current: 3,
+            required: 12,
+        };
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            serde_json::json!({
+                "code": "CP3007",
+                "message": "Insufficient confirmations: 3/12",
+                "details": { "current": 3, "required": 12 }
+            })
+        );
+
+        let error = Error::api_response("NOTOK", Some("Invalid API Key"));
+        let json = serde_json::to_value(&error).unwrap();
+        assert_eq!(json["code"], "CP1002");
+        assert_eq!(json["details"]["kind"], "invalid_api_key");
+        assert_eq!(Error::generic("x").code(), "CP9999");
+    }
+}
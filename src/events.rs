@@ -0,0 +1,176 @@
+//! Application-wide event bus
+//!
+//! [`EventBus`] is one broadcast channel the monitor pool, the webhook
+//! queue, and explorer clients publish to, so an application subscribes once
+//! instead of wiring callbacks into each of them. Attach a bus with
+//! [`MonitorPool::events`](crate::payment::MonitorPool::events),
+//! [`WebhookQueue::events`](crate::webhook::WebhookQueue::events), and
+//! [`ClientConfigBuilder::events`](crate::config::ClientConfigBuilder::events);
+//! clones of a bus share its channel.
+
+use crate::payment::{PaymentStatus, StatusTransition};
+use crate::primitives::Address;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::fmt;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+/// Default capacity of the channel; slow subscribers miss older events
+const DEFAULT_CAPACITY: usize = 1024;
+
+/// Event published on an [`EventBus`]
+///
+/// Serializes with a `type` tag, e.g.
+/// `{"type": "status_changed", "payment_id": "...", "status": ..., "redelivery": false}`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum Event {
+    /// A payment was saved and is now monitored
+    PaymentCreated {
+        /// Payment ID
+        payment_id: Uuid,
+    },
+    /// The status of a monitored payment changed, or was re-delivered
+    StatusChanged {
+        /// Payment ID
+        payment_id: Uuid,
+        /// New status
+        status: PaymentStatus,
+        /// Whether this is a re-delivery of an earlier status
+        redelivery: bool,
+    },
+    /// A stored payment moved from one status to another, see
+    /// [`PaymentStorage::update_status`](crate::storage::PaymentStorage::update_status)
+    StatusTransition {
+        /// Payment ID
+        payment_id: Uuid,
+        /// Previous status
+        from: PaymentStatus,
+        /// New status
+        to: PaymentStatus,
+        /// Time of the change
+        at: DateTime<Utc>,
+    },
+    /// A [`PaymentMonitor`](crate::PaymentMonitor) callback panicked on
+    /// every attempt its [`CallbackErrorPolicy`](crate::payment::CallbackErrorPolicy) allows
+    CallbackFailed {
+        /// Recipient of the monitored payment request
+        recipient: Address,
+        /// Status the callback was given
+        status: PaymentStatus,
+        /// Calls made
+        attempts: u32,
+        /// Panic message
+        message: String,
+    },
+    /// A webhook notification was delivered
+    WebhookDelivered {
+        /// Delivery ID in the webhook queue
+        id: Uuid,
+        /// Target URL
+        url: String,
+        /// Failed attempts before the successful one
+        attempts: u32,
+    },
+    /// An explorer request failed
+    ApiError {
+        /// Chain the client queries
+        chain_id: u64,
+        /// `module/action` of the request
+        endpoint: String,
+        /// Error code, see [`Error::code`](crate::Error::code)
+        code: &'static str,
+        /// Error message, without the API key
+        message: String,
+    },
+    /// The explorer rejected an API key; it is skipped until the keys are
+    /// replaced with [`update_keys`](crate::BscScanClient::update_keys)
+    KeyQuarantined {
+        /// Chain the client queries
+        chain_id: u64,
+        /// Position of the key in the client's key list
+        key_index: usize,
+    },
+}
+
+impl From<StatusTransition> for Event {
+    fn from(transition: StatusTransition) -> Self {
+        Event::StatusTransition {
+            payment_id: transition.payment_id,
+            from: transition.from,
+            to: transition.to,
+            at: transition.at,
+        }
+    }
+}
+
+/// Broadcast channel of [`Event`]s
+///
+/// # Example
+/// ```no_run
+/// # use cryptopay::*;
+/// # use cryptopay::events::{Event, EventBus};
+/// # use cryptopay::payment::MonitorPool;
+/// # use std::sync::Arc;
+/// # use std::time::Duration;
+/// # async fn example() -> Result<()> {
+/// let events = EventBus::new();
+/// let client = BscScanClient::with_config(
+///     ClientConfig::builder().api_key("api-key").events(events.clone()).build()?,
+/// )?;
+/// let monitor = PaymentMonitor::new(client, Duration::from_secs(15));
+/// let pool = MonitorPool::new(monitor, Arc::new(MemoryStorage::new())).events(events.clone());
+///
+/// let mut subscriber = events.subscribe();
+/// while let Ok(event) = subscriber.recv().await {
+///     if let Event::StatusChanged { payment_id, status, .. } = event {
+///         println!("{}: {:?}", payment_id, status);
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<Event>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for EventBus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventBus")
+            .field("subscribers", &self.sender.receiver_count())
+            .finish()
+    }
+}
+
+impl EventBus {
+    /// Create a bus keeping up to 1024 events for slow subscribers
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY)
+    }
+
+    /// Create a bus keeping up to `capacity` events for slow subscribers
+    pub fn with_capacity(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        Self { sender }
+    }
+
+    /// Subscribe to the events published from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<Event> {
+        self.sender.subscribe()
+    }
+
+    /// Publish an event to every subscriber
+    pub fn publish(&self, event: Event) {
+        // Nobody listening is not an error; the event is simply dropped
+        let _ = self.sender.send(event);
+    }
+}
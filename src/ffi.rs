@@ -0,0 +1,212 @@
+//! C ABI for embedding in other languages (`ffi` feature)
+//!
+//! A small surface for services written in Go, .NET, PHP, and the like:
+//! create a client, verify a payment request, check its status. Requests
+//! and results cross the boundary as JSON in the same shape as the Rust
+//! types ([`PaymentRequest`], [`VerificationResult`], [`PaymentStatus`]).
+//!
+//! [`VerificationResult`]: crate::payment::VerificationResult
+//! [`PaymentStatus`]: crate::payment::PaymentStatus
+//!
+//! Build the shared library with
+//!
+//! ```bash
+//! cargo rustc --release --features ffi --crate-type cdylib
+//! ```
+//!
+//! and include `include/cryptopay.h`.
+//!
+//! Ownership rules:
+//! - clients from [`cryptopay_client_new`] are freed with [`cryptopay_client_free`]
+//! - every returned string is freed with [`cryptopay_string_free`]
+//! - functions returning a pointer return null on error; the message is then
+//!   available from [`cryptopay_last_error`] on the same thread
+
+use crate::blocking::{Client, PaymentVerifier};
+use crate::config::ClientConfig;
+use crate::error::{Error, Result};
+use crate::payment::PaymentRequest;
+use serde::Serialize;
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::ptr;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Client handle owned by the caller
+pub struct CryptopayClient {
+    verifier: PaymentVerifier,
+}
+
+/// Create a client for `chain_id`
+///
+/// `api_key` may hold several comma-separated keys. Returns null on error.
+///
+/// # Safety
+/// `api_key` must be a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn cryptopay_client_new(
+    api_key: *const c_char,
+    chain_id: u64,
+) -> *mut CryptopayClient {
+    let result = str_arg(api_key, "api_key").and_then(|api_key| {
+        let mut builder = ClientConfig::builder().chain_id(chain_id);
+        for key in api_key
+            .split(',')
+            .map(str::trim)
+            .filter(|key| !key.is_empty())
+        {
+            builder = builder.api_key(key);
+        }
+        let client = Client::with_config(builder.build()?)?;
+        Ok(CryptopayClient {
+            verifier: PaymentVerifier::new(&client),
+        })
+    });
+
+    match result {
+        Ok(client) => Box::into_raw(Box::new(client)),
+        Err(e) => fail(e),
+    }
+}
+
+/// Free a client; null is ignored
+///
+/// # Safety
+/// `client` must come from [`cryptopay_client_new`] and not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn cryptopay_client_free(client: *mut CryptopayClient) {
+    if !client.is_null() {
+        drop(Box::from_raw(client));
+    }
+}
+
+/// Verify a payment request given as JSON, returning the result as JSON
+///
+/// Blocks until the explorer has answered. Returns null on error.
+///
+/// # Safety
+/// `client` must be a live client and `request_json` a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn cryptopay_verify_payment(
+    client: *const CryptopayClient,
+    request_json: *const c_char,
+) -> *mut c_char {
+    json_result(with_request(client, request_json, |client, request| {
+        client.verifier.verify_payment(&request)
+    }))
+}
+
+/// Check the status of a payment request given as JSON, returning the status as JSON
+///
+/// Poll this periodically to follow a payment. Returns null on error.
+///
+/// # Safety
+/// `client` must be a live client and `request_json` a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn cryptopay_payment_status(
+    client: *const CryptopayClient,
+    request_json: *const c_char,
+) -> *mut c_char {
+    json_result(with_request(client, request_json, |client, request| {
+        client.verifier.check_payment_status(&request)
+    }))
+}
+
+/// Message of the last error on this thread, or null if there was none
+///
+/// The returned string must be freed with [`cryptopay_string_free`].
+#[no_mangle]
+pub extern "C" fn cryptopay_last_error() -> *mut c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(ptr::null_mut(), |message| message.clone().into_raw())
+    })
+}
+
+/// Free a string returned by this library; null is ignored
+///
+/// # Safety
+/// `string` must come from this library and not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn cryptopay_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
+
+unsafe fn str_arg<'a>(pointer: *const c_char, name: &str) -> Result<&'a str> {
+    if pointer.is_null() {
+        return Err(Error::InvalidConfig(format!("{} is null", name)));
+    }
+    CStr::from_ptr(pointer)
+        .to_str()
+        .map_err(|_| Error::InvalidConfig(format!("{} is not valid UTF-8", name)))
+}
+
+unsafe fn with_request<T>(
+    client: *const CryptopayClient,
+    request_json: *const c_char,
+    f: impl FnOnce(&CryptopayClient, PaymentRequest) -> Result<T>,
+) -> Result<T> {
+    let client = client
+        .as_ref()
+        .ok_or_else(|| Error::InvalidConfig("client is null".to_string()))?;
+    let request = serde_json::from_str(str_arg(request_json, "request_json")?)?;
+    f(client, request)
+}
+
+fn json_result<T: Serialize>(result: Result<T>) -> *mut c_char {
+    let json = result.and_then(|value| Ok(serde_json::to_string(&value)?));
+    match json.map(CString::new) {
+        Ok(Ok(json)) => {
+            LAST_ERROR.with(|last| last.borrow_mut().take());
+            json.into_raw()
+        }
+        Ok(Err(_)) => fail(Error::generic("result contains a NUL byte")),
+        Err(e) => fail(e),
+    }
+}
+
+fn fail<T>(error: Error) -> *mut T {
+    let message = CString::new(error.to_string().replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+    ptr::null_mut()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_errors_and_ownership() {
+        unsafe {
+            assert!(cryptopay_client_new(ptr::null(), 1).is_null());
+            let error = cryptopay_last_error();
+            assert_eq!(
+                CStr::from_ptr(error).to_str().unwrap(),
+                "Invalid configuration: api_key is null"
+            );
+            cryptopay_string_free(error);
+
+            let key = CString::new("key-a, key-b").unwrap();
+            let client = cryptopay_client_new(key.as_ptr(), 56);
+            assert!(!client.is_null());
+
+            let invalid = CString::new("{\"amount\":").unwrap();
+            assert!(cryptopay_verify_payment(client, invalid.as_ptr()).is_null());
+            let error = cryptopay_last_error();
+            assert!(CStr::from_ptr(error)
+                .to_str()
+                .unwrap()
+                .starts_with("Serialization error"));
+            cryptopay_string_free(error);
+
+            cryptopay_client_free(client);
+            cryptopay_client_free(ptr::null_mut());
+        }
+    }
+}
@@ -0,0 +1,646 @@
+//! Deployable payment gateway (`gateway` feature)
+//!
+//! [`Daemon`] wires the crate's pieces into a long-running service: one
+//! [`MonitorPool`] per configured chain, the payment API from
+//! [`server::axum_router`](crate::server::axum_router), a `/health` endpoint,
+//! and queued webhook delivery of every [`PaymentUpdate`]. Configuration is read from
+//! a TOML file, with `${VAR}` replaced by environment variables as in
+//! [`ClientConfig::from_file`]:
+//!
+//! ```toml
+//! api_keys = ["${ETHERSCAN_API_KEY}"]
+//! storage = "file:///var/lib/cryptopay"   # or "memory" (the default)
+//! poll_interval_secs = 15
+//!
+//! [[chains]]
+//! id = 1
+//!
+//! [[chains]]
+//! id = 56
+//! api_keys = ["bsc-key"]   # overrides the top-level keys
+//!
+//! [server]
+//! bind = "0.0.0.0:3000"
+//! api_keys = ["secret"]    # without keys, only /health is served
+//!
+//! [[webhooks]]
+//! url = "https://example.com/hooks/payments"
+//! secret = "${WEBHOOK_SECRET}"   # optional, signs every notification
+//! ```
+
+use crate::chain::Chain;
+use crate::client::BscScanClient;
+use crate::config::{substitute_env, ClientConfig, Secret};
+use crate::error::{Error, Result};
+use crate::events::EventBus;
+use crate::payment::{ExpirySweeper, MonitorPool, PaymentMonitor, PaymentUpdate};
+use crate::retry::RetryPolicy;
+use crate::storage::{FileStorage, MemoryStorage, PaymentStorage, WebhookStorage};
+use crate::webhook::WebhookQueue;
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+
+/// Gateway configuration, usually loaded from a TOML file
+#[derive(Debug, Clone, Deserialize)]
+pub struct GatewayConfig {
+    /// Explorer API keys, used by chains without their own keys
+    #[serde(default)]
+    pub api_keys: Vec<String>,
+    /// Storage backend DSN: `memory` (the default) keeps everything in the
+    /// process, `file://<dir>` persists payments and queued webhooks in `dir`
+    #[serde(default = "default_storage")]
+    pub storage: String,
+    /// Seconds between checks of each payment
+    #[serde(default = "default_poll_interval")]
+    pub poll_interval_secs: u64,
+    /// Chains to monitor (defaults to Ethereum mainnet)
+    #[serde(default = "default_chains")]
+    pub chains: Vec<ChainSettings>,
+    /// HTTP server for the payment API and health checks
+    pub server: Option<ServerSettings>,
+    /// Webhook targets receiving every payment update
+    #[serde(default)]
+    pub webhooks: Vec<WebhookTarget>,
+}
+
+/// Settings of one monitored chain
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChainSettings {
+    /// Chain ID
+    pub id: u64,
+    /// Explorer API keys for this chain (defaults to the top-level keys)
+    #[serde(default)]
+    pub api_keys: Vec<String>,
+    /// Poll interval override in seconds
+    pub poll_interval_secs: Option<u64>,
+}
+
+/// HTTP server settings
+#[derive(Debug, Clone, Deserialize)]
+pub struct ServerSettings {
+    /// Listen address
+    pub bind: SocketAddr,
+    /// API keys accepted by the payment API; without keys the API is not served
+    #[serde(default)]
+    pub api_keys: Vec<String>,
+}
+
+/// Webhook target
+#[derive(Debug, Clone, Deserialize)]
+pub struct WebhookTarget {
+    /// URL receiving a JSON `POST` per update
+    pub url: String,
+    /// Retries of failed deliveries before the notification is dead-lettered
+    /// (default: 3, backing off from 500 ms)
+    #[serde(default)]
+    pub retry: RetryPolicy,
+    /// Signs every notification to this URL, see [`webhook::sign`](crate::webhook::sign)
+    pub secret: Option<Secret<String>>,
+}
+
+fn default_storage() -> String {
+    "memory".to_string()
+}
+
+fn default_poll_interval() -> u64 {
+    15
+}
+
+fn default_chains() -> Vec<ChainSettings> {
+    vec![ChainSettings {
+        id: Chain::Ethereum.id(),
+        api_keys: Vec::new(),
+        poll_interval_secs: None,
+    }]
+}
+
+impl GatewayConfig {
+    /// Load a configuration from a TOML file
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)?;
+        Self::from_toml(&contents)
+            .map_err(|e| Error::InvalidConfig(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Parse a configuration from TOML
+    pub fn from_toml(contents: &str) -> Result<Self> {
+        toml::from_str(&substitute_env(contents)?).map_err(|e| Error::InvalidConfig(e.to_string()))
+    }
+}
+
+/// Payment update as delivered to webhooks and logged by the daemon
+#[derive(Debug, Clone, Serialize)]
+struct ChainUpdate<'a> {
+    chain_id: u64,
+    #[serde(flatten)]
+    update: &'a PaymentUpdate,
+}
+
+/// Long-running payment gateway
+///
+/// # Example
+/// ```no_run
+/// # use cryptopay::gateway::Daemon;
+/// # async fn example() -> cryptopay::Result<()> {
+/// let daemon = Daemon::from_file("cryptopay.toml")?;
+/// daemon.run().await?; // until SIGTERM or Ctrl-C
+/// # Ok(())
+/// # }
+/// ```
+pub struct Daemon {
+    config: GatewayConfig,
+    pools: Vec<(Chain, Arc<MonitorPool>)>,
+    clients: Vec<BscScanClient>,
+    webhooks: Arc<WebhookQueue>,
+    events: EventBus,
+}
+
+impl Daemon {
+    /// Build the gateway: one client, storage, and monitor pool per chain
+    pub fn new(config: GatewayConfig) -> Result<Self> {
+        if config.chains.is_empty() {
+            return Err(Error::InvalidConfig("no chains configured".to_string()));
+        }
+
+        let events = EventBus::new();
+        let mut pools = Vec::with_capacity(config.chains.len());
+        let mut clients = Vec::with_capacity(config.chains.len());
+        for settings in &config.chains {
+            let chain = Chain::from(settings.id);
+            if pools.iter().any(|(existing, _)| *existing == chain) {
+                return Err(Error::InvalidConfig(format!(
+                    "chain {} configured twice",
+                    chain
+                )));
+            }
+
+            let api_keys = if settings.api_keys.is_empty() {
+                config.api_keys.clone()
+            } else {
+                settings.api_keys.clone()
+            };
+            let client = BscScanClient::with_config(
+                ClientConfig::builder()
+                    .api_keys(api_keys)
+                    .chain_id(settings.id)
+                    .events(events.clone())
+                    .build()?,
+            )?;
+            let interval = settings
+                .poll_interval_secs
+                .unwrap_or(config.poll_interval_secs);
+            clients.push(client.clone());
+            let monitor = PaymentMonitor::new(client, Duration::from_secs(interval));
+
+            let storage = open_storage(&config.storage, chain)?;
+            let pool = MonitorPool::new(monitor, storage).events(events.clone());
+            pools.push((chain, Arc::new(pool)));
+        }
+
+        let mut webhooks =
+            WebhookQueue::new(open_webhook_storage(&config.storage)?).events(events.clone());
+        for target in &config.webhooks {
+            if let Some(secret) = &target.secret {
+                webhooks = webhooks.sign_with(target.url.clone(), secret.clone());
+            }
+        }
+
+        Ok(Self {
+            config,
+            pools,
+            clients,
+            webhooks: Arc::new(webhooks),
+            events,
+        })
+    }
+
+    /// Build the gateway from a TOML configuration file
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        Self::new(GatewayConfig::from_file(path)?)
+    }
+
+    /// Configuration the gateway was built from
+    pub fn config(&self) -> &GatewayConfig {
+        &self.config
+    }
+
+    /// Bus of the events of every chain's pool and client and of webhook delivery
+    pub fn events(&self) -> &EventBus {
+        &self.events
+    }
+
+    /// Monitor pool of a chain
+    pub fn pool(&self, chain_id: u64) -> Option<&Arc<MonitorPool>> {
+        self.pools
+            .iter()
+            .find(|(chain, _)| chain.id() == chain_id)
+            .map(|(_, pool)| pool)
+    }
+
+    /// HTTP routes: `GET /health` and `GET /ready`, plus the payment API of
+    /// each chain under `/chains/{chain_id}` when API keys are configured
+    ///
+    /// `/ready` answers `503 Service Unavailable` while the explorer client of
+    /// any chain is unhealthy.
+    pub fn router(&self) -> Router {
+        let health_pools = self.pools.clone();
+        let health_clients = self.clients.clone();
+        let ready_pools = self.pools.clone();
+        let ready_clients = self.clients.clone();
+        let mut router = Router::new()
+            .route(
+                "/health",
+                get(move || async move { Json(health(&health_pools, &health_clients).1) }),
+            )
+            .route(
+                "/ready",
+                get(move || async move {
+                    let (ready, body) = health(&ready_pools, &ready_clients);
+                    let status = if ready {
+                        axum::http::StatusCode::OK
+                    } else {
+                        axum::http::StatusCode::SERVICE_UNAVAILABLE
+                    };
+                    (status, Json(body))
+                }),
+            );
+
+        let api_keys = self
+            .config
+            .server
+            .as_ref()
+            .map(|server| server.api_keys.clone())
+            .unwrap_or_default();
+        if !api_keys.is_empty() {
+            for (chain, pool) in &self.pools {
+                router = router.nest(
+                    &format!("/chains/{}", chain.id()),
+                    crate::server::axum_router(pool.clone(), api_keys.clone()),
+                );
+            }
+        }
+
+        router
+    }
+
+    /// Run until SIGTERM or Ctrl-C
+    pub async fn run(self) -> Result<()> {
+        self.run_until(shutdown_signal()).await
+    }
+
+    /// Run until `shutdown` completes
+    ///
+    /// Restores pending payments from storage, starts webhook delivery, expiry
+    /// of overdue payments, and the HTTP server, and on shutdown stops
+    /// accepting requests and monitoring. With `file://` storage, payment
+    /// state and queued webhook notifications are persisted, so the next start
+    /// resumes where this one stopped; `memory` storage starts empty.
+    pub async fn run_until<F>(self, shutdown: F) -> Result<()>
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        for (chain, pool) in &self.pools {
+            let resumed = pool.resume().await?;
+            tracing::info!(chain = %chain, resumed, "monitoring pending payments");
+        }
+
+        let mut workers = Vec::new();
+
+        let queue = self.webhooks.clone();
+        workers.push(tokio::spawn(async move {
+            queue.run(WEBHOOK_DELIVERY_INTERVAL).await
+        }));
+
+        for (chain, pool) in &self.pools {
+            workers.push(tokio::spawn(deliver_updates(
+                chain.id(),
+                pool.subscribe(),
+                self.webhooks.clone(),
+                self.config.webhooks.clone(),
+            )));
+
+            let sweeper_pool = pool.clone();
+            workers.push(tokio::spawn(async move {
+                ExpirySweeper::for_pool(&sweeper_pool)
+                    .run(sweeper_pool.storage().as_ref(), EXPIRY_SWEEP_INTERVAL)
+                    .await
+            }));
+        }
+
+        match &self.config.server {
+            Some(server) => {
+                let listener = tokio::net::TcpListener::bind(server.bind).await?;
+                tracing::info!(bind = %server.bind, "serving gateway");
+                axum::serve(listener, self.router())
+                    .with_graceful_shutdown(shutdown)
+                    .await?;
+            }
+            None => shutdown.await,
+        }
+
+        tracing::info!("shutting down");
+        for worker in workers {
+            worker.abort();
+        }
+        // Dropping the pools aborts their monitoring tasks
+        drop(self.pools);
+        Ok(())
+    }
+}
+
+/// Interval between scans for overdue payments
+const EXPIRY_SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Interval between delivery rounds of the webhook queue
+const WEBHOOK_DELIVERY_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Open the webhook queue storage of the backend named by `dsn`
+///
+/// The queue gets its own directory next to the chains'.
+fn open_webhook_storage(dsn: &str) -> Result<Arc<dyn WebhookStorage>> {
+    match storage_dir(dsn)? {
+        None => Ok(Arc::new(MemoryStorage::new())),
+        Some(dir) => Ok(Arc::new(FileStorage::open(
+            Path::new(dir).join("webhooks"),
+        )?)),
+    }
+}
+
+/// Open the payment storage of `chain` in the backend named by `dsn`
+///
+/// Every chain gets its own directory, so a pool only resumes its own payments.
+fn open_storage(dsn: &str, chain: Chain) -> Result<Arc<dyn PaymentStorage>> {
+    match storage_dir(dsn)? {
+        None => Ok(Arc::new(MemoryStorage::new())),
+        Some(dir) => Ok(Arc::new(FileStorage::open(
+            Path::new(dir).join("chains").join(chain.id().to_string()),
+        )?)),
+    }
+}
+
+/// Directory of a `file://` DSN, or `None` for `memory`
+fn storage_dir(dsn: &str) -> Result<Option<&str>> {
+    if dsn == "memory" {
+        return Ok(None);
+    }
+    match dsn.strip_prefix("file://") {
+        Some(dir) if !dir.is_empty() => Ok(Some(dir)),
+        _ => Err(Error::InvalidConfig(format!(
+            "unsupported storage backend: {}",
+            dsn
+        ))),
+    }
+}
+
+/// Health of every chain, and whether all explorer clients are healthy
+fn health(
+    pools: &[(Chain, Arc<MonitorPool>)],
+    clients: &[BscScanClient],
+) -> (bool, serde_json::Value) {
+    let mut ready = true;
+    let chains: Vec<serde_json::Value> = pools
+        .iter()
+        .zip(clients)
+        .map(|((chain, pool), client)| {
+            let explorer = client.health();
+            ready &= explorer.healthy;
+            serde_json::json!({
+                "chain_id": chain.id(),
+                "watching": pool.len(),
+                "explorer": explorer,
+            })
+        })
+        .collect();
+    let status = if ready { "ok" } else { "degraded" };
+    (
+        ready,
+        serde_json::json!({ "status": status, "chains": chains }),
+    )
+}
+
+/// Log every update of one chain and queue it for the webhook targets
+async fn deliver_updates(
+    chain_id: u64,
+    mut updates: tokio::sync::broadcast::Receiver<PaymentUpdate>,
+    queue: Arc<WebhookQueue>,
+    webhooks: Vec<WebhookTarget>,
+) {
+    loop {
+        let update = match updates.recv().await {
+            Ok(update) => update,
+            Err(RecvError::Lagged(missed)) => {
+                tracing::warn!(
+                    chain_id,
+                    missed,
+                    "webhook delivery lagging; updates skipped"
+                );
+                continue;
+            }
+            Err(RecvError::Closed) => break,
+        };
+
+        let body = match serde_json::to_value(ChainUpdate {
+            chain_id,
+            update: &update,
+        }) {
+            Ok(body) => body,
+            Err(e) => {
+                tracing::warn!(error = %e, "webhook payload not serializable");
+                continue;
+            }
+        };
+        tracing::info!(chain_id, payment_id = %update.payment_id, status = ?update.status, "payment update");
+
+        for webhook in &webhooks {
+            if let Err(e) = queue
+                .enqueue(webhook.url.clone(), body.clone(), webhook.retry)
+                .await
+            {
+                tracing::warn!(url = %webhook.url, error = %e, "webhook not queued");
+            }
+        }
+    }
+}
+
+/// Resolve on SIGTERM (Unix) or Ctrl-C
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                tokio::select! {
+                    _ = terminate.recv() => {}
+                    _ = tokio::signal::ctrl_c() => {}
+                }
+            }
+            Err(_) => {
+                let _ = tokio::signal::ctrl_c().await;
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use tower::ServiceExt;
+
+    const CONFIG: &str = r#"
+        api_keys = ["explorer-key"]
+
+        [[chains]]
+        id = 1
+
+        [[chains]]
+        id = 56
+        api_keys = ["bsc-key"]
+        poll_interval_secs = 5
+
+        [server]
+        bind = "127.0.0.1:0"
+        api_keys = ["secret"]
+
+        [[webhooks]]
+        url = "http://localhost:9000/hooks"
+    "#;
+
+    #[test]
+    fn test_config_from_toml() {
+        let config = GatewayConfig::from_toml(CONFIG).unwrap();
+        assert_eq!(config.storage, "memory");
+        assert_eq!(config.poll_interval_secs, 15);
+        assert_eq!(config.chains.len(), 2);
+        assert_eq!(config.chains[1].poll_interval_secs, Some(5));
+        assert_eq!(config.webhooks[0].url, "http://localhost:9000/hooks");
+
+        assert!(GatewayConfig::from_toml("storage = 5").is_err());
+        let config =
+            GatewayConfig::from_toml("storage = \"postgres://db\"\napi_keys = [\"k\"]").unwrap();
+        assert_eq!(config.chains[0].id, 1);
+        assert!(Daemon::new(config).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_file_storage_outlives_the_daemon() {
+        let dir = std::env::temp_dir().join(format!("cryptopay-gateway-{}", uuid::Uuid::new_v4()));
+        let config = format!(
+            "storage = \"file://{}\"\napi_keys = [\"k\"]\nchains = [{{ id = 1 }}, {{ id = 56 }}]",
+            dir.display()
+        );
+        let payment = crate::payment::Payment::new(crate::payment::PaymentRequest::eth(
+            rust_decimal::Decimal::ONE,
+            "0x0000000000000000000000000000000000000001"
+                .parse()
+                .unwrap(),
+            12,
+        ));
+
+        let daemon = Daemon::new(GatewayConfig::from_toml(&config).unwrap()).unwrap();
+        let pool = daemon.pool(56).unwrap();
+        pool.storage().save_payment(&payment).await.unwrap();
+        let delivery = daemon
+            .webhooks
+            .enqueue(
+                "https://example.com/hook",
+                serde_json::json!({}),
+                RetryPolicy::default(),
+            )
+            .await
+            .unwrap();
+        drop(daemon);
+
+        // Chains and the webhook queue each keep to their own directory
+        let mut entries: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        entries.sort();
+        assert_eq!(entries, ["chains", "webhooks"]);
+        let queued = FileStorage::open(dir.join("webhooks"))
+            .unwrap()
+            .due_webhooks(chrono::Utc::now())
+            .await
+            .unwrap();
+        assert_eq!(queued.len(), 1);
+        assert_eq!(queued[0].id, delivery.id);
+
+        let daemon = Daemon::new(GatewayConfig::from_toml(&config).unwrap()).unwrap();
+        let pending = daemon
+            .pool(56)
+            .unwrap()
+            .storage()
+            .list_pending()
+            .await
+            .unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].id, payment.id);
+        let other = daemon
+            .pool(1)
+            .unwrap()
+            .storage()
+            .list_pending()
+            .await
+            .unwrap();
+        assert!(other.is_empty());
+
+        assert!(GatewayConfig::from_toml("storage = \"file://\"")
+            .and_then(Daemon::new)
+            .is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_router_serves_health_and_chain_apis() {
+        let daemon = Daemon::new(GatewayConfig::from_toml(CONFIG).unwrap()).unwrap();
+        assert!(daemon.pool(56).is_some());
+        assert!(daemon.pool(137).is_none());
+
+        let app = daemon.router();
+        let response = app
+            .clone()
+            .oneshot(Request::get("/health").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let health: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(health["chains"][1]["chain_id"], 56);
+        assert_eq!(health["chains"][1]["explorer"]["healthy"], true);
+
+        let response = app
+            .clone()
+            .oneshot(Request::get("/ready").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app
+            .oneshot(
+                Request::get("/chains/56/payments")
+                    .header("x-api-key", "secret")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
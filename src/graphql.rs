@@ -0,0 +1,508 @@
+//! GraphQL API over stored payments (`graphql` feature)
+//!
+//! [`schema`] builds an [async-graphql](https://docs.rs/async-graphql) schema
+//! on top of a [`MonitorPool`], so dashboards can query exactly the payment
+//! data they need:
+//!
+//! | Field                          | Description                                     |
+//! |--------------------------------|-------------------------------------------------|
+//! | `payment(id)`                  | A payment and its status                        |
+//! | `payments(status, first)`      | Payments, optionally filtered by status name    |
+//! | `invoice(id)` / `invoices`     | Payments created as [`Invoice`]s                |
+//! | `transaction(hash)`            | A transaction fetched from the explorer         |
+//! | `statusChanged(paymentId)`     | Subscription to the pool's status updates       |
+//!
+//! The schema is transport-agnostic: serve it with `async-graphql-axum` or
+//! any other integration. Errors carry the crate's [error
+//! code](crate::Error::code) in their `code` extension.
+
+use crate::client::{BscScanClient, TransactionEndpoints};
+use crate::error::Error;
+use crate::invoice::{Invoice as InvoiceRecord, InvoiceStatus};
+use crate::payment::{Currency, MonitorPool, Payment as PaymentRecord, PaymentStatus};
+use crate::primitives::TxHash;
+use async_graphql::{
+    Context, EmptyMutation, Enum, ErrorExtensions, Json, Object, Schema, SimpleObject, Subscription,
+};
+use chrono::{DateTime, Utc};
+use futures::stream::{self, Stream};
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+/// The cryptopay GraphQL schema
+pub type CryptopaySchema = Schema<Query, EmptyMutation, Subscription>;
+
+/// Build the schema over the payments stored in and monitored by `pool`
+///
+/// `client` resolves `transaction` fields.
+///
+/// # Example
+/// ```no_run
+/// # use cryptopay::*;
+/// # use cryptopay::payment::MonitorPool;
+/// # use std::sync::Arc;
+/// # use std::time::Duration;
+/// # async fn example() -> Result<()> {
+/// let client = BscScanClient::new("api-key")?;
+/// let monitor = PaymentMonitor::new(client.clone(), Duration::from_secs(15));
+/// let pool = Arc::new(MonitorPool::new(monitor, Arc::new(MemoryStorage::new())));
+///
+/// let schema = cryptopay::graphql::schema(pool, client);
+/// let response = schema
+///     .execute(r#"{ payments(status: "pending") { id amount recipient } }"#)
+///     .await;
+/// println!("{}", serde_json::to_string(&response).unwrap());
+/// # Ok(())
+/// # }
+/// ```
+pub fn schema(pool: Arc<MonitorPool>, client: BscScanClient) -> CryptopaySchema {
+    Schema::build(Query, EmptyMutation, Subscription)
+        .data(pool)
+        .data(client)
+        .finish()
+}
+
+/// Convert a crate error, keeping API keys out of the message
+fn to_graphql(error: Error) -> async_graphql::Error {
+    async_graphql::Error::new(error.public_message())
+        .extend_with(|_, extensions| extensions.set("code", error.code()))
+}
+
+/// Hash of the transaction a status refers to, if any
+fn status_tx_hash(status: &PaymentStatus) -> Option<&str> {
+    match status {
+        PaymentStatus::Detected { tx_hash, .. }
+        | PaymentStatus::Confirmed { tx_hash, .. }
+        | PaymentStatus::Reorged { tx_hash }
+        | PaymentStatus::Replaced {
+            replacement: tx_hash,
+            ..
+        } => Some(tx_hash),
+        _ => None,
+    }
+}
+
+/// Currency label: `native` or the token contract address
+fn currency_label(currency: &Currency) -> String {
+    match currency {
+        Currency::Native => "native".to_string(),
+        Currency::ERC20 {
+            contract_address, ..
+        } => contract_address.to_string(),
+    }
+}
+
+async fn fetch_transaction(
+    client: &BscScanClient,
+    hash: &str,
+) -> async_graphql::Result<Option<Transaction>> {
+    let hash: TxHash = hash.parse().map_err(to_graphql)?;
+    match client.get_transaction(&hash).await {
+        Ok(tx) => Ok(Some(tx.into())),
+        Err(Error::TransactionNotFound(_)) => Ok(None),
+        Err(e) => Err(to_graphql(e)),
+    }
+}
+
+/// Root query type
+pub struct Query;
+
+#[Object]
+impl Query {
+    /// Payment by ID
+    async fn payment(&self, ctx: &Context<'_>, id: Uuid) -> async_graphql::Result<Option<Payment>> {
+        let pool = ctx.data_unchecked::<Arc<MonitorPool>>();
+        let payment = pool.storage().get_payment(id).await.map_err(to_graphql)?;
+        Ok(payment.map(Payment))
+    }
+
+    /// Payments, newest first, optionally only those in `status` (e.g. `"confirmed"`)
+    async fn payments(
+        &self,
+        ctx: &Context<'_>,
+        status: Option<String>,
+        first: Option<usize>,
+    ) -> async_graphql::Result<Vec<Payment>> {
+        let pool = ctx.data_unchecked::<Arc<MonitorPool>>();
+        let mut payments = pool.storage().list_payments().await.map_err(to_graphql)?;
+        if let Some(status) = status {
+            payments.retain(|payment| payment.status.name() == status);
+        }
+        payments.sort_by_key(|payment| std::cmp::Reverse(payment.created_at));
+        if let Some(first) = first {
+            payments.truncate(first);
+        }
+        Ok(payments.into_iter().map(Payment).collect())
+    }
+
+    /// Invoice by ID; `null` for unknown IDs and payments that aren't invoices
+    async fn invoice(&self, ctx: &Context<'_>, id: Uuid) -> async_graphql::Result<Option<Invoice>> {
+        let pool = ctx.data_unchecked::<Arc<MonitorPool>>();
+        let payment = pool.storage().get_payment(id).await.map_err(to_graphql)?;
+        Ok(payment.and_then(|payment| InvoiceRecord::from_payment(payment).ok().map(Invoice)))
+    }
+
+    /// Invoices, newest first
+    async fn invoices(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<Invoice>> {
+        let pool = ctx.data_unchecked::<Arc<MonitorPool>>();
+        let mut payments = pool.storage().list_payments().await.map_err(to_graphql)?;
+        payments.sort_by_key(|payment| std::cmp::Reverse(payment.created_at));
+        Ok(payments
+            .into_iter()
+            .filter_map(|payment| InvoiceRecord::from_payment(payment).ok())
+            .map(Invoice)
+            .collect())
+    }
+
+    /// Transaction by hash, fetched from the explorer
+    async fn transaction(
+        &self,
+        ctx: &Context<'_>,
+        hash: String,
+    ) -> async_graphql::Result<Option<Transaction>> {
+        fetch_transaction(ctx.data_unchecked::<BscScanClient>(), &hash).await
+    }
+}
+
+/// Root subscription type
+pub struct Subscription;
+
+#[Subscription]
+impl Subscription {
+    /// Status changes of monitored payments, or of one payment if `payment_id` is set
+    async fn status_changed(
+        &self,
+        ctx: &Context<'_>,
+        payment_id: Option<Uuid>,
+    ) -> impl Stream<Item = StatusChange> {
+        let updates = ctx.data_unchecked::<Arc<MonitorPool>>().subscribe();
+        stream::unfold(updates, move |mut updates| async move {
+            loop {
+                match updates.recv().await {
+                    Ok(update) if payment_id.is_none_or(|id| id == update.payment_id) => {
+                        let change = StatusChange {
+                            payment_id: update.payment_id,
+                            status: update.status.name().to_string(),
+                            detail: Json(update.status),
+                            redelivery: update.redelivery,
+                        };
+                        return Some((change, updates));
+                    }
+                    Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                }
+            }
+        })
+    }
+}
+
+/// Status update of a monitored payment
+#[derive(SimpleObject)]
+pub struct StatusChange {
+    /// Payment ID
+    pub payment_id: Uuid,
+    /// Status name, e.g. `confirmed`
+    pub status: String,
+    /// Full status with its fields, as JSON
+    pub detail: Json<PaymentStatus>,
+    /// Whether this is a re-delivery of an earlier update
+    pub redelivery: bool,
+}
+
+/// Payment record
+pub struct Payment(pub PaymentRecord);
+
+#[Object]
+impl Payment {
+    /// Payment ID
+    async fn id(&self) -> Uuid {
+        self.0.id
+    }
+
+    /// Status name, e.g. `confirmed`
+    async fn status(&self) -> &str {
+        self.0.status.name()
+    }
+
+    /// Full status with its fields, as JSON
+    async fn status_detail(&self) -> Json<&PaymentStatus> {
+        Json(&self.0.status)
+    }
+
+    /// Requested amount, as a decimal string
+    async fn amount(&self) -> String {
+        self.0.request.amount.to_string()
+    }
+
+    /// `native` or the token contract address
+    async fn currency(&self) -> String {
+        currency_label(&self.0.request.currency)
+    }
+
+    /// Receiving address
+    async fn recipient(&self) -> String {
+        self.0.request.recipient_address.to_string()
+    }
+
+    /// Confirmations required before the payment is confirmed
+    async fn required_confirmations(&self) -> u64 {
+        self.0.request.required_confirmations
+    }
+
+    /// Hash of the paying transaction, once one is detected
+    async fn tx_hash(&self) -> Option<&str> {
+        status_tx_hash(&self.0.status)
+    }
+
+    /// Paying transaction, fetched from the explorer
+    async fn transaction(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<Transaction>> {
+        match status_tx_hash(&self.0.status) {
+            Some(hash) => fetch_transaction(ctx.data_unchecked::<BscScanClient>(), hash).await,
+            None => Ok(None),
+        }
+    }
+
+    /// Creation time
+    async fn created_at(&self) -> DateTime<Utc> {
+        self.0.created_at
+    }
+
+    /// Last update time
+    async fn updated_at(&self) -> DateTime<Utc> {
+        self.0.updated_at
+    }
+
+    /// Custom metadata
+    async fn metadata(&self) -> Json<&serde_json::Value> {
+        Json(&self.0.metadata)
+    }
+}
+
+/// Invoice status
+#[derive(Enum, Clone, Copy, PartialEq, Eq)]
+pub enum InvoiceState {
+    /// Waiting for payment
+    Open,
+    /// Payment transaction seen, waiting for confirmations
+    Processing,
+    /// Part of the amount received
+    PartiallyPaid,
+    /// Payment confirmed
+    Paid,
+    /// Expired before being paid
+    Expired,
+    /// Payment failed
+    Failed,
+}
+
+/// Billed item of an invoice
+#[derive(SimpleObject)]
+pub struct LineItem {
+    /// What is being billed
+    pub description: String,
+    /// Number of units
+    pub quantity: u32,
+    /// Price per unit, as a decimal string
+    pub unit_price: String,
+    /// Quantity × unit price, as a decimal string
+    pub total: String,
+}
+
+/// Itemized invoice
+pub struct Invoice(pub InvoiceRecord);
+
+#[Object]
+impl Invoice {
+    /// Invoice ID (same as the payment ID)
+    async fn id(&self) -> Uuid {
+        self.0.id
+    }
+
+    /// Billed items
+    async fn line_items(&self) -> Vec<LineItem> {
+        self.0
+            .line_items
+            .iter()
+            .map(|item| LineItem {
+                description: item.description.clone(),
+                quantity: item.quantity,
+                unit_price: item.unit_price.to_string(),
+                total: item.total().to_string(),
+            })
+            .collect()
+    }
+
+    /// Sum of all line items, as a decimal string
+    async fn subtotal(&self) -> String {
+        self.0.subtotal.to_string()
+    }
+
+    /// Address the payer sends funds to
+    async fn deposit_address(&self) -> String {
+        self.0.deposit_address.to_string()
+    }
+
+    /// Time after which the invoice can no longer be paid
+    async fn expires_at(&self) -> DateTime<Utc> {
+        self.0.expires_at
+    }
+
+    /// Current status
+    async fn status(&self) -> InvoiceState {
+        self.0.status.into()
+    }
+
+    /// Underlying payment
+    async fn payment(&self) -> Payment {
+        Payment(self.0.payment.clone())
+    }
+}
+
+/// On-chain transaction
+#[derive(SimpleObject)]
+pub struct Transaction {
+    /// Transaction hash
+    pub hash: String,
+    /// Block the transaction was mined in (0 while pending)
+    pub block_number: u64,
+    /// Sender
+    pub from: String,
+    /// Recipient; `null` for contract creation
+    pub to: Option<String>,
+    /// Value in wei
+    pub value: String,
+    /// Block time
+    pub timestamp: Option<DateTime<Utc>>,
+    /// Confirmations when fetched
+    pub confirmations: u64,
+    /// Whether execution failed
+    pub is_error: bool,
+}
+
+impl From<crate::client::Transaction> for Transaction {
+    fn from(tx: crate::client::Transaction) -> Self {
+        Self {
+            hash: tx.hash.to_string(),
+            block_number: tx.block_number,
+            from: tx.from.to_string(),
+            to: tx.to.map(|to| to.to_string()),
+            value: tx.value,
+            timestamp: tx.time_stamp,
+            confirmations: tx.confirmations,
+            is_error: tx.is_error == "1",
+        }
+    }
+}
+
+impl From<InvoiceStatus> for InvoiceState {
+    fn from(status: InvoiceStatus) -> Self {
+        match status {
+            InvoiceStatus::Open => InvoiceState::Open,
+            InvoiceStatus::Processing => InvoiceState::Processing,
+            InvoiceStatus::PartiallyPaid => InvoiceState::PartiallyPaid,
+            InvoiceStatus::Paid => InvoiceState::Paid,
+            InvoiceStatus::Expired => InvoiceState::Expired,
+            InvoiceStatus::Failed => InvoiceState::Failed,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payment::{PaymentMonitor, PaymentRequest};
+    use crate::storage::MemoryStorage;
+    use futures::StreamExt;
+    use rust_decimal::Decimal;
+    use std::time::Duration;
+
+    fn pool() -> (Arc<MonitorPool>, BscScanClient) {
+        let client = BscScanClient::new("test-key").unwrap();
+        let monitor = PaymentMonitor::new(client.clone(), Duration::from_secs(60));
+        let pool = MonitorPool::new(monitor, Arc::new(MemoryStorage::new()));
+        (Arc::new(pool), client)
+    }
+
+    fn payment() -> PaymentRecord {
+        PaymentRecord::new(PaymentRequest::eth(
+            Decimal::new(15, 1),
+            "0x742d35cc6634c0532925a3b844bc9e7595f0beb0"
+                .parse()
+                .unwrap(),
+            12,
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_queries_payments_by_status() {
+        let (pool, client) = pool();
+        let storage = pool.storage().clone();
+
+        let pending = payment();
+        let mut confirmed = payment();
+        confirmed.update_status(PaymentStatus::Confirmed {
+            tx_hash: "0xabc".to_string(),
+            confirmations: 12,
+        });
+        storage.save_payment(&pending).await.unwrap();
+        storage.save_payment(&confirmed).await.unwrap();
+
+        let schema = schema(pool, client);
+        let response = schema
+            .execute(r#"{ payments(status: "confirmed") { id status amount txHash } }"#)
+            .await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        let data = response.data.into_json().unwrap();
+        assert_eq!(
+            data["payments"],
+            serde_json::json!([{
+                "id": confirmed.id.to_string(),
+                "status": "confirmed",
+                "amount": "1.5",
+                "txHash": "0xabc",
+            }])
+        );
+
+        let query = format!(
+            r#"{{ payment(id: "{}") {{ status }} invoice(id: "{}") {{ id }} }}"#,
+            pending.id, pending.id
+        );
+        let data = schema.execute(query).await.data.into_json().unwrap();
+        assert_eq!(data["payment"]["status"], "pending");
+        assert!(data["invoice"].is_null());
+    }
+
+    #[tokio::test]
+    async fn test_subscribes_to_status_changes() {
+        let (pool, client) = pool();
+        let first = payment();
+        let second = payment();
+        pool.storage().save_payment(&first).await.unwrap();
+        pool.storage().save_payment(&second).await.unwrap();
+
+        let schema = schema(pool.clone(), client);
+        let query = format!(
+            r#"subscription {{ statusChanged(paymentId: "{}") {{ paymentId status redelivery }} }}"#,
+            second.id
+        );
+        let mut stream = schema.execute_stream(query);
+
+        // The subscription starts listening when first polled
+        let next = tokio::spawn(async move { stream.next().await.unwrap() });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        pool.redeliver(first.id).await.unwrap();
+        pool.redeliver(second.id).await.unwrap();
+
+        let response = next.await.unwrap();
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        assert_eq!(
+            response.data.into_json().unwrap()["statusChanged"],
+            serde_json::json!({
+                "paymentId": second.id.to_string(),
+                "status": "pending",
+                "redelivery": true,
+            })
+        );
+    }
+}
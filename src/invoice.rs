@@ -0,0 +1,553 @@
+//! Invoices: itemized payment requests with a deposit address and expiry
+//!
+//! An [`Invoice`] is a thin layer over [`Payment`]: the line items are priced
+//! in a primary currency, the subtotal becomes the payment amount, and any
+//! other accepted currencies become alternatives of the payment request.
+//! Invoices are persisted through [`PaymentStorage`] as ordinary payments,
+//! with the invoice-only data kept in the payment's metadata.
+
+use crate::chain::Chain;
+use crate::error::{Error, Result};
+use crate::payment::{
+    Currency, Payment, PaymentMonitor, PaymentOption, PaymentRequest, PaymentStatus,
+};
+use crate::primitives::Address;
+use crate::storage::PaymentStorage;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+/// Metadata key under which invoice data is stored on the payment
+const METADATA_KEY: &str = "invoice";
+
+/// Single billed item
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LineItem {
+    /// What is being billed
+    pub description: String,
+    /// Number of units
+    pub quantity: u32,
+    /// Price per unit, in the invoice's primary currency
+    pub unit_price: Decimal,
+}
+
+impl LineItem {
+    /// Create a new line item
+    pub fn new(description: impl Into<String>, quantity: u32, unit_price: Decimal) -> Self {
+        Self {
+            description: description.into(),
+            quantity,
+            unit_price,
+        }
+    }
+
+    /// Total price of the line (quantity × unit price)
+    pub fn total(&self) -> Decimal {
+        self.unit_price * Decimal::from(self.quantity)
+    }
+}
+
+/// Invoice status, derived from the underlying payment status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InvoiceStatus {
+    /// Waiting for payment
+    Open,
+    /// Payment transaction seen, waiting for confirmations
+    Processing,
+    /// Part of the amount received
+    PartiallyPaid,
+    /// Payment confirmed
+    Paid,
+    /// Expired before being paid
+    Expired,
+    /// Payment failed
+    Failed,
+}
+
+impl From<&PaymentStatus> for InvoiceStatus {
+    fn from(status: &PaymentStatus) -> Self {
+        match status {
+            PaymentStatus::Pending | PaymentStatus::Reorged { .. } => InvoiceStatus::Open,
+            PaymentStatus::Detected { .. } | PaymentStatus::Replaced { .. } => {
+                InvoiceStatus::Processing
+            }
+            PaymentStatus::PartiallyPaid { .. } => InvoiceStatus::PartiallyPaid,
+            PaymentStatus::Confirmed { .. } => InvoiceStatus::Paid,
+            PaymentStatus::Expired => InvoiceStatus::Expired,
+            PaymentStatus::Failed { .. } => InvoiceStatus::Failed,
+        }
+    }
+}
+
+/// Invoice data stored in the payment metadata
+#[derive(Debug, Serialize, Deserialize)]
+struct InvoiceMetadata {
+    line_items: Vec<LineItem>,
+    #[serde(default = "default_chain")]
+    chain: Chain,
+}
+
+/// Chain of invoices stored before the chain was recorded
+fn default_chain() -> Chain {
+    Chain::Ethereum
+}
+
+/// Itemized invoice backed by a [`Payment`]
+#[derive(Debug, Clone)]
+pub struct Invoice {
+    /// Invoice ID (same as the payment ID)
+    pub id: Uuid,
+    /// Billed items
+    pub line_items: Vec<LineItem>,
+    /// Sum of all line items, in the primary currency
+    pub subtotal: Decimal,
+    /// Accepted ways to pay, primary currency first
+    pub options: Vec<PaymentOption>,
+    /// Address the payer sends funds to
+    pub deposit_address: Address,
+    /// Chain the invoice is paid on
+    pub chain: Chain,
+    /// Time after which the invoice can no longer be paid
+    pub expires_at: DateTime<Utc>,
+    /// Current status
+    pub status: InvoiceStatus,
+    /// Underlying payment record
+    pub payment: Payment,
+}
+
+impl Invoice {
+    /// Create a builder for an invoice paid to `deposit_address`
+    ///
+    /// # Example
+    /// ```
+    /// # use cryptopay::invoice::{Invoice, LineItem};
+    /// # use cryptopay::Currency;
+    /// # use rust_decimal::Decimal;
+    /// # fn example() -> cryptopay::Result<()> {
+    /// let invoice = Invoice::builder("0x742D35CC6634c0532925A3b844BC9E7595F0BEb0")
+    ///     .currency(Currency::usdc())
+    ///     .line_item(LineItem::new("Hosting (monthly)", 1, Decimal::from(20)))
+    ///     .line_item(LineItem::new("Extra IP", 2, Decimal::from(3)))
+    ///     .accept(Currency::usdt(), Decimal::from(26))
+    ///     .build()?;
+    ///
+    /// assert_eq!(invoice.subtotal, Decimal::from(26));
+    /// println!("{}", invoice);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn builder(deposit_address: impl Into<String>) -> InvoiceBuilder {
+        InvoiceBuilder::new(deposit_address)
+    }
+
+    /// Rebuild an invoice from a stored payment
+    ///
+    /// Fails if the payment was not created from an invoice.
+    pub fn from_payment(payment: Payment) -> Result<Self> {
+        let data =
+            payment.metadata.get(METADATA_KEY).cloned().ok_or_else(|| {
+                Error::generic(format!("payment {} is not an invoice", payment.id))
+            })?;
+        let data: InvoiceMetadata = serde_json::from_value(data)?;
+
+        let request = &payment.request;
+        let expires_at = payment.created_at
+            + chrono::Duration::seconds(request.timeout_seconds.unwrap_or(0) as i64);
+
+        Ok(Self {
+            id: payment.id,
+            line_items: data.line_items,
+            subtotal: request.amount,
+            options: request.options(),
+            deposit_address: request.recipient_address.clone(),
+            chain: data.chain,
+            expires_at,
+            status: InvoiceStatus::from(&payment.status),
+            payment,
+        })
+    }
+
+    /// Payment request the payer has to fulfil
+    pub fn request(&self) -> &PaymentRequest {
+        &self.payment.request
+    }
+
+    /// Check whether the invoice is past its expiry time
+    pub fn is_expired(&self) -> bool {
+        self.is_expired_at(Utc::now())
+    }
+
+    /// Check whether the invoice is past its expiry time at `now`
+    pub fn is_expired_at(&self, now: DateTime<Utc>) -> bool {
+        now >= self.expires_at
+    }
+
+    /// Record a new payment status
+    pub fn apply_status(&mut self, status: PaymentStatus) {
+        self.apply_status_at(status, Utc::now());
+    }
+
+    /// Record a new payment status as of `now`
+    pub fn apply_status_at(&mut self, status: PaymentStatus, now: DateTime<Utc>) {
+        self.status = InvoiceStatus::from(&status);
+        self.payment.update_status_at(status, now);
+    }
+
+    /// Persist the invoice
+    pub async fn save<S: PaymentStorage + ?Sized>(&self, storage: &S) -> Result<()> {
+        storage.save_payment(&self.payment).await
+    }
+
+    /// Load an invoice by ID
+    pub async fn load<S: PaymentStorage + ?Sized>(storage: &S, id: Uuid) -> Result<Option<Self>> {
+        storage
+            .get_payment(id)
+            .await?
+            .map(Self::from_payment)
+            .transpose()
+    }
+
+    /// Monitor the invoice until it is paid, fails, or expires
+    ///
+    /// Every status change is applied to the invoice and written to `storage`.
+    /// If no payment transaction has been seen by `expires_at`, the invoice is
+    /// marked [`InvoiceStatus::Expired`]. A payment already detected at that
+    /// point is still followed until it is confirmed or fails.
+    ///
+    /// Expiry and status timestamps follow the monitor's [`Clock`](crate::clock::Clock).
+    pub async fn monitor<S: PaymentStorage + ?Sized>(
+        &mut self,
+        monitor: &PaymentMonitor,
+        storage: &S,
+    ) -> Result<InvoiceStatus> {
+        let clock = monitor.clock();
+        let remaining = (self.expires_at - clock.now()).to_std().unwrap_or_default();
+        let timed_out = self.follow(monitor, storage, Some(remaining)).await?;
+
+        if timed_out {
+            if matches!(
+                self.status,
+                InvoiceStatus::Open | InvoiceStatus::PartiallyPaid
+            ) {
+                self.apply_status_at(PaymentStatus::Expired, clock.now());
+                storage
+                    .update_status(self.id, PaymentStatus::Expired)
+                    .await?;
+            } else if !self.payment.status.is_finalized() {
+                self.follow(monitor, storage, None).await?;
+            }
+        }
+
+        Ok(self.status)
+    }
+
+    /// Run the monitor, persisting status updates, optionally with a time limit
+    ///
+    /// Returns whether the time limit was hit.
+    async fn follow<S: PaymentStorage + ?Sized>(
+        &mut self,
+        monitor: &PaymentMonitor,
+        storage: &S,
+        limit: Option<Duration>,
+    ) -> Result<bool> {
+        let (sender, mut updates) = mpsc::unbounded_channel();
+        let request = self.payment.request.clone();
+        let clock = monitor.clock();
+
+        // The callback is dropped with the monitoring future, which ends the update loop
+        let watch = async move {
+            let monitoring = monitor.start_monitoring(request, move |status| {
+                let _ = sender.send(status);
+            });
+            match limit {
+                Some(limit) => tokio::select! {
+                    result = monitoring => result.map(|_| false),
+                    () = clock.sleep(limit) => Ok(true),
+                },
+                None => monitoring.await.map(|_| false),
+            }
+        };
+
+        let persist = async {
+            while let Some(status) = updates.recv().await {
+                self.apply_status_at(status.clone(), clock.now());
+                storage.update_status(self.id, status).await?;
+            }
+            Ok(())
+        };
+
+        let (timed_out, ()) = tokio::try_join!(watch, persist)?;
+        Ok(timed_out)
+    }
+}
+
+impl fmt::Display for Invoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let primary = currency_label(&self.payment.request.currency, self.chain);
+
+        writeln!(f, "Invoice {}", self.id)?;
+        writeln!(f, "Status: {:?}", self.status)?;
+        writeln!(f)?;
+        for item in &self.line_items {
+            writeln!(
+                f,
+                "  {} x {} @ {} {} = {} {}",
+                item.quantity,
+                item.description,
+                item.unit_price,
+                primary,
+                item.total(),
+                primary
+            )?;
+        }
+        writeln!(f)?;
+        writeln!(f, "Subtotal: {} {}", self.subtotal, primary)?;
+        writeln!(f, "Pay to: {}", self.deposit_address.to_checksum())?;
+        writeln!(f, "Accepted:")?;
+        for option in &self.options {
+            writeln!(
+                f,
+                "  {} {}",
+                option.amount,
+                currency_label(&option.currency, self.chain)
+            )?;
+        }
+        write!(f, "Expires: {}", self.expires_at.to_rfc3339())
+    }
+}
+
+/// Short human-readable name of a currency on `chain`
+fn currency_label(currency: &Currency, chain: Chain) -> String {
+    currency.symbol(chain).unwrap_or_else(|| match currency {
+        Currency::Native => chain.native_symbol().to_string(),
+        Currency::ERC20 {
+            contract_address, ..
+        } => format!("token {}", contract_address),
+    })
+}
+
+/// Builder for [`Invoice`]
+#[derive(Debug, Clone)]
+pub struct InvoiceBuilder {
+    deposit_address: String,
+    chain: Chain,
+    currency: Currency,
+    line_items: Vec<LineItem>,
+    alternatives: Vec<(Currency, Pricing)>,
+    required_confirmations: u64,
+    expires_in: Duration,
+}
+
+/// How an alternative currency is priced
+#[derive(Debug, Clone)]
+enum Pricing {
+    /// Fixed amount
+    Amount(Decimal),
+    /// Units of the alternative currency per unit of the primary currency
+    Rate(Decimal),
+}
+
+impl InvoiceBuilder {
+    fn new(deposit_address: impl Into<String>) -> Self {
+        Self {
+            deposit_address: deposit_address.into(),
+            chain: Chain::Ethereum,
+            currency: Currency::Native,
+            line_items: Vec::new(),
+            alternatives: Vec::new(),
+            required_confirmations: 12,
+            expires_in: Duration::from_secs(3600),
+        }
+    }
+
+    /// Set the chain the invoice is paid on (default: Ethereum)
+    pub fn chain(mut self, chain: Chain) -> Self {
+        self.chain = chain;
+        self
+    }
+
+    /// Set the primary currency line items are priced in (default: the
+    /// chain's native currency)
+    pub fn currency(mut self, currency: Currency) -> Self {
+        self.currency = currency;
+        self
+    }
+
+    /// Add a line item
+    pub fn line_item(mut self, item: LineItem) -> Self {
+        self.line_items.push(item);
+        self
+    }
+
+    /// Also accept a fixed `amount` of `currency`
+    pub fn accept(mut self, currency: Currency, amount: Decimal) -> Self {
+        self.alternatives.push((currency, Pricing::Amount(amount)));
+        self
+    }
+
+    /// Also accept `currency`, priced from the subtotal at `rate`
+    ///
+    /// `rate` is the number of `currency` units per unit of the primary
+    /// currency; the resulting amount is rounded to the currency's decimals.
+    pub fn accept_at_rate(mut self, currency: Currency, rate: Decimal) -> Self {
+        self.alternatives.push((currency, Pricing::Rate(rate)));
+        self
+    }
+
+    /// Set the required number of confirmations (default: 12)
+    pub fn required_confirmations(mut self, confirmations: u64) -> Self {
+        self.required_confirmations = confirmations;
+        self
+    }
+
+    /// Set how long the invoice can be paid for (default: 1 hour)
+    pub fn expires_in(mut self, duration: Duration) -> Self {
+        self.expires_in = duration;
+        self
+    }
+
+    /// Price the line items and build the invoice
+    pub fn build(self) -> Result<Invoice> {
+        let deposit_address = Address::parse(&self.deposit_address)?;
+        if self.line_items.is_empty() {
+            return Err(Error::InvalidConfig(
+                "invoice needs at least one line item".to_string(),
+            ));
+        }
+
+        let subtotal: Decimal = self.line_items.iter().map(LineItem::total).sum();
+        if subtotal <= Decimal::ZERO {
+            return Err(Error::InvalidConfig(
+                "invoice subtotal must be positive".to_string(),
+            ));
+        }
+
+        let created_at = Utc::now();
+        let mut request = PaymentRequest::new(
+            subtotal,
+            self.currency,
+            deposit_address,
+            self.required_confirmations,
+        )
+        .with_timeout(self.expires_in.as_secs())
+        .with_not_before(created_at);
+
+        for (currency, pricing) in self.alternatives {
+            let amount = match pricing {
+                Pricing::Amount(amount) => amount,
+                Pricing::Rate(rate) => (subtotal * rate).round_dp(currency.decimals() as u32),
+            };
+            request = request.with_alternative(amount, currency);
+        }
+
+        let metadata = InvoiceMetadata {
+            line_items: self.line_items,
+            chain: self.chain,
+        };
+        let mut payment =
+            Payment::new(request).with_metadata(serde_json::json!({ METADATA_KEY: metadata }));
+        payment.created_at = created_at;
+        payment.updated_at = created_at;
+
+        Invoice::from_payment(payment)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::MemoryStorage;
+    use std::str::FromStr;
+
+    const DEPOSIT: &str = "0x742D35CC6634c0532925A3b844BC9E7595F0BEb0";
+
+    #[test]
+    fn test_invoice_pricing() {
+        let invoice = Invoice::builder(DEPOSIT)
+            .line_item(LineItem::new(
+                "Widget",
+                3,
+                Decimal::from_str("0.01").unwrap(),
+            ))
+            .line_item(LineItem::new(
+                "Shipping",
+                1,
+                Decimal::from_str("0.005").unwrap(),
+            ))
+            .accept_at_rate(Currency::usdc(), Decimal::from_str("2500.1234567").unwrap())
+            .accept(Currency::usdt(), Decimal::from(90))
+            .build()
+            .unwrap();
+
+        assert_eq!(invoice.subtotal, Decimal::from_str("0.035").unwrap());
+        assert_eq!(invoice.status, InvoiceStatus::Open);
+        assert_eq!(invoice.options.len(), 3);
+        assert_eq!(
+            invoice.options[1].amount,
+            Decimal::from_str("87.504321").unwrap()
+        );
+        assert_eq!(invoice.options[2].amount, Decimal::from(90));
+        assert_eq!(
+            invoice.expires_at,
+            invoice.payment.created_at + chrono::Duration::hours(1)
+        );
+        assert_eq!(
+            invoice.request().not_before,
+            Some(invoice.payment.created_at)
+        );
+
+        let text = invoice.to_string();
+        assert!(text.contains("Subtotal: 0.035 ETH"));
+        assert!(text.contains("87.504321 USDC"));
+
+        let bnb = Invoice::builder(DEPOSIT)
+            .chain(Chain::Bsc)
+            .line_item(LineItem::new("Widget", 1, Decimal::ONE))
+            .build()
+            .unwrap();
+        assert!(bnb.to_string().contains("Subtotal: 1 BNB"));
+
+        assert!(Invoice::builder(DEPOSIT).build().is_err());
+        assert!(Invoice::builder("0xinvalid")
+            .line_item(LineItem::new("Widget", 1, Decimal::ONE))
+            .build()
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_invoice_persistence() {
+        let storage = MemoryStorage::new();
+        let mut invoice = Invoice::builder(DEPOSIT)
+            .line_item(LineItem::new("Widget", 2, Decimal::ONE))
+            .build()
+            .unwrap();
+        invoice.save(&storage).await.unwrap();
+
+        invoice.apply_status(PaymentStatus::Detected {
+            confirmations: 1,
+            tx_hash: "0xhash".to_string(),
+        });
+        storage
+            .update_status(invoice.id, invoice.payment.status.clone())
+            .await
+            .unwrap();
+
+        let loaded = Invoice::load(&storage, invoice.id).await.unwrap().unwrap();
+        assert_eq!(loaded.line_items, invoice.line_items);
+        assert_eq!(loaded.subtotal, Decimal::from(2));
+        assert_eq!(loaded.expires_at, invoice.expires_at);
+        assert_eq!(loaded.chain, Chain::Ethereum);
+        assert_eq!(loaded.status, InvoiceStatus::Processing);
+
+        let plain = Payment::new(PaymentRequest::eth(
+            Decimal::ONE,
+            DEPOSIT.parse().unwrap(),
+            12,
+        ));
+        assert!(Invoice::from_payment(plain).is_err());
+    }
+}
@@ -0,0 +1,469 @@
+//! Accounting ledger for received funds
+//!
+//! [`Ledger::replay`] walks the on-chain history of a set of deposit
+//! addresses and books every inbound transfer as a double-entry style
+//! [`LedgerEntry`]: the receiving wallet is debited with the gross amount, and
+//! the matching payment is credited, or the unmatched-deposits account if no
+//! stored payment claims the transfer.
+
+use crate::chain::Chain;
+use crate::client::{
+    BeaconWithdrawal, BscScanClient, InternalTransaction, TokenTransfer, Transaction,
+};
+use crate::error::Result;
+use crate::payment::{Currency, Payment, PaymentDetails, PaymentStatus};
+use crate::primitives::Address;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::io::Write;
+use uuid::Uuid;
+
+/// Inbound transfer to a watched address
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Deposit {
+    /// Transaction hash, or `withdrawal:<index>` for beacon chain withdrawals
+    pub tx_hash: String,
+    /// Currency symbol as reported by the explorer (the chain's native symbol,
+    /// e.g. `ETH` or `BNB`, for native transfers)
+    pub symbol: String,
+    /// Transfer details (currency, amount, sender, recipient, block, time)
+    pub details: PaymentDetails,
+}
+
+/// Fetch all inbound transfers to `address` between two blocks (inclusive)
+///
+/// Covers successful native transactions, internal transactions, ERC20
+/// transfers, and beacon chain withdrawals on chains that have them, ordered
+/// by block.
+pub async fn fetch_deposits(
+    client: &BscScanClient,
+    address: &Address,
+    start_block: u64,
+    end_block: u64,
+) -> Result<Vec<Deposit>> {
+    let inbound =
+        |details: &PaymentDetails| *address == details.to && details.amount > Decimal::ZERO;
+    let native = Chain::from(client.chain_id()).native_symbol();
+    let mut deposits = Vec::new();
+
+    let transactions: Vec<Transaction> = client
+        .fetch_history("txlist", address, start_block, end_block)
+        .await?;
+    for tx in transactions.iter().filter(|tx| tx.is_successful()) {
+        let details = PaymentDetails::from_transaction(tx);
+        if inbound(&details) {
+            deposits.push(Deposit {
+                tx_hash: tx.hash.to_string(),
+                symbol: native.to_string(),
+                details,
+            });
+        }
+    }
+
+    let internal: Vec<InternalTransaction> = client
+        .fetch_history("txlistinternal", address, start_block, end_block)
+        .await?;
+    for tx in internal.iter().filter(|tx| tx.is_error == "0") {
+        let details = PaymentDetails::from_internal_transaction(tx);
+        if inbound(&details) {
+            deposits.push(Deposit {
+                tx_hash: tx.hash.to_string(),
+                symbol: native.to_string(),
+                details,
+            });
+        }
+    }
+
+    let transfers: Vec<TokenTransfer> = client
+        .fetch_history("tokentx", address, start_block, end_block)
+        .await?;
+    for transfer in &transfers {
+        let details = PaymentDetails::from_token_transfer(transfer);
+        if inbound(&details) {
+            deposits.push(Deposit {
+                tx_hash: transfer.hash.to_string(),
+                symbol: transfer.token_symbol.clone(),
+                details,
+            });
+        }
+    }
+
+    if Chain::from(client.chain_id()).has_beacon_withdrawals() {
+        let withdrawals: Vec<BeaconWithdrawal> = client
+            .fetch_history("txsBeaconWithdrawal", address, start_block, end_block)
+            .await?;
+        for withdrawal in &withdrawals {
+            let details = PaymentDetails::from_beacon_withdrawal(withdrawal);
+            if inbound(&details) {
+                deposits.push(Deposit {
+                    tx_hash: format!("withdrawal:{}", withdrawal.withdrawal_index),
+                    symbol: native.to_string(),
+                    details,
+                });
+            }
+        }
+    }
+
+    deposits.sort_by_key(|deposit| deposit.details.block_number);
+    Ok(deposits)
+}
+
+/// Account a ledger entry is booked against
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum LedgerAccount {
+    /// On-chain wallet (deposit address)
+    Wallet(String),
+    /// Stored payment the funds settle
+    Payment(Uuid),
+    /// Funds no stored payment accounts for
+    Unmatched,
+}
+
+impl fmt::Display for LedgerAccount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LedgerAccount::Wallet(address) => write!(f, "wallet:{}", address.to_lowercase()),
+            LedgerAccount::Payment(id) => write!(f, "payment:{}", id),
+            LedgerAccount::Unmatched => f.write_str("unmatched"),
+        }
+    }
+}
+
+/// Single booked inbound transfer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LedgerEntry {
+    /// Transaction hash
+    pub tx_hash: String,
+    /// Block the transfer was included in
+    pub block_number: u64,
+    /// Block timestamp, if reported by the explorer
+    pub timestamp: Option<DateTime<Utc>>,
+    /// Sender address
+    pub from: String,
+    /// Currency received
+    pub currency: Currency,
+    /// Currency symbol
+    pub symbol: String,
+    /// Gross amount received
+    pub amount: Decimal,
+    /// Debited account (the receiving wallet)
+    pub debit: LedgerAccount,
+    /// Credited account (the matched payment, or unmatched)
+    pub credit: LedgerAccount,
+    /// ID of the matched payment, if any
+    pub payment_id: Option<Uuid>,
+}
+
+/// Totals received in one currency
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CurrencyBalance {
+    /// Currency
+    pub currency: Currency,
+    /// Currency symbol
+    pub symbol: String,
+    /// Amount matched to payments
+    pub matched: Decimal,
+    /// Amount not matched to any payment
+    pub unmatched: Decimal,
+}
+
+impl CurrencyBalance {
+    /// Total amount received
+    pub fn total(&self) -> Decimal {
+        self.matched + self.unmatched
+    }
+}
+
+/// Ledger of inbound transfers
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Ledger {
+    /// Entries ordered by block
+    pub entries: Vec<LedgerEntry>,
+}
+
+impl Ledger {
+    /// Replay the history of `addresses` between two blocks into a ledger
+    ///
+    /// Transfers are matched to `payments` by transaction hash, recipient,
+    /// and currency.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use cryptopay::*;
+    /// # use cryptopay::ledger::Ledger;
+    /// # async fn example(payments: Vec<Payment>) -> Result<()> {
+    /// let client = BscScanClient::new("api-key")?;
+    /// let wallet: Address = "0x742d35cc6634c0532925a3b844bc9e7595f0beb0".parse()?;
+    /// let ledger = Ledger::replay(&client, &[wallet], &payments, 0, 99_999_999).await?;
+    ///
+    /// for balance in ledger.balances() {
+    ///     println!("{}: {} ({} unmatched)", balance.symbol, balance.total(), balance.unmatched);
+    /// }
+    /// ledger.write_csv(std::fs::File::create("ledger.csv")?)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn replay(
+        client: &BscScanClient,
+        addresses: &[Address],
+        payments: &[Payment],
+        start_block: u64,
+        end_block: u64,
+    ) -> Result<Self> {
+        let mut deposits = Vec::new();
+        for address in addresses {
+            deposits.extend(fetch_deposits(client, address, start_block, end_block).await?);
+        }
+        deposits.sort_by_key(|deposit| deposit.details.block_number);
+
+        Ok(Self::from_deposits(deposits, payments))
+    }
+
+    /// Book already fetched deposits, matching them against `payments`
+    pub fn from_deposits(deposits: Vec<Deposit>, payments: &[Payment]) -> Self {
+        let mut claims: HashMap<String, Vec<&Payment>> = HashMap::new();
+        for payment in payments {
+            for tx_hash in paid_by(payment) {
+                claims
+                    .entry(tx_hash.to_lowercase())
+                    .or_default()
+                    .push(payment);
+            }
+        }
+
+        let entries = deposits
+            .into_iter()
+            .map(|deposit| {
+                let details = deposit.details;
+                let payment_id = claims
+                    .get(&deposit.tx_hash.to_lowercase())
+                    .and_then(|candidates| {
+                        candidates.iter().find(|payment| {
+                            payment.request.recipient_address == details.to
+                                && payment.request.options().iter().any(|option| {
+                                    same_currency(&option.currency, &details.currency)
+                                })
+                        })
+                    })
+                    .map(|payment| payment.id);
+
+                LedgerEntry {
+                    tx_hash: deposit.tx_hash,
+                    block_number: details.block_number,
+                    timestamp: details.timestamp,
+                    from: details.from.to_string(),
+                    currency: details.currency,
+                    symbol: deposit.symbol,
+                    amount: details.amount,
+                    debit: LedgerAccount::Wallet(details.to.to_string()),
+                    credit: payment_id.map_or(LedgerAccount::Unmatched, LedgerAccount::Payment),
+                    payment_id,
+                }
+            })
+            .collect();
+
+        Self { entries }
+    }
+
+    /// Entries not matched to any payment
+    pub fn unmatched(&self) -> impl Iterator<Item = &LedgerEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.payment_id.is_none())
+    }
+
+    /// Totals per currency, in order of first appearance
+    pub fn balances(&self) -> Vec<CurrencyBalance> {
+        let mut balances: Vec<CurrencyBalance> = Vec::new();
+
+        for entry in &self.entries {
+            let index = match balances
+                .iter()
+                .position(|balance| same_currency(&balance.currency, &entry.currency))
+            {
+                Some(index) => index,
+                None => {
+                    balances.push(CurrencyBalance {
+                        currency: entry.currency.clone(),
+                        symbol: entry.symbol.clone(),
+                        matched: Decimal::ZERO,
+                        unmatched: Decimal::ZERO,
+                    });
+                    balances.len() - 1
+                }
+            };
+
+            let balance = &mut balances[index];
+            if entry.payment_id.is_some() {
+                balance.matched += entry.amount;
+            } else {
+                balance.unmatched += entry.amount;
+            }
+        }
+
+        balances
+    }
+
+    /// Write the ledger as CSV, one row per entry
+    pub fn write_csv<W: Write>(&self, mut writer: W) -> Result<()> {
+        writeln!(
+            writer,
+            "timestamp,block_number,tx_hash,from,currency,symbol,amount,debit,credit,payment_id"
+        )?;
+
+        for entry in &self.entries {
+            let currency = match &entry.currency {
+                // Native deposits carry the chain's native symbol
+                Currency::Native => entry.symbol.clone(),
+                Currency::ERC20 {
+                    contract_address, ..
+                } => contract_address.to_string(),
+            };
+            let fields = [
+                entry
+                    .timestamp
+                    .map(|timestamp| timestamp.to_rfc3339())
+                    .unwrap_or_default(),
+                entry.block_number.to_string(),
+                entry.tx_hash.clone(),
+                entry.from.clone(),
+                currency,
+                entry.symbol.clone(),
+                entry.amount.to_string(),
+                entry.debit.to_string(),
+                entry.credit.to_string(),
+                entry
+                    .payment_id
+                    .map(|id| id.to_string())
+                    .unwrap_or_default(),
+            ];
+
+            let row: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+            writeln!(writer, "{}", row.join(","))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Transaction hashes a payment's status attributes to it
+fn paid_by(payment: &Payment) -> Vec<&str> {
+    match &payment.status {
+        PaymentStatus::Detected { tx_hash, .. } | PaymentStatus::Confirmed { tx_hash, .. } => {
+            vec![tx_hash.as_str()]
+        }
+        PaymentStatus::PartiallyPaid { tx_hashes, .. } => {
+            tx_hashes.iter().map(String::as_str).collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Compare currencies, ignoring the case of token contract addresses
+pub(crate) fn same_currency(a: &Currency, b: &Currency) -> bool {
+    match (a, b) {
+        (Currency::Native, Currency::Native) => true,
+        (
+            Currency::ERC20 {
+                contract_address: a,
+                ..
+            },
+            Currency::ERC20 {
+                contract_address: b,
+                ..
+            },
+        ) => a == b,
+        _ => false,
+    }
+}
+
+/// Quote a CSV field if it contains a delimiter, quote, or line break
+pub(crate) fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payment::PaymentRequest;
+
+    const WALLET: &str = "0x742d35cc6634c0532925a3b844bc9e7595f0beb0";
+
+    fn deposit(tx_hash: &str, currency: Currency, symbol: &str, amount: i64) -> Deposit {
+        Deposit {
+            tx_hash: tx_hash.to_string(),
+            symbol: symbol.to_string(),
+            details: PaymentDetails {
+                currency,
+                amount: Decimal::from(amount),
+                from: "0x00000000000000000000000000000000000000c1"
+                    .parse()
+                    .unwrap(),
+                to: WALLET.parse().unwrap(),
+                block_number: 100,
+                timestamp: None,
+                gas_cost: None,
+                funded_by: None,
+            },
+        }
+    }
+
+    #[test]
+    fn test_ledger_matching_and_balances() {
+        let mut paid = Payment::new(PaymentRequest::new(
+            Decimal::from(25),
+            Currency::usdc(),
+            WALLET.parse().unwrap(),
+            6,
+        ));
+        paid.update_status(PaymentStatus::Confirmed {
+            tx_hash: "0xAAA".to_string(),
+            confirmations: 6,
+        });
+
+        let usdc = Currency::erc20(
+            "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48"
+                .parse()
+                .unwrap(),
+            6,
+        );
+        let ledger = Ledger::from_deposits(
+            vec![
+                deposit("0xaaa", usdc.clone(), "USDC", 25),
+                deposit("0xbbb", usdc, "USDC", 5),
+                deposit("0xccc", Currency::Native, "BNB", 1),
+            ],
+            &[paid.clone()],
+        );
+
+        assert_eq!(ledger.entries[0].payment_id, Some(paid.id));
+        assert_eq!(ledger.entries[0].credit, LedgerAccount::Payment(paid.id));
+        assert_eq!(ledger.unmatched().count(), 2);
+
+        let balances = ledger.balances();
+        assert_eq!(balances.len(), 2);
+        assert_eq!(balances[0].symbol, "USDC");
+        assert_eq!(balances[0].matched, Decimal::from(25));
+        assert_eq!(balances[0].unmatched, Decimal::from(5));
+        assert_eq!(balances[1].total(), Decimal::from(1));
+
+        let mut csv = Vec::new();
+        ledger.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[1].ends_with(&format!(
+            "USDC,25,wallet:{},payment:{},{}",
+            WALLET, paid.id, paid.id
+        )));
+        assert!(lines[3].contains(",BNB,BNB,1,"));
+        assert_eq!(csv_field("a,\"b\""), "\"a,\"\"b\"\"\"");
+    }
+}
@@ -9,10 +9,22 @@
 //!
 //! - **Etherscan API Integration**: Full support for accounts, transactions, tokens, and gas tracking
 //! - **Payment Verification**: Verify ETH and ERC20 token payments with confirmation tracking
+//! - **Log Decoding**: Decode ERC20/ERC721 `Transfer` events from transaction receipts
 //! - **Payment Monitoring**: Monitor pending payments with callbacks
 //! - **Rate Limiting**: Built-in rate limiter respecting Etherscan's 5 req/s limit
 //! - **Caching**: In-memory LRU cache to minimize API calls
-//! - **Optional Storage**: PostgreSQL and SQLite storage implementations (feature-gated)
+//! - **Checkout Sessions**: Stripe-like sessions with deposit addresses, payment URIs, and signed tokens
+//! - **Fiat Pricing**: Convert USD/EUR amounts to crypto with pluggable exchange rate providers
+//! - **Subscriptions**: Recurring payments with grace windows and paid/late/lapsed events
+//! - **Ledger**: Replay received funds into ledger entries with per-currency balances and CSV export
+//! - **Reconciliation**: Cross-check stored payments against on-chain history
+//! - **Token Vetting**: Flag unverified, brand-new, or inactive token contracts before accepting them
+//! - **Sweep Tracking**: Detect and confirm sweeps from deposit addresses to a treasury address
+//! - **Invoices**: Itemized invoices with multiple accepted currencies and expiry
+//! - **Storage**: In-memory and file-backed payment storage behind the `PaymentStorage` trait
+//! - **Payment API**: Background monitoring pool and a drop-in axum REST router (`server` feature)
+//! - **GraphQL**: Query payments, invoices, and transactions, and subscribe to status changes (`graphql` feature)
+//! - **Gateway**: Config-file driven daemon with webhooks and health checks (`gateway` feature)
 //!
 //! ## Quick Start
 //!
@@ -28,13 +40,13 @@
 //!     let verifier = PaymentVerifier::new(client);
 //!     
 //!     // Create payment request
-//!     let payment = PaymentRequest {
-//!         amount: Decimal::from_str("0.1").unwrap(),
-//!         currency: Currency::ETH,
-//!         recipient_address: "0x...".to_string(),
-//!         required_confirmations: 12,
-//!         timeout_seconds: Some(1800),
-//!     };
+//!     let payment = PaymentRequest::new(
+//!         Decimal::from_str("0.1").unwrap(),
+//!         Currency::Native,
+//!         "0x742d35cc6634c0532925a3b844bc9e7595f0beb0".parse()?,
+//!         12,
+//!     )
+//!     .with_timeout(1800);
 //!     
 //!     // Verify payment
 //!     match verifier.verify_payment(&payment).await? {
@@ -42,7 +54,7 @@
 //!             println!("Payment confirmed: {}", tx_hash);
 //!         }
 //!         VerificationResult::Pending { confirmations, .. } => {
-//!             println!("Waiting for confirmations: {}/{}", 
+//!             println!("Waiting for confirmations: {}/{}",
 //!                 confirmations, payment.required_confirmations);
 //!         }
 //!         _ => println!("No payment found"),
@@ -52,26 +64,53 @@
 //! }
 //! ```
 
+pub mod abi;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod chain;
+pub mod checkout;
 pub mod client;
+pub mod clock;
 pub mod config;
 pub mod error;
+pub mod events;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "gateway")]
+pub mod gateway;
+#[cfg(feature = "graphql")]
+pub mod graphql;
+pub mod invoice;
+pub mod ledger;
+pub mod notify;
 pub mod payment;
-
-#[cfg(feature = "postgres-storage")]
+pub mod pricing;
+pub mod primitives;
+pub mod reconcile;
+pub mod retry;
+mod rt;
+#[cfg(feature = "server")]
+pub mod server;
 pub mod storage;
+pub mod subscription;
+pub mod sweep;
+#[cfg(feature = "test-utils")]
+pub mod testing;
+pub mod vetting;
+pub mod webhook;
 
 // Re-export main types for convenience
+pub use chain::{Chain, RiskLevel, StablecoinRegistry};
 pub use client::BscScanClient as EtherscanClient;
 pub use client::BscScanClient; // Keep for backward compat
-pub use config::ClientConfig;
-pub use error::{Error, Result};
+pub use clock::{Clock, MockClock, SystemClock};
+pub use config::{ClientConfig, Secret};
+pub use error::{ApiErrorKind, Error, Result};
+pub use invoice::{Invoice, InvoiceStatus, LineItem};
 pub use payment::{
-    Currency, Payment, PaymentMonitor, PaymentRequest, PaymentStatus, PaymentVerifier,
-    VerificationResult,
+    Currency, Payment, PaymentDetails, PaymentMonitor, PaymentRequest, PaymentStatus,
+    PaymentVerifier, VerificationResult,
 };
-
-#[cfg(feature = "postgres-storage")]
-pub use storage::{PaymentStorage, PostgresStorage};
-
-#[cfg(feature = "sqlite-storage")]
-pub use storage::SqliteStorage;
+pub use primitives::{Address, TokenAmount, TxHash, U256};
+pub use retry::RetryPolicy;
+pub use storage::{MemoryStorage, PaymentStorage};
@@ -0,0 +1,272 @@
+//! SMTP email notifications (`email` feature)
+
+use crate::error::{Error, Result};
+use crate::notify::Notifier;
+use crate::payment::{Currency, Payment, PaymentStatus};
+use async_trait::async_trait;
+use lettre::message::header::ContentType;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use std::collections::HashMap;
+
+/// Metadata key holding the customer's email address by default
+const DEFAULT_RECIPIENT_KEY: &str = "customer_email";
+
+/// Subject and body of an email, with `{placeholders}`
+///
+/// Available placeholders: `{id}`, `{amount}`, `{currency}`, `{recipient}`
+/// (the receiving address), `{status}`, `{tx_hash}`, and `{reason}`. Those
+/// that don't apply to the status are empty.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmailTemplate {
+    /// Subject line
+    pub subject: String,
+    /// Plain-text body
+    pub body: String,
+}
+
+impl EmailTemplate {
+    /// Create a template
+    pub fn new(subject: impl Into<String>, body: impl Into<String>) -> Self {
+        Self {
+            subject: subject.into(),
+            body: body.into(),
+        }
+    }
+
+    /// Fill in the placeholders for `payment`
+    pub fn render(&self, payment: &Payment) -> (String, String) {
+        let currency = match &payment.request.currency {
+            Currency::Native => "native".to_string(),
+            Currency::ERC20 {
+                contract_address, ..
+            } => contract_address.to_string(),
+        };
+        let (tx_hash, reason) = match &payment.status {
+            PaymentStatus::Detected { tx_hash, .. }
+            | PaymentStatus::Confirmed { tx_hash, .. }
+            | PaymentStatus::Reorged { tx_hash }
+            | PaymentStatus::Replaced {
+                replacement: tx_hash,
+                ..
+            } => (tx_hash.as_str(), ""),
+            PaymentStatus::Failed { reason } => ("", reason.as_str()),
+            _ => ("", ""),
+        };
+        let values = [
+            ("{id}", payment.id.to_string()),
+            ("{amount}", payment.request.amount.to_string()),
+            ("{currency}", currency),
+            ("{recipient}", payment.request.recipient_address.to_string()),
+            ("{status}", payment.status.name().to_string()),
+            ("{tx_hash}", tx_hash.to_string()),
+            ("{reason}", reason.to_string()),
+        ];
+        let fill = |template: &str| {
+            values
+                .iter()
+                .fold(template.to_string(), |text, (key, value)| {
+                    text.replace(key, value)
+                })
+        };
+        (fill(&self.subject), fill(&self.body))
+    }
+}
+
+/// Emails customers when their payment is confirmed, expires, or fails
+///
+/// The recipient is read from the payment's metadata (`customer_email` by
+/// default); payments without one are skipped. Other statuses are not
+/// emailed unless a template is set for them.
+///
+/// # Example
+/// ```no_run
+/// # use cryptopay::*;
+/// # use cryptopay::notify::{EmailNotifier, EmailTemplate};
+/// # use cryptopay::payment::MonitorPool;
+/// # fn example(pool: &MonitorPool) -> Result<()> {
+/// let email = EmailNotifier::smtp("smtp.example.com", "user", "password", "Shop <pay@shop.example>")?
+///     .template(
+///         "confirmed",
+///         EmailTemplate::new("Thank you!", "We received your payment of {amount}."),
+///     );
+/// pool.notify(email);
+/// # Ok(())
+/// # }
+/// ```
+pub struct EmailNotifier<T = AsyncSmtpTransport<Tokio1Executor>> {
+    transport: T,
+    from: Mailbox,
+    recipient_key: String,
+    templates: HashMap<&'static str, EmailTemplate>,
+}
+
+impl EmailNotifier {
+    /// Send through the SMTP server at `relay` (TLS on port 465) as `from`
+    pub fn smtp(
+        relay: &str,
+        username: impl Into<String>,
+        password: impl Into<String>,
+        from: &str,
+    ) -> Result<Self> {
+        let transport = AsyncSmtpTransport::<Tokio1Executor>::relay(relay)
+            .map_err(|e| Error::InvalidConfig(format!("SMTP relay {}: {}", relay, e)))?
+            .credentials(Credentials::new(username.into(), password.into()))
+            .build();
+        Self::with_transport(transport, from)
+    }
+}
+
+impl<T> EmailNotifier<T>
+where
+    T: AsyncTransport + Send + Sync,
+    T::Error: std::fmt::Display,
+{
+    /// Send through `transport` as `from`, e.g. a STARTTLS or local relay
+    pub fn with_transport(transport: T, from: &str) -> Result<Self> {
+        let from = from
+            .parse()
+            .map_err(|e| Error::InvalidConfig(format!("email sender {}: {}", from, e)))?;
+        let templates = HashMap::from([
+            (
+                "confirmed",
+                EmailTemplate::new(
+                    "Payment received",
+                    "Your payment of {amount} ({currency}) was confirmed.\n\nTransaction: {tx_hash}\nReference: {id}\n",
+                ),
+            ),
+            (
+                "expired",
+                EmailTemplate::new(
+                    "Payment expired",
+                    "We didn't receive your payment of {amount} ({currency}) in time.\n\nReference: {id}\n",
+                ),
+            ),
+            (
+                "failed",
+                EmailTemplate::new(
+                    "Payment failed",
+                    "Your payment of {amount} ({currency}) failed: {reason}\n\nReference: {id}\n",
+                ),
+            ),
+        ]);
+        Ok(Self {
+            transport,
+            from,
+            recipient_key: DEFAULT_RECIPIENT_KEY.to_string(),
+            templates,
+        })
+    }
+
+    /// Read the recipient from this metadata key (default: `customer_email`)
+    pub fn recipient_key(mut self, key: impl Into<String>) -> Self {
+        self.recipient_key = key.into();
+        self
+    }
+
+    /// Email payments entering `status` (a [`PaymentStatus::name`]) with `template`
+    pub fn template(mut self, status: &'static str, template: EmailTemplate) -> Self {
+        self.templates.insert(status, template);
+        self
+    }
+
+    /// Stop emailing payments entering `status`
+    pub fn without_template(mut self, status: &str) -> Self {
+        self.templates.remove(status);
+        self
+    }
+
+    /// Email the customer of `payment` about its current status
+    ///
+    /// Returns whether an email was sent: without a template for the status
+    /// or a recipient in the metadata, nothing is.
+    pub async fn send(&self, payment: &Payment) -> Result<bool> {
+        let Some(template) = self.templates.get(payment.status.name()) else {
+            return Ok(false);
+        };
+        let Some(to) = payment
+            .metadata
+            .get(&self.recipient_key)
+            .and_then(|value| value.as_str())
+        else {
+            return Ok(false);
+        };
+        let to: Mailbox = to
+            .parse()
+            .map_err(|e| Error::InvalidMetadata(format!("{}: {}", self.recipient_key, e)))?;
+
+        let (subject, body) = template.render(payment);
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(subject)
+            .header(ContentType::TEXT_PLAIN)
+            .body(body)
+            .map_err(|e| Error::generic(format!("email: {}", e)))?;
+        self.transport
+            .send(message)
+            .await
+            .map_err(|e| Error::generic(format!("email delivery failed: {}", e)))?;
+        Ok(true)
+    }
+}
+
+#[async_trait]
+impl<T> Notifier for EmailNotifier<T>
+where
+    T: AsyncTransport + Send + Sync,
+    T::Error: std::fmt::Display,
+{
+    /// Messages without a payment have no customer to email; they are dropped
+    async fn notify(&self, _message: &str) -> Result<()> {
+        Ok(())
+    }
+
+    async fn notify_payment(&self, payment: &Payment) -> Result<()> {
+        self.send(payment).await.map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payment::PaymentRequest;
+    use lettre::transport::stub::AsyncStubTransport;
+    use rust_decimal::Decimal;
+
+    #[tokio::test]
+    async fn test_emails_customer_from_metadata() {
+        let transport = AsyncStubTransport::new_ok();
+        let email =
+            EmailNotifier::with_transport(transport.clone(), "Shop <pay@shop.example>").unwrap();
+
+        let mut payment = Payment::new(PaymentRequest::eth(
+            Decimal::ONE,
+            "0x742d35cc6634c0532925a3b844bc9e7595f0beb0"
+                .parse()
+                .unwrap(),
+            12,
+        ))
+        .with_metadata(serde_json::json!({ "customer_email": "alice@example.com" }));
+
+        // No template for pending payments
+        assert!(!email.send(&payment).await.unwrap());
+
+        payment.update_status(PaymentStatus::Confirmed {
+            tx_hash: "0xabc".to_string(),
+            confirmations: 12,
+        });
+        assert!(email.send(&payment).await.unwrap());
+
+        let messages = transport.messages().await;
+        assert_eq!(messages.len(), 1);
+        let (envelope, raw) = &messages[0];
+        assert_eq!(envelope.to()[0].to_string(), "alice@example.com");
+        assert!(raw.contains("Subject: Payment received"));
+        assert!(raw.contains("Transaction: 0xabc"));
+
+        payment.metadata = serde_json::Value::Null;
+        assert!(!email.send(&payment).await.unwrap());
+    }
+}
@@ -0,0 +1,299 @@
+//! Human-readable payment notifications
+//!
+//! A [`Notifier`] pushes a short message per status change to a chat, for
+//! merchants who don't run a webhook endpoint. Attach notifiers to a
+//! [`PaymentMonitor`](crate::PaymentMonitor) with
+//! [`PaymentMonitorBuilder::notifier`](crate::payment::monitor::PaymentMonitorBuilder::notifier),
+//! or to every payment of a [`MonitorPool`](crate::payment::MonitorPool) with
+//! [`MonitorPool::notify`](crate::payment::MonitorPool::notify).
+//!
+//! Built-in sinks: [`SlackNotifier`] and [`DiscordNotifier`] (incoming
+//! webhooks), [`TelegramNotifier`] (bot API), and with the `email` feature
+//! `EmailNotifier` (SMTP, to the customer address in the payment's metadata).
+
+use crate::error::{Error, Result};
+use crate::payment::{Currency, Payment, PaymentRequest, PaymentStatus};
+use async_trait::async_trait;
+use serde_json::json;
+
+#[cfg(feature = "email")]
+mod email;
+
+#[cfg(feature = "email")]
+pub use email::{EmailNotifier, EmailTemplate};
+
+/// Default Telegram bot API endpoint
+const TELEGRAM_API_URL: &str = "https://api.telegram.org";
+
+/// Sink for human-readable status messages
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+pub trait Notifier: Send + Sync {
+    /// Send one message
+    async fn notify(&self, message: &str) -> Result<()>;
+
+    /// Report the status change of a stored payment, now in `payment.status`
+    ///
+    /// Defaults to [`notify`](Self::notify) with the message from
+    /// [`describe`]; sinks that need the payment itself, e.g. for its
+    /// metadata, override this.
+    async fn notify_payment(&self, payment: &Payment) -> Result<()> {
+        self.notify(&describe(&payment.request, &payment.status))
+            .await
+    }
+}
+
+/// Message describing `status` of a payment for `request`
+///
+/// # Example
+/// ```
+/// # use cryptopay::*;
+/// # use cryptopay::notify::describe;
+/// let request = PaymentRequest::eth(
+///     rust_decimal::Decimal::new(1, 1),
+///     "0x742d35cc6634c0532925a3b844bc9e7595f0beb0".parse().unwrap(),
+///     12,
+/// );
+/// assert_eq!(
+///     describe(&request, &PaymentStatus::Expired),
+///     "Payment of 0.1 (native) to 0x742d35cc6634c0532925a3b844bc9e7595f0beb0 expired"
+/// );
+/// ```
+pub fn describe(request: &PaymentRequest, status: &PaymentStatus) -> String {
+    let currency = match &request.currency {
+        Currency::Native => "(native)".to_string(),
+        Currency::ERC20 {
+            contract_address, ..
+        } => format!("of token {}", contract_address),
+    };
+    let status = match status {
+        PaymentStatus::Pending => "is pending".to_string(),
+        PaymentStatus::Detected {
+            confirmations,
+            tx_hash,
+        } => format!(
+            "detected with {} confirmation(s) (tx {})",
+            confirmations, tx_hash
+        ),
+        PaymentStatus::Confirmed { tx_hash, .. } => format!("confirmed (tx {})", tx_hash),
+        PaymentStatus::PartiallyPaid {
+            received,
+            remaining,
+            ..
+        } => format!(
+            "partially paid: {} received, {} remaining",
+            received, remaining
+        ),
+        PaymentStatus::Reorged { tx_hash } => {
+            format!("reorged out of its block (tx {})", tx_hash)
+        }
+        PaymentStatus::Replaced {
+            tx_hash,
+            replacement,
+        } => format!("replaced (tx {} by tx {})", tx_hash, replacement),
+        PaymentStatus::Failed { reason } => format!("failed: {}", reason),
+        PaymentStatus::Expired => "expired".to_string(),
+    };
+    format!(
+        "Payment of {} {} to {} {}",
+        request.amount, currency, request.recipient_address, status
+    )
+}
+
+/// Slack incoming webhook
+#[derive(Debug, Clone)]
+pub struct SlackNotifier {
+    webhook_url: String,
+    http: reqwest::Client,
+}
+
+impl SlackNotifier {
+    /// Post to the incoming webhook at `webhook_url`
+    pub fn new(webhook_url: impl Into<String>) -> Self {
+        Self {
+            webhook_url: webhook_url.into(),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Send messages with `client`
+    pub fn http_client(mut self, client: reqwest::Client) -> Self {
+        self.http = client;
+        self
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl Notifier for SlackNotifier {
+    async fn notify(&self, message: &str) -> Result<()> {
+        post(&self.http, &self.webhook_url, json!({ "text": message })).await
+    }
+}
+
+/// Discord channel webhook
+#[derive(Debug, Clone)]
+pub struct DiscordNotifier {
+    webhook_url: String,
+    http: reqwest::Client,
+}
+
+impl DiscordNotifier {
+    /// Post to the channel webhook at `webhook_url`
+    pub fn new(webhook_url: impl Into<String>) -> Self {
+        Self {
+            webhook_url: webhook_url.into(),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Send messages with `client`
+    pub fn http_client(mut self, client: reqwest::Client) -> Self {
+        self.http = client;
+        self
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl Notifier for DiscordNotifier {
+    async fn notify(&self, message: &str) -> Result<()> {
+        post(&self.http, &self.webhook_url, json!({ "content": message })).await
+    }
+}
+
+/// Telegram bot sending to one chat
+#[derive(Debug, Clone)]
+pub struct TelegramNotifier {
+    bot_token: crate::config::Secret<String>,
+    chat_id: String,
+    api_url: String,
+    http: reqwest::Client,
+}
+
+impl TelegramNotifier {
+    /// Send as the bot with `bot_token` to `chat_id` (a numeric ID or `@channel`)
+    pub fn new(bot_token: impl Into<String>, chat_id: impl Into<String>) -> Self {
+        Self {
+            bot_token: crate::config::Secret::new(bot_token.into()),
+            chat_id: chat_id.into(),
+            api_url: TELEGRAM_API_URL.to_string(),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Use a different bot API endpoint, e.g. a self-hosted bot API server
+    pub fn api_url(mut self, url: impl Into<String>) -> Self {
+        self.api_url = url.into();
+        self
+    }
+
+    /// Send messages with `client`
+    pub fn http_client(mut self, client: reqwest::Client) -> Self {
+        self.http = client;
+        self
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl Notifier for TelegramNotifier {
+    async fn notify(&self, message: &str) -> Result<()> {
+        let url = format!(
+            "{}/bot{}/sendMessage",
+            self.api_url.trim_end_matches('/'),
+            self.bot_token.expose()
+        );
+        post(
+            &self.http,
+            &url,
+            json!({ "chat_id": self.chat_id, "text": message }),
+        )
+        .await
+        // The URL contains the bot token; keep it out of error messages
+        .map_err(|e| match e {
+            Error::HttpRequest(e) => Error::HttpRequest(e.without_url()),
+            e => e,
+        })
+    }
+}
+
+async fn post(http: &reqwest::Client, url: &str, body: serde_json::Value) -> Result<()> {
+    http.post(url)
+        .json(&body)
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+    use wiremock::matchers::{body_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    fn test_describe() {
+        let request = PaymentRequest::new(
+            Decimal::from(25),
+            Currency::usdt(),
+            "0x742d35cc6634c0532925a3b844bc9e7595f0beb0"
+                .parse()
+                .unwrap(),
+            12,
+        );
+        let message = describe(
+            &request,
+            &PaymentStatus::Confirmed {
+                tx_hash: "0xabc".to_string(),
+                confirmations: 12,
+            },
+        );
+        assert!(message.starts_with("Payment of 25 of token 0x"));
+        assert!(message.ends_with(" confirmed (tx 0xabc)"));
+    }
+
+    #[tokio::test]
+    async fn test_sinks() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/slack"))
+            .and(body_json(json!({ "text": "hello" })))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/discord"))
+            .and(body_json(json!({ "content": "hello" })))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/bot123:abc/sendMessage"))
+            .and(body_json(json!({ "chat_id": "@shop", "text": "hello" })))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let sinks: Vec<Box<dyn Notifier>> = vec![
+            Box::new(SlackNotifier::new(format!("{}/slack", server.uri()))),
+            Box::new(DiscordNotifier::new(format!("{}/discord", server.uri()))),
+            Box::new(TelegramNotifier::new("123:abc", "@shop").api_url(server.uri())),
+        ];
+        for sink in &sinks {
+            sink.notify("hello").await.unwrap();
+        }
+
+        let error = TelegramNotifier::new("123:secret", "@shop")
+            .api_url(server.uri())
+            .notify("hello")
+            .await
+            .unwrap_err();
+        assert!(!error.to_string().contains("secret"));
+    }
+}
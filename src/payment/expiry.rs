@@ -0,0 +1,265 @@
+//! Expiry of stored payments that were never paid
+//!
+//! Without a sweeper, a payment only expires when something checks it, e.g.
+//! a running [`Invoice::monitor`](crate::invoice::Invoice::monitor).
+//! [`ExpirySweeper`] scans the storage periodically, marks overdue payments
+//! as [`PaymentStatus::Expired`], releases what was allocated to them, and
+//! broadcasts the change like a [`MonitorPool`] does.
+
+use crate::checkout::AddressPool;
+use crate::clock::{Clock, SystemClock};
+use crate::error::{Error, Result};
+use crate::events::EventBus;
+use crate::payment::models::{Payment, PaymentStatus};
+use crate::payment::pool::{publish_update, MonitorPool, PaymentUpdate};
+use crate::storage::PaymentStorage;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+/// Capacity of the update channel of a standalone sweeper
+const UPDATE_CAPACITY: usize = 256;
+
+type ExpiredHook = Arc<dyn Fn(&Payment) + Send + Sync>;
+
+/// Marks overdue payments as expired
+///
+/// A payment is overdue once its request's `timeout_seconds` have passed
+/// since creation while it is still pending or only partially paid. Payments
+/// with a detected transaction are left to their monitor.
+///
+/// # Example
+/// ```no_run
+/// # use cryptopay::*;
+/// # use cryptopay::checkout::AddressPool;
+/// # use cryptopay::payment::{ExpirySweeper, MonitorPool};
+/// # use std::sync::Arc;
+/// # use std::time::Duration;
+/// # async fn example(pool: Arc<MonitorPool>, addresses: Arc<AddressPool>) {
+/// // Expiries are broadcast to the pool's subscribers, e.g. webhook delivery
+/// let sweeper = ExpirySweeper::for_pool(&pool).release_addresses(addresses);
+/// sweeper.run(pool.storage().as_ref(), Duration::from_secs(60)).await;
+/// # }
+/// ```
+pub struct ExpirySweeper {
+    clock: Arc<dyn Clock>,
+    updates: broadcast::Sender<PaymentUpdate>,
+    events: Option<EventBus>,
+    hooks: Vec<ExpiredHook>,
+}
+
+impl Default for ExpirySweeper {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ExpirySweeper {
+    /// Create a sweeper with its own update channel, see [`subscribe`](Self::subscribe)
+    pub fn new() -> Self {
+        let (updates, _) = broadcast::channel(UPDATE_CAPACITY);
+        Self {
+            clock: Arc::new(SystemClock),
+            updates,
+            events: None,
+            hooks: Vec::new(),
+        }
+    }
+
+    /// Create a sweeper for the payments of `pool`
+    ///
+    /// Expiries are broadcast to the pool's subscribers and event bus, the
+    /// pool stops monitoring expired payments, and the pool monitor's clock
+    /// is used.
+    pub fn for_pool(pool: &Arc<MonitorPool>) -> Self {
+        let weak = Arc::downgrade(pool);
+        Self {
+            clock: pool.clock().clone(),
+            updates: pool.update_sender(),
+            events: pool.event_bus().cloned(),
+            hooks: Vec::new(),
+        }
+        .on_expired(move |payment| {
+            if let Some(pool) = weak.upgrade() {
+                pool.cancel(payment.id);
+            }
+        })
+    }
+
+    /// Use `clock` for the current time and between sweeps
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Return the deposit addresses of expired payments to `addresses`
+    pub fn release_addresses(self, addresses: Arc<AddressPool>) -> Self {
+        self.on_expired(move |payment| {
+            addresses.release(payment.request.recipient_address.to_string());
+        })
+    }
+
+    /// Call `hook` with every payment after it was marked expired, e.g. to
+    /// release amounts or addresses reserved for it
+    pub fn on_expired<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&Payment) + Send + Sync + 'static,
+    {
+        self.hooks.push(Arc::new(hook));
+        self
+    }
+
+    /// Subscribe to expiries
+    pub fn subscribe(&self) -> broadcast::Receiver<PaymentUpdate> {
+        self.updates.subscribe()
+    }
+
+    /// Expire every overdue payment in `storage` once
+    ///
+    /// Returns the payments marked expired. A payment finalized by its
+    /// monitor in the meantime is skipped.
+    pub async fn sweep<S: PaymentStorage + ?Sized>(&self, storage: &S) -> Result<Vec<Payment>> {
+        let now = self.clock.now();
+        let mut expired = Vec::new();
+
+        for mut payment in storage.list_pending().await? {
+            let open = matches!(
+                payment.status,
+                PaymentStatus::Pending | PaymentStatus::PartiallyPaid { .. }
+            );
+            if !open || !payment.is_expired_at(now) {
+                continue;
+            }
+
+            match storage
+                .update_status(payment.id, PaymentStatus::Expired)
+                .await
+            {
+                Ok(transition) => {
+                    if let Some(events) = &self.events {
+                        events.publish(transition.into());
+                    }
+                }
+                Err(Error::InvalidTransition(_)) => continue,
+                Err(e) => return Err(e),
+            }
+            payment.update_status_at(PaymentStatus::Expired, now);
+
+            for hook in &self.hooks {
+                hook(&payment);
+            }
+            let update = PaymentUpdate {
+                payment_id: payment.id,
+                status: PaymentStatus::Expired,
+                redelivery: false,
+            };
+            publish_update(self.events.as_ref(), &update);
+            // Nobody listening is not an error; the update is simply not delivered
+            let _ = self.updates.send(update);
+            expired.push(payment);
+        }
+
+        Ok(expired)
+    }
+
+    /// Sweep every `interval` until the future is dropped
+    ///
+    /// Failed sweeps are logged and retried at the next interval.
+    pub async fn run<S: PaymentStorage + ?Sized>(&self, storage: &S, interval: Duration) {
+        loop {
+            match self.sweep(storage).await {
+                Ok(expired) if !expired.is_empty() => {
+                    tracing::info!(expired = expired.len(), "expired overdue payments");
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!(error = %e, "expiry sweep failed"),
+            }
+            self.clock.sleep(interval).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkout::DepositAddressSource;
+    use crate::clock::MockClock;
+    use crate::payment::PaymentRequest;
+    use crate::storage::MemoryStorage;
+    use rust_decimal::Decimal;
+
+    const DEPOSIT: &str = "0x742d35cc6634c0532925a3b844bc9e7595f0beb0";
+
+    #[tokio::test]
+    async fn test_sweep_expires_overdue_payments() {
+        let clock = MockClock::new("2024-01-01T00:00:00Z".parse().unwrap());
+        let addresses = Arc::new(AddressPool::new([DEPOSIT]));
+        let deposit = addresses.next_address().await.unwrap();
+
+        let storage = MemoryStorage::new();
+        let request = PaymentRequest::eth(Decimal::ONE, deposit.parse().unwrap(), 12);
+        let overdue = Payment::new_at(request.clone().with_timeout(60), clock.now());
+        let open = Payment::new_at(request.with_timeout(3600), clock.now());
+        storage.save_payment(&overdue).await.unwrap();
+        storage.save_payment(&open).await.unwrap();
+
+        let sweeper = ExpirySweeper::new()
+            .clock(Arc::new(clock.clone()))
+            .release_addresses(addresses.clone());
+        let mut updates = sweeper.subscribe();
+
+        assert!(sweeper.sweep(&storage).await.unwrap().is_empty());
+        clock.advance(Duration::from_secs(61));
+        let expired = sweeper.sweep(&storage).await.unwrap();
+
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].id, overdue.id);
+        let stored = storage.get_payment(overdue.id).await.unwrap().unwrap();
+        assert_eq!(stored.status, PaymentStatus::Expired);
+        assert_eq!(addresses.remaining(), 1);
+        assert_eq!(updates.recv().await.unwrap().payment_id, overdue.id);
+        assert!(sweeper.sweep(&storage).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_sweep_publishes_transitions() {
+        use crate::client::BscScanClient;
+        use crate::events::Event;
+        use crate::payment::PaymentMonitor;
+
+        let monitor = PaymentMonitor::new(
+            BscScanClient::new("test-key").unwrap(),
+            Duration::from_secs(60),
+        );
+        let storage = Arc::new(MemoryStorage::new());
+        let events = EventBus::new();
+        let pool = Arc::new(MonitorPool::new(monitor, storage.clone()).events(events.clone()));
+        let mut events = events.subscribe();
+
+        let request = PaymentRequest::eth(Decimal::ONE, DEPOSIT.parse().unwrap(), 12);
+        let payment = Payment::new_at(request.with_timeout(60), chrono::DateTime::UNIX_EPOCH);
+        storage.save_payment(&payment).await.unwrap();
+        ExpirySweeper::for_pool(&pool)
+            .sweep(storage.as_ref())
+            .await
+            .unwrap();
+
+        match events.recv().await.unwrap() {
+            Event::StatusTransition {
+                payment_id,
+                from,
+                to,
+                ..
+            } => {
+                assert_eq!(payment_id, payment.id);
+                assert_eq!(from, PaymentStatus::Pending);
+                assert_eq!(to, PaymentStatus::Expired);
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+        assert!(matches!(
+            events.recv().await.unwrap(),
+            Event::StatusChanged { .. }
+        ));
+    }
+}
@@ -1,11 +1,32 @@
 //! Payment processing module
 
+#[cfg(not(target_arch = "wasm32"))]
+pub mod expiry;
 pub mod models;
 pub mod monitor;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod pool;
+#[cfg(feature = "qrcode")]
+pub mod qr;
+pub mod refund;
+pub mod simulation;
+mod uri;
 pub mod utils;
 pub mod verification;
 
-pub use models::{Currency, Payment, PaymentRequest, PaymentStatus};
-pub use monitor::PaymentMonitor;
+#[cfg(not(target_arch = "wasm32"))]
+pub use expiry::ExpirySweeper;
+pub use models::{
+    AmountTolerance, Currency, InvalidTransition, Metadata, Payment, PaymentOption, PaymentRequest,
+    PaymentStatus, StatusTransition, PAYMENT_SCHEMA_VERSION,
+};
+pub use monitor::{CallbackErrorPolicy, PaymentMonitor};
+#[cfg(not(target_arch = "wasm32"))]
+pub use pool::{MonitorPool, MonitoredPaymentState, PaymentUpdate};
+pub use refund::{Refund, RefundStatus, RefundTracker};
+pub use simulation::{SimulatedMonitor, SimulatedVerifier, SimulationSchedule};
 pub use utils::*;
-pub use verification::{PaymentVerifier, VerificationResult};
+pub use verification::{
+    DroppedTransaction, MatchDecision, Matcher, PaymentDetails, PaymentVerifier, TransferRef,
+    TxInclusion, VerificationResult,
+};
@@ -1,38 +1,52 @@
 //! Payment models and types
 
+use crate::chain::{Chain, RiskLevel, StablecoinRegistry};
+use crate::error::{Error, Result};
+use crate::payment::utils::{amount_sufficient, raw_to_token};
+use crate::primitives::Address;
 use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 /// Payment currency type
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub enum Currency {
-    /// Native ETH
-    ETH,
+    /// The chain's native currency (ETH, BNB, POL, ...)
+    #[serde(alias = "ETH")]
+    Native,
     /// ERC20 token
     ERC20 {
         /// Token contract address
-        contract_address: String,
+        contract_address: Address,
         /// Token decimals
         decimals: u8,
     },
 }
 
 impl Currency {
+    /// Native currency
+    #[deprecated(note = "use `Currency::Native`")]
+    #[allow(non_upper_case_globals)]
+    pub const ETH: Currency = Currency::Native;
+
     /// Create an ERC20 currency
-    pub fn erc20(contract_address: impl Into<String>, decimals: u8) -> Self {
+    pub fn erc20(contract_address: Address, decimals: u8) -> Self {
         Self::ERC20 {
-            contract_address: contract_address.into(),
+            contract_address,
             decimals,
         }
     }
 
     /// Common stablecoins on Ethereum
+    ///
+    /// Use [`usdt_on`](Self::usdt_on) and friends for other chains.
     pub fn usdt() -> Self {
         // Ethereum USDT contract
         Self::ERC20 {
-            contract_address: "0xdAC17F958D2ee523a2206206994597C13D831ec7".to_string(),
+            contract_address: Address::known("0xdAC17F958D2ee523a2206206994597C13D831ec7"),
             decimals: 6, // USDT has 6 decimals on Ethereum
         }
     }
@@ -40,7 +54,7 @@ impl Currency {
     pub fn usdc() -> Self {
         // Ethereum USDC contract
         Self::ERC20 {
-            contract_address: "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48".to_string(),
+            contract_address: Address::known("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"),
             decimals: 6, // USDC has 6 decimals
         }
     }
@@ -48,14 +62,111 @@ impl Currency {
     pub fn dai() -> Self {
         // Ethereum DAI contract
         Self::ERC20 {
-            contract_address: "0x6B175474E89094C44Da98b954EedeAC495271d0F".to_string(),
+            contract_address: Address::known("0x6B175474E89094C44Da98b954EedeAC495271d0F"),
             decimals: 18,
         }
     }
+
+    /// USDT on the given chain, if registered
+    pub fn usdt_on(chain: Chain) -> Option<Self> {
+        StablecoinRegistry::builtin().get(chain, "USDT")
+    }
+
+    /// USDC on the given chain, if registered
+    pub fn usdc_on(chain: Chain) -> Option<Self> {
+        StablecoinRegistry::builtin().get(chain, "USDC")
+    }
+
+    /// DAI on the given chain, if registered
+    pub fn dai_on(chain: Chain) -> Option<Self> {
+        StablecoinRegistry::builtin().get(chain, "DAI")
+    }
+
+    /// Check that the currency can be paid on `chain_id`
+    ///
+    /// See [`StablecoinRegistry::validate`].
+    pub fn validate_chain(&self, chain_id: u64) -> Result<()> {
+        StablecoinRegistry::builtin().validate(self, chain_id)
+    }
+
+    /// Ticker symbol on the given chain
+    ///
+    /// The native currency's symbol comes from the chain; tokens are looked up
+    /// in the built-in [`StablecoinRegistry`].
+    pub fn symbol(&self, chain: Chain) -> Option<String> {
+        match self {
+            Currency::Native => Some(chain.native_symbol().to_string()),
+            Currency::ERC20 {
+                contract_address, ..
+            } => StablecoinRegistry::builtin()
+                .entries()
+                .iter()
+                .find(|entry| entry.chain == chain && entry.contract_address == *contract_address)
+                .map(|entry| entry.symbol.clone()),
+        }
+    }
+
+    /// Number of decimals of the currency's smallest unit
+    pub fn decimals(&self) -> u8 {
+        match self {
+            Currency::Native => 18,
+            Currency::ERC20 { decimals, .. } => *decimals,
+        }
+    }
+}
+
+/// One acceptable way to pay a request: an amount in a given currency
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct PaymentOption {
+    /// Payment amount (in token/ETH units, not wei)
+    pub amount: Decimal,
+    /// Currency type
+    pub currency: Currency,
+}
+
+/// How far the received amount may deviate from the requested amount
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum AmountTolerance {
+    /// Received amount must equal the requested amount exactly
+    ///
+    /// Useful when invoices are told apart by dust-level amount differences.
+    Exact,
+
+    /// Received amount must be at least this percentage of the requested amount
+    MinPercent(Decimal),
+
+    /// Received amount may fall short by at most this many base units
+    /// (wei for ETH, raw units for tokens)
+    AbsoluteWei(u128),
+}
+
+impl Default for AmountTolerance {
+    /// Accept payments of at least 99.9% of the requested amount
+    fn default() -> Self {
+        Self::MinPercent(Decimal::new(999, 1))
+    }
+}
+
+impl AmountTolerance {
+    /// Check whether `actual` satisfies `expected` under this tolerance
+    ///
+    /// `decimals` is the currency's decimals, used to scale `AbsoluteWei`.
+    pub fn accepts(&self, expected: Decimal, actual: Decimal, decimals: u8) -> bool {
+        match self {
+            AmountTolerance::Exact => actual == expected,
+            AmountTolerance::MinPercent(percent) => amount_sufficient(expected, actual, *percent),
+            AmountTolerance::AbsoluteWei(max_shortfall) => {
+                actual >= expected - raw_to_token(*max_shortfall, decimals)
+            }
+        }
+    }
 }
 
 /// Payment request
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct PaymentRequest {
     /// Payment amount (in token/ETH units, not wei)
     pub amount: Decimal,
@@ -64,46 +175,94 @@ pub struct PaymentRequest {
     pub currency: Currency,
 
     /// Recipient address (where payment should be sent)
-    pub recipient_address: String,
+    pub recipient_address: Address,
 
     /// Required number of confirmations
     pub required_confirmations: u64,
 
     /// Optional timeout in seconds (payment expires if not confirmed)
     pub timeout_seconds: Option<u64>,
+
+    /// Accepted deviation of the received amount (default: at least 99.9%)
+    #[serde(default)]
+    pub amount_tolerance: AmountTolerance,
+
+    /// Ignore transfers mined before this time (e.g. the invoice creation time)
+    #[serde(default)]
+    pub not_before: Option<DateTime<Utc>>,
+
+    /// Accept the requested amount split across several transfers
+    #[serde(default)]
+    pub accept_partial: bool,
+
+    /// Other (amount, currency) pairs accepted instead of `amount`/`currency`
+    #[serde(default)]
+    pub alternatives: Vec<PaymentOption>,
+
+    /// If non-empty, only transfers from these addresses count as payment
+    #[serde(default)]
+    pub allowed_senders: Vec<Address>,
+
+    /// Transfers from these addresses never count as payment
+    #[serde(default)]
+    pub blocked_senders: Vec<Address>,
 }
 
 impl PaymentRequest {
-    /// Create a new ETH payment request
-    pub fn eth(
+    /// Create a new payment request for any currency
+    pub fn new(
         amount: Decimal,
-        recipient_address: impl Into<String>,
+        currency: Currency,
+        recipient_address: Address,
         required_confirmations: u64,
     ) -> Self {
         Self {
             amount,
-            currency: Currency::ETH,
-            recipient_address: recipient_address.into(),
+            currency,
+            recipient_address,
             required_confirmations,
             timeout_seconds: None,
+            amount_tolerance: AmountTolerance::default(),
+            not_before: None,
+            accept_partial: false,
+            alternatives: Vec::new(),
+            allowed_senders: Vec::new(),
+            blocked_senders: Vec::new(),
         }
     }
 
+    /// Create a new ETH payment request
+    pub fn eth(amount: Decimal, recipient_address: Address, required_confirmations: u64) -> Self {
+        Self::new(
+            amount,
+            Currency::Native,
+            recipient_address,
+            required_confirmations,
+        )
+    }
+
     /// Create a new ERC20 token payment request
     pub fn token(
         amount: Decimal,
-        contract_address: impl Into<String>,
+        contract_address: Address,
         decimals: u8,
-        recipient_address: impl Into<String>,
+        recipient_address: Address,
         required_confirmations: u64,
     ) -> Self {
-        Self {
+        Self::new(
             amount,
-            currency: Currency::erc20(contract_address, decimals),
-            recipient_address: recipient_address.into(),
+            Currency::erc20(contract_address, decimals),
+            recipient_address,
             required_confirmations,
-            timeout_seconds: None,
-        }
+        )
+    }
+
+    /// Set the required confirmations to the chain's recommendation for `risk`
+    ///
+    /// See [`Chain::recommended_confirmations`].
+    pub fn with_recommended_confirmations(mut self, chain: Chain, risk: RiskLevel) -> Self {
+        self.required_confirmations = chain.recommended_confirmations(risk);
+        self
     }
 
     /// Set timeout for the payment
@@ -112,10 +271,104 @@ impl PaymentRequest {
         self
     }
 
+    /// Set the accepted amount tolerance
+    pub fn with_amount_tolerance(mut self, tolerance: AmountTolerance) -> Self {
+        self.amount_tolerance = tolerance;
+        self
+    }
+
+    /// Ignore transfers mined before the given time
+    pub fn with_not_before(mut self, not_before: DateTime<Utc>) -> Self {
+        self.not_before = Some(not_before);
+        self
+    }
+
+    /// Accept the amount split across several transfers made since `not_before`
+    ///
+    /// All inbound transfers to the recipient after `not_before` are summed,
+    /// so the recipient address should be dedicated to this request.
+    pub fn with_partial_payments(mut self, not_before: DateTime<Utc>) -> Self {
+        self.not_before = Some(not_before);
+        self.accept_partial = true;
+        self
+    }
+
+    /// Also accept payment of `amount` in `currency`
+    ///
+    /// For example, a request for 0.05 ETH can additionally accept 100 USDT
+    /// or 100 USDC; the verifier reports which currency arrived.
+    pub fn with_alternative(mut self, amount: Decimal, currency: Currency) -> Self {
+        self.alternatives.push(PaymentOption { amount, currency });
+        self
+    }
+
+    /// Only accept payments sent from these addresses
+    pub fn with_allowed_senders<I>(mut self, senders: I) -> Self
+    where
+        I: IntoIterator<Item = Address>,
+    {
+        self.allowed_senders = senders.into_iter().collect();
+        self
+    }
+
+    /// Reject payments sent from these addresses
+    pub fn with_blocked_senders<I>(mut self, senders: I) -> Self
+    where
+        I: IntoIterator<Item = Address>,
+    {
+        self.blocked_senders = senders.into_iter().collect();
+        self
+    }
+
+    /// Check whether a transfer from `sender` may pay this request
+    pub fn is_sender_allowed(&self, sender: &Address) -> bool {
+        let listed = |list: &[Address]| list.contains(sender);
+
+        if listed(&self.blocked_senders) {
+            return false;
+        }
+        self.allowed_senders.is_empty() || listed(&self.allowed_senders)
+    }
+
+    /// All accepted payment options, primary option first
+    pub fn options(&self) -> Vec<PaymentOption> {
+        let primary = PaymentOption {
+            amount: self.amount,
+            currency: self.currency.clone(),
+        };
+        std::iter::once(primary)
+            .chain(self.alternatives.iter().cloned())
+            .collect()
+    }
+
+    /// Single-currency request for each accepted option, primary option first
+    pub fn option_requests(&self) -> Vec<PaymentRequest> {
+        self.options()
+            .into_iter()
+            .map(|option| PaymentRequest {
+                amount: option.amount,
+                currency: option.currency,
+                alternatives: Vec::new(),
+                ..self.clone()
+            })
+            .collect()
+    }
+
+    /// Check whether a received amount satisfies this request
+    pub fn accepts_amount(&self, actual: Decimal) -> bool {
+        self.amount_tolerance
+            .accepts(self.amount, actual, self.currency.decimals())
+    }
+
     /// Check if payment has expired
     pub fn is_expired(&self, created_at: DateTime<Utc>) -> bool {
+        self.is_expired_at(created_at, Utc::now())
+    }
+
+    /// Check if payment created at `created_at` has expired by `now`
+    pub fn is_expired_at(&self, created_at: DateTime<Utc>, now: DateTime<Utc>) -> bool {
         if let Some(timeout) = self.timeout_seconds {
-            let elapsed = Utc::now().signed_duration_since(created_at);
+            let elapsed = now.signed_duration_since(created_at);
             elapsed.num_seconds() as u64 >= timeout
         } else {
             false
@@ -125,6 +378,7 @@ impl PaymentRequest {
 
 /// Payment status
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub enum PaymentStatus {
     /// Payment is pending (no transaction found yet)
     Pending,
@@ -145,6 +399,36 @@ pub enum PaymentStatus {
         confirmations: u64,
     },
 
+    /// Part of the amount received across one or more transfers
+    PartiallyPaid {
+        /// Total amount received so far
+        received: Decimal,
+        /// Amount still missing
+        remaining: Decimal,
+        /// Hashes of the transfers counted towards the total
+        tx_hashes: Vec<String>,
+    },
+
+    /// Previously confirmed transaction was reorganized out of its block
+    ///
+    /// Monitoring resumes after this status; the payment may be detected
+    /// again once the transaction is re-included.
+    Reorged {
+        /// Transaction hash that was reorganized
+        tx_hash: String,
+    },
+
+    /// Detected transaction was dropped, and another transaction pays the
+    /// request instead
+    ///
+    /// Monitoring continues with the replacement, whose status follows.
+    Replaced {
+        /// Hash of the dropped transaction
+        tx_hash: String,
+        /// Hash of the transaction replacing it
+        replacement: String,
+    },
+
     /// Payment failed
     Failed {
         /// Failure reason
@@ -160,9 +444,7 @@ impl PaymentStatus {
     pub fn is_finalized(&self) -> bool {
         matches!(
             self,
-            PaymentStatus::Confirmed { .. }
-                | PaymentStatus::Failed { .. }
-                | PaymentStatus::Expired
+            PaymentStatus::Confirmed { .. } | PaymentStatus::Failed { .. } | PaymentStatus::Expired
         )
     }
 
@@ -170,10 +452,131 @@ impl PaymentStatus {
     pub fn is_successful(&self) -> bool {
         matches!(self, PaymentStatus::Confirmed { .. })
     }
+
+    /// Name of the status without its fields, e.g. `"confirmed"`
+    pub fn name(&self) -> &'static str {
+        match self {
+            PaymentStatus::Pending => "pending",
+            PaymentStatus::Detected { .. } => "detected",
+            PaymentStatus::Confirmed { .. } => "confirmed",
+            PaymentStatus::PartiallyPaid { .. } => "partially_paid",
+            PaymentStatus::Reorged { .. } => "reorged",
+            PaymentStatus::Replaced { .. } => "replaced",
+            PaymentStatus::Failed { .. } => "failed",
+            PaymentStatus::Expired => "expired",
+        }
+    }
+
+    /// Check whether a payment in this status may move to `to`
+    ///
+    /// Failed and expired payments are final, and a confirmed payment only
+    /// gains confirmations or gets reorged. Open payments (pending, detected,
+    /// partially paid, reorged, replaced) may move to any status except reorged.
+    /// Repeating the current status is always allowed.
+    pub fn can_transition_to(&self, to: &PaymentStatus) -> bool {
+        use PaymentStatus::*;
+
+        if self == to {
+            return true;
+        }
+        match (self, to) {
+            (Failed { .. } | Expired, _) => false,
+            (Confirmed { .. }, Confirmed { .. } | Reorged { .. }) => true,
+            (Confirmed { .. }, _) => false,
+            (Reorged { .. }, Reorged { .. }) => true,
+            (_, Reorged { .. }) => false,
+            _ => true,
+        }
+    }
+}
+
+/// Rejected change of a payment's status, see [`PaymentStatus::can_transition_to`]
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("{} -> {}", from.name(), to.name())]
+pub struct InvalidTransition {
+    /// Status the payment is in
+    pub from: PaymentStatus,
+    /// Rejected new status
+    pub to: PaymentStatus,
+}
+
+/// Validated change of a payment's status, returned by [`Payment::transition`]
+/// and [`PaymentStorage::update_status`](crate::storage::PaymentStorage::update_status)
+///
+/// Converts into [`Event::StatusTransition`](crate::events::Event::StatusTransition)
+/// for an [`EventBus`](crate::events::EventBus).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatusTransition {
+    /// Payment ID
+    pub payment_id: Uuid,
+    /// Previous status
+    pub from: PaymentStatus,
+    /// New status
+    pub to: PaymentStatus,
+    /// Time of the change
+    pub at: DateTime<Utc>,
+}
+
+/// Application data attached to a [`Payment`] with compile-time checked types
+///
+/// Stored as JSON in [`Payment::metadata`], so records written with raw
+/// values stay readable. Implement [`validate`](Self::validate) to check
+/// values beyond their types.
+///
+/// # Example
+/// ```
+/// use cryptopay::payment::{Metadata, Payment, PaymentRequest};
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Order {
+///     order_id: u64,
+///     customer_id: String,
+/// }
+///
+/// impl Metadata for Order {
+///     fn validate(&self) -> Result<(), String> {
+///         if self.customer_id.is_empty() {
+///             return Err("customer_id is empty".to_string());
+///         }
+///         Ok(())
+///     }
+/// }
+///
+/// # let request = PaymentRequest::eth(
+/// #     "0.1".parse().unwrap(),
+/// #     "0x742d35cc6634c0532925a3b844bc9e7595f0beb0".parse().unwrap(),
+/// #     12,
+/// # );
+/// let order = Order { order_id: 42, customer_id: "c-7".to_string() };
+/// let payment = Payment::new(request).with_typed_metadata(&order).unwrap();
+///
+/// let order: Order = payment.typed_metadata().unwrap();
+/// assert_eq!(order.order_id, 42);
+/// ```
+pub trait Metadata: Serialize + DeserializeOwned {
+    /// Check the values beyond their types, e.g. IDs that must not be empty
+    fn validate(&self) -> std::result::Result<(), String> {
+        Ok(())
+    }
 }
 
+impl Metadata for serde_json::Value {}
+
+/// Schema version of [`Payment`] records written by this version of the crate
+pub const PAYMENT_SCHEMA_VERSION: u32 = 1;
+
 /// Complete payment record
+///
+/// Records are versioned by `schema_version` and upgraded to
+/// [`PAYMENT_SCHEMA_VERSION`] when deserialized, so rows written by older
+/// versions of the crate stay readable. Records without a version are
+/// version 0. Records written by a newer version of the crate fail to
+/// deserialize, as saving them again would drop the fields this version
+/// doesn't know.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(try_from = "serde_json::Value")]
 pub struct Payment {
     /// Unique payment ID
     pub id: Uuid,
@@ -193,12 +596,85 @@ pub struct Payment {
     /// Optional metadata (for user's custom data)
     #[serde(default)]
     pub metadata: serde_json::Value,
+
+    /// Version of the record's schema, see [`PAYMENT_SCHEMA_VERSION`]
+    pub schema_version: u32,
+}
+
+/// Fields of a [`Payment`] record in the current schema
+#[derive(Deserialize)]
+struct PaymentRecord {
+    id: Uuid,
+    request: PaymentRequest,
+    status: PaymentStatus,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+    #[serde(default)]
+    metadata: serde_json::Value,
+}
+
+impl TryFrom<serde_json::Value> for Payment {
+    type Error = serde_json::Error;
+
+    fn try_from(record: serde_json::Value) -> std::result::Result<Self, Self::Error> {
+        let version = schema_version(&record);
+        if version > u64::from(PAYMENT_SCHEMA_VERSION) {
+            return Err(serde::de::Error::custom(format!(
+                "payment record has schema version {}, newer than the supported {}",
+                version, PAYMENT_SCHEMA_VERSION
+            )));
+        }
+
+        let record: PaymentRecord = serde_json::from_value(upgrade_payment(record))?;
+        Ok(Self {
+            id: record.id,
+            request: record.request,
+            status: record.status,
+            created_at: record.created_at,
+            updated_at: record.updated_at,
+            metadata: record.metadata,
+            schema_version: PAYMENT_SCHEMA_VERSION,
+        })
+    }
+}
+
+/// Schema version of a serialized payment record (0 if missing)
+fn schema_version(record: &serde_json::Value) -> u64 {
+    record
+        .get("schema_version")
+        .and_then(|version| version.as_u64())
+        .unwrap_or(0)
+}
+
+/// Upgrade a serialized payment record to [`PAYMENT_SCHEMA_VERSION`]
+///
+/// Each step migrates one version to the next.
+fn upgrade_payment(mut record: serde_json::Value) -> serde_json::Value {
+    let version = schema_version(&record);
+
+    if version < 1 {
+        // Version 1 renamed the native currency from "ETH" to "Native"
+        if let Some(currency) = record.pointer_mut("/request/currency") {
+            if currency == "ETH" {
+                *currency = serde_json::Value::from("Native");
+            }
+        }
+    }
+
+    if let Some(fields) = record.as_object_mut() {
+        fields.insert("schema_version".to_string(), PAYMENT_SCHEMA_VERSION.into());
+    }
+    record
 }
 
 impl Payment {
     /// Create a new payment
     pub fn new(request: PaymentRequest) -> Self {
-        let now = Utc::now();
+        Self::new_at(request, Utc::now())
+    }
+
+    /// Create a new payment as of `now`, e.g. from a [`Clock`](crate::clock::Clock)
+    pub fn new_at(request: PaymentRequest, now: DateTime<Utc>) -> Self {
         Self {
             id: Uuid::new_v4(),
             request,
@@ -206,13 +682,62 @@ impl Payment {
             created_at: now,
             updated_at: now,
             metadata: serde_json::Value::Null,
+            schema_version: PAYMENT_SCHEMA_VERSION,
         }
     }
 
-    /// Update payment status
+    /// Update payment status, forcing it without checking the transition
+    ///
+    /// Use [`transition`](Self::transition) to reject illegal moves.
     pub fn update_status(&mut self, status: PaymentStatus) {
+        self.update_status_at(status, Utc::now());
+    }
+
+    /// Update payment status as of `now`, forcing it without checking the transition
+    pub fn update_status_at(&mut self, status: PaymentStatus, now: DateTime<Utc>) {
         self.status = status;
-        self.updated_at = Utc::now();
+        self.updated_at = now;
+    }
+
+    /// Move the payment to `to` if [`PaymentStatus::can_transition_to`] allows it
+    ///
+    /// E.g. an expired payment can't become confirmed; a late monitor task
+    /// reporting it fails here instead of overwriting the stored status.
+    pub fn transition(
+        &mut self,
+        to: PaymentStatus,
+    ) -> std::result::Result<StatusTransition, InvalidTransition> {
+        self.transition_at(to, Utc::now())
+    }
+
+    /// Move the payment to `to` as of `now`, see [`transition`](Self::transition)
+    pub fn transition_at(
+        &mut self,
+        to: PaymentStatus,
+        now: DateTime<Utc>,
+    ) -> std::result::Result<StatusTransition, InvalidTransition> {
+        if !self.status.can_transition_to(&to) {
+            return Err(InvalidTransition {
+                from: self.status.clone(),
+                to,
+            });
+        }
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            payment_id = %self.id,
+            from = self.status.name(),
+            to = to.name(),
+            "payment status transition"
+        );
+        let from = std::mem::replace(&mut self.status, to.clone());
+        self.updated_at = now;
+        Ok(StatusTransition {
+            payment_id: self.id,
+            from,
+            to,
+            at: now,
+        })
     }
 
     /// Check if payment has expired
@@ -220,11 +745,42 @@ impl Payment {
         self.request.is_expired(self.created_at)
     }
 
+    /// Check if payment has expired by `now`
+    pub fn is_expired_at(&self, now: DateTime<Utc>) -> bool {
+        self.request.is_expired_at(self.created_at, now)
+    }
+
     /// Add custom metadata
     pub fn with_metadata(mut self, metadata: serde_json::Value) -> Self {
         self.metadata = metadata;
         self
     }
+
+    /// Add typed metadata, validating it first
+    ///
+    /// Fails with [`Error::InvalidMetadata`] if validation fails.
+    pub fn with_typed_metadata<M: Metadata>(mut self, metadata: &M) -> Result<Self> {
+        self.set_metadata(metadata)?;
+        Ok(self)
+    }
+
+    /// Replace the metadata with typed metadata, validating it first
+    pub fn set_metadata<M: Metadata>(&mut self, metadata: &M) -> Result<()> {
+        metadata.validate().map_err(Error::InvalidMetadata)?;
+        self.metadata = serde_json::to_value(metadata)?;
+        Ok(())
+    }
+
+    /// Read the metadata as `M`
+    ///
+    /// Fails with [`Error::InvalidMetadata`] if the stored JSON doesn't match
+    /// `M` or doesn't pass its validation.
+    pub fn typed_metadata<M: Metadata>(&self) -> Result<M> {
+        let metadata =
+            M::deserialize(&self.metadata).map_err(|e| Error::InvalidMetadata(e.to_string()))?;
+        metadata.validate().map_err(Error::InvalidMetadata)?;
+        Ok(metadata)
+    }
 }
 
 #[cfg(test)]
@@ -232,25 +788,43 @@ mod tests {
     use super::*;
     use std::str::FromStr;
 
+    const RECIPIENT: &str = "0x1234567890123456789012345678901234567890";
+
+    fn address(address: &str) -> Address {
+        address.parse().unwrap()
+    }
+
     #[test]
     fn test_eth_payment_request() {
-        let request = PaymentRequest::eth(
-            Decimal::from_str("0.1").unwrap(),
-            "0x1234567890123456789012345678901234567890",
-            12,
-        );
+        let request =
+            PaymentRequest::eth(Decimal::from_str("0.1").unwrap(), address(RECIPIENT), 12);
 
-        assert_eq!(request.currency, Currency::ETH);
+        assert_eq!(request.currency, Currency::Native);
         assert_eq!(request.required_confirmations, 12);
+
+        // Stored as "ETH" before the native currency was renamed
+        let stored: Currency = serde_json::from_str("\"ETH\"").unwrap();
+        assert_eq!(stored, Currency::Native);
+        #[allow(deprecated)]
+        let legacy = matches!(stored, Currency::ETH);
+        assert!(legacy);
+
+        assert_eq!(Currency::Native.symbol(Chain::Bsc).as_deref(), Some("BNB"));
+        assert_eq!(
+            Currency::usdc().symbol(Chain::Ethereum).as_deref(),
+            Some("USDC")
+        );
+        assert_eq!(Currency::usdc().symbol(Chain::Base), None);
     }
 
     #[test]
     fn test_token_payment_request() {
+        let contract = address("0x6B175474E89094C44Da98b954EedeAC495271d0F");
         let request = PaymentRequest::token(
             Decimal::from(100),
-            "0xcontract",
+            contract.clone(),
             18,
-            "0x1234567890123456789012345678901234567890",
+            address(RECIPIENT),
             6,
         );
 
@@ -259,22 +833,195 @@ mod tests {
                 ref contract_address,
                 decimals,
             } => {
-                assert_eq!(contract_address, "0xcontract");
+                assert_eq!(*contract_address, contract);
                 assert_eq!(decimals, 18);
             }
             _ => panic!("Expected ERC20 currency"),
         }
     }
 
+    #[test]
+    fn test_amount_tolerance() {
+        let amount = Decimal::from_str("0.1").unwrap();
+        let request = PaymentRequest::eth(amount, address(RECIPIENT), 12);
+        assert!(request.accepts_amount(Decimal::from_str("0.0999").unwrap()));
+        assert!(!request.accepts_amount(Decimal::from_str("0.0998").unwrap()));
+
+        let exact = request
+            .clone()
+            .with_amount_tolerance(AmountTolerance::Exact);
+        assert!(exact.accepts_amount(amount));
+        assert!(!exact.accepts_amount(Decimal::from_str("0.100000000000000001").unwrap()));
+
+        // 1000 wei short is allowed, 1001 is not
+        let absolute = request.with_amount_tolerance(AmountTolerance::AbsoluteWei(1000));
+        assert!(absolute.accepts_amount(Decimal::from_str("0.099999999999999000").unwrap()));
+        assert!(!absolute.accepts_amount(Decimal::from_str("0.099999999999998999").unwrap()));
+    }
+
+    #[test]
+    fn test_alternative_options() {
+        let request = PaymentRequest::eth(Decimal::new(5, 2), address(RECIPIENT), 12)
+            .with_alternative(Decimal::from(100), Currency::usdt())
+            .with_alternative(Decimal::from(100), Currency::usdc());
+
+        let requests = request.option_requests();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[0].currency, Currency::Native);
+        assert_eq!(requests[1].currency, Currency::usdt());
+        assert_eq!(requests[2].amount, Decimal::from(100));
+        assert!(requests.iter().all(|r| r.alternatives.is_empty()));
+    }
+
+    #[test]
+    fn test_sender_constraints() {
+        const CUSTOMER: &str = "0x00000000000000000000000000000000000000c1";
+        const PARTNER: &str = "0x00000000000000000000000000000000000000b2";
+        const ANYONE: &str = "0x00000000000000000000000000000000000000a3";
+
+        let request = PaymentRequest::eth(Decimal::from(1), address(RECIPIENT), 12);
+        assert!(request.is_sender_allowed(&address(ANYONE)));
+
+        let request = request
+            .with_allowed_senders([CUSTOMER, PARTNER].map(address))
+            .with_blocked_senders([address(PARTNER)]);
+        assert!(request.is_sender_allowed(&address(&CUSTOMER.to_uppercase().replace("0X", "0x"))));
+        assert!(!request.is_sender_allowed(&address(PARTNER)));
+        assert!(!request.is_sender_allowed(&address(ANYONE)));
+    }
+
     #[test]
     fn test_payment_creation() {
-        let request = PaymentRequest::eth(Decimal::from(1), "0xrecipient", 12);
+        let request = PaymentRequest::eth(Decimal::from(1), address(RECIPIENT), 12);
         let payment = Payment::new(request);
 
         assert_eq!(payment.status, PaymentStatus::Pending);
         assert!(!payment.is_expired());
     }
 
+    #[test]
+    fn test_payment_transitions() {
+        let request = PaymentRequest::eth(Decimal::from(1), address(RECIPIENT), 12);
+        let mut payment = Payment::new(request);
+        let confirmed = PaymentStatus::Confirmed {
+            tx_hash: "0xhash".to_string(),
+            confirmations: 12,
+        };
+
+        payment
+            .transition(PaymentStatus::Detected {
+                tx_hash: "0xhash".to_string(),
+                confirmations: 1,
+            })
+            .unwrap();
+        let transition = payment.transition(confirmed.clone()).unwrap();
+        assert_eq!(transition.payment_id, payment.id);
+        assert_eq!(transition.from.name(), "detected");
+        assert_eq!(transition.to, confirmed);
+        assert!(payment.transition(PaymentStatus::Expired).is_err());
+        payment
+            .transition(PaymentStatus::Reorged {
+                tx_hash: "0xhash".to_string(),
+            })
+            .unwrap();
+        payment.transition(PaymentStatus::Expired).unwrap();
+
+        let error = payment.transition(confirmed.clone()).unwrap_err();
+        assert_eq!(error.to_string(), "expired -> confirmed");
+        assert_eq!(payment.status, PaymentStatus::Expired);
+
+        // Forcing still works
+        payment.update_status(confirmed.clone());
+        assert_eq!(payment.status, confirmed);
+    }
+
+    #[test]
+    fn test_typed_metadata() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Order {
+            order_id: u64,
+        }
+
+        impl Metadata for Order {
+            fn validate(&self) -> std::result::Result<(), String> {
+                match self.order_id {
+                    0 => Err("order_id must not be 0".to_string()),
+                    _ => Ok(()),
+                }
+            }
+        }
+
+        let request = PaymentRequest::eth(Decimal::from(1), address(RECIPIENT), 12);
+        let payment = Payment::new(request)
+            .with_typed_metadata(&Order { order_id: 7 })
+            .unwrap();
+        assert_eq!(payment.metadata, serde_json::json!({ "order_id": 7 }));
+        assert_eq!(
+            payment.typed_metadata::<Order>().unwrap(),
+            Order { order_id: 7 }
+        );
+
+        let raw = payment
+            .clone()
+            .with_metadata(serde_json::json!({ "order_id": 0 }));
+        assert!(matches!(
+            raw.typed_metadata::<Order>(),
+            Err(Error::InvalidMetadata(_))
+        ));
+        let raw = payment
+            .clone()
+            .with_metadata(serde_json::json!({ "order": "x" }));
+        assert!(matches!(
+            raw.typed_metadata::<Order>(),
+            Err(Error::InvalidMetadata(_))
+        ));
+        assert!(payment.with_typed_metadata(&Order { order_id: 0 }).is_err());
+    }
+
+    #[test]
+    fn test_payment_schema_upgrade() {
+        // Version 0 record: no schema_version, native currency still "ETH"
+        let stored = serde_json::json!({
+            "id": "6a1f3c1e-0b5e-4a36-9f0a-8f5e2a0f4d1b",
+            "request": {
+                "amount": "0.5",
+                "currency": "ETH",
+                "recipient_address": RECIPIENT,
+                "required_confirmations": 12
+            },
+            "status": "Pending",
+            "created_at": "2024-01-01T00:00:00Z",
+            "updated_at": "2024-01-01T00:00:00Z"
+        });
+
+        let payment: Payment = serde_json::from_value(stored).unwrap();
+        assert_eq!(payment.schema_version, PAYMENT_SCHEMA_VERSION);
+        assert_eq!(payment.request.currency, Currency::Native);
+        assert_eq!(payment.metadata, serde_json::Value::Null);
+
+        let json = serde_json::to_value(&payment).unwrap();
+        assert_eq!(json["schema_version"], PAYMENT_SCHEMA_VERSION);
+        assert_eq!(json["request"]["currency"], "Native");
+
+        // A newer crate may have added fields that would be lost on save
+        let mut newer = json;
+        newer["schema_version"] = (PAYMENT_SCHEMA_VERSION + 1).into();
+        newer["refunds"] = serde_json::json!([]);
+        let error = serde_json::from_value::<Payment>(newer).unwrap_err();
+        assert!(error.to_string().contains("newer than the supported"));
+    }
+
+    #[test]
+    fn test_payment_expiry_at() {
+        let request =
+            PaymentRequest::eth(Decimal::from(1), address(RECIPIENT), 12).with_timeout(60);
+        let created_at = DateTime::<Utc>::UNIX_EPOCH;
+        let payment = Payment::new_at(request, created_at);
+
+        assert!(!payment.is_expired_at(created_at + chrono::Duration::seconds(59)));
+        assert!(payment.is_expired_at(created_at + chrono::Duration::seconds(60)));
+    }
+
     #[test]
     fn test_payment_status_finalized() {
         let status = PaymentStatus::Pending;
@@ -286,5 +1033,11 @@ mod tests {
         };
         assert!(status.is_finalized());
         assert!(status.is_successful());
+
+        let status = PaymentStatus::Reorged {
+            tx_hash: "0xhash".to_string(),
+        };
+        assert!(!status.is_finalized());
+        assert!(!status.is_successful());
     }
 }
@@ -1,17 +1,49 @@
 //! Payment monitoring with callbacks
 
 use crate::client::BscScanClient;
-use crate::error::Result;
+use crate::clock::{Clock, SystemClock};
+use crate::error::{Error, Result};
+use crate::events::{Event, EventBus};
+use crate::notify::{describe, Notifier};
 use crate::payment::models::{PaymentRequest, PaymentStatus};
-use crate::payment::verification::{PaymentVerifier, VerificationResult};
+use crate::payment::verification::{PaymentDetails, PaymentVerifier};
+use crate::primitives::TxHash;
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::time::sleep;
+
+/// What a [`PaymentMonitor`] does when a status callback panics
+///
+/// Panics are always caught, logged, and published as
+/// [`Event::CallbackFailed`] if the monitor has an event bus.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CallbackErrorPolicy {
+    /// Keep monitoring; the status counts as reported
+    #[default]
+    Continue,
+    /// Call again up to this many times, then keep monitoring
+    Retry(u32),
+    /// Stop monitoring with [`Error::CallbackFailed`]
+    Abort,
+}
 
 /// Payment monitor with background polling
 pub struct PaymentMonitor {
     verifier: PaymentVerifier,
     poll_interval: Duration,
+    block_time: Option<Duration>,
+    finalization_depth: Option<u64>,
+    clock: Arc<dyn Clock>,
+    notifiers: Vec<Arc<dyn Notifier>>,
+    on_callback_error: CallbackErrorPolicy,
+    events: Option<EventBus>,
+}
+
+/// Confirmed transaction being watched until it reaches finalization depth
+struct FinalityWatch {
+    tx_hash: TxHash,
+    block_hash: String,
 }
 
 impl PaymentMonitor {
@@ -20,9 +52,25 @@ impl PaymentMonitor {
         Self {
             verifier: PaymentVerifier::new(client),
             poll_interval,
+            block_time: None,
+            finalization_depth: None,
+            clock: Arc::new(SystemClock),
+            notifiers: Vec::new(),
+            on_callback_error: CallbackErrorPolicy::default(),
+            events: None,
         }
     }
 
+    /// Interval between status checks
+    pub fn poll_interval(&self) -> Duration {
+        self.poll_interval
+    }
+
+    /// Clock the monitor waits on between polls
+    pub fn clock(&self) -> &Arc<dyn Clock> {
+        &self.clock
+    }
+
     /// Create a builder for PaymentMonitor
     pub fn builder() -> PaymentMonitorBuilder {
         PaymentMonitorBuilder::default()
@@ -33,6 +81,17 @@ impl PaymentMonitor {
     /// This will poll the blockchain at regular intervals and call the callback
     /// with status updates until the payment is finalized.
     ///
+    /// If a finalization depth is configured, a confirmed payment keeps being
+    /// watched until its transaction reaches that depth. Should the transaction
+    /// disappear or move to a different block in the meantime, the callback
+    /// receives [`PaymentStatus::Reorged`] and monitoring resumes.
+    ///
+    /// A detected transaction that is dropped from the mempool or replaced
+    /// without a replacement paying the request ends monitoring with
+    /// `PaymentStatus::Failed { reason: "transaction dropped/replaced" }`. If
+    /// another transaction pays the request instead, the callback receives
+    /// [`PaymentStatus::Replaced`] followed by the replacement's status.
+    ///
     /// # Example
     /// ```no_run
     /// # use cryptopay::*;
@@ -43,7 +102,7 @@ impl PaymentMonitor {
     ///
     /// let payment_request = PaymentRequest::eth(
     ///     rust_decimal::Decimal::new(1, 1), // 0.1 ETH
-    ///     "0x...",
+    ///     "0x742d35cc6634c0532925a3b844bc9e7595f0beb0".parse()?,
     ///     12,
     /// );
     ///
@@ -56,39 +115,159 @@ impl PaymentMonitor {
     pub async fn start_monitoring<F>(&self, request: PaymentRequest, callback: F) -> Result<()>
     where
         F: Fn(PaymentStatus) + Send + Sync,
+    {
+        self.start_monitoring_with_details(request, move |status, _| callback(status))
+            .await
+    }
+
+    /// Start monitoring a payment with a callback that also receives transfer details
+    ///
+    /// Behaves like [`start_monitoring`](Self::start_monitoring), but the
+    /// callback additionally gets the matched [`PaymentDetails`] whenever the
+    /// payment has been found, e.g. to tell which currency of a multi-currency
+    /// request arrived.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(recipient = %request.recipient_address, amount = %request.amount)
+        )
+    )]
+    pub async fn start_monitoring_with_details<F>(
+        &self,
+        request: PaymentRequest,
+        callback: F,
+    ) -> Result<()>
+    where
+        F: Fn(PaymentStatus, Option<&PaymentDetails>) + Send + Sync,
+    {
+        self.monitor_from(request, None, self.poll_interval, callback)
+            .await
+    }
+
+    /// Monitor a payment whose status was last reported as `last_status`,
+    /// polling every `poll_interval`
+    ///
+    /// Unchanged statuses aren't reported again, and a transaction that was
+    /// detected before is still checked for being dropped.
+    pub(crate) async fn monitor_from<F>(
+        &self,
+        request: PaymentRequest,
+        mut last_status: Option<PaymentStatus>,
+        poll_interval: Duration,
+        callback: F,
+    ) -> Result<()>
+    where
+        F: Fn(PaymentStatus, Option<&PaymentDetails>) + Send + Sync,
     {
         let callback = Arc::new(callback);
-        let mut last_status: Option<PaymentStatus> = None;
+        let mut finality: Option<FinalityWatch> = None;
 
         loop {
+            // Re-check a confirmed transaction until it is deep enough to be final
+            if let (Some(watch), Some(depth)) = (&finality, self.finalization_depth) {
+                let mut delay = poll_interval;
+                match self.verifier.get_inclusion(&watch.tx_hash).await? {
+                    Some(inclusion) if inclusion.block_hash == watch.block_hash => {
+                        if inclusion.confirmations >= depth {
+                            break;
+                        }
+                        delay =
+                            self.confirmation_delay(poll_interval, depth - inclusion.confirmations);
+                    }
+                    _ => {
+                        let status = PaymentStatus::Reorged {
+                            tx_hash: watch.tx_hash.to_string(),
+                        };
+                        #[cfg(feature = "tracing")]
+                        tracing::debug!(tx_hash = %watch.tx_hash, "confirmed payment reorged");
+                        self.call(&*callback, &request, &status, None)?;
+                        self.notify(&request, &status).await;
+                        last_status = Some(status);
+                        finality = None;
+                    }
+                }
+
+                self.clock.sleep(delay).await;
+                continue;
+            }
+
             // Check payment status
             let result = self.verifier.verify_payment(&request).await?;
+            let details = result.details().cloned();
 
-            let current_status = match result {
-                VerificationResult::NotFound => PaymentStatus::Pending,
-                VerificationResult::Pending {
-                    tx_hash,
-                    confirmations,
-                } => PaymentStatus::Detected {
-                    tx_hash,
-                    confirmations,
-                },
-                VerificationResult::Confirmed {
-                    tx_hash,
-                    confirmations,
-                } => PaymentStatus::Confirmed {
-                    tx_hash,
-                    confirmations,
-                },
-                VerificationResult::Failed { reason } => PaymentStatus::Failed { reason },
-            };
+            let mut current_status = PaymentStatus::from(result.clone());
+
+            // A detected transaction that is no longer reported may have been dropped
+            if let Some(PaymentStatus::Detected {
+                tx_hash: previous, ..
+            }) = &last_status
+            {
+                let still_reported = matches!(
+                    &current_status,
+                    PaymentStatus::Detected { tx_hash, .. } | PaymentStatus::Confirmed { tx_hash, .. }
+                        if tx_hash == previous
+                );
+
+                if !still_reported {
+                    let previous = TxHash::parse(previous)?;
+                    if let Some(dropped) = self.verifier.check_dropped(&previous, &result).await? {
+                        match dropped.replacement {
+                            // Report the replacement; the current status already tracks it
+                            Some(replacement) => {
+                                let status = PaymentStatus::Replaced {
+                                    tx_hash: dropped.tx_hash.into(),
+                                    replacement: replacement.into(),
+                                };
+                                self.call(&*callback, &request, &status, None)?;
+                                self.notify(&request, &status).await;
+                                last_status = Some(status);
+                            }
+                            None => {
+                                current_status = PaymentStatus::Failed {
+                                    reason: "transaction dropped/replaced".to_string(),
+                                };
+                            }
+                        }
+                    }
+                }
+            }
 
             // Call callback if status changed
             if last_status.as_ref() != Some(&current_status) {
-                callback(current_status.clone());
+                #[cfg(feature = "tracing")]
+                tracing::debug!(from = ?last_status, to = ?current_status, "payment status changed");
+                self.call(&*callback, &request, &current_status, details.as_ref())?;
+                self.notify(&request, &current_status).await;
                 last_status = Some(current_status.clone());
             }
 
+            // Keep watching confirmed payments that are not yet final
+            if let (
+                PaymentStatus::Confirmed {
+                    tx_hash,
+                    confirmations,
+                },
+                Some(depth),
+            ) = (&current_status, self.finalization_depth)
+            {
+                if *confirmations < depth {
+                    // A missing inclusion means the tx is already gone; verify again next tick
+                    let tx_hash = TxHash::parse(tx_hash)?;
+                    if let Some(inclusion) = self.verifier.get_inclusion(&tx_hash).await? {
+                        finality = Some(FinalityWatch {
+                            tx_hash,
+                            block_hash: inclusion.block_hash,
+                        });
+                    }
+                    self.clock
+                        .sleep(self.confirmation_delay(poll_interval, depth - confirmations))
+                        .await;
+                    continue;
+                }
+            }
+
             // Break if finalized
             if current_status.is_finalized() {
                 break;
@@ -98,35 +277,104 @@ impl PaymentMonitor {
             // Note: In real usage, you'd want to track creation time
             // For now, we rely on the user to handle timeouts externally
 
-            // Wait before next poll
-            sleep(self.poll_interval).await;
+            // Wait before next poll, longer while confirmations accumulate
+            let delay = match &current_status {
+                PaymentStatus::Detected { confirmations, .. } => self.confirmation_delay(
+                    poll_interval,
+                    request
+                        .required_confirmations
+                        .saturating_sub(*confirmations),
+                ),
+                _ => poll_interval,
+            };
+            self.clock.sleep(delay).await;
         }
 
         Ok(())
     }
 
+    /// Report a status to the callback, applying the callback error policy
+    ///
+    /// Fails only under [`CallbackErrorPolicy::Abort`].
+    fn call<F>(
+        &self,
+        callback: &F,
+        request: &PaymentRequest,
+        status: &PaymentStatus,
+        details: Option<&PaymentDetails>,
+    ) -> Result<()>
+    where
+        F: Fn(PaymentStatus, Option<&PaymentDetails>),
+    {
+        let attempts = match self.on_callback_error {
+            CallbackErrorPolicy::Retry(retries) => retries.saturating_add(1),
+            _ => 1,
+        };
+        let mut message = String::new();
+        for _ in 0..attempts {
+            match panic::catch_unwind(AssertUnwindSafe(|| callback(status.clone(), details))) {
+                Ok(()) => return Ok(()),
+                Err(payload) => message = panic_message(payload.as_ref()),
+            }
+        }
+
+        tracing::warn!(status = status.name(), attempts, error = %message, "payment callback panicked");
+        if let Some(events) = &self.events {
+            events.publish(Event::CallbackFailed {
+                recipient: request.recipient_address.clone(),
+                status: status.clone(),
+                attempts,
+                message: message.clone(),
+            });
+        }
+        match self.on_callback_error {
+            CallbackErrorPolicy::Abort => Err(Error::CallbackFailed(message)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Time to wait for `remaining` confirmations: one block time each if
+    /// [`block_time`](PaymentMonitorBuilder::block_time) is set, but never
+    /// less than `poll_interval`
+    fn confirmation_delay(&self, poll_interval: Duration, remaining: u64) -> Duration {
+        match self.block_time {
+            Some(block_time) => {
+                let blocks = u32::try_from(remaining).unwrap_or(u32::MAX);
+                poll_interval.max(block_time.saturating_mul(blocks))
+            }
+            None => poll_interval,
+        }
+    }
+
     /// Check payment status once (no monitoring)
     pub async fn check_payment_status(&self, request: &PaymentRequest) -> Result<PaymentStatus> {
         let result = self.verifier.verify_payment(request).await?;
 
-        Ok(match result {
-            VerificationResult::NotFound => PaymentStatus::Pending,
-            VerificationResult::Pending {
-                tx_hash,
-                confirmations,
-            } => PaymentStatus::Detected {
-                tx_hash,
-                confirmations,
-            },
-            VerificationResult::Confirmed {
-                tx_hash,
-                confirmations,
-            } => PaymentStatus::Confirmed {
-                tx_hash,
-                confirmations,
-            },
-            VerificationResult::Failed { reason } => PaymentStatus::Failed { reason },
-        })
+        Ok(PaymentStatus::from(result))
+    }
+
+    /// Send a status change to every notifier; failures are logged only
+    async fn notify(&self, request: &PaymentRequest, status: &PaymentStatus) {
+        if self.notifiers.is_empty() {
+            return;
+        }
+        let message = describe(request, status);
+        for notifier in &self.notifiers {
+            if let Err(e) = notifier.notify(&message).await {
+                tracing::warn!(error = %e, "payment notification failed");
+            }
+        }
+    }
+}
+
+/// Message of a caught panic
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "callback panicked".to_string()
     }
 }
 
@@ -135,6 +383,12 @@ impl PaymentMonitor {
 pub struct PaymentMonitorBuilder {
     client: Option<BscScanClient>,
     poll_interval: Option<Duration>,
+    block_time: Option<Duration>,
+    finalization_depth: Option<u64>,
+    clock: Option<Arc<dyn Clock>>,
+    notifiers: Vec<Arc<dyn Notifier>>,
+    on_callback_error: CallbackErrorPolicy,
+    events: Option<EventBus>,
 }
 
 impl PaymentMonitorBuilder {
@@ -150,11 +404,346 @@ impl PaymentMonitorBuilder {
         self
     }
 
+    /// Slow polling while a payment waits for confirmations to once per
+    /// `block_time` × remaining confirmations (default: always poll at the
+    /// poll interval)
+    ///
+    /// Saves API calls on payments that need many confirmations; the poll
+    /// interval stays the minimum.
+    pub fn block_time(mut self, block_time: Duration) -> Self {
+        self.block_time = Some(block_time);
+        self
+    }
+
+    /// Keep re-checking confirmed payments for reorgs until this many confirmations
+    pub fn finalization_depth(mut self, depth: u64) -> Self {
+        self.finalization_depth = Some(depth);
+        self
+    }
+
+    /// Set the clock to wait on between polls (default: [`SystemClock`])
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// Send a human-readable message to `notifier` on every status change
+    pub fn notifier(mut self, notifier: impl Notifier + 'static) -> Self {
+        self.notifiers.push(Arc::new(notifier));
+        self
+    }
+
+    /// Set what happens when a status callback panics (default: [`CallbackErrorPolicy::Continue`])
+    pub fn on_callback_error(mut self, policy: CallbackErrorPolicy) -> Self {
+        self.on_callback_error = policy;
+        self
+    }
+
+    /// Publish callback failures to `events`
+    pub fn events(mut self, events: EventBus) -> Self {
+        self.events = Some(events);
+        self
+    }
+
     /// Build the PaymentMonitor
     pub fn build(self) -> PaymentMonitor {
-        PaymentMonitor::new(
-            self.client.expect("BscScanClient is required"),
-            self.poll_interval.unwrap_or(Duration::from_secs(10)),
+        let client = self.client.expect("BscScanClient is required");
+        PaymentMonitor {
+            verifier: PaymentVerifier::new(client),
+            poll_interval: self.poll_interval.unwrap_or(Duration::from_secs(10)),
+            block_time: self.block_time,
+            finalization_depth: self.finalization_depth,
+            clock: self.clock.unwrap_or_else(|| Arc::new(SystemClock)),
+            notifiers: self.notifiers,
+            on_callback_error: self.on_callback_error,
+            events: self.events,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::config::ClientConfig;
+    use serde_json::{json, Value};
+    use std::sync::Mutex;
+    use wiremock::matchers::query_param;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const RECIPIENT: &str = "0x742d35cc6634c0532925a3b844bc9e7595f0beb0";
+
+    fn hash(id: u64) -> String {
+        format!("0x{:064x}", id)
+    }
+
+    /// `txlist` entry paying 1 ETH to the recipient in block 100
+    fn tx(id: u64, confirmations: u64) -> Value {
+        json!({
+            "blockNumber": "100",
+            "hash": hash(id),
+            "blockHash": hash(1000),
+            "from": "0x00000000000000000000000000000000000000b2",
+            "to": RECIPIENT,
+            "value": "1000000000000000000",
+            "isError": "0",
+            "txreceipt_status": "1",
+            "input": "0x",
+            "contractAddress": "",
+            "confirmations": confirmations.to_string()
+        })
+    }
+
+    /// `eth_getTransactionByHash` result for a transaction in block 100
+    fn rpc_tx(id: u64, block_hash: Option<String>) -> Value {
+        json!({
+            "blockHash": block_hash,
+            "blockNumber": block_hash.as_ref().map(|_| "0x64"),
+            "from": "0x00000000000000000000000000000000000000b2",
+            "gas": "0x5208",
+            "gasPrice": "0x3b9aca00",
+            "hash": hash(id),
+            "input": "0x",
+            "nonce": "0x1",
+            "to": RECIPIENT,
+            "transactionIndex": block_hash.as_ref().map(|_| "0x0"),
+            "value": "0xde0b6b3a7640000"
+        })
+    }
+
+    /// Answer `action` with `results` in order, repeating the last one
+    async fn respond_in_order(server: &MockServer, action: &str, results: Vec<Value>) {
+        let last = results.len() - 1;
+        for (index, result) in results.into_iter().enumerate() {
+            let body = if action == "txlist" {
+                json!({ "status": "1", "message": "OK", "result": result })
+            } else {
+                json!({ "jsonrpc": "2.0", "id": 1, "result": result })
+            };
+            let mock = Mock::given(query_param("action", action))
+                .respond_with(ResponseTemplate::new(200).set_body_json(body));
+            let mock = if index < last {
+                mock.up_to_n_times(1)
+            } else {
+                mock
+            };
+            mock.mount(server).await;
+        }
+    }
+
+    /// Monitor against `server` without caching, on a clock that never waits
+    ///
+    /// The recipient never receives internal transactions.
+    async fn builder(server: &MockServer) -> PaymentMonitorBuilder {
+        Mock::given(query_param("action", "txlistinternal"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "status": "0", "message": "No transactions found", "result": []
+            })))
+            .mount(server)
+            .await;
+        let config = ClientConfig::builder()
+            .api_key("key")
+            .base_url(server.uri())
+            .cache_ttl(0)
+            .block_number_ttl(0)
+            .build()
+            .unwrap();
+        PaymentMonitor::builder()
+            .client(BscScanClient::with_config(config).unwrap())
+            .clock(Arc::new(MockClock::default().auto_advance(true)))
+    }
+
+    /// Monitor a 1 ETH request with 12 confirmations to the end, collecting the reported statuses
+    async fn reported(monitor: PaymentMonitor) -> Vec<PaymentStatus> {
+        let request =
+            PaymentRequest::eth(rust_decimal::Decimal::ONE, RECIPIENT.parse().unwrap(), 12);
+        let statuses = Mutex::new(Vec::new());
+        monitor
+            .start_monitoring(request, |status| statuses.lock().unwrap().push(status))
+            .await
+            .unwrap();
+        statuses.into_inner().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_reorged_payment_is_reported_and_confirmed_again() {
+        for vanished in [false, true] {
+            let server = MockServer::start().await;
+            respond_in_order(
+                &server,
+                "txlist",
+                vec![json!([tx(1, 12)]), json!([tx(1, 25)])],
+            )
+            .await;
+            // Included in block 100 when confirmed, then in another block or nowhere
+            let moved = if vanished {
+                Value::Null
+            } else {
+                rpc_tx(1, Some(hash(2000)))
+            };
+            respond_in_order(
+                &server,
+                "eth_getTransactionByHash",
+                vec![rpc_tx(1, Some(hash(1000))), moved],
+            )
+            .await;
+            respond_in_order(&server, "eth_blockNumber", vec![json!("0x6f")]).await;
+
+            let monitor = builder(&server).await.finalization_depth(20).build();
+            assert_eq!(
+                reported(monitor).await,
+                vec![
+                    PaymentStatus::Confirmed {
+                        tx_hash: hash(1),
+                        confirmations: 12
+                    },
+                    PaymentStatus::Reorged { tx_hash: hash(1) },
+                    PaymentStatus::Confirmed {
+                        tx_hash: hash(1),
+                        confirmations: 25
+                    },
+                ],
+                "vanished: {}",
+                vanished
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dropped_transaction_fails_payment() {
+        let server = MockServer::start().await;
+        respond_in_order(&server, "txlist", vec![json!([tx(1, 1)]), json!([])]).await;
+        respond_in_order(&server, "eth_getTransactionByHash", vec![Value::Null]).await;
+
+        let monitor = builder(&server).await.build();
+        assert_eq!(
+            reported(monitor).await,
+            vec![
+                PaymentStatus::Detected {
+                    confirmations: 1,
+                    tx_hash: hash(1)
+                },
+                PaymentStatus::Failed {
+                    reason: "transaction dropped/replaced".to_string()
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_replacement_transaction_is_tracked() {
+        let server = MockServer::start().await;
+        respond_in_order(
+            &server,
+            "txlist",
+            vec![json!([tx(1, 1)]), json!([tx(2, 1)]), json!([tx(2, 12)])],
         )
+        .await;
+        respond_in_order(&server, "eth_getTransactionByHash", vec![Value::Null]).await;
+
+        let monitor = builder(&server).await.build();
+        assert_eq!(
+            reported(monitor).await,
+            vec![
+                PaymentStatus::Detected {
+                    confirmations: 1,
+                    tx_hash: hash(1)
+                },
+                PaymentStatus::Replaced {
+                    tx_hash: hash(1),
+                    replacement: hash(2)
+                },
+                PaymentStatus::Detected {
+                    confirmations: 1,
+                    tx_hash: hash(2)
+                },
+                PaymentStatus::Confirmed {
+                    tx_hash: hash(2),
+                    confirmations: 12
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_confirmation_delay_scales_with_remaining_blocks() {
+        let client = BscScanClient::new("test-key").unwrap();
+        let base = Duration::from_secs(15);
+
+        let monitor = PaymentMonitor::new(client.clone(), base);
+        assert_eq!(monitor.confirmation_delay(base, 30), base);
+
+        let monitor = PaymentMonitor::builder()
+            .client(client)
+            .block_time(Duration::from_secs(12))
+            .build();
+        assert_eq!(
+            monitor.confirmation_delay(base, 30),
+            Duration::from_secs(360)
+        );
+        assert_eq!(monitor.confirmation_delay(base, 1), base);
+        assert_eq!(monitor.confirmation_delay(base, 0), base);
+    }
+
+    #[tokio::test]
+    async fn test_callback_panics_follow_policy() {
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        let server = MockServer::start().await;
+        Mock::given(query_param("module", "proxy"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "jsonrpc": "2.0", "id": 1, "result": "0x10"
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(query_param("action", "txlist"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "status": "0", "message": "No transactions found", "result": []
+            })))
+            .mount(&server)
+            .await;
+        let request =
+            PaymentRequest::eth(rust_decimal::Decimal::ONE, RECIPIENT.parse().unwrap(), 12);
+        let events = EventBus::new();
+        let mut subscriber = events.subscribe();
+        let calls = Arc::new(AtomicU32::new(0));
+        let failing = |calls: Arc<AtomicU32>| {
+            move |_: PaymentStatus| {
+                calls.fetch_add(1, Ordering::SeqCst);
+                panic!("callback broke");
+            }
+        };
+
+        let monitor = builder(&server)
+            .await
+            .on_callback_error(CallbackErrorPolicy::Abort)
+            .build();
+        let error = monitor
+            .start_monitoring(request.clone(), failing(calls.clone()))
+            .await
+            .unwrap_err();
+        assert!(matches!(error, Error::CallbackFailed(ref message) if message == "callback broke"));
+        assert_eq!(calls.swap(0, Ordering::SeqCst), 1);
+
+        // Retried, reported, and monitoring goes on
+        let monitor = builder(&server)
+            .await
+            .poll_interval(Duration::from_secs(60))
+            .on_callback_error(CallbackErrorPolicy::Retry(2))
+            .events(events)
+            .build();
+        let task =
+            tokio::spawn(async move { monitor.start_monitoring(request, failing(calls)).await });
+        match subscriber.recv().await.unwrap() {
+            Event::CallbackFailed {
+                status, attempts, ..
+            } => {
+                assert_eq!(status, PaymentStatus::Pending);
+                assert_eq!(attempts, 3);
+            }
+            event => panic!("unexpected event {:?}", event),
+        }
+        assert!(!task.is_finished());
+        task.abort();
     }
 }
@@ -0,0 +1,767 @@
+//! Background monitoring of many stored payments
+//!
+//! [`MonitorPool`] runs one [`PaymentMonitor`] task per payment, persists every
+//! status change to a [`PaymentStorage`], and broadcasts the changes as
+//! [`PaymentUpdate`]s for notifiers (webhooks, emails, ...) to consume.
+
+use crate::clock::Clock;
+use crate::error::{Error, Result};
+use crate::events::{Event, EventBus};
+use crate::notify::Notifier;
+use crate::payment::models::{Payment, PaymentStatus};
+use crate::payment::monitor::PaymentMonitor;
+use crate::retry::RetryPolicy;
+use crate::storage::PaymentStorage;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc};
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+/// Capacity of the update channel; slow subscribers miss older updates
+const UPDATE_CAPACITY: usize = 256;
+
+/// Monitor state of each payment, updated as statuses are reported
+type States = Arc<Mutex<HashMap<Uuid, MonitoredPaymentState>>>;
+
+/// Status change of a monitored payment
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct PaymentUpdate {
+    /// Payment ID
+    pub payment_id: Uuid,
+    /// New status
+    pub status: PaymentStatus,
+    /// Whether this is a re-delivery of an earlier update
+    pub redelivery: bool,
+}
+
+/// In-flight state of a payment monitored by a [`MonitorPool`]
+///
+/// Taken with [`MonitorPool::snapshot`] and handed to
+/// [`MonitorPool::restore`], e.g. across a restart.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonitoredPaymentState {
+    /// Monitored payment, with the latest status
+    pub payment: Payment,
+    /// Status the monitor last reported, `None` before its first check
+    pub last_status: Option<PaymentStatus>,
+    /// Poll interval overriding the monitor's, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub poll_interval: Option<Duration>,
+}
+
+/// Monitors stored payments in the background
+///
+/// # Example
+/// ```no_run
+/// # use cryptopay::*;
+/// # use cryptopay::payment::MonitorPool;
+/// # use std::sync::Arc;
+/// # use std::time::Duration;
+/// # async fn example() -> Result<()> {
+/// let monitor = PaymentMonitor::new(BscScanClient::new("api-key")?, Duration::from_secs(15));
+/// let pool = MonitorPool::new(monitor, Arc::new(MemoryStorage::new()));
+///
+/// let mut updates = pool.subscribe();
+/// pool.resume().await?; // pick up payments left pending by a restart
+///
+/// while let Ok(update) = updates.recv().await {
+///     println!("{}: {:?}", update.payment_id, update.status);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct MonitorPool {
+    monitor: Arc<PaymentMonitor>,
+    storage: Arc<dyn PaymentStorage>,
+    tasks: Mutex<HashMap<Uuid, JoinHandle<()>>>,
+    states: States,
+    updates: broadcast::Sender<PaymentUpdate>,
+    storage_retry: RetryPolicy,
+    events: Option<EventBus>,
+}
+
+impl MonitorPool {
+    /// Create a pool monitoring with `monitor` and persisting to `storage`
+    pub fn new(monitor: PaymentMonitor, storage: Arc<dyn PaymentStorage>) -> Self {
+        let (updates, _) = broadcast::channel(UPDATE_CAPACITY);
+        Self {
+            monitor: Arc::new(monitor),
+            storage,
+            tasks: Mutex::new(HashMap::new()),
+            states: States::default(),
+            updates,
+            storage_retry: RetryPolicy::default(),
+            events: None,
+        }
+    }
+
+    /// Retry failed status writes by `policy` (default: [`RetryPolicy::default`])
+    pub fn storage_retry(mut self, policy: RetryPolicy) -> Self {
+        self.storage_retry = policy;
+        self
+    }
+
+    /// Publish created payments and status changes to `events`
+    pub fn events(mut self, events: EventBus) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Storage the pool persists to
+    pub fn storage(&self) -> &Arc<dyn PaymentStorage> {
+        &self.storage
+    }
+
+    /// Subscribe to status changes of monitored payments
+    pub fn subscribe(&self) -> broadcast::Receiver<PaymentUpdate> {
+        self.updates.subscribe()
+    }
+
+    /// Report every status change to `notifier`, with the stored payment
+    /// (see [`Notifier::notify_payment`])
+    ///
+    /// Runs until the update channel closes or the returned task is aborted.
+    /// Failed notifications are logged and not retried.
+    pub fn notify(&self, notifier: impl Notifier + 'static) -> JoinHandle<()> {
+        let mut updates = self.subscribe();
+        let storage = self.storage.clone();
+        tokio::spawn(async move {
+            loop {
+                let update = match updates.recv().await {
+                    Ok(update) => update,
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                let result = match storage.get_payment(update.payment_id).await {
+                    Ok(Some(mut payment)) => {
+                        payment.status = update.status;
+                        notifier.notify_payment(&payment).await
+                    }
+                    _ => {
+                        let message =
+                            format!("Payment {} {}", update.payment_id, update.status.name());
+                        notifier.notify(&message).await
+                    }
+                };
+                if let Err(e) = result {
+                    tracing::warn!(payment_id = %update.payment_id, error = %e, "payment notification failed");
+                }
+            }
+        })
+    }
+
+    /// Sender of the update channel, for components broadcasting on the pool's behalf
+    pub(crate) fn update_sender(&self) -> broadcast::Sender<PaymentUpdate> {
+        self.updates.clone()
+    }
+
+    /// Event bus of the pool, for components publishing on the pool's behalf
+    pub(crate) fn event_bus(&self) -> Option<&EventBus> {
+        self.events.as_ref()
+    }
+
+    /// Clock of the pool's monitor
+    pub(crate) fn clock(&self) -> &Arc<dyn Clock> {
+        self.monitor.clock()
+    }
+
+    /// Save a payment and start monitoring it
+    ///
+    /// A payment that is already monitored keeps its running task.
+    pub async fn watch(&self, payment: Payment) -> Result<()> {
+        self.save_and_spawn(payment, None).await
+    }
+
+    /// Save a payment and start monitoring it every `poll_interval` instead
+    /// of the monitor's interval
+    ///
+    /// A payment that is already monitored keeps its running task and interval.
+    pub async fn watch_with_interval(
+        &self,
+        payment: Payment,
+        poll_interval: Duration,
+    ) -> Result<()> {
+        self.save_and_spawn(payment, Some(poll_interval)).await
+    }
+
+    async fn save_and_spawn(
+        &self,
+        payment: Payment,
+        poll_interval: Option<Duration>,
+    ) -> Result<()> {
+        self.storage.save_payment(&payment).await?;
+        if let Some(events) = &self.events {
+            events.publish(Event::PaymentCreated {
+                payment_id: payment.id,
+            });
+        }
+        self.spawn(payment, None, poll_interval);
+        Ok(())
+    }
+
+    /// Start monitoring every stored payment that is not finalized yet
+    ///
+    /// Returns the number of payments now monitored by the pool.
+    pub async fn resume(&self) -> Result<usize> {
+        for payment in self.storage.list_pending().await? {
+            self.spawn(payment, None, None);
+        }
+        Ok(self.len())
+    }
+
+    /// State of every monitored payment, e.g. to persist on shutdown
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use cryptopay::*;
+    /// # use cryptopay::payment::{MonitorPool, MonitoredPaymentState};
+    /// # async fn example(pool: MonitorPool) -> Result<()> {
+    /// // On SIGTERM
+    /// std::fs::write("monitors.json", serde_json::to_vec(&pool.snapshot())?)?;
+    ///
+    /// // After the restart
+    /// let states: Vec<MonitoredPaymentState> =
+    ///     serde_json::from_slice(&std::fs::read("monitors.json")?)?;
+    /// pool.restore(states).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn snapshot(&self) -> Vec<MonitoredPaymentState> {
+        let watching = self.watching();
+        let states = lock(&self.states);
+        watching
+            .iter()
+            .filter_map(|id| states.get(id).cloned())
+            .collect()
+    }
+
+    /// Resume monitoring payments from a [`snapshot`](Self::snapshot)
+    ///
+    /// Payments missing from storage are saved first, so their status
+    /// changes can be persisted. Monitoring picks up at the last reported
+    /// status without reporting it again. Finalized payments and payments
+    /// that are already monitored are skipped. Returns the number of
+    /// payments now monitored by the pool.
+    pub async fn restore(&self, states: Vec<MonitoredPaymentState>) -> Result<usize> {
+        for state in states {
+            if state.payment.status.is_finalized() {
+                continue;
+            }
+            if self.storage.get_payment(state.payment.id).await?.is_none() {
+                self.storage.save_payment(&state.payment).await?;
+            }
+            self.spawn(state.payment, state.last_status, state.poll_interval);
+        }
+        Ok(self.len())
+    }
+
+    /// Stop monitoring a payment, returning whether it was monitored
+    pub fn cancel(&self, id: Uuid) -> bool {
+        lock(&self.states).remove(&id);
+        match self.lock_tasks().remove(&id) {
+            Some(task) => {
+                task.abort();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// IDs of the payments currently monitored
+    pub fn watching(&self) -> Vec<Uuid> {
+        let mut tasks = self.lock_tasks();
+        tasks.retain(|_, task| !task.is_finished());
+        tasks.keys().copied().collect()
+    }
+
+    /// Number of payments currently monitored
+    pub fn len(&self) -> usize {
+        self.watching().len()
+    }
+
+    /// Check if no payment is monitored
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Broadcast a payment's current status again, e.g. to retry a webhook
+    pub async fn redeliver(&self, id: Uuid) -> Result<PaymentUpdate> {
+        let payment = self
+            .storage
+            .get_payment(id)
+            .await?
+            .ok_or(Error::PaymentNotFound(id))?;
+
+        let update = PaymentUpdate {
+            payment_id: id,
+            status: payment.status,
+            redelivery: true,
+        };
+        publish_update(self.events.as_ref(), &update);
+        // Nobody listening is not an error; the update is simply not delivered
+        let _ = self.updates.send(update.clone());
+        Ok(update)
+    }
+
+    fn spawn(
+        &self,
+        payment: Payment,
+        last_status: Option<PaymentStatus>,
+        poll_interval: Option<Duration>,
+    ) {
+        let mut tasks = self.lock_tasks();
+        if tasks
+            .get(&payment.id)
+            .is_some_and(|task| !task.is_finished())
+        {
+            return;
+        }
+
+        let id = payment.id;
+        lock(&self.states).insert(
+            id,
+            MonitoredPaymentState {
+                payment,
+                last_status,
+                poll_interval,
+            },
+        );
+        let task = tokio::spawn(run(
+            self.monitor.clone(),
+            self.storage.clone(),
+            self.storage_retry,
+            self.updates.clone(),
+            self.events.clone(),
+            self.states.clone(),
+            id,
+        ));
+        tasks.insert(id, task);
+    }
+
+    fn lock_tasks(&self) -> std::sync::MutexGuard<'_, HashMap<Uuid, JoinHandle<()>>> {
+        lock(&self.tasks)
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+impl Drop for MonitorPool {
+    fn drop(&mut self) {
+        for task in self.lock_tasks().values() {
+            task.abort();
+        }
+    }
+}
+
+/// Monitor one payment until it is finalized, persisting and broadcasting changes
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(payment_id = %payment_id))
+)]
+async fn run(
+    monitor: Arc<PaymentMonitor>,
+    storage: Arc<dyn PaymentStorage>,
+    storage_retry: RetryPolicy,
+    updates: broadcast::Sender<PaymentUpdate>,
+    events: Option<EventBus>,
+    states: States,
+    payment_id: Uuid,
+) {
+    let Some((request, poll_interval)) = lock(&states).get(&payment_id).map(|state| {
+        let poll_interval = state.poll_interval.unwrap_or(monitor.poll_interval());
+        (state.payment.request.clone(), poll_interval)
+    }) else {
+        return;
+    };
+    let (status_tx, mut status_rx) = mpsc::unbounded_channel::<PaymentStatus>();
+
+    // The monitor's callback is synchronous; persist from a separate task.
+    // Only statuses storage accepts are kept and broadcast; once storage
+    // rejects a transition (e.g. the payment expired meanwhile), the task
+    // ends and monitoring stops.
+    let persisted_states = states.clone();
+    let mut persist = tokio::spawn(async move {
+        while let Some(status) = status_rx.recv().await {
+            let persisted = storage_retry
+                .retry(|| storage.update_status(payment_id, status.clone()))
+                .await;
+            match persisted {
+                Ok(transition) => {
+                    if let Some(events) = &events {
+                        events.publish(transition.into());
+                    }
+                }
+                Err(Error::InvalidTransition(e)) => {
+                    tracing::warn!(%payment_id, transition = %e, "payment status rejected by storage; monitoring stopped");
+                    return;
+                }
+                Err(e) => {
+                    tracing::warn!(%payment_id, error = %e, "failed to persist payment status");
+                    continue;
+                }
+            }
+            if let Some(state) = lock(&persisted_states).get_mut(&payment_id) {
+                state.payment.status = status.clone();
+                state.last_status = Some(status.clone());
+            }
+            let update = PaymentUpdate {
+                payment_id,
+                status,
+                redelivery: false,
+            };
+            publish_update(events.as_ref(), &update);
+            let _ = updates.send(update);
+        }
+    });
+
+    let mut stopped = false;
+    loop {
+        // Set when the pool was restored, and after a failed attempt
+        let last_status = lock(&states)
+            .get(&payment_id)
+            .and_then(|state| state.last_status.clone());
+        let status_tx = status_tx.clone();
+        let monitoring = monitor.monitor_from(
+            request.clone(),
+            last_status,
+            poll_interval,
+            move |status, _| {
+                let _ = status_tx.send(status);
+            },
+        );
+        let result = tokio::select! {
+            result = monitoring => result,
+            _ = &mut persist => {
+                stopped = true;
+                break;
+            }
+        };
+
+        match result {
+            Ok(()) => break,
+            Err(e) => {
+                tracing::warn!(%payment_id, error = %e, "payment monitoring failed; retrying");
+                monitor.clock().sleep(poll_interval).await;
+            }
+        }
+    }
+
+    drop(status_tx);
+    if !stopped {
+        let _ = persist.await;
+    }
+    lock(&states).remove(&payment_id);
+}
+
+/// Publish an update to the event bus, if there is one
+pub(crate) fn publish_update(events: Option<&EventBus>, update: &PaymentUpdate) {
+    if let Some(events) = events {
+        events.publish(Event::StatusChanged {
+            payment_id: update.payment_id,
+            status: update.status.clone(),
+            redelivery: update.redelivery,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::BscScanClient;
+    use crate::payment::PaymentRequest;
+    use crate::storage::MemoryStorage;
+    use rust_decimal::Decimal;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_redeliver_broadcasts_stored_status() {
+        let monitor = PaymentMonitor::new(
+            BscScanClient::new("test-key").unwrap(),
+            Duration::from_secs(60),
+        );
+        let storage = Arc::new(MemoryStorage::new());
+        let events = EventBus::new();
+        let pool = MonitorPool::new(monitor, storage.clone()).events(events.clone());
+        let mut updates = pool.subscribe();
+        let mut events = events.subscribe();
+
+        let request = PaymentRequest::eth(
+            Decimal::ONE,
+            "0x742d35cc6634c0532925a3b844bc9e7595f0beb0"
+                .parse()
+                .unwrap(),
+            12,
+        );
+        let payment = Payment::new(request);
+        storage.save_payment(&payment).await.unwrap();
+
+        let update = pool.redeliver(payment.id).await.unwrap();
+        assert!(update.redelivery);
+        assert_eq!(update.status, PaymentStatus::Pending);
+        assert_eq!(updates.recv().await.unwrap(), update);
+        assert_eq!(
+            events.recv().await.unwrap(),
+            Event::StatusChanged {
+                payment_id: payment.id,
+                status: PaymentStatus::Pending,
+                redelivery: true
+            }
+        );
+
+        assert!(matches!(
+            pool.redeliver(Uuid::new_v4()).await,
+            Err(Error::PaymentNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_and_restore() {
+        use crate::clock::MockClock;
+        use crate::config::ClientConfig;
+        use wiremock::matchers::query_param;
+        use wiremock::{Mock, MockServer, Respond, ResponseTemplate};
+
+        /// Empty history, signalling every `txlist` fetch (one per poll)
+        struct Polls(mpsc::UnboundedSender<()>);
+
+        impl Respond for Polls {
+            fn respond(&self, _: &wiremock::Request) -> ResponseTemplate {
+                let _ = self.0.send(());
+                ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "status": "0", "message": "No transactions found", "result": []
+                }))
+            }
+        }
+
+        let server = MockServer::start().await;
+        let (polls_tx, mut polls) = mpsc::unbounded_channel();
+        Mock::given(query_param("module", "proxy"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0", "id": 1, "result": "0x10"
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(query_param("action", "txlist"))
+            .respond_with(Polls(polls_tx))
+            .mount(&server)
+            .await;
+        Mock::given(query_param("action", "txlistinternal"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "status": "0", "message": "No transactions found", "result": []
+            })))
+            .mount(&server)
+            .await;
+        let pool = |clock: &MockClock| {
+            let config = ClientConfig::builder()
+                .api_key("key")
+                .base_url(server.uri())
+                .cache_ttl(0)
+                .block_number_ttl(0)
+                .build()
+                .unwrap();
+            let monitor = PaymentMonitor::builder()
+                .client(BscScanClient::with_config(config).unwrap())
+                .poll_interval(Duration::from_secs(60))
+                .clock(Arc::new(clock.clone()))
+                .build();
+            MonitorPool::new(monitor, Arc::new(MemoryStorage::new()))
+        };
+
+        let first = pool(&MockClock::default());
+        let mut updates = first.subscribe();
+        let payment = Payment::new(PaymentRequest::eth(
+            Decimal::ONE,
+            "0x742d35cc6634c0532925a3b844bc9e7595f0beb0"
+                .parse()
+                .unwrap(),
+            12,
+        ));
+        first
+            .watch_with_interval(payment.clone(), Duration::from_secs(30))
+            .await
+            .unwrap();
+        assert_eq!(updates.recv().await.unwrap().status, PaymentStatus::Pending);
+
+        let states = first.snapshot();
+        assert_eq!(states.len(), 1);
+        assert_eq!(states[0].payment.id, payment.id);
+        assert_eq!(states[0].last_status, Some(PaymentStatus::Pending));
+        assert_eq!(states[0].poll_interval, Some(Duration::from_secs(30)));
+        let states: Vec<MonitoredPaymentState> =
+            serde_json::from_value(serde_json::to_value(&states).unwrap()).unwrap();
+        drop(first);
+        while polls.try_recv().is_ok() {}
+
+        // The restored pool keeps polling without reporting `Pending` again
+        let clock = MockClock::default();
+        let second = pool(&clock);
+        let mut updates = second.subscribe();
+        assert_eq!(second.restore(states).await.unwrap(), 1);
+        assert_eq!(second.watching(), vec![payment.id]);
+        assert!(second
+            .storage()
+            .get_payment(payment.id)
+            .await
+            .unwrap()
+            .is_some());
+        polls.recv().await.unwrap();
+        for _ in 0..2 {
+            // Advance until the monitor, asleep for its 30 s interval, polls again
+            loop {
+                clock.advance(Duration::from_secs(30));
+                let poll = tokio::time::timeout(Duration::from_millis(10), polls.recv());
+                if poll.await.is_ok() {
+                    break;
+                }
+            }
+        }
+        // The second poll has finished; let its status reach the persist task
+        tokio::task::yield_now().await;
+        assert!(updates.try_recv().is_err());
+        let state = &second.snapshot()[0];
+        assert_eq!(state.last_status, Some(PaymentStatus::Pending));
+        assert_eq!(state.poll_interval, Some(Duration::from_secs(30)));
+    }
+
+    #[tokio::test]
+    async fn test_status_rejected_by_storage_is_not_broadcast() {
+        use crate::clock::MockClock;
+        use crate::config::ClientConfig;
+        use wiremock::matchers::query_param;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let recipient = "0x742d35cc6634c0532925a3b844bc9e7595f0beb0";
+        let server = MockServer::start().await;
+        Mock::given(query_param("module", "proxy"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0", "id": 1, "result": "0x70"
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(query_param("action", "txlist"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "status": "1", "message": "OK", "result": [{
+                    "blockNumber": "100",
+                    "hash": format!("0x{:064x}", 1),
+                    "blockHash": format!("0x{:064x}", 1000),
+                    "from": "0x00000000000000000000000000000000000000b2",
+                    "to": recipient,
+                    "value": "1000000000000000000",
+                    "isError": "0",
+                    "txreceipt_status": "1",
+                    "input": "0x",
+                    "contractAddress": "",
+                    "confirmations": "13"
+                }]
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(query_param("action", "txlistinternal"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "status": "0", "message": "No transactions found", "result": []
+            })))
+            .mount(&server)
+            .await;
+        let config = ClientConfig::builder()
+            .api_key("key")
+            .base_url(server.uri())
+            .cache_ttl(0)
+            .block_number_ttl(0)
+            .build()
+            .unwrap();
+        let monitor = PaymentMonitor::builder()
+            .client(BscScanClient::with_config(config).unwrap())
+            .clock(Arc::new(MockClock::default().auto_advance(true)))
+            .build();
+        let storage = Arc::new(MemoryStorage::new());
+        let events = EventBus::new();
+        let pool = MonitorPool::new(monitor, storage.clone()).events(events.clone());
+        let mut updates = pool.subscribe();
+        let mut events = events.subscribe();
+
+        // The payment expired in storage while the pool still sees it pending
+        let payment = Payment::new(PaymentRequest::eth(
+            Decimal::ONE,
+            recipient.parse().unwrap(),
+            12,
+        ));
+        storage.save_payment(&payment).await.unwrap();
+        storage
+            .update_status(payment.id, PaymentStatus::Expired)
+            .await
+            .unwrap();
+        let state = MonitoredPaymentState {
+            payment: payment.clone(),
+            last_status: Some(PaymentStatus::Pending),
+            poll_interval: None,
+        };
+        assert_eq!(pool.restore(vec![state]).await.unwrap(), 1);
+
+        // Storage refuses the payment's confirmation, which stops monitoring
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !pool.watching().is_empty() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        assert!(updates.try_recv().is_err());
+        assert!(events.try_recv().is_err());
+        assert!(pool.snapshot().is_empty());
+        assert_eq!(
+            storage
+                .get_payment(payment.id)
+                .await
+                .unwrap()
+                .unwrap()
+                .status,
+            PaymentStatus::Expired
+        );
+    }
+
+    #[tokio::test]
+    async fn test_notify_describes_updates() {
+        struct Recorder(mpsc::UnboundedSender<String>);
+
+        #[async_trait::async_trait]
+        impl Notifier for Recorder {
+            async fn notify(&self, message: &str) -> Result<()> {
+                let _ = self.0.send(message.to_string());
+                Ok(())
+            }
+        }
+
+        let monitor = PaymentMonitor::new(
+            BscScanClient::new("test-key").unwrap(),
+            Duration::from_secs(60),
+        );
+        let storage = Arc::new(MemoryStorage::new());
+        let pool = MonitorPool::new(monitor, storage.clone());
+        let (tx, mut messages) = mpsc::unbounded_channel();
+        let task = pool.notify(Recorder(tx));
+
+        let payment = Payment::new(PaymentRequest::eth(
+            Decimal::ONE,
+            "0x742d35cc6634c0532925a3b844bc9e7595f0beb0"
+                .parse()
+                .unwrap(),
+            12,
+        ));
+        storage.save_payment(&payment).await.unwrap();
+        pool.redeliver(payment.id).await.unwrap();
+
+        assert_eq!(
+            messages.recv().await.unwrap(),
+            "Payment of 1 (native) to 0x742d35cc6634c0532925a3b844bc9e7595f0beb0 is pending"
+        );
+        task.abort();
+    }
+}
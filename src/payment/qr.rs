@@ -0,0 +1,70 @@
+//! QR code rendering for payment URIs (requires the `qrcode` feature)
+
+use crate::error::{Error, Result};
+use crate::payment::models::PaymentRequest;
+use image::{DynamicImage, ImageFormat, Luma};
+use qrcode::render::svg;
+use qrcode::QrCode;
+use std::io::Cursor;
+
+/// Render `data` as a QR code PNG at least `size` pixels wide
+pub fn render_png(data: &str, size: u32) -> Result<Vec<u8>> {
+    let image = encode(data)?
+        .render::<Luma<u8>>()
+        .min_dimensions(size, size)
+        .build();
+
+    let mut png = Vec::new();
+    DynamicImage::ImageLuma8(image)
+        .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+        .map_err(|e| Error::generic(format!("Failed to encode QR code PNG: {}", e)))?;
+    Ok(png)
+}
+
+/// Render `data` as a QR code SVG document at least `size` pixels wide
+pub fn render_svg(data: &str, size: u32) -> Result<String> {
+    Ok(encode(data)?
+        .render::<svg::Color>()
+        .min_dimensions(size, size)
+        .build())
+}
+
+fn encode(data: &str) -> Result<QrCode> {
+    QrCode::new(data.as_bytes())
+        .map_err(|e| Error::generic(format!("Failed to encode QR code: {}", e)))
+}
+
+impl PaymentRequest {
+    /// Render the mainnet EIP-681 URI as a QR code PNG
+    pub fn to_qr_png(&self, size: u32) -> Result<Vec<u8>> {
+        render_png(&self.to_eip681_uri(), size)
+    }
+
+    /// Render the mainnet EIP-681 URI as a QR code SVG document
+    pub fn to_qr_svg(&self, size: u32) -> Result<String> {
+        render_svg(&self.to_eip681_uri(), size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+
+    #[test]
+    fn test_render_qr() {
+        let request = PaymentRequest::eth(
+            Decimal::new(1, 1),
+            "0x742D35CC6634c0532925A3b844BC9E7595F0BEb0"
+                .parse()
+                .unwrap(),
+            12,
+        );
+
+        let png = request.to_qr_png(200).unwrap();
+        assert!(png.starts_with(b"\x89PNG"));
+
+        let svg = request.to_qr_svg(200).unwrap();
+        assert!(svg.contains("<svg"));
+    }
+}
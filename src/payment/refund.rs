@@ -0,0 +1,298 @@
+//! Refund tracking
+//!
+//! A refund is an outgoing transfer sending (part of) a received payment back
+//! to the address that paid it. [`RefundTracker`] verifies the refund
+//! transaction on-chain, follows its confirmations, and stores the refund in
+//! the metadata of the original [`Payment`].
+
+use crate::client::BscScanClient;
+use crate::error::{Error, Result};
+use crate::payment::models::{Currency, Payment, PaymentRequest, PaymentStatus};
+use crate::payment::verification::{PaymentVerifier, VerificationResult};
+use crate::primitives::{Address, TxHash};
+use crate::rt::sleep;
+use crate::storage::PaymentStorage;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Metadata key under which refunds are stored on the payment
+const METADATA_KEY: &str = "refunds";
+
+/// Refund status
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RefundStatus {
+    /// Refund transaction not (sufficiently) confirmed yet
+    Pending {
+        /// Current confirmations (0 if not mined or not indexed yet)
+        confirmations: u64,
+    },
+
+    /// Refund confirmed on-chain
+    Confirmed {
+        /// Confirmations at the last check
+        confirmations: u64,
+    },
+
+    /// Refund transaction does not refund the payment
+    Failed {
+        /// Failure reason
+        reason: String,
+    },
+}
+
+impl RefundStatus {
+    /// Check if the refund is finalized (confirmed or failed)
+    pub fn is_finalized(&self) -> bool {
+        matches!(
+            self,
+            RefundStatus::Confirmed { .. } | RefundStatus::Failed { .. }
+        )
+    }
+}
+
+/// Refund of a received payment
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Refund {
+    /// Unique refund ID
+    pub id: Uuid,
+
+    /// ID of the refunded payment
+    pub payment_id: Uuid,
+
+    /// Outgoing refund transaction hash
+    pub tx_hash: TxHash,
+
+    /// Address receiving the refund (the original payment's sender)
+    pub recipient: Address,
+
+    /// Refunded amount (in token/ETH units, not wei)
+    pub amount: Decimal,
+
+    /// Refunded currency (the currency the payment was made in)
+    pub currency: Currency,
+
+    /// Required number of confirmations
+    pub required_confirmations: u64,
+
+    /// Current status
+    pub status: RefundStatus,
+
+    /// Creation timestamp
+    pub created_at: DateTime<Utc>,
+
+    /// Last update timestamp
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Refund {
+    /// Payment request the refund transaction has to satisfy
+    fn request(&self) -> PaymentRequest {
+        PaymentRequest::new(
+            self.amount,
+            self.currency.clone(),
+            self.recipient.clone(),
+            self.required_confirmations,
+        )
+    }
+}
+
+impl Payment {
+    /// Refunds recorded for this payment, oldest first
+    pub fn refunds(&self) -> Vec<Refund> {
+        self.metadata
+            .get(METADATA_KEY)
+            .and_then(|refunds| serde_json::from_value(refunds.clone()).ok())
+            .unwrap_or_default()
+    }
+
+    /// Add or replace (by ID) a refund in the payment's metadata
+    fn record_refund(&mut self, refund: &Refund) -> Result<()> {
+        let mut refunds = self.refunds();
+        match refunds.iter_mut().find(|r| r.id == refund.id) {
+            Some(existing) => *existing = refund.clone(),
+            None => refunds.push(refund.clone()),
+        }
+
+        let refunds = serde_json::to_value(refunds)?;
+        match self.metadata.as_object_mut() {
+            Some(metadata) => {
+                metadata.insert(METADATA_KEY.to_string(), refunds);
+            }
+            None => self.metadata = serde_json::json!({ METADATA_KEY: refunds }),
+        }
+        self.updated_at = Utc::now();
+        Ok(())
+    }
+}
+
+/// Verifies and tracks refund transactions
+pub struct RefundTracker {
+    verifier: PaymentVerifier,
+}
+
+impl RefundTracker {
+    /// Create a new refund tracker
+    pub fn new(client: BscScanClient) -> Self {
+        Self {
+            verifier: PaymentVerifier::new(client),
+        }
+    }
+
+    /// Register a refund of `payment` made by `tx_hash`
+    ///
+    /// The refund recipient and currency are taken from the original payment
+    /// transaction; `amount` defaults to the full amount received. The refund
+    /// is verified once before returning.
+    ///
+    /// Fails if the payment has no single detected or confirmed transaction.
+    pub async fn refund(
+        &self,
+        payment: &Payment,
+        tx_hash: TxHash,
+        amount: Option<Decimal>,
+    ) -> Result<Refund> {
+        let original = match &payment.status {
+            PaymentStatus::Detected { tx_hash, .. } | PaymentStatus::Confirmed { tx_hash, .. } => {
+                TxHash::parse(tx_hash)?
+            }
+            _ => {
+                return Err(Error::verification_failed(format!(
+                    "payment {} has no transaction to refund",
+                    payment.id
+                )))
+            }
+        };
+
+        let result = self.verifier.verify_tx(&original, &payment.request).await?;
+        let details = result.details().ok_or_else(|| {
+            Error::verification_failed(format!(
+                "original transaction {} no longer pays the request",
+                original
+            ))
+        })?;
+
+        let now = Utc::now();
+        let mut refund = Refund {
+            id: Uuid::new_v4(),
+            payment_id: payment.id,
+            tx_hash,
+            recipient: details.from.clone(),
+            amount: amount.unwrap_or(details.amount),
+            currency: details.currency.clone(),
+            required_confirmations: payment.request.required_confirmations,
+            status: RefundStatus::Pending { confirmations: 0 },
+            created_at: now,
+            updated_at: now,
+        };
+        self.update(&mut refund).await?;
+
+        Ok(refund)
+    }
+
+    /// Re-verify a refund transaction and update its status
+    pub async fn update(&self, refund: &mut Refund) -> Result<RefundStatus> {
+        let result = self
+            .verifier
+            .verify_tx(&refund.tx_hash, &refund.request())
+            .await?;
+
+        refund.status = match result {
+            VerificationResult::Confirmed { confirmations, .. } => {
+                RefundStatus::Confirmed { confirmations }
+            }
+            VerificationResult::Pending { confirmations, .. } => {
+                RefundStatus::Pending { confirmations }
+            }
+            VerificationResult::NotFound | VerificationResult::Seen { .. } => {
+                RefundStatus::Pending { confirmations: 0 }
+            }
+            VerificationResult::PartiallyPaid { received, .. } => RefundStatus::Failed {
+                reason: format!("only {} refunded", received),
+            },
+            VerificationResult::Failed { reason } => RefundStatus::Failed { reason },
+        };
+        refund.updated_at = Utc::now();
+
+        Ok(refund.status.clone())
+    }
+
+    /// Store a refund next to its payment
+    pub async fn save<S: PaymentStorage + ?Sized>(
+        &self,
+        storage: &S,
+        refund: &Refund,
+    ) -> Result<()> {
+        let mut payment = storage
+            .get_payment(refund.payment_id)
+            .await?
+            .ok_or(Error::PaymentNotFound(refund.payment_id))?;
+        payment.record_refund(refund)?;
+        storage.save_payment(&payment).await
+    }
+
+    /// Poll a refund until it is confirmed or fails, storing every status change
+    pub async fn track<S: PaymentStorage + ?Sized>(
+        &self,
+        storage: &S,
+        refund: &mut Refund,
+        poll_interval: Duration,
+    ) -> Result<()> {
+        loop {
+            let previous = refund.status.clone();
+            self.update(refund).await?;
+            if refund.status != previous {
+                self.save(storage, refund).await?;
+            }
+
+            if refund.status.is_finalized() {
+                return Ok(());
+            }
+            sleep(poll_interval).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::MemoryStorage;
+
+    fn address(last: u8) -> Address {
+        format!("0x{:040x}", last).parse().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_refunds_stored_in_payment_metadata() {
+        let storage = MemoryStorage::new();
+        let payment = Payment::new(PaymentRequest::eth(Decimal::ONE, address(1), 12))
+            .with_metadata(serde_json::json!({ "order": 7 }));
+        storage.save_payment(&payment).await.unwrap();
+
+        let now = Utc::now();
+        let mut refund = Refund {
+            id: Uuid::new_v4(),
+            payment_id: payment.id,
+            tx_hash: format!("0x{:064x}", 1).parse().unwrap(),
+            recipient: address(2),
+            amount: Decimal::new(5, 1),
+            currency: Currency::Native,
+            required_confirmations: 12,
+            status: RefundStatus::Pending { confirmations: 0 },
+            created_at: now,
+            updated_at: now,
+        };
+
+        let tracker = RefundTracker::new(BscScanClient::new("test-key").unwrap());
+        tracker.save(&storage, &refund).await.unwrap();
+
+        refund.status = RefundStatus::Confirmed { confirmations: 12 };
+        tracker.save(&storage, &refund).await.unwrap();
+
+        let stored = storage.get_payment(payment.id).await.unwrap().unwrap();
+        assert_eq!(stored.refunds(), vec![refund]);
+        assert_eq!(stored.metadata["order"], 7);
+    }
+}
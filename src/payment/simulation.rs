@@ -0,0 +1,368 @@
+//! Simulated payments for staging environments
+//!
+//! [`SimulatedVerifier`] and [`SimulatedMonitor`] mirror [`PaymentVerifier`]
+//! and [`PaymentMonitor`] without touching a chain: every request is detected
+//! after a delay and gains one confirmation per interval until it is
+//! confirmed. Results can also be injected per request, e.g. to exercise
+//! failure paths of a checkout. With a [`MockClock`](crate::clock::MockClock)
+//! the schedule runs in simulated time.
+//!
+//! [`PaymentVerifier`]: crate::payment::PaymentVerifier
+//! [`PaymentMonitor`]: crate::payment::PaymentMonitor
+
+use crate::clock::{Clock, SystemClock};
+use crate::error::Result;
+use crate::payment::models::{PaymentRequest, PaymentStatus};
+use crate::payment::verification::{PaymentDetails, VerificationResult};
+use crate::primitives::{Address, TxHash};
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+/// Sender reported for simulated payments
+pub const SIMULATED_SENDER: &str = "0x000000000000000000000000000000000000dead";
+
+/// Block number of the first simulated confirmation
+const SIMULATED_BLOCK: u64 = 1_000_000;
+
+/// When simulated payments progress
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SimulationSchedule {
+    /// Time from the first check of a request until its payment is detected
+    pub detect_after: Duration,
+    /// Time between confirmations once detected
+    pub confirmation_interval: Duration,
+}
+
+impl Default for SimulationSchedule {
+    fn default() -> Self {
+        Self {
+            detect_after: Duration::from_secs(10),
+            confirmation_interval: Duration::from_secs(2),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Simulation {
+    first_seen: DateTime<Utc>,
+    injected: Option<VerificationResult>,
+}
+
+/// Verifier that simulates payments on a schedule
+///
+/// Requests are told apart by their content; the clock of a request starts
+/// the first time it is verified. Clones share their state, so a
+/// [`SimulatedMonitor`] and an injection endpoint can use the same verifier.
+///
+/// # Example
+/// ```
+/// use cryptopay::payment::{SimulatedVerifier, SimulationSchedule};
+/// use cryptopay::{PaymentRequest, VerificationResult};
+/// use std::time::Duration;
+///
+/// # tokio_test::block_on(async {
+/// let verifier = SimulatedVerifier::new(SimulationSchedule {
+///     detect_after: Duration::ZERO,
+///     confirmation_interval: Duration::from_secs(60),
+/// });
+/// let request = PaymentRequest::eth(
+///     "0.1".parse().unwrap(),
+///     "0x742d35cc6634c0532925a3b844bc9e7595f0beb0".parse().unwrap(),
+///     12,
+/// );
+///
+/// let result = verifier.verify_payment(&request).await.unwrap();
+/// assert!(matches!(result, VerificationResult::Pending { confirmations: 1, .. }));
+///
+/// verifier.confirm(&request);
+/// assert!(matches!(
+///     verifier.verify_payment(&request).await.unwrap(),
+///     VerificationResult::Confirmed { .. }
+/// ));
+/// # });
+/// ```
+#[derive(Debug, Clone)]
+pub struct SimulatedVerifier {
+    schedule: SimulationSchedule,
+    clock: Arc<dyn Clock>,
+    simulations: Arc<Mutex<HashMap<String, Simulation>>>,
+}
+
+impl Default for SimulatedVerifier {
+    fn default() -> Self {
+        Self::new(SimulationSchedule::default())
+    }
+}
+
+impl SimulatedVerifier {
+    /// Create a verifier progressing payments on `schedule`
+    pub fn new(schedule: SimulationSchedule) -> Self {
+        Self {
+            schedule,
+            clock: Arc::new(SystemClock),
+            simulations: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Use `clock` for the schedule and for [`SimulatedMonitor`] polls
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Schedule payments progress on
+    pub fn schedule(&self) -> SimulationSchedule {
+        self.schedule
+    }
+
+    /// Simulated verification result of a request
+    pub async fn verify_payment(&self, request: &PaymentRequest) -> Result<VerificationResult> {
+        let key = simulation_key(request);
+        let mut simulations = self.lock();
+        let simulation = simulations
+            .entry(key.clone())
+            .or_insert_with(|| Simulation {
+                first_seen: self.clock.now(),
+                injected: None,
+            });
+
+        if let Some(result) = &simulation.injected {
+            return Ok(result.clone());
+        }
+
+        let elapsed = (self.clock.now() - simulation.first_seen)
+            .to_std()
+            .unwrap_or_default();
+        let Some(detected_for) = elapsed.checked_sub(self.schedule.detect_after) else {
+            return Ok(VerificationResult::NotFound);
+        };
+        let confirmations = match self.schedule.confirmation_interval.as_nanos() {
+            0 => request.required_confirmations.max(1),
+            interval => 1 + (detected_for.as_nanos() / interval) as u64,
+        };
+
+        Ok(simulated_result(request, &key, confirmations))
+    }
+
+    /// Simulated status of a request
+    pub async fn check_payment_status(&self, request: &PaymentRequest) -> Result<PaymentStatus> {
+        Ok(PaymentStatus::from(self.verify_payment(request).await?))
+    }
+
+    /// Always report `result` for a request, overriding the schedule
+    pub fn inject(&self, request: &PaymentRequest, result: VerificationResult) {
+        let mut simulations = self.lock();
+        let simulation = simulations
+            .entry(simulation_key(request))
+            .or_insert_with(|| Simulation {
+                first_seen: self.clock.now(),
+                injected: None,
+            });
+        simulation.injected = Some(result);
+    }
+
+    /// Report the payment as detected with `confirmations`
+    pub fn detect(&self, request: &PaymentRequest, confirmations: u64) {
+        let key = simulation_key(request);
+        self.inject(request, simulated_result(request, &key, confirmations));
+    }
+
+    /// Report the payment as confirmed
+    pub fn confirm(&self, request: &PaymentRequest) {
+        self.detect(request, request.required_confirmations.max(1));
+    }
+
+    /// Report the payment as failed
+    pub fn fail(&self, request: &PaymentRequest, reason: impl Into<String>) {
+        self.inject(
+            request,
+            VerificationResult::Failed {
+                reason: reason.into(),
+            },
+        );
+    }
+
+    /// Forget a request: remove injected results and restart its schedule
+    pub fn reset(&self, request: &PaymentRequest) {
+        self.lock().remove(&simulation_key(request));
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, Simulation>> {
+        self.simulations
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Monitor polling a [`SimulatedVerifier`]
+///
+/// Behaves like [`PaymentMonitor::start_monitoring`](crate::PaymentMonitor::start_monitoring):
+/// the callback is called on every status change until the payment is
+/// confirmed, failed, or expired.
+#[derive(Debug, Clone)]
+pub struct SimulatedMonitor {
+    verifier: SimulatedVerifier,
+    poll_interval: Duration,
+}
+
+impl SimulatedMonitor {
+    /// Create a monitor polling `verifier` every `poll_interval`
+    pub fn new(verifier: SimulatedVerifier, poll_interval: Duration) -> Self {
+        Self {
+            verifier,
+            poll_interval,
+        }
+    }
+
+    /// Verifier the monitor polls, e.g. to inject results
+    pub fn verifier(&self) -> &SimulatedVerifier {
+        &self.verifier
+    }
+
+    /// Monitor a simulated payment until it is finalized
+    pub async fn start_monitoring<F>(&self, request: PaymentRequest, callback: F) -> Result<()>
+    where
+        F: Fn(PaymentStatus) + Send + Sync,
+    {
+        let mut last_status: Option<PaymentStatus> = None;
+
+        loop {
+            let status = self.verifier.check_payment_status(&request).await?;
+            if last_status.as_ref() != Some(&status) {
+                callback(status.clone());
+                last_status = Some(status.clone());
+            }
+
+            if status.is_finalized() {
+                return Ok(());
+            }
+
+            self.verifier.clock.sleep(self.poll_interval).await;
+        }
+    }
+
+    /// Simulated status of a payment
+    pub async fn check_payment_status(&self, request: &PaymentRequest) -> Result<PaymentStatus> {
+        self.verifier.check_payment_status(request).await
+    }
+}
+
+fn simulation_key(request: &PaymentRequest) -> String {
+    serde_json::to_string(request).unwrap_or_else(|_| format!("{:?}", request))
+}
+
+/// Deterministic result for a request with `confirmations`
+fn simulated_result(request: &PaymentRequest, key: &str, confirmations: u64) -> VerificationResult {
+    let tx_hash: TxHash = format!("0x{}", hex::encode(Sha256::digest(key.as_bytes())))
+        .parse()
+        .expect("a SHA-256 digest is a valid transaction hash");
+    let details = PaymentDetails {
+        currency: request.currency.clone(),
+        amount: request.amount,
+        from: Address::known(SIMULATED_SENDER),
+        to: request.recipient_address.clone(),
+        block_number: SIMULATED_BLOCK,
+        timestamp: None,
+        gas_cost: None,
+        funded_by: None,
+    };
+
+    if confirmations >= request.required_confirmations {
+        VerificationResult::Confirmed {
+            tx_hash,
+            confirmations,
+            details,
+        }
+    } else {
+        VerificationResult::Pending {
+            tx_hash,
+            confirmations,
+            details,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use rust_decimal::Decimal;
+
+    fn request() -> PaymentRequest {
+        PaymentRequest::eth(
+            Decimal::ONE,
+            "0x742d35cc6634c0532925a3b844bc9e7595f0beb0"
+                .parse()
+                .unwrap(),
+            3,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_monitor_follows_schedule() {
+        let clock = MockClock::default().auto_advance(true);
+        let verifier = SimulatedVerifier::new(SimulationSchedule {
+            detect_after: Duration::from_secs(10),
+            confirmation_interval: Duration::from_secs(5),
+        })
+        .with_clock(Arc::new(clock.clone()));
+        let monitor = SimulatedMonitor::new(verifier, Duration::from_secs(1));
+
+        let statuses = Arc::new(Mutex::new(Vec::new()));
+        let seen = statuses.clone();
+        monitor
+            .start_monitoring(request(), move |status| seen.lock().unwrap().push(status))
+            .await
+            .unwrap();
+
+        let statuses = statuses.lock().unwrap();
+        let confirmations: Vec<Option<u64>> = statuses
+            .iter()
+            .map(|status| match status {
+                PaymentStatus::Pending => None,
+                PaymentStatus::Detected { confirmations, .. }
+                | PaymentStatus::Confirmed { confirmations, .. } => Some(*confirmations),
+                other => panic!("unexpected status {:?}", other),
+            })
+            .collect();
+        assert_eq!(confirmations, vec![None, Some(1), Some(2), Some(3)]);
+        assert!(matches!(statuses[3], PaymentStatus::Confirmed { .. }));
+        assert_eq!(clock.now().timestamp(), 20);
+    }
+
+    #[tokio::test]
+    async fn test_injected_results() {
+        let verifier = SimulatedVerifier::new(SimulationSchedule {
+            detect_after: Duration::from_secs(3600),
+            confirmation_interval: Duration::from_secs(60),
+        });
+        let request = request();
+        assert_eq!(
+            verifier.verify_payment(&request).await.unwrap(),
+            VerificationResult::NotFound
+        );
+
+        verifier.fail(&request, "rejected by staging");
+        assert_eq!(
+            verifier.check_payment_status(&request).await.unwrap(),
+            PaymentStatus::Failed {
+                reason: "rejected by staging".to_string()
+            }
+        );
+
+        verifier.reset(&request);
+        verifier.detect(&request, 1);
+        let result = verifier.verify_payment(&request).await.unwrap();
+        assert_eq!(result.details().unwrap().amount, Decimal::ONE);
+        assert!(matches!(
+            result,
+            VerificationResult::Pending {
+                confirmations: 1,
+                ..
+            }
+        ));
+    }
+}
@@ -0,0 +1,92 @@
+//! EIP-681 payment URIs
+//!
+//! Wallets understand `ethereum:` URIs (usually scanned from a QR code) and
+//! pre-fill the transaction from them:
+//!
+//! - native: `ethereum:<recipient>@<chain_id>?value=<wei>`
+//! - ERC20: `ethereum:<token>@<chain_id>/transfer?address=<recipient>&uint256=<raw amount>`
+
+use crate::payment::models::{Currency, PaymentRequest};
+use crate::primitives::TokenAmount;
+use rust_decimal::RoundingStrategy;
+
+/// Chain ID used by [`PaymentRequest::to_eip681_uri`]
+const MAINNET_CHAIN_ID: u64 = 1;
+
+impl PaymentRequest {
+    /// Build an EIP-681 payment URI for Ethereum mainnet
+    ///
+    /// # Example
+    /// ```
+    /// # use cryptopay::PaymentRequest;
+    /// # use rust_decimal::Decimal;
+    /// let request = PaymentRequest::eth(
+    ///     Decimal::new(15, 2), // 0.15 ETH
+    ///     "0x742D35CC6634c0532925A3b844BC9E7595F0BEb0".parse().unwrap(),
+    ///     12,
+    /// );
+    /// assert_eq!(
+    ///     request.to_eip681_uri(),
+    ///     "ethereum:0x742D35CC6634c0532925A3b844BC9E7595F0BEb0@1?value=150000000000000000"
+    /// );
+    /// ```
+    pub fn to_eip681_uri(&self) -> String {
+        self.to_eip681_uri_for_chain(MAINNET_CHAIN_ID)
+    }
+
+    /// Build an EIP-681 payment URI for the given chain
+    ///
+    /// Only the primary option is encoded; alternatives need a URI each
+    /// (see [`option_requests`](Self::option_requests)).
+    pub fn to_eip681_uri_for_chain(&self, chain_id: u64) -> String {
+        let decimals = self.currency.decimals();
+        let amount = self
+            .amount
+            .round_dp_with_strategy(decimals as u32, RoundingStrategy::ToZero);
+        let raw_amount = TokenAmount::from_decimal(amount, decimals)
+            .map(|amount| amount.raw())
+            .unwrap_or_default();
+
+        match &self.currency {
+            Currency::Native => format!(
+                "ethereum:{}@{}?value={}",
+                self.recipient_address.to_checksum(),
+                chain_id,
+                raw_amount
+            ),
+            Currency::ERC20 {
+                contract_address, ..
+            } => format!(
+                "ethereum:{}@{}/transfer?address={}&uint256={}",
+                contract_address.to_checksum(),
+                chain_id,
+                self.recipient_address.to_checksum(),
+                raw_amount
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+
+    #[test]
+    fn test_erc20_uri() {
+        let request = PaymentRequest::new(
+            Decimal::new(2550, 2), // 25.50 USDC
+            Currency::usdc(),
+            "0x742D35CC6634c0532925A3b844BC9E7595F0BEb0"
+                .parse()
+                .unwrap(),
+            6,
+        );
+
+        assert_eq!(
+            request.to_eip681_uri_for_chain(11155111),
+            "ethereum:0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48@11155111/transfer\
+             ?address=0x742D35CC6634c0532925A3b844BC9E7595F0BEb0&uint256=25500000"
+        );
+    }
+}
@@ -1,31 +1,28 @@
 //! Payment utility functions for amount conversion and comparison
 
 use crate::error::{Error, Result};
-use rust_decimal::prelude::ToPrimitive;
-use rust_decimal::Decimal;
+use crate::primitives::{TokenAmount, U256};
+use rust_decimal::{Decimal, RoundingStrategy};
+use sha3::{Digest, Keccak256};
 
-/// Convert wei to BNB/ether
+/// Convert wei to ether (or the chain's native currency)
 pub fn wei_to_ether(wei: u128) -> Decimal {
-    Decimal::from(wei) / Decimal::from(1_000_000_000_000_000_000u128)
+    raw_to_token(wei, 18)
 }
 
-/// Convert BNB/ether to wei
+/// Convert ether (or the chain's native currency) to wei
 pub fn ether_to_wei(ether: Decimal) -> u128 {
-    (ether * Decimal::from(1_000_000_000_000_000_000u128))
-        .to_u128()
-        .unwrap_or(0)
+    token_to_raw(ether, 18)
 }
 
 /// Convert gwei to wei
 pub fn gwei_to_wei(gwei: Decimal) -> u128 {
-    (gwei * Decimal::from(1_000_000_000u128))
-        .to_u128()
-        .unwrap_or(0)
+    token_to_raw(gwei, 9)
 }
 
 /// Convert wei to gwei
 pub fn wei_to_gwei(wei: u128) -> Decimal {
-    Decimal::from(wei) / Decimal::from(1_000_000_000u128)
+    raw_to_token(wei, 9)
 }
 
 /// Parse token amount from string with custom decimals
@@ -42,31 +39,54 @@ pub fn parse_token_amount(amount: &str, _decimals: u8) -> Result<u128> {
         .map_err(|_| Error::generic(format!("Invalid token amount: {}", amount)))
 }
 
+/// Parse a `0x`-prefixed hex quantity as returned by JSON-RPC proxy endpoints
+///
+/// # Example
+/// ```
+/// # use cryptopay::payment::utils::parse_hex_u128;
+/// assert_eq!(parse_hex_u128("0x5208").unwrap(), 21000);
+/// ```
+pub fn parse_hex_u128(value: &str) -> Result<u128> {
+    let digits = value.trim_start_matches("0x");
+    if digits.is_empty() {
+        return Ok(0);
+    }
+    u128::from_str_radix(digits, 16)
+        .map_err(|_| Error::generic(format!("Invalid hex quantity: {}", value)))
+}
+
 /// Format token amount to string with custom decimals
 ///
-/// Converts raw token amount (in smallest unit) to human-readable format
+/// Converts raw token amount (in smallest unit) to human-readable format,
+/// without trailing zeros.
+///
+/// # Example
+/// ```
+/// # use cryptopay::payment::utils::format_token_amount;
+/// assert_eq!(format_token_amount(1_500_000, 6), "1.5");
+/// ```
 pub fn format_token_amount(amount: u128, decimals: u8) -> String {
-    let divisor = 10u128.pow(decimals as u32);
-    let whole = amount / divisor;
-    let fractional = amount % divisor;
-
-    if fractional == 0 {
-        whole.to_string()
-    } else {
-        format!("{}.{:0width$}", whole, fractional, width = decimals as usize)
-    }
+    TokenAmount::new(U256::from(amount), decimals).to_string()
 }
 
 /// Convert token amount (human-readable) to raw units
+///
+/// Digits beyond `decimals` are truncated. Returns 0 for negative amounts and
+/// amounts that don't fit in a `u128`; use [`TokenAmount::from_decimal`] for
+/// checked conversion.
 pub fn token_to_raw(amount: Decimal, decimals: u8) -> u128 {
-    let multiplier = 10u128.pow(decimals as u32);
-    (amount * Decimal::from(multiplier)).to_u128().unwrap_or(0)
+    let truncated = amount.round_dp_with_strategy(decimals as u32, RoundingStrategy::ToZero);
+    TokenAmount::from_decimal(truncated, decimals)
+        .ok()
+        .and_then(|amount| u128::try_from(amount.raw()).ok())
+        .unwrap_or(0)
 }
 
 /// Convert raw token units to human-readable amount
+///
+/// Rounded to `Decimal` precision (28 significant digits).
 pub fn raw_to_token(raw_amount: u128, decimals: u8) -> Decimal {
-    let divisor = 10u128.pow(decimals as u32);
-    Decimal::from(raw_amount) / Decimal::from(divisor)
+    TokenAmount::new(U256::from(raw_amount), decimals).to_decimal_lossy()
 }
 
 /// Compare two amounts with tolerance
@@ -95,7 +115,31 @@ pub fn amount_sufficient(expected: Decimal, actual: Decimal, min_percent: Decima
 }
 
 /// Validate Ethereum/BSC address format
+///
+/// Mixed-case addresses must carry a valid EIP-55 checksum; all-lowercase and
+/// all-uppercase addresses are accepted as unchecksummed. Use
+/// [`is_valid_address_format`] to skip the checksum check.
+///
+/// # Example
+/// ```
+/// # use cryptopay::payment::utils::is_valid_address;
+/// assert!(is_valid_address("0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed"));
+/// assert!(is_valid_address("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"));
+/// assert!(!is_valid_address("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD"));
+/// ```
 pub fn is_valid_address(address: &str) -> bool {
+    if !is_valid_address_format(address) {
+        return false;
+    }
+
+    let digits = &address[2..];
+    let mixed_case = digits.chars().any(|c| c.is_ascii_lowercase())
+        && digits.chars().any(|c| c.is_ascii_uppercase());
+    !mixed_case || is_checksum_valid(address)
+}
+
+/// Validate address format only (`0x` + 40 hex digits), ignoring the checksum
+pub fn is_valid_address_format(address: &str) -> bool {
     if !address.starts_with("0x") {
         return false;
     }
@@ -107,6 +151,46 @@ pub fn is_valid_address(address: &str) -> bool {
     address[2..].chars().all(|c| c.is_ascii_hexdigit())
 }
 
+/// Convert an address to its EIP-55 mixed-case checksum form
+///
+/// # Example
+/// ```
+/// # use cryptopay::payment::utils::to_checksum_address;
+/// assert_eq!(
+///     to_checksum_address("0xfb6916095ca1df60bb79ce92ce3ea74c37c5d359").unwrap(),
+///     "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359"
+/// );
+/// ```
+pub fn to_checksum_address(address: &str) -> Result<String> {
+    if !is_valid_address_format(address) {
+        return Err(Error::InvalidAddress(address.to_string()));
+    }
+
+    let lower = address[2..].to_ascii_lowercase();
+    let hash = Keccak256::digest(lower.as_bytes());
+
+    let mut checksummed = String::with_capacity(42);
+    checksummed.push_str("0x");
+    for (i, c) in lower.chars().enumerate() {
+        let nibble = (hash[i / 2] >> if i % 2 == 0 { 4 } else { 0 }) & 0x0f;
+        if c.is_ascii_alphabetic() && nibble >= 8 {
+            checksummed.push(c.to_ascii_uppercase());
+        } else {
+            checksummed.push(c);
+        }
+    }
+
+    Ok(checksummed)
+}
+
+/// Check if an address matches its EIP-55 checksum exactly
+///
+/// Unlike [`is_valid_address`], this rejects all-lowercase addresses that
+/// aren't also their own checksum form.
+pub fn is_checksum_valid(address: &str) -> bool {
+    to_checksum_address(address).is_ok_and(|checksummed| checksummed == address)
+}
+
 /// Validate transaction hash format
 pub fn is_valid_tx_hash(hash: &str) -> bool {
     if !hash.starts_with("0x") {
@@ -140,6 +224,21 @@ mod tests {
         let raw = token_to_raw(Decimal::from(100), 18);
         let back = raw_to_token(raw, 18);
         assert_eq!(back, Decimal::from(100));
+
+        // Previously overflowed Decimal or 10u128.pow
+        assert_eq!(
+            token_to_raw(Decimal::from(1_000_000_000_000i64), 18),
+            10u128.pow(30)
+        );
+        assert_eq!(
+            raw_to_token(u128::MAX, 40),
+            Decimal::from_str("0.0340282366920938463463374607").unwrap()
+        );
+        assert_eq!(
+            format_token_amount(10, 40),
+            "0.000000000000000000000000000000000000001"
+        );
+        assert_eq!(token_to_raw(Decimal::new(1_999, 3), 2), 199);
     }
 
     #[test]
@@ -166,18 +265,61 @@ mod tests {
         assert!(!amount_sufficient(expected, actual_low, min_percent));
     }
 
+    #[test]
+    fn test_parse_hex_u128() {
+        assert_eq!(parse_hex_u128("0x0").unwrap(), 0);
+        assert_eq!(parse_hex_u128("0x").unwrap(), 0);
+        assert_eq!(
+            parse_hex_u128("0xde0b6b3a7640000").unwrap(),
+            1_000_000_000_000_000_000
+        );
+        assert!(parse_hex_u128("0xzz").is_err());
+    }
+
     #[test]
     fn test_address_validation() {
         assert!(is_valid_address(
             "0x1234567890123456789012345678901234567890"
         ));
-        assert!(!is_valid_address("1234567890123456789012345678901234567890")); // No 0x
+        assert!(!is_valid_address(
+            "1234567890123456789012345678901234567890"
+        )); // No 0x
         assert!(!is_valid_address("0x123")); // Too short
         assert!(!is_valid_address(
             "0xGGGG567890123456789012345678901234567890"
         )); // Invalid hex
     }
 
+    #[test]
+    fn test_checksum_addresses() {
+        // Test vectors from EIP-55
+        for address in [
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+            "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+            "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+            "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+        ] {
+            assert_eq!(
+                to_checksum_address(&address.to_lowercase()).unwrap(),
+                address
+            );
+            assert!(is_checksum_valid(address));
+            assert!(is_valid_address(address));
+        }
+
+        // One flipped letter case
+        assert!(!is_valid_address(
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD"
+        ));
+        assert!(!is_checksum_valid(
+            "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed"
+        ));
+        assert!(is_valid_address(
+            "0x5AAEB6053F3E94C9B9A09F33669435E7EF1BEAED"
+        ));
+        assert!(to_checksum_address("0x123").is_err());
+    }
+
     #[test]
     fn test_tx_hash_validation() {
         assert!(is_valid_tx_hash(
@@ -1,38 +1,107 @@
 //! Payment verification logic
 
-use crate::client::endpoints::{AccountEndpoints, TokenEndpoints, TransactionEndpoints};
+use crate::abi::{decode_transfers, DecodedCall};
+use crate::client::endpoints::{AccountEndpoints, BlockEndpoints, Closest, TransactionEndpoints};
+use crate::client::types::{
+    BeaconWithdrawal, FundingSource, InternalTransaction, RpcReceipt, RpcTransaction,
+    TokenTransfer, Transaction,
+};
 use crate::client::BscScanClient;
 use crate::error::{Error, Result};
-use crate::payment::models::{Currency, PaymentRequest};
-use crate::payment::utils::{amount_sufficient, is_valid_address};
+use crate::payment::models::{Currency, PaymentRequest, PaymentStatus};
+use crate::payment::utils::parse_hex_u128;
+use crate::primitives::{Address, TokenAmount, TxHash};
+use crate::rt::sleep;
+use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
 
 /// Payment verifier
 pub struct PaymentVerifier {
     client: BscScanClient,
+    accept_unconfirmed: bool,
+    scan_internal: bool,
+    trace_funding: bool,
+    matcher: Option<Matcher>,
 }
 
+/// Transfer offered to a custom matcher
+#[derive(Debug, Clone, Copy)]
+pub enum TransferRef<'a> {
+    /// Native transaction
+    Transaction(&'a Transaction),
+    /// Internal transaction (value sent by a contract)
+    Internal(&'a InternalTransaction),
+    /// ERC20 token transfer
+    Token(&'a TokenTransfer),
+}
+
+/// Verdict of a custom matcher on a transfer
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MatchDecision {
+    /// Apply the built-in amount and sender checks
+    #[default]
+    Default,
+    /// Count the transfer as paying the request, skipping the built-in checks
+    Accept,
+    /// Ignore the transfer
+    Reject,
+}
+
+/// Custom matching predicate, see [`PaymentVerifier::with_matcher`]
+pub type Matcher = Arc<dyn Fn(TransferRef<'_>, &PaymentRequest) -> MatchDecision + Send + Sync>;
+
 /// Verification result
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub enum VerificationResult {
     /// No matching transaction found
     NotFound,
 
+    /// Matching transaction seen in the mempool, not yet included in a block
+    ///
+    /// Only reported for a known transaction hash, by
+    /// [`PaymentVerifier::verify_tx`] and
+    /// [`PaymentVerifier::check_pending_transaction`] in `accept_unconfirmed`
+    /// mode; history scans only see mined transactions.
+    Seen {
+        /// Transaction hash
+        tx_hash: TxHash,
+    },
+
     /// Transaction found but not yet confirmed
     Pending {
         /// Transaction hash
-        tx_hash: String,
+        tx_hash: TxHash,
         /// Current confirmations
         confirmations: u64,
+        /// Details of the matched transaction
+        details: PaymentDetails,
     },
 
     /// Payment confirmed
     Confirmed {
         /// Transaction hash
-        tx_hash: String,
+        tx_hash: TxHash,
         /// Final confirmations
         confirmations: u64,
+        /// Details of the matched transaction
+        details: PaymentDetails,
+    },
+
+    /// Some inbound transfers found, but their total is below the requested amount
+    ///
+    /// Only reported for requests that accept partial payments.
+    PartiallyPaid {
+        /// Total amount received so far
+        received: Decimal,
+        /// Amount still missing
+        remaining: Decimal,
+        /// Hashes of the transfers counted towards the total
+        tx_hashes: Vec<TxHash>,
     },
 
     /// Payment failed verification
@@ -42,168 +111,2062 @@ pub enum VerificationResult {
     },
 }
 
+impl VerificationResult {
+    /// How far along the payment is, used to pick the best of several results
+    fn progress(&self) -> u8 {
+        match self {
+            VerificationResult::NotFound => 0,
+            VerificationResult::Failed { .. } => 1,
+            VerificationResult::PartiallyPaid { .. } => 2,
+            VerificationResult::Seen { .. } => 3,
+            VerificationResult::Pending { .. } => 4,
+            VerificationResult::Confirmed { .. } => 5,
+        }
+    }
+
+    /// Details of the matched transfer, if the payment was found
+    pub fn details(&self) -> Option<&PaymentDetails> {
+        match self {
+            VerificationResult::Pending { details, .. }
+            | VerificationResult::Confirmed { details, .. } => Some(details),
+            _ => None,
+        }
+    }
+}
+
+/// Details of the on-chain transfer matched to a payment request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct PaymentDetails {
+    /// Currency that was received
+    pub currency: Currency,
+    /// Amount actually received (in token/ETH units, not wei)
+    pub amount: Decimal,
+    /// Sender address
+    pub from: Address,
+    /// Recipient address
+    pub to: Address,
+    /// Block the transfer was included in
+    pub block_number: u64,
+    /// Block timestamp, if reported by the explorer
+    pub timestamp: Option<DateTime<Utc>>,
+    /// Gas fee the sender paid, in the chain's native currency (if known)
+    #[serde(default)]
+    pub gas_cost: Option<Decimal>,
+    /// Transfer that first funded the sender, if the verifier traces funding
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub funded_by: Option<Box<FundingSource>>,
+}
+
+impl PaymentDetails {
+    /// Build details from a native-currency transaction
+    pub fn from_transaction(tx: &Transaction) -> Self {
+        Self {
+            currency: Currency::Native,
+            amount: tx.value_native(),
+            from: tx.from.clone(),
+            to: recipient_of(tx.to.as_ref(), &tx.contract_address),
+            block_number: tx.block_number,
+            timestamp: tx.time_stamp,
+            gas_cost: Some(tx.gas_cost_eth()),
+            funded_by: None,
+        }
+    }
+
+    /// Build details from an internal (contract-initiated) transaction
+    pub fn from_internal_transaction(tx: &InternalTransaction) -> Self {
+        Self {
+            currency: Currency::Native,
+            amount: tx.value_native(),
+            from: tx.from.clone(),
+            to: recipient_of(tx.to.as_ref(), &tx.contract_address),
+            block_number: tx.block_number,
+            timestamp: tx.time_stamp,
+            // Paid by the outer transaction, which isn't part of the listing
+            gas_cost: None,
+            funded_by: None,
+        }
+    }
+
+    /// Build details from a beacon chain withdrawal
+    ///
+    /// Withdrawals have no sender; `from` is the zero address.
+    pub fn from_beacon_withdrawal(withdrawal: &BeaconWithdrawal) -> Self {
+        Self {
+            currency: Currency::Native,
+            amount: withdrawal.amount_native(),
+            from: zero_address(),
+            to: withdrawal.address.clone(),
+            block_number: withdrawal.block_number,
+            timestamp: withdrawal.timestamp,
+            gas_cost: None,
+            funded_by: None,
+        }
+    }
+
+    /// Build details from an ERC20 token transfer
+    pub fn from_token_transfer(transfer: &TokenTransfer) -> Self {
+        Self {
+            currency: Currency::erc20(transfer.contract_address.clone(), transfer.decimals()),
+            amount: transfer.value_tokens(),
+            from: transfer.from.clone(),
+            to: transfer.to.clone(),
+            block_number: transfer.block_number,
+            timestamp: transfer.time_stamp,
+            gas_cost: Some(transfer.gas_cost_eth()),
+            funded_by: None,
+        }
+    }
+
+    /// Amount received above what the request asked for, if any
+    pub fn overpayment(&self, request: &PaymentRequest) -> Option<Decimal> {
+        (self.amount > request.amount).then(|| self.amount - request.amount)
+    }
+
+    /// Gas fee the sender paid, in the chain's native currency (if known)
+    pub fn gas_cost_eth(&self) -> Option<Decimal> {
+        self.gas_cost
+    }
+
+    /// Amount net of the sender's gas fee
+    ///
+    /// Only defined for native-currency payments, where amount and fee share
+    /// a unit; `None` for token payments and when the fee is unknown.
+    pub fn effective_amount(&self) -> Option<Decimal> {
+        match self.currency {
+            Currency::Native => Some(self.amount - self.gas_cost?),
+            Currency::ERC20 { .. } => None,
+        }
+    }
+}
+
+/// Successful inbound transfer to a request's recipient
+#[derive(Clone)]
+struct Candidate {
+    tx_hash: TxHash,
+    confirmations: u64,
+    details: PaymentDetails,
+    source: Option<Source>,
+}
+
+/// Explorer record a candidate was built from
+#[derive(Clone)]
+enum Source {
+    Transaction(Transaction),
+    Internal(InternalTransaction),
+    Token(TokenTransfer),
+}
+
+impl Source {
+    fn as_transfer(&self) -> TransferRef<'_> {
+        match self {
+            Source::Transaction(tx) => TransferRef::Transaction(tx),
+            Source::Internal(tx) => TransferRef::Internal(tx),
+            Source::Token(transfer) => TransferRef::Token(transfer),
+        }
+    }
+}
+
+impl Candidate {
+    fn from_transaction(tx: Transaction) -> Self {
+        Self {
+            tx_hash: tx.hash.clone(),
+            confirmations: tx.confirmations,
+            details: PaymentDetails::from_transaction(&tx),
+            source: Some(Source::Transaction(tx)),
+        }
+    }
+
+    /// Confirmations are filled in by the caller; the explorer doesn't report them
+    fn from_internal_transaction(tx: InternalTransaction) -> Self {
+        Self {
+            tx_hash: tx.hash.clone(),
+            confirmations: 0,
+            details: PaymentDetails::from_internal_transaction(&tx),
+            source: Some(Source::Internal(tx)),
+        }
+    }
+
+    fn from_token_transfer(transfer: TokenTransfer) -> Self {
+        Self {
+            tx_hash: transfer.hash.clone(),
+            confirmations: transfer.confirmations,
+            details: PaymentDetails::from_token_transfer(&transfer),
+            source: Some(Source::Token(transfer)),
+        }
+    }
+
+    /// Whether the transfer pays the recipient and is not older than the request allows
+    fn is_inbound(&self, request: &PaymentRequest) -> bool {
+        if request.recipient_address != self.details.to {
+            return false;
+        }
+
+        match (request.not_before, self.details.timestamp) {
+            (Some(not_before), Some(timestamp)) => timestamp >= not_before,
+            _ => true,
+        }
+    }
+}
+
+/// Block inclusion of a transaction as currently reported by the chain
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TxInclusion {
+    /// Hash of the block containing the transaction
+    pub block_hash: String,
+    /// Number of the block containing the transaction
+    pub block_number: u64,
+    /// Current confirmations
+    pub confirmations: u64,
+}
+
+/// A previously detected transaction that the node no longer knows about
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DroppedTransaction {
+    /// Hash of the dropped transaction
+    pub tx_hash: TxHash,
+    /// Hash of a different transaction that pays the same request, if any
+    pub replacement: Option<TxHash>,
+}
+
+impl From<VerificationResult> for PaymentStatus {
+    fn from(result: VerificationResult) -> Self {
+        match result {
+            VerificationResult::NotFound => PaymentStatus::Pending,
+            VerificationResult::Seen { tx_hash } => PaymentStatus::Detected {
+                tx_hash: tx_hash.into(),
+                confirmations: 0,
+            },
+            VerificationResult::Pending {
+                tx_hash,
+                confirmations,
+                ..
+            } => PaymentStatus::Detected {
+                tx_hash: tx_hash.into(),
+                confirmations,
+            },
+            VerificationResult::Confirmed {
+                tx_hash,
+                confirmations,
+                ..
+            } => PaymentStatus::Confirmed {
+                tx_hash: tx_hash.into(),
+                confirmations,
+            },
+            VerificationResult::PartiallyPaid {
+                received,
+                remaining,
+                tx_hashes,
+            } => PaymentStatus::PartiallyPaid {
+                received,
+                remaining,
+                tx_hashes: tx_hashes.into_iter().map(String::from).collect(),
+            },
+            VerificationResult::Failed { reason } => PaymentStatus::Failed { reason },
+        }
+    }
+}
+
 impl PaymentVerifier {
     /// Create a new payment verifier
     pub fn new(client: BscScanClient) -> Self {
-        Self { client }
+        Self {
+            client,
+            accept_unconfirmed: false,
+            scan_internal: true,
+            trace_funding: false,
+            matcher: None,
+        }
+    }
+
+    /// Register a custom matching predicate
+    ///
+    /// The predicate sees every inbound transfer to a request's recipient
+    /// (after `not_before` filtering) found by history-based verification
+    /// ([`verify_payment`](Self::verify_payment),
+    /// [`verify_batch`](Self::verify_batch)). Returning
+    /// [`MatchDecision::Accept`] counts the transfer regardless of amount
+    /// tolerance and sender lists, [`MatchDecision::Reject`] ignores it, and
+    /// [`MatchDecision::Default`] leaves it to the built-in checks.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use cryptopay::*;
+    /// # use cryptopay::payment::{MatchDecision, TransferRef};
+    /// # fn example() -> Result<()> {
+    /// // Native payments must carry the memo as calldata
+    /// let memo = "0xc0ffee";
+    /// let verifier = PaymentVerifier::new(BscScanClient::new("api-key")?).with_matcher(
+    ///     move |transfer, _request: &PaymentRequest| match transfer {
+    ///         TransferRef::Transaction(tx) if tx.input != memo => MatchDecision::Reject,
+    ///         _ => MatchDecision::Default,
+    ///     },
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_matcher<F>(mut self, matcher: F) -> Self
+    where
+        F: Fn(TransferRef<'_>, &PaymentRequest) -> MatchDecision + Send + Sync + 'static,
+    {
+        self.matcher = Some(Arc::new(matcher));
+        self
+    }
+
+    /// Enable or disable zero-confirmation detection
+    ///
+    /// When enabled, [`verify_tx`](Self::verify_tx) and
+    /// [`check_pending_transaction`](Self::check_pending_transaction) report a
+    /// matching transaction that has not been mined yet as
+    /// [`VerificationResult::Seen`] instead of ignoring it. This is intended
+    /// for low-value point-of-sale flows; unconfirmed transactions can still be
+    /// dropped or replaced. [`verify_payment`](Self::verify_payment) and
+    /// [`verify_batch`](Self::verify_batch) are unaffected, as the explorer's
+    /// history only holds mined transactions.
+    pub fn accept_unconfirmed(mut self, accept: bool) -> Self {
+        self.accept_unconfirmed = accept;
+        self
+    }
+
+    /// Enable or disable scanning internal transactions for native payments
+    ///
+    /// Payments sent from smart-contract wallets (Safe, Argent) or through
+    /// routers reach the recipient as internal transactions. Scanning them is
+    /// enabled by default and costs one extra API call per native-currency
+    /// verification.
+    pub fn scan_internal_transactions(mut self, scan: bool) -> Self {
+        self.scan_internal = scan;
+        self
+    }
+
+    /// Enable or disable tracing where the sender of a found payment was funded
+    ///
+    /// When enabled, [`PaymentDetails::funded_by`] of pending and confirmed
+    /// results is filled with [`get_first_funder`](AccountEndpoints::get_first_funder)
+    /// of the sender, for compliance checks. Costs one extra API call per
+    /// found payment; a failed lookup leaves the field empty. Disabled by
+    /// default.
+    pub fn trace_funding(mut self, trace: bool) -> Self {
+        self.trace_funding = trace;
+        self
     }
 
     /// Verify a payment request
     ///
     /// This checks if a matching transaction exists on the blockchain and
     /// verifies it meets all requirements (amount, recipient, confirmations).
+    ///
+    /// Requests that accept partial payments are matched against the sum of
+    /// all inbound transfers since their `not_before` time instead of a single
+    /// transfer, reporting [`VerificationResult::PartiallyPaid`] until the
+    /// total is reached.
+    ///
+    /// Transfers from senders excluded by the request's allow/block lists are
+    /// ignored; if only such transfers would pay the request, the result is
+    /// `Failed { reason: "sender not allowed" }`.
+    ///
+    /// Requests with alternative currencies are verified once per option; the
+    /// most advanced result wins, and its `details.currency` tells which
+    /// option was paid.
+    ///
+    /// Requests with a `not_before` time only query the blocks mined since
+    /// then, looked up with
+    /// [`get_block_number_by_time`](BlockEndpoints::get_block_number_by_time).
+    /// Every page of the recipient's history from that block on is fetched;
+    /// without a `not_before` time that is its whole history, so set one for
+    /// busy addresses.
+    ///
+    /// Fails with `Error::ChainMismatch` if an option's currency is a known
+    /// token of another chain than the client's.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(recipient = %request.recipient_address, amount = %request.amount),
+            ret,
+            err
+        )
+    )]
     pub async fn verify_payment(&self, request: &PaymentRequest) -> Result<VerificationResult> {
-        // Validate recipient address
-        if !is_valid_address(&request.recipient_address) {
-            return Err(Error::InvalidAddress(request.recipient_address.clone()));
+        self.check_chain(request)?;
+        if request.alternatives.is_empty() {
+            let result = self.verify_option(request).await?;
+            return Ok(self.with_funding(result).await);
+        }
+
+        let mut best = VerificationResult::NotFound;
+        for option in request.option_requests() {
+            let result = self.verify_option(&option).await?;
+            if result.progress() > best.progress() {
+                best = result;
+            }
         }
 
-        // Find matching transaction based on currency type
-        let matching_tx = match &request.currency {
-            Currency::ETH => self.find_eth_transaction(request).await?,
+        Ok(self.with_funding(best).await)
+    }
+
+    /// Verify several payment requests, sharing explorer fetches between them
+    ///
+    /// Requests are grouped by recipient address and currency; each group
+    /// costs one `txlist` (plus `txlistinternal`) or `tokentx` fetch from the
+    /// earliest block any of its requests accepts, and its requests are
+    /// matched locally. Results are in request order and equal
+    /// what [`verify_payment`](Self::verify_payment) would report for each
+    /// request on its own, so requests for the same amount to the same
+    /// address can match the same transfer.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use cryptopay::*;
+    /// # async fn example(requests: Vec<PaymentRequest>) -> Result<()> {
+    /// let verifier = PaymentVerifier::new(BscScanClient::new("api-key")?);
+    /// let results = verifier.verify_batch(&requests).await?;
+    ///
+    /// for (request, result) in requests.iter().zip(&results) {
+    ///     println!("{} {}: {:?}", request.amount, request.recipient_address, result);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(requests = requests.len()), err)
+    )]
+    pub async fn verify_batch(
+        &self,
+        requests: &[PaymentRequest],
+    ) -> Result<Vec<VerificationResult>> {
+        for request in requests {
+            self.check_chain(request)?;
+        }
+
+        // Requests sharing a `not_before` time share its block lookup
+        let mut lookups: HashMap<Option<DateTime<Utc>>, u64> = HashMap::new();
+        let mut start_blocks: HashMap<(Address, Currency), u64> = HashMap::new();
+        for request in requests {
+            let start_block = match lookups.get(&request.not_before) {
+                Some(block) => *block,
+                None => {
+                    let block = self.start_block(request).await;
+                    *lookups.entry(request.not_before).or_insert(block)
+                }
+            };
+            for option in request.option_requests() {
+                let key = (option.recipient_address.clone(), option.currency.clone());
+                let group_start = start_blocks.entry(key).or_insert(start_block);
+                *group_start = (*group_start).min(start_block);
+            }
+        }
+
+        let mut fetched: HashMap<(Address, Currency), Vec<Candidate>> = HashMap::new();
+        let mut results = Vec::with_capacity(requests.len());
+        for request in requests {
+            let mut best = VerificationResult::NotFound;
+            for option in request.option_requests() {
+                let key = (option.recipient_address.clone(), option.currency.clone());
+                let candidates = match fetched.get(&key) {
+                    Some(candidates) => candidates.clone(),
+                    None => {
+                        let start_block = start_blocks.get(&key).copied().unwrap_or(0);
+                        let candidates = self.find_candidates(&key.0, &key.1, start_block).await?;
+                        fetched.entry(key).or_insert(candidates).clone()
+                    }
+                };
+
+                let result = self.match_candidates(&option, candidates);
+                if result.progress() > best.progress() {
+                    best = result;
+                }
+            }
+            results.push(self.with_funding(best).await);
+        }
+
+        Ok(results)
+    }
+
+    /// Check that every option's currency can be paid on the client's chain
+    fn check_chain(&self, request: &PaymentRequest) -> Result<()> {
+        let chain_id = self.client.chain_id();
+        request
+            .options()
+            .iter()
+            .try_for_each(|option| option.currency.validate_chain(chain_id))
+    }
+
+    /// Verify a single-currency request
+    async fn verify_option(&self, request: &PaymentRequest) -> Result<VerificationResult> {
+        let start_block = self.start_block(request).await;
+        let candidates = self
+            .find_candidates(&request.recipient_address, &request.currency, start_block)
+            .await?;
+        Ok(self.match_candidates(request, candidates))
+    }
+
+    /// First block that can hold transfers the request accepts
+    ///
+    /// Looked up from the request's `not_before` time, so the history query
+    /// skips older blocks. Falls back to the whole history if the explorer
+    /// can't resolve the time.
+    async fn start_block(&self, request: &PaymentRequest) -> u64 {
+        let Some(not_before) = request.not_before else {
+            return 0;
+        };
+        match self
+            .client
+            .get_block_number_by_time(not_before, Closest::Before)
+            .await
+        {
+            Ok(block) => block,
+            Err(e) => {
+                tracing::warn!(error = %e, %not_before, "block lookup by time failed");
+                0
+            }
+        }
+    }
+
+    /// Collect successful transfers to `recipient` from `start_block` on, based on currency type
+    async fn find_candidates(
+        &self,
+        recipient: &Address,
+        currency: &Currency,
+        start_block: u64,
+    ) -> Result<Vec<Candidate>> {
+        match currency {
+            Currency::Native => self.find_eth_transactions(recipient, start_block).await,
             Currency::ERC20 {
-                contract_address,
-                decimals,
+                contract_address, ..
             } => {
-                self.find_token_transaction(request, contract_address, *decimals)
-                    .await?
+                self.find_token_transactions(recipient, contract_address, start_block)
+                    .await
             }
+        }
+    }
+
+    /// Match a single-currency request against fetched transfers
+    fn match_candidates(
+        &self,
+        request: &PaymentRequest,
+        candidates: Vec<Candidate>,
+    ) -> VerificationResult {
+        // Let the custom matcher drop or force transfers first
+        let decided = candidates
+            .into_iter()
+            .filter(|candidate| candidate.is_inbound(request))
+            .map(|candidate| {
+                let decision = self.decide(&candidate, request);
+                (candidate, decision)
+            })
+            .filter(|(_, decision)| *decision != MatchDecision::Reject);
+
+        // Set aside transfers from senders the request doesn't accept
+        let (candidates, rejected): (Vec<_>, Vec<_>) =
+            decided.partition(|(candidate, decision)| {
+                *decision == MatchDecision::Accept
+                    || request.is_sender_allowed(&candidate.details.from)
+            });
+        let sender_not_allowed = || VerificationResult::Failed {
+            reason: "sender not allowed".to_string(),
+        };
+        let pays = |(candidate, decision): &(Candidate, MatchDecision)| {
+            *decision == MatchDecision::Accept || request.accepts_amount(candidate.details.amount)
         };
 
+        if request.accept_partial {
+            let candidates = candidates
+                .into_iter()
+                .map(|(candidate, _)| candidate)
+                .collect();
+            return match self.accumulate(request, candidates) {
+                VerificationResult::NotFound if !rejected.is_empty() => sender_not_allowed(),
+                result => result,
+            };
+        }
+
         // If no matching transaction, return NotFound
-        let (tx_hash, confirmations, actual_amount) = match matching_tx {
-            Some(data) => data,
-            None => return Ok(VerificationResult::NotFound),
-        };
+        match candidates.into_iter().find(pays) {
+            Some((candidate, _)) => self.classify(
+                request,
+                candidate.tx_hash,
+                candidate.confirmations,
+                candidate.details,
+            ),
+            None if rejected.iter().any(pays) => sender_not_allowed(),
+            None => VerificationResult::NotFound,
+        }
+    }
 
-        // Check if amount matches (allow 99.9% minimum to account for dust/rounding)
-        let min_percent = Decimal::from_str_radix("99.9", 10).unwrap();
-        if !amount_sufficient(request.amount, actual_amount, min_percent) {
-            return Ok(VerificationResult::Failed {
-                reason: format!(
-                    "Amount mismatch: expected {}, got {}",
-                    request.amount, actual_amount
-                ),
-            });
+    /// Ask the custom matcher, if any, about a transfer
+    fn decide(&self, candidate: &Candidate, request: &PaymentRequest) -> MatchDecision {
+        match (&self.matcher, &candidate.source) {
+            (Some(matcher), Some(source)) => matcher(source.as_transfer(), request),
+            _ => MatchDecision::Default,
         }
+    }
+
+    /// Map a matched transfer to Pending/Confirmed based on its confirmations
+    fn classify(
+        &self,
+        request: &PaymentRequest,
+        tx_hash: TxHash,
+        confirmations: u64,
+        mut details: PaymentDetails,
+    ) -> VerificationResult {
+        // Report the currency exactly as the request configured it
+        details.currency = request.currency.clone();
 
         // Check confirmations
         if confirmations >= request.required_confirmations {
-            Ok(VerificationResult::Confirmed {
+            VerificationResult::Confirmed {
                 tx_hash,
                 confirmations,
-            })
+                details,
+            }
         } else {
-            Ok(VerificationResult::Pending {
+            VerificationResult::Pending {
                 tx_hash,
                 confirmations,
-            })
+                details,
+            }
         }
     }
 
-    /// Find matching ETH transaction
-    async fn find_eth_transaction(
+    /// Sum inbound transfers, oldest first, until the requested amount is met
+    fn accumulate(
         &self,
         request: &PaymentRequest,
-    ) -> Result<Option<(String, u64, Decimal)>> {
-        // Get recent transactions to the recipient address
-        let transactions = self
+        mut candidates: Vec<Candidate>,
+    ) -> VerificationResult {
+        candidates.sort_by_key(|candidate| candidate.details.block_number);
+
+        let mut received = Decimal::ZERO;
+        let mut gas_cost = Some(Decimal::ZERO);
+        let mut tx_hashes = Vec::new();
+        for candidate in candidates {
+            received += candidate.details.amount;
+            gas_cost = gas_cost
+                .zip(candidate.details.gas_cost)
+                .map(|(sum, gas)| sum + gas);
+            tx_hashes.push(candidate.tx_hash.clone());
+
+            if request.accepts_amount(received) {
+                // The completing transfer is the least confirmed one counted
+                let details = PaymentDetails {
+                    amount: received,
+                    gas_cost,
+                    ..candidate.details
+                };
+                return self.classify(request, candidate.tx_hash, candidate.confirmations, details);
+            }
+        }
+
+        if tx_hashes.is_empty() {
+            VerificationResult::NotFound
+        } else {
+            VerificationResult::PartiallyPaid {
+                received,
+                remaining: request.amount - received,
+                tx_hashes,
+            }
+        }
+    }
+
+    /// Find successful ETH transactions involving the recipient
+    async fn find_eth_transactions(
+        &self,
+        recipient: &Address,
+        start_block: u64,
+    ) -> Result<Vec<Candidate>> {
+        // Every transaction of the recipient address since `start_block`, newest first
+        let transactions: Vec<Transaction> = self
             .client
-            .get_transactions(&request.recipient_address, 0, 99999999, 1, 100, "desc")
+            .fetch_history("txlist", recipient, start_block, 99999999)
             .await?;
 
-        // Find matching transaction
-        for tx in transactions {
+        let mut candidates: Vec<Candidate> = transactions
+            .into_iter()
+            .rev()
             // Skip failed transactions
-            if !tx.is_successful() {
-                continue;
+            .filter(|tx| tx.is_successful())
+            .map(Candidate::from_transaction)
+            .collect();
+
+        if self.scan_internal {
+            candidates.extend(
+                self.find_internal_transactions(recipient, start_block)
+                    .await?,
+            );
+        }
+
+        Ok(candidates)
+    }
+
+    /// Find successful internal transactions (value sent by contracts) to the recipient
+    async fn find_internal_transactions(
+        &self,
+        recipient: &Address,
+        start_block: u64,
+    ) -> Result<Vec<Candidate>> {
+        let internal: Vec<InternalTransaction> = self
+            .client
+            .fetch_history("txlistinternal", recipient, start_block, 99999999)
+            .await?;
+
+        let mut candidates: Vec<Candidate> = internal
+            .into_iter()
+            .rev()
+            .filter(|tx| tx.is_error == "0")
+            .map(Candidate::from_internal_transaction)
+            .filter(|candidate| *recipient == candidate.details.to)
+            .collect();
+
+        if candidates.is_empty() {
+            return Ok(candidates);
+        }
+
+        // Internal transactions don't report confirmations; derive them from the head
+        let head = self.client.latest_block_number().await?;
+        for candidate in &mut candidates {
+            let block = candidate.details.block_number;
+            candidate.confirmations = if head >= block { head - block + 1 } else { 0 };
+        }
+
+        Ok(candidates)
+    }
+
+    /// Find ERC20 token transfers involving the recipient
+    async fn find_token_transactions(
+        &self,
+        recipient: &Address,
+        contract_address: &Address,
+        start_block: u64,
+    ) -> Result<Vec<Candidate>> {
+        // Every token transfer of the recipient address since `start_block`, newest first
+        let transfers: Vec<TokenTransfer> = self
+            .client
+            .fetch_history("tokentx", recipient, start_block, 99999999)
+            .await?;
+
+        let candidates = transfers
+            .into_iter()
+            .rev()
+            .filter(|transfer| transfer.contract_address == *contract_address)
+            .map(Candidate::from_token_transfer)
+            .collect();
+
+        Ok(candidates)
+    }
+
+    /// Verify a specific transaction hash against a payment request
+    ///
+    /// Intended for checkout flows where the payer submits their transaction
+    /// hash: instead of scanning the recipient's history, the transaction and
+    /// its receipt are fetched directly. The transaction must have succeeded,
+    /// come from an allowed sender, and pay the recipient an accepted amount
+    /// of one of the request's currencies (native value, or ERC20 `Transfer`
+    /// logs emitted by the configured token contract).
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(tx_hash = %tx_hash, recipient = %request.recipient_address),
+            ret,
+            err
+        )
+    )]
+    pub async fn verify_tx(
+        &self,
+        tx_hash: &TxHash,
+        request: &PaymentRequest,
+    ) -> Result<VerificationResult> {
+        self.check_chain(request)?;
+
+        let tx = match self.fetch_rpc_transaction(tx_hash).await? {
+            Some(tx) => tx,
+            None => return Ok(VerificationResult::NotFound),
+        };
+
+        // Not mined yet: only reportable in zero-confirmation mode
+        if tx.block_number.is_some() {
+            self.verify_mined(tx_hash, &tx, request).await
+        } else if self.accept_unconfirmed {
+            self.check_pending_transaction(tx_hash, request).await
+        } else {
+            Ok(VerificationResult::NotFound)
+        }
+    }
+
+    /// Verify a mined transaction from its receipt
+    async fn verify_mined(
+        &self,
+        tx_hash: &TxHash,
+        tx: &RpcTransaction,
+        request: &PaymentRequest,
+    ) -> Result<VerificationResult> {
+        let block_number = match tx.block_number.as_deref() {
+            Some(block) => parse_hex_u128(block)? as u64,
+            None => return Ok(VerificationResult::NotFound),
+        };
+
+        let receipt = match self.fetch_receipt(tx_hash).await? {
+            Some(receipt) => receipt,
+            None => return Ok(VerificationResult::NotFound),
+        };
+
+        let result = self
+            .match_receipt(Some(tx), &receipt, block_number, request)
+            .await?;
+        Ok(self.with_funding(result).await)
+    }
+
+    /// Verify a token payment from a transaction receipt
+    ///
+    /// For callers that already hold the receipt (e.g. from their own node or
+    /// a wallet callback): the ERC20 `Transfer` logs are decoded directly, so
+    /// the explorer's token transfer index is not consulted. Only the
+    /// request's token options can match, since a receipt doesn't carry the
+    /// native value; the sender is the `from` of the matching transfer.
+    pub async fn verify_receipt(
+        &self,
+        receipt: &RpcReceipt,
+        request: &PaymentRequest,
+    ) -> Result<VerificationResult> {
+        self.check_chain(request)?;
+        let block_number = receipt.block_number_u64()?;
+        let result = self
+            .match_receipt(None, receipt, block_number, request)
+            .await?;
+        Ok(self.with_funding(result).await)
+    }
+
+    /// Attach the sender's funding source to a found payment, if tracing is enabled
+    async fn with_funding(&self, mut result: VerificationResult) -> VerificationResult {
+        if !self.trace_funding {
+            return result;
+        }
+        if let VerificationResult::Pending { details, .. }
+        | VerificationResult::Confirmed { details, .. } = &mut result
+        {
+            details.funded_by = self.first_funder(&details.from).await;
+        }
+        result
+    }
+
+    async fn first_funder(&self, sender: &Address) -> Option<Box<FundingSource>> {
+        match self.client.get_first_funder(sender).await {
+            Ok(source) => Some(Box::new(source)),
+            Err(e) => {
+                tracing::warn!(error = %e, sender = %sender, "funding source lookup failed");
+                None
             }
+        }
+    }
 
-            let tx_value = tx.value_bnb();
+    /// Match a mined transaction's receipt (and the transaction, if known)
+    /// against the request's options
+    async fn match_receipt(
+        &self,
+        tx: Option<&RpcTransaction>,
+        receipt: &RpcReceipt,
+        block_number: u64,
+        request: &PaymentRequest,
+    ) -> Result<VerificationResult> {
+        if !receipt.is_successful() {
+            return Ok(VerificationResult::Failed {
+                reason: "transaction reverted".to_string(),
+            });
+        }
 
-            // Check if amount matches (within tolerance)
-            if amount_sufficient(request.amount, tx_value, Decimal::new(999, 1)) {
-                let confirmations = tx.confirmations_u64();
-                return Ok(Some((tx.hash, confirmations, tx_value)));
+        let sender_not_allowed = || VerificationResult::Failed {
+            reason: "sender not allowed".to_string(),
+        };
+        if let Some(tx) = tx {
+            if !request.is_sender_allowed(&tx.from) {
+                return Ok(sender_not_allowed());
             }
         }
 
-        Ok(None)
+        let head = self.client.latest_block_number().await?;
+        let confirmations = if head >= block_number {
+            head - block_number + 1
+        } else {
+            0
+        };
+
+        let mut mismatch = None;
+        for option in request.option_requests() {
+            let received = match received_in_tx(tx, receipt, &option)? {
+                Some(received) => received,
+                None => continue,
+            };
+            if tx.is_none() && !request.is_sender_allowed(&received.from) {
+                return Ok(sender_not_allowed());
+            }
+
+            if !option.accepts_amount(received.amount) {
+                mismatch.get_or_insert(format!(
+                    "Amount mismatch: expected {}, got {}",
+                    option.amount, received.amount
+                ));
+                continue;
+            }
+
+            let details = PaymentDetails {
+                currency: option.currency.clone(),
+                amount: received.amount,
+                from: received.from,
+                to: request.recipient_address.clone(),
+                block_number,
+                timestamp: None,
+                gas_cost: receipt.gas_cost().map(|cost| cost.to_decimal_lossy()),
+                funded_by: None,
+            };
+            return Ok(self.classify(
+                &option,
+                TxHash::parse(&receipt.transaction_hash)?,
+                confirmations,
+                details,
+            ));
+        }
+
+        Ok(VerificationResult::Failed {
+            reason: mismatch
+                .unwrap_or_else(|| "transaction does not pay the recipient".to_string()),
+        })
     }
 
-    /// Find matching ERC20 token transaction
-    async fn find_token_transaction(
+    /// Check whether a known transaction hash pays the request from the mempool
+    ///
+    /// Looks the transaction up with `eth_getTransactionByHash`. A pending
+    /// transaction whose recipient and amount match is reported as
+    /// [`VerificationResult::Seen`]; once the transaction is mined it is
+    /// verified from its receipt, as [`verify_tx`](Self::verify_tx) does.
+    /// Requires `accept_unconfirmed` mode.
+    pub async fn check_pending_transaction(
         &self,
+        tx_hash: &TxHash,
         request: &PaymentRequest,
-        contract_address: &str,
-        _decimals: u8,
-    ) -> Result<Option<(String, u64, Decimal)>> {
-        // Get recent token transfers to the recipient address
-        let transfers = self
-            .client
-            .get_token_transfers(
-                &request.recipient_address,
-                Some(contract_address),
-                0,
-                99999999,
-                1,
-                100,
-                "desc",
-            )
-            .await?;
+    ) -> Result<VerificationResult> {
+        if !self.accept_unconfirmed {
+            return Err(Error::InvalidConfig(
+                "Unconfirmed payment detection is disabled; enable accept_unconfirmed".to_string(),
+            ));
+        }
 
-        // Find matching transfer
-        for transfer in transfers {
-            let tx_value = transfer.value_tokens();
+        let tx = match self.fetch_rpc_transaction(tx_hash).await? {
+            Some(tx) => tx,
+            None => return Ok(VerificationResult::NotFound),
+        };
 
-            // Check if amount matches (within tolerance)
-            if amount_sufficient(request.amount, tx_value, Decimal::new(999, 1)) {
-                let confirmations = transfer.confirmations_u64();
-                return Ok(Some((transfer.hash, confirmations, tx_value)));
+        if tx.block_number.is_some() {
+            return self.verify_mined(tx_hash, &tx, request).await;
+        }
+
+        if !request.is_sender_allowed(&tx.from) {
+            return Ok(VerificationResult::Failed {
+                reason: "sender not allowed".to_string(),
+            });
+        }
+
+        for option in request.option_requests() {
+            match pending_payment_amount(&tx, &option) {
+                Some(amount) if option.accepts_amount(amount) => {
+                    return Ok(VerificationResult::Seen { tx_hash: tx.hash });
+                }
+                Some(amount) => {
+                    return Ok(VerificationResult::Failed {
+                        reason: format!(
+                            "Amount mismatch: expected {}, got {}",
+                            option.amount, amount
+                        ),
+                    });
+                }
+                None => continue,
             }
         }
 
-        Ok(None)
+        Ok(VerificationResult::NotFound)
+    }
+
+    /// Look up which block currently includes a transaction
+    ///
+    /// Bypasses the response cache so the answer reflects the chain right now.
+    /// Returns `None` if the transaction is unknown or back in the mempool,
+    /// which after a confirmation indicates it was reorganized out.
+    pub async fn get_inclusion(&self, tx_hash: &TxHash) -> Result<Option<TxInclusion>> {
+        let tx = self.fetch_rpc_transaction(tx_hash).await?;
+        let (block_hash, block_number) = match tx.and_then(|tx| tx.block_hash.zip(tx.block_number))
+        {
+            Some(inclusion) => inclusion,
+            None => return Ok(None),
+        };
+        let block_number = parse_hex_u128(&block_number)? as u64;
+
+        let head = self.client.latest_block_number().await?;
+        let confirmations = if head >= block_number {
+            head - block_number + 1
+        } else {
+            0
+        };
+
+        Ok(Some(TxInclusion {
+            block_hash,
+            block_number,
+            confirmations,
+        }))
+    }
+
+    /// Check whether a previously detected transaction was dropped or replaced
+    ///
+    /// A transaction is considered dropped when `eth_getTransactionByHash` no
+    /// longer knows it (evicted from the mempool, or replaced by a same-nonce
+    /// speed-up/cancel). `current` is the latest verification result of the
+    /// request; a different transaction it found paying the same invoice is
+    /// the replacement. Returns `None` while the transaction is still known to
+    /// the node.
+    pub async fn check_dropped(
+        &self,
+        tx_hash: &TxHash,
+        current: &VerificationResult,
+    ) -> Result<Option<DroppedTransaction>> {
+        if self.fetch_rpc_transaction(tx_hash).await?.is_some() {
+            return Ok(None);
+        }
+
+        let replacement = match current {
+            VerificationResult::Seen { tx_hash: found }
+            | VerificationResult::Pending { tx_hash: found, .. }
+            | VerificationResult::Confirmed { tx_hash: found, .. }
+                if found != tx_hash =>
+            {
+                Some(found.clone())
+            }
+            _ => None,
+        };
+
+        Ok(Some(DroppedTransaction {
+            tx_hash: tx_hash.clone(),
+            replacement,
+        }))
+    }
+
+    /// Wait until a transaction has at least `confirmations` confirmations
+    ///
+    /// Polls the transaction receipt every `poll_interval` and resolves with it
+    /// once the transaction is deep enough. Fails with
+    /// [`Error::VerificationFailed`] if the transaction reverted, and with
+    /// [`Error::TransactionNotFound`] if it is unknown to the node or was
+    /// dropped/replaced while waiting.
+    pub async fn wait_for_confirmations(
+        &self,
+        tx_hash: &TxHash,
+        confirmations: u64,
+        poll_interval: Duration,
+    ) -> Result<RpcReceipt> {
+        loop {
+            match self.fetch_receipt(tx_hash).await? {
+                Some(receipt) => {
+                    if !receipt.is_successful() {
+                        return Err(Error::verification_failed(format!(
+                            "transaction {} reverted",
+                            tx_hash
+                        )));
+                    }
+
+                    let block = receipt.block_number_u64()?;
+                    let head = self.client.latest_block_number().await?;
+                    if head >= block && head - block + 1 >= confirmations {
+                        return Ok(receipt);
+                    }
+                }
+                // No receipt yet: still pending, unless the node forgot the tx entirely
+                None => {
+                    if self.fetch_rpc_transaction(tx_hash).await?.is_none() {
+                        return Err(Error::TransactionNotFound(tx_hash.to_string()));
+                    }
+                }
+            }
+
+            sleep(poll_interval).await;
+        }
+    }
+
+    /// Fetch a transaction receipt, bypassing the response cache
+    async fn fetch_receipt(&self, tx_hash: &TxHash) -> Result<Option<RpcReceipt>> {
+        let params = [("txhash", tx_hash.as_str())];
+        self.client
+            .request_uncached("proxy", "eth_getTransactionReceipt", &params)
+            .await
+    }
+
+    /// Fetch a transaction by hash from the node, bypassing the response cache
+    async fn fetch_rpc_transaction(&self, tx_hash: &TxHash) -> Result<Option<RpcTransaction>> {
+        let params = [("txhash", tx_hash.as_str())];
+        self.client
+            .request_uncached("proxy", "eth_getTransactionByHash", &params)
+            .await
     }
 
     /// Check confirmations for a specific transaction hash
-    pub async fn check_confirmations(&self, tx_hash: &str) -> Result<u64> {
+    pub async fn check_confirmations(&self, tx_hash: &TxHash) -> Result<u64> {
         self.client.get_confirmations(tx_hash).await
     }
 
     /// Find any matching transaction for a payment request
     ///
     /// Returns the transaction hash if found
-    pub async fn find_matching_transaction(&self, request: &PaymentRequest) -> Result<Option<String>> {
+    pub async fn find_matching_transaction(
+        &self,
+        request: &PaymentRequest,
+    ) -> Result<Option<TxHash>> {
         let result = self.verify_payment(request).await?;
 
         match result {
             VerificationResult::Confirmed { tx_hash, .. } => Ok(Some(tx_hash)),
             VerificationResult::Pending { tx_hash, .. } => Ok(Some(tx_hash)),
+            VerificationResult::Seen { tx_hash } => Ok(Some(tx_hash)),
             _ => Ok(None),
         }
     }
 }
 
+/// Address standing in for a missing sender or recipient
+fn zero_address() -> Address {
+    Address::known("0x0000000000000000000000000000000000000000")
+}
+
+/// Recipient of a listed transaction: the created contract for contract creations
+fn recipient_of(to: Option<&Address>, contract_address: &str) -> Address {
+    match to {
+        Some(to) => to.clone(),
+        None => contract_address.parse().unwrap_or_else(|_| zero_address()),
+    }
+}
+
+/// Value a mined transaction delivered to the recipient in one currency
+struct Received {
+    amount: Decimal,
+    from: Address,
+}
+
+/// Amount of the option's currency a transaction paid to the recipient, if any
+///
+/// Native value is only known from the transaction itself.
+fn received_in_tx(
+    tx: Option<&RpcTransaction>,
+    receipt: &RpcReceipt,
+    option: &PaymentRequest,
+) -> Result<Option<Received>> {
+    match &option.currency {
+        Currency::Native => {
+            let Some(tx) = tx else {
+                return Ok(None);
+            };
+            let to_recipient = tx.to.as_ref() == Some(&option.recipient_address);
+            let value = TokenAmount::from_hex(&tx.value, 18)?;
+            if !to_recipient || value.is_zero() {
+                return Ok(None);
+            }
+            Ok(Some(Received {
+                amount: value.to_decimal()?,
+                from: tx.from.clone(),
+            }))
+        }
+        Currency::ERC20 {
+            contract_address,
+            decimals,
+        } => {
+            let mut total = TokenAmount::zero(*decimals);
+            let mut from = None;
+            for transfer in decode_transfers(receipt) {
+                let amount = match transfer.amount() {
+                    Some(amount) => TokenAmount::new(amount, *decimals),
+                    None => continue,
+                };
+                if transfer.token != *contract_address || transfer.to != option.recipient_address {
+                    continue;
+                }
+                total = total
+                    .checked_add(amount)
+                    .ok_or_else(|| Error::InvalidAmount(format!("{} + {}", total, amount)))?;
+                from.get_or_insert(transfer.from);
+            }
+
+            match from {
+                Some(from) => Ok(Some(Received {
+                    amount: total.to_decimal()?,
+                    from,
+                })),
+                None => Ok(None),
+            }
+        }
+    }
+}
+
+/// Amount a pending transaction pays to the request's recipient, if any
+///
+/// Native payments are read from the transaction value; token payments are
+/// decoded from `transfer`/`transferFrom` calldata sent to the token
+/// contract, or from router swaps delivering the token to the recipient
+/// (counted at their minimum output).
+fn pending_payment_amount(tx: &RpcTransaction, request: &PaymentRequest) -> Option<Decimal> {
+    let to = tx.to.as_ref()?;
+
+    match &request.currency {
+        Currency::Native => {
+            if *to != request.recipient_address {
+                return None;
+            }
+            TokenAmount::from_hex(&tx.value, 18)
+                .and_then(|value| value.to_decimal())
+                .ok()
+        }
+        Currency::ERC20 {
+            contract_address,
+            decimals,
+        } => {
+            let call = tx.decode_input()?;
+            if *call.recipient() != request.recipient_address {
+                return None;
+            }
+            let amount = match call {
+                DecodedCall::Transfer { amount, .. } | DecodedCall::TransferFrom { amount, .. }
+                    if to == contract_address =>
+                {
+                    amount
+                }
+                DecodedCall::Swap {
+                    amount_out, path, ..
+                } if matches!(path.last(), Some(token) if token == contract_address) => amount_out,
+                _ => return None,
+            };
+            TokenAmount::new(amount, *decimals).to_decimal().ok()
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::abi::TRANSFER_TOPIC;
+    use crate::primitives::Address;
+
+    fn address(last: u8) -> Address {
+        format!("0x{:040x}", last).parse().unwrap()
+    }
+
+    fn hash(id: u64) -> TxHash {
+        format!("0x{:064x}", id).parse().unwrap()
+    }
 
     #[test]
     fn test_verification_result() {
         let result = VerificationResult::Confirmed {
-            tx_hash: "0x123".to_string(),
+            tx_hash: hash(0x123),
             confirmations: 15,
+            details: PaymentDetails {
+                currency: Currency::Native,
+                amount: Decimal::from(1),
+                from: address(2),
+                to: address(1),
+                block_number: 100,
+                timestamp: DateTime::from_timestamp(1700000000, 0),
+                gas_cost: None,
+                funded_by: None,
+            },
         };
 
         match result {
-            VerificationResult::Confirmed {
-                confirmations,
-                ..
-            } => {
+            VerificationResult::Confirmed { confirmations, .. } => {
                 assert_eq!(confirmations, 15);
             }
             _ => panic!("Expected Confirmed"),
         }
     }
+
+    #[tokio::test]
+    async fn test_trace_funding() {
+        use wiremock::matchers::query_param;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(query_param("action", "fundedby"))
+            .and(query_param("address", address(2).as_str()))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "status": "1",
+                "message": "OK",
+                "result": {
+                    "block": 53708500,
+                    "timeStamp": "1711626475",
+                    "fundingAddress": address(9).as_str(),
+                    "fundingTxn": hash(7).as_str(),
+                    "value": "1000000000000000000"
+                }
+            })))
+            .mount(&server)
+            .await;
+        let config = crate::config::ClientConfig::builder()
+            .api_key("key")
+            .base_url(server.uri())
+            .build()
+            .unwrap();
+        let verifier = PaymentVerifier::new(BscScanClient::with_config(config).unwrap());
+
+        let confirmed = VerificationResult::Confirmed {
+            tx_hash: hash(1),
+            confirmations: 12,
+            details: PaymentDetails {
+                currency: Currency::Native,
+                amount: Decimal::ONE,
+                from: address(2),
+                to: address(1),
+                block_number: 100,
+                timestamp: None,
+                gas_cost: None,
+                funded_by: None,
+            },
+        };
+        // Off by default
+        let result = verifier.with_funding(confirmed.clone()).await;
+        assert!(result.details().unwrap().funded_by.is_none());
+
+        let result = verifier.trace_funding(true).with_funding(confirmed).await;
+        let funder = result.details().unwrap().funded_by.clone().unwrap();
+        assert_eq!(funder.funding_address, address(9));
+        assert_eq!(funder.funding_txn, hash(7));
+        assert_eq!(funder.block, 53708500);
+    }
+
+    #[test]
+    fn test_partial_payment_accumulation() {
+        let verifier = PaymentVerifier::new(BscScanClient::new("test-key").unwrap());
+        let request =
+            PaymentRequest::eth(Decimal::from(1), address(1), 2).with_partial_payments(Utc::now());
+        let candidate = |id: u64, amount: Decimal, block: u64, confirmations: u64| Candidate {
+            tx_hash: hash(id),
+            confirmations,
+            details: PaymentDetails {
+                currency: Currency::Native,
+                amount,
+                from: address(2),
+                to: address(1),
+                block_number: block,
+                timestamp: None,
+                gas_cost: None,
+                funded_by: None,
+            },
+            source: None,
+        };
+
+        let partial =
+            verifier.accumulate(&request, vec![candidate(0xa, Decimal::new(4, 1), 10, 5)]);
+        assert_eq!(
+            partial,
+            VerificationResult::PartiallyPaid {
+                received: Decimal::new(4, 1),
+                remaining: Decimal::new(6, 1),
+                tx_hashes: vec![hash(0xa)],
+            }
+        );
+
+        let complete = verifier.accumulate(
+            &request,
+            vec![
+                candidate(0xb, Decimal::new(6, 1), 12, 3),
+                candidate(0xa, Decimal::new(4, 1), 10, 5),
+            ],
+        );
+        match complete {
+            VerificationResult::Confirmed {
+                tx_hash, details, ..
+            } => {
+                assert_eq!(tx_hash, hash(0xb));
+                assert_eq!(details.amount, Decimal::from(1));
+            }
+            other => panic!("Expected Confirmed, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_match_candidates_shared() {
+        let verifier = PaymentVerifier::new(BscScanClient::new("test-key").unwrap());
+        let candidate = |id: u64, amount: i64, to: Address| Candidate {
+            tx_hash: hash(id),
+            confirmations: 20,
+            details: PaymentDetails {
+                currency: Currency::Native,
+                amount: Decimal::from(amount),
+                from: address(2),
+                to,
+                block_number: 100,
+                timestamp: None,
+                gas_cost: None,
+                funded_by: None,
+            },
+            source: None,
+        };
+        // One fetch of address(1)'s history, including an outgoing transfer
+        let fetched = vec![
+            candidate(3, 2, address(3)),
+            candidate(1, 1, address(1)),
+            candidate(2, 2, address(1)),
+        ];
+
+        let results: Vec<_> = [1, 2, 3]
+            .into_iter()
+            .map(|amount| {
+                let request = PaymentRequest::eth(Decimal::from(amount), address(1), 12);
+                verifier.match_candidates(&request, fetched.clone())
+            })
+            .collect();
+
+        assert!(
+            matches!(&results[0], VerificationResult::Confirmed { tx_hash, .. } if *tx_hash == hash(1))
+        );
+        assert!(
+            matches!(&results[1], VerificationResult::Confirmed { tx_hash, .. } if *tx_hash == hash(2))
+        );
+        assert_eq!(results[2], VerificationResult::NotFound);
+    }
+
+    #[test]
+    fn test_custom_matcher() {
+        let transaction = |id: u64, value: &str, input: &str| {
+            let tx: Transaction = serde_json::from_value(serde_json::json!({
+                "blockNumber": "100",
+                "hash": hash(id).to_string(),
+                "blockHash": "0x",
+                "from": address(2).to_string(),
+                "to": address(1).to_string(),
+                "value": value,
+                "isError": "0",
+                "txreceipt_status": "1",
+                "input": input,
+                "contractAddress": "",
+                "confirmations": "20"
+            }))
+            .unwrap();
+            Candidate::from_transaction(tx)
+        };
+        let fetched = vec![
+            transaction(1, "1000000000000000000", "0x"),
+            transaction(2, "900000000000000000", "0xc0ffee"),
+        ];
+        let request = PaymentRequest::eth(Decimal::from(1), address(1), 12);
+
+        let verifier = PaymentVerifier::new(BscScanClient::new("test-key").unwrap());
+        let result = verifier.match_candidates(&request, fetched.clone());
+        assert_eq!(
+            result.details().map(|details| details.amount),
+            Some(Decimal::ONE)
+        );
+
+        // Match by memo: reject other transfers, accept the memo despite the amount
+        let verifier =
+            verifier.with_matcher(|transfer, _request: &PaymentRequest| match transfer {
+                TransferRef::Transaction(tx) if tx.input == "0xc0ffee" => MatchDecision::Accept,
+                _ => MatchDecision::Reject,
+            });
+        let result = verifier.match_candidates(&request, fetched);
+        assert!(
+            matches!(result, VerificationResult::Confirmed { tx_hash, .. } if hash(2) == tx_hash.as_str())
+        );
+    }
+
+    #[test]
+    fn test_internal_transaction_details() {
+        let tx = InternalTransaction {
+            block_number: 19000000,
+            time_stamp: DateTime::from_timestamp(1700000000, 0),
+            hash: hash(0xabc),
+            from: address(0x5a),
+            to: Some(address(1)),
+            value: "250000000000000000".to_string(),
+            contract_address: String::new(),
+            input: String::new(),
+            tx_type: "call".to_string(),
+            gas: 0,
+            gas_used: 0,
+            trace_id: "0_1".to_string(),
+            is_error: "0".to_string(),
+            err_code: String::new(),
+            extra: Default::default(),
+        };
+
+        let details = PaymentDetails::from_internal_transaction(&tx);
+        assert_eq!(details.amount, Decimal::new(25, 2));
+        assert_eq!(details.from, address(0x5a).to_string());
+        assert_eq!(details.block_number, 19000000);
+        assert!(details.timestamp.is_some());
+    }
+
+    #[test]
+    fn test_received_in_tx_token_logs() {
+        let recipient = "0x742D35CC6634c0532925A3b844BC9E7595F0BEb0";
+        let usdt = "0xdAC17F958D2ee523a2206206994597C13D831ec7";
+        let request = PaymentRequest::token(
+            Decimal::from(100),
+            usdt.parse().unwrap(),
+            6,
+            recipient.parse().unwrap(),
+            1,
+        );
+        let tx = RpcTransaction {
+            block_hash: Some("0xblock".to_string()),
+            block_number: Some("0x10".to_string()),
+            from: address(1),
+            gas: "0x0".to_string(),
+            gas_price: "0x0".to_string(),
+            hash: hash(1),
+            input: "0x".to_string(),
+            nonce: "0x0".to_string(),
+            to: Some(usdt.parse().unwrap()),
+            transaction_index: Some("0x0".to_string()),
+            value: "0x0".to_string(),
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
+            chain_id: None,
+            tx_type: None,
+            extra: Default::default(),
+        };
+        let log = crate::client::types::Log {
+            address: usdt.to_lowercase(),
+            topics: vec![
+                TRANSFER_TOPIC.to_string(),
+                "0x0000000000000000000000000000000000000000000000000000000000000001".to_string(),
+                "0x000000000000000000000000742d35cc6634c0532925a3b844bc9e7595f0beb0".to_string(),
+            ],
+            data: format!("0x{:064x}", 100_000_000u128),
+            block_number: "0x10".to_string(),
+            transaction_hash: "0xhash".to_string(),
+            transaction_index: "0x0".to_string(),
+            block_hash: "0xblock".to_string(),
+            log_index: "0x0".to_string(),
+            removed: false,
+        };
+        let receipt = RpcReceipt {
+            block_hash: "0xblock".to_string(),
+            block_number: "0x10".to_string(),
+            contract_address: None,
+            cumulative_gas_used: "0x0".to_string(),
+            gas_used: "0x0".to_string(),
+            logs: vec![log],
+            status: "0x1".to_string(),
+            transaction_hash: "0xhash".to_string(),
+            transaction_index: "0x0".to_string(),
+            from: None,
+            to: None,
+            effective_gas_price: None,
+            tx_type: None,
+            extra: Default::default(),
+        };
+
+        let received = received_in_tx(Some(&tx), &receipt, &request)
+            .unwrap()
+            .unwrap();
+        assert_eq!(received.amount, Decimal::from(100));
+        assert_eq!(received.from, "0x0000000000000000000000000000000000000001");
+        let from_receipt = received_in_tx(None, &receipt, &request).unwrap().unwrap();
+        assert_eq!(from_receipt.amount, Decimal::from(100));
+
+        let eth_request = PaymentRequest::eth(Decimal::from(1), recipient.parse().unwrap(), 1);
+        assert!(received_in_tx(Some(&tx), &receipt, &eth_request)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_payment_details_overpayment() {
+        let request = PaymentRequest::eth(Decimal::from(1), address(1), 12);
+        let mut details = PaymentDetails {
+            currency: Currency::Native,
+            amount: Decimal::new(15, 1),
+            from: address(2),
+            to: address(1),
+            block_number: 100,
+            timestamp: None,
+            gas_cost: Some(Decimal::new(2, 3)),
+            funded_by: None,
+        };
+        assert_eq!(details.overpayment(&request), Some(Decimal::new(5, 1)));
+        assert_eq!(details.effective_amount(), Some(Decimal::new(1498, 3)));
+
+        details.amount = Decimal::from(1);
+        assert_eq!(details.overpayment(&request), None);
+
+        details.currency = Currency::usdt();
+        assert_eq!(details.effective_amount(), None);
+    }
+
+    #[test]
+    fn test_pending_token_transfer_amount() {
+        let request = PaymentRequest::token(
+            Decimal::from(100),
+            "0xdAC17F958D2ee523a2206206994597C13D831ec7"
+                .parse()
+                .unwrap(),
+            6,
+            "0x742D35CC6634c0532925A3b844BC9E7595F0BEb0"
+                .parse()
+                .unwrap(),
+            12,
+        );
+        let tx = RpcTransaction {
+            block_hash: None,
+            block_number: None,
+            from: address(1),
+            gas: "0x5208".to_string(),
+            gas_price: "0x1".to_string(),
+            hash: hash(0xabc),
+            input: format!(
+                "0xa9059cbb000000000000000000000000742d35cc6634c0532925a3b844bc9e7595f0beb0{:064x}",
+                100_000_000u128
+            ),
+            nonce: "0x1".to_string(),
+            to: Some(
+                "0xdac17f958d2ee523a2206206994597c13d831ec7"
+                    .parse()
+                    .unwrap(),
+            ),
+            transaction_index: None,
+            value: "0x0".to_string(),
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
+            chain_id: None,
+            tx_type: None,
+            extra: Default::default(),
+        };
+
+        assert_eq!(
+            pending_payment_amount(&tx, &request),
+            Some(Decimal::from(100))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_confirmations() {
+        use wiremock::matchers::query_param;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let rpc = |result: serde_json::Value| {
+            ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0", "id": 1, "result": result
+            }))
+        };
+        let receipt = |id: u64, status: &str| {
+            serde_json::json!({
+                "blockHash": hash(1000).as_str(),
+                "blockNumber": "0x64",
+                "contractAddress": null,
+                "cumulativeGasUsed": "0x5208",
+                "gasUsed": "0x5208",
+                "logs": [],
+                "status": status,
+                "transactionHash": hash(id).as_str(),
+                "transactionIndex": "0x0"
+            })
+        };
+
+        let server = MockServer::start().await;
+        for (id, status) in [(1, "0x1"), (2, "0x0")] {
+            Mock::given(query_param("action", "eth_getTransactionReceipt"))
+                .and(query_param("txhash", hash(id).as_str()))
+                .respond_with(rpc(receipt(id, status)))
+                .mount(&server)
+                .await;
+        }
+        Mock::given(query_param("action", "eth_getTransactionReceipt"))
+            .and(query_param("txhash", hash(3).as_str()))
+            .respond_with(rpc(serde_json::Value::Null))
+            .mount(&server)
+            .await;
+        Mock::given(query_param("action", "eth_getTransactionByHash"))
+            .respond_with(rpc(serde_json::Value::Null))
+            .mount(&server)
+            .await;
+        // One confirmation on the first check, twelve on the next
+        Mock::given(query_param("action", "eth_blockNumber"))
+            .respond_with(rpc(serde_json::json!("0x64")))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(query_param("action", "eth_blockNumber"))
+            .respond_with(rpc(serde_json::json!("0x6f")))
+            .mount(&server)
+            .await;
+
+        let config = crate::config::ClientConfig::builder()
+            .api_key("key")
+            .base_url(server.uri())
+            .block_number_ttl(0)
+            .build()
+            .unwrap();
+        let verifier = PaymentVerifier::new(BscScanClient::with_config(config).unwrap());
+        let poll = Duration::from_millis(1);
+
+        let mined = verifier
+            .wait_for_confirmations(&hash(1), 12, poll)
+            .await
+            .unwrap();
+        assert_eq!(mined.transaction_hash, hash(1).as_str());
+        let block_checks = server
+            .received_requests()
+            .await
+            .unwrap()
+            .iter()
+            .filter(|request| {
+                request
+                    .url
+                    .query()
+                    .unwrap_or("")
+                    .contains("eth_blockNumber")
+            })
+            .count();
+        assert_eq!(block_checks, 2);
+
+        assert!(matches!(
+            verifier.wait_for_confirmations(&hash(2), 12, poll).await,
+            Err(Error::VerificationFailed(_))
+        ));
+        assert!(matches!(
+            verifier.wait_for_confirmations(&hash(3), 12, poll).await,
+            Err(Error::TransactionNotFound(ref tx_hash)) if *tx_hash == hash(3).as_str()
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_verify_batch_fetches_from_the_earliest_start_block() {
+        use wiremock::matchers::query_param;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let explorer = |result: serde_json::Value| {
+            ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "status": "1", "message": "OK", "result": result
+            }))
+        };
+
+        let server = MockServer::start().await;
+        for (timestamp, block) in [("1704067200", "100"), ("1704153600", "200")] {
+            Mock::given(query_param("action", "getblocknobytime"))
+                .and(query_param("timestamp", timestamp))
+                .respond_with(explorer(serde_json::json!(block)))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+        for action in ["txlist", "txlistinternal"] {
+            Mock::given(query_param("action", action))
+                .and(query_param("startblock", "100"))
+                .respond_with(explorer(serde_json::json!([])))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+
+        let config = crate::config::ClientConfig::builder()
+            .api_key("key")
+            .base_url(server.uri())
+            .cache_ttl(0)
+            .build()
+            .unwrap();
+        let verifier = PaymentVerifier::new(BscScanClient::with_config(config).unwrap());
+        let recipient = address(2);
+        let requests = [
+            PaymentRequest::eth(Decimal::ONE, recipient.clone(), 12)
+                .with_not_before("2024-01-02T00:00:00Z".parse().unwrap()),
+            PaymentRequest::eth(Decimal::TWO, recipient.clone(), 12)
+                .with_not_before("2024-01-01T00:00:00Z".parse().unwrap()),
+            PaymentRequest::eth(Decimal::TEN, recipient, 12)
+                .with_not_before("2024-01-02T00:00:00Z".parse().unwrap()),
+        ];
+
+        let results = verifier.verify_batch(&requests).await.unwrap();
+        assert_eq!(results.len(), 3);
+        assert!(results
+            .iter()
+            .all(|result| matches!(result, VerificationResult::NotFound)));
+    }
+
+    #[tokio::test]
+    async fn test_check_pending_transaction_verifies_the_mined_hash() {
+        use wiremock::matchers::query_param;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let rpc = |result: serde_json::Value| {
+            ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0", "id": 1, "result": result
+            }))
+        };
+        let recipient = address(2);
+
+        let server = MockServer::start().await;
+        // Hash 1 pays 0.5 ETH, hash 2 the requested 1 ETH
+        for (id, value) in [(1, "0x6f05b59d3b20000"), (2, "0xde0b6b3a7640000")] {
+            Mock::given(query_param("action", "eth_getTransactionByHash"))
+                .and(query_param("txhash", hash(id).as_str()))
+                .respond_with(rpc(serde_json::json!({
+                    "blockHash": hash(1000).as_str(),
+                    "blockNumber": "0x64",
+                    "from": address(1).as_str(),
+                    "gas": "0x5208",
+                    "gasPrice": "0x3b9aca00",
+                    "hash": hash(id).as_str(),
+                    "input": "0x",
+                    "nonce": "0x1",
+                    "to": recipient.as_str(),
+                    "transactionIndex": "0x0",
+                    "value": value
+                })))
+                .mount(&server)
+                .await;
+            Mock::given(query_param("action", "eth_getTransactionReceipt"))
+                .and(query_param("txhash", hash(id).as_str()))
+                .respond_with(rpc(serde_json::json!({
+                    "blockHash": hash(1000).as_str(),
+                    "blockNumber": "0x64",
+                    "contractAddress": null,
+                    "cumulativeGasUsed": "0x5208",
+                    "gasUsed": "0x5208",
+                    "logs": [],
+                    "status": "0x1",
+                    "transactionHash": hash(id).as_str(),
+                    "transactionIndex": "0x0"
+                })))
+                .mount(&server)
+                .await;
+        }
+        Mock::given(query_param("action", "eth_blockNumber"))
+            .respond_with(rpc(serde_json::json!("0x6f")))
+            .mount(&server)
+            .await;
+        // The recipient's history holds another transfer matching the request
+        Mock::given(query_param("action", "txlist"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "status": "1", "message": "OK", "result": [{
+                    "blockNumber": "100",
+                    "hash": hash(3).as_str(),
+                    "blockHash": hash(1000).as_str(),
+                    "from": address(1).as_str(),
+                    "to": recipient.as_str(),
+                    "value": "1000000000000000000",
+                    "isError": "0",
+                    "txreceipt_status": "1",
+                    "input": "0x",
+                    "contractAddress": "",
+                    "confirmations": "12"
+                }]
+            })))
+            .mount(&server)
+            .await;
+
+        let config = crate::config::ClientConfig::builder()
+            .api_key("key")
+            .base_url(server.uri())
+            .cache_ttl(0)
+            .block_number_ttl(0)
+            .build()
+            .unwrap();
+        let verifier = PaymentVerifier::new(BscScanClient::with_config(config).unwrap())
+            .accept_unconfirmed(true);
+        let request = PaymentRequest::eth(Decimal::ONE, recipient, 12);
+
+        assert!(matches!(
+            verifier.check_pending_transaction(&hash(1), &request).await.unwrap(),
+            VerificationResult::Failed { ref reason } if reason.starts_with("Amount mismatch")
+        ));
+        assert!(matches!(
+            verifier.check_pending_transaction(&hash(2), &request).await.unwrap(),
+            VerificationResult::Confirmed { ref tx_hash, confirmations: 12, .. }
+                if *tx_hash == hash(2).as_str()
+        ));
+        let scanned = server
+            .received_requests()
+            .await
+            .unwrap()
+            .iter()
+            .any(|request| request.url.query().unwrap_or("").contains("txlist"));
+        assert!(!scanned);
+    }
+
+    #[tokio::test]
+    async fn test_get_inclusion() {
+        use wiremock::matchers::query_param;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let rpc = |result: serde_json::Value| {
+            ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0", "id": 1, "result": result
+            }))
+        };
+
+        let server = MockServer::start().await;
+        for (id, block_number) in [(1, "0x64"), (2, "0xzz")] {
+            Mock::given(query_param("action", "eth_getTransactionByHash"))
+                .and(query_param("txhash", hash(id).as_str()))
+                .respond_with(rpc(serde_json::json!({
+                    "blockHash": hash(1000).as_str(),
+                    "blockNumber": block_number,
+                    "from": address(1).as_str(),
+                    "gas": "0x5208",
+                    "gasPrice": "0x3b9aca00",
+                    "hash": hash(id).as_str(),
+                    "input": "0x",
+                    "nonce": "0x1",
+                    "to": address(2).as_str(),
+                    "transactionIndex": "0x0",
+                    "value": "0x0"
+                })))
+                .mount(&server)
+                .await;
+        }
+        Mock::given(query_param("action", "eth_blockNumber"))
+            .respond_with(rpc(serde_json::json!("0x6f")))
+            .mount(&server)
+            .await;
+
+        let config = crate::config::ClientConfig::builder()
+            .api_key("key")
+            .base_url(server.uri())
+            .block_number_ttl(0)
+            .build()
+            .unwrap();
+        let verifier = PaymentVerifier::new(BscScanClient::with_config(config).unwrap());
+
+        let inclusion = verifier.get_inclusion(&hash(1)).await.unwrap().unwrap();
+        assert_eq!(inclusion.block_hash, hash(1000).as_str());
+        assert_eq!(inclusion.block_number, 100);
+        assert_eq!(inclusion.confirmations, 12);
+        assert!(matches!(
+            verifier.get_inclusion(&hash(2)).await,
+            Err(Error::Generic(ref message)) if message.contains("0xzz")
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_only_mempool_transactions_are_seen() {
+        use wiremock::matchers::query_param;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let recipient = address(2);
+        let server = MockServer::start().await;
+        Mock::given(query_param("action", "txlist"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "status": "1", "message": "OK", "result": [{
+                    "blockNumber": "100",
+                    "hash": hash(1).as_str(),
+                    "blockHash": hash(1000).as_str(),
+                    "from": address(1).as_str(),
+                    "to": recipient.as_str(),
+                    "value": "1000000000000000000",
+                    "isError": "0",
+                    "txreceipt_status": "1",
+                    "input": "0x",
+                    "contractAddress": "",
+                    "confirmations": "0"
+                }]
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(query_param("action", "eth_getTransactionByHash"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0", "id": 1, "result": {
+                    "blockHash": null,
+                    "blockNumber": null,
+                    "from": address(1).as_str(),
+                    "gas": "0x5208",
+                    "gasPrice": "0x3b9aca00",
+                    "hash": hash(2).as_str(),
+                    "input": "0x",
+                    "nonce": "0x1",
+                    "to": recipient.as_str(),
+                    "transactionIndex": null,
+                    "value": "0xde0b6b3a7640000"
+                }
+            })))
+            .mount(&server)
+            .await;
+
+        let config = crate::config::ClientConfig::builder()
+            .api_key("key")
+            .base_url(server.uri())
+            .build()
+            .unwrap();
+        let verifier = PaymentVerifier::new(BscScanClient::with_config(config).unwrap())
+            .accept_unconfirmed(true)
+            .scan_internal_transactions(false);
+        let request = PaymentRequest::eth(Decimal::ONE, recipient, 12);
+
+        // Scanned transfers are mined, so they are never reported as `Seen`
+        assert!(matches!(
+            verifier.verify_payment(&request).await.unwrap(),
+            VerificationResult::Pending { ref tx_hash, confirmations: 0, .. }
+                if *tx_hash == hash(1).as_str()
+        ));
+        assert_eq!(
+            verifier.verify_tx(&hash(2), &request).await.unwrap(),
+            VerificationResult::Seen { tx_hash: hash(2) }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_verify_payment_pages_through_history() {
+        use wiremock::matchers::query_param;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let recipient = address(2);
+        let transfer = |id: u64, value: &str| {
+            serde_json::json!({
+                "blockNumber": (100 + id).to_string(),
+                "hash": hash(id).as_str(),
+                "blockHash": hash(1000).as_str(),
+                "from": address(1).as_str(),
+                "to": recipient.as_str(),
+                "value": value,
+                "isError": "0",
+                "txreceipt_status": "1",
+                "input": "0x",
+                "contractAddress": "",
+                "confirmations": "12"
+            })
+        };
+        let explorer = |result: serde_json::Value| {
+            ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "status": "1", "message": "OK", "result": result
+            }))
+        };
+
+        // A full first page of small transfers; the payment is on the second
+        let server = MockServer::start().await;
+        let small: Vec<_> = (1..=1000).map(|id| transfer(id, "1")).collect();
+        Mock::given(query_param("action", "txlist"))
+            .and(query_param("page", "1"))
+            .respond_with(explorer(small.into()))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(query_param("action", "txlist"))
+            .and(query_param("page", "2"))
+            .respond_with(explorer(serde_json::json!([transfer(
+                1001,
+                "1000000000000000000"
+            )])))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let config = crate::config::ClientConfig::builder()
+            .api_key("key")
+            .base_url(server.uri())
+            .build()
+            .unwrap();
+        let verifier = PaymentVerifier::new(BscScanClient::with_config(config).unwrap())
+            .scan_internal_transactions(false);
+        let request = PaymentRequest::eth(Decimal::ONE, recipient, 12);
+
+        assert!(matches!(
+            verifier.verify_payment(&request).await.unwrap(),
+            VerificationResult::Confirmed { ref tx_hash, .. } if *tx_hash == hash(1001).as_str()
+        ));
+    }
 }
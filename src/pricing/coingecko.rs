@@ -0,0 +1,155 @@
+//! Exchange rates from the CoinGecko API
+
+use crate::error::{Error, Result};
+use crate::payment::Currency;
+use crate::pricing::{decimal_from_json, ExchangeRate, ExchangeRateProvider, Fiat};
+use async_trait::async_trait;
+use chrono::Utc;
+use reqwest::{Client, StatusCode};
+use serde_json::Value;
+use std::time::Duration;
+
+/// Default CoinGecko API base URL
+const DEFAULT_BASE_URL: &str = "https://api.coingecko.com/api/v3";
+
+/// Rate provider backed by CoinGecko's `simple/price` endpoints
+///
+/// ETH is priced by coin ID, ERC20 tokens by contract address on the
+/// Ethereum platform.
+#[derive(Debug, Clone)]
+pub struct CoinGeckoProvider {
+    http_client: Client,
+    base_url: String,
+    api_key: Option<String>,
+}
+
+impl CoinGeckoProvider {
+    /// Create a provider for the public CoinGecko API
+    pub fn new() -> Result<Self> {
+        let http_client = Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .map_err(|e| Error::InvalidConfig(format!("Failed to create HTTP client: {}", e)))?;
+
+        Ok(Self {
+            http_client,
+            base_url: DEFAULT_BASE_URL.to_string(),
+            api_key: None,
+        })
+    }
+
+    /// Send a (demo) API key with every request
+    pub fn with_api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    /// Use a different base URL (e.g. the Pro API or a proxy)
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    async fn get_json(&self, path: &str, params: &[(&str, &str)]) -> Result<Value> {
+        let mut request = self
+            .http_client
+            .get(format!("{}{}", self.base_url, path))
+            .query(params);
+        if let Some(api_key) = &self.api_key {
+            request = request.header("x-cg-demo-api-key", api_key);
+        }
+
+        let response = request.send().await?;
+        match response.status() {
+            StatusCode::TOO_MANY_REQUESTS => Err(Error::RateLimitExceeded { retry_after: None }),
+            status if !status.is_success() => Err(Error::api_error(format!(
+                "CoinGecko returned HTTP {}",
+                status
+            ))),
+            _ => Ok(response.json().await?),
+        }
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl ExchangeRateProvider for CoinGeckoProvider {
+    async fn get_rate(&self, currency: &Currency, fiat: Fiat) -> Result<ExchangeRate> {
+        let (body, key) = match currency {
+            Currency::Native => {
+                let params = [("ids", "ethereum"), ("vs_currencies", fiat.code())];
+                (
+                    self.get_json("/simple/price", &params).await?,
+                    "ethereum".to_string(),
+                )
+            }
+            Currency::ERC20 {
+                contract_address, ..
+            } => {
+                let params = [
+                    ("contract_addresses", contract_address.as_str()),
+                    ("vs_currencies", fiat.code()),
+                ];
+                let body = self
+                    .get_json("/simple/token_price/ethereum", &params)
+                    .await?;
+                (body, contract_address.to_string())
+            }
+        };
+
+        let rate = decimal_from_json(&body[key.as_str()][fiat.code()]).ok_or_else(|| {
+            Error::api_error(format!(
+                "CoinGecko has no {} price for {:?}",
+                fiat, currency
+            ))
+        })?;
+
+        Ok(ExchangeRate {
+            fiat,
+            rate,
+            source: "coingecko".to_string(),
+            fetched_at: Utc::now(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+    use std::str::FromStr;
+    use wiremock::matchers::{header, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_coingecko_token_price() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/simple/token_price/ethereum"))
+            .and(query_param("vs_currencies", "eur"))
+            .and(header("x-cg-demo-api-key", "demo"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48": { "eur": 0.921 }
+            })))
+            .mount(&server)
+            .await;
+
+        let provider = CoinGeckoProvider::new()
+            .unwrap()
+            .with_api_key("demo")
+            .with_base_url(server.uri());
+
+        let rate = provider
+            .get_rate(&Currency::usdc(), Fiat::Eur)
+            .await
+            .unwrap();
+        assert_eq!(rate.rate, Decimal::from_str("0.921").unwrap());
+        assert_eq!(rate.source, "coingecko");
+
+        // No mock for the ETH endpoint: the 404 surfaces as an API error
+        assert!(provider
+            .get_rate(&Currency::Native, Fiat::Usd)
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,45 @@
+//! Exchange rates from the Etherscan `ethprice` endpoint
+
+use crate::client::{BscScanClient, StatsEndpoints};
+use crate::error::{Error, Result};
+use crate::payment::Currency;
+use crate::pricing::{ExchangeRate, ExchangeRateProvider, Fiat};
+use async_trait::async_trait;
+use chrono::Utc;
+
+/// Rate provider backed by Etherscan's `stats/ethprice`
+///
+/// Only supports ETH priced in USD, but needs no extra API key. Combine with
+/// another provider for tokens or other fiat currencies.
+#[derive(Clone)]
+pub struct EtherscanRateProvider {
+    client: BscScanClient,
+}
+
+impl EtherscanRateProvider {
+    /// Create a provider using an existing client
+    pub fn new(client: BscScanClient) -> Self {
+        Self { client }
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl ExchangeRateProvider for EtherscanRateProvider {
+    async fn get_rate(&self, currency: &Currency, fiat: Fiat) -> Result<ExchangeRate> {
+        if *currency != Currency::Native || fiat != Fiat::Usd {
+            return Err(Error::generic(format!(
+                "Etherscan only provides ETH/USD, not {:?}/{}",
+                currency, fiat
+            )));
+        }
+
+        let price = self.client.get_eth_price().await?;
+        Ok(ExchangeRate {
+            fiat,
+            rate: price.usd(),
+            source: "etherscan".to_string(),
+            fetched_at: Utc::now(),
+        })
+    }
+}
@@ -0,0 +1,391 @@
+//! Fiat pricing with pluggable exchange rate providers
+//!
+//! Merchants usually price in USD or EUR. [`PaymentRequest::from_fiat`]
+//! converts a fiat amount into the crypto amount to request, using an
+//! [`ExchangeRateProvider`], and returns a [`FiatQuote`] recording the rate
+//! that was used. The quote can be attached to the payment record with
+//! [`Payment::with_fiat_quote`].
+
+use crate::error::{Error, Result};
+use crate::payment::{Currency, Payment, PaymentRequest};
+use crate::primitives::Address;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use moka::future::Cache;
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::time::Duration;
+
+mod coingecko;
+mod etherscan;
+
+pub use coingecko::CoinGeckoProvider;
+pub use etherscan::EtherscanRateProvider;
+
+/// Metadata key under which the fiat quote is stored on the payment
+const METADATA_KEY: &str = "fiat_quote";
+
+/// Fiat currency
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Fiat {
+    /// US dollar
+    Usd,
+    /// Euro
+    Eur,
+}
+
+impl Fiat {
+    /// Lowercase ISO 4217 code, as used by most price APIs
+    pub fn code(&self) -> &'static str {
+        match self {
+            Fiat::Usd => "usd",
+            Fiat::Eur => "eur",
+        }
+    }
+}
+
+impl fmt::Display for Fiat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.code().to_uppercase())
+    }
+}
+
+/// Amount of fiat money
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FiatAmount {
+    /// Amount in whole fiat units (e.g. dollars, not cents)
+    pub amount: Decimal,
+    /// Fiat currency
+    pub fiat: Fiat,
+}
+
+impl FiatAmount {
+    /// Amount in US dollars
+    pub fn usd(amount: Decimal) -> Self {
+        Self {
+            amount,
+            fiat: Fiat::Usd,
+        }
+    }
+
+    /// Amount in euros
+    pub fn eur(amount: Decimal) -> Self {
+        Self {
+            amount,
+            fiat: Fiat::Eur,
+        }
+    }
+}
+
+impl fmt::Display for FiatAmount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.amount, self.fiat)
+    }
+}
+
+/// Price of one unit of a crypto currency in fiat
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExchangeRate {
+    /// Fiat currency the price is quoted in
+    pub fiat: Fiat,
+    /// Fiat units per one unit of the crypto currency (e.g. USD per ETH)
+    pub rate: Decimal,
+    /// Name of the provider that supplied the rate
+    pub source: String,
+    /// When the rate was fetched
+    pub fetched_at: DateTime<Utc>,
+}
+
+/// Record of a fiat amount converted into a crypto amount
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FiatQuote {
+    /// Fiat amount that was priced
+    pub fiat_amount: FiatAmount,
+    /// Rate used for the conversion
+    pub rate: ExchangeRate,
+    /// Resulting amount of the crypto currency
+    pub amount: Decimal,
+}
+
+/// Source of crypto to fiat exchange rates
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+pub trait ExchangeRateProvider: Send + Sync {
+    /// Get the current price of one unit of `currency` in `fiat`
+    async fn get_rate(&self, currency: &Currency, fiat: Fiat) -> Result<ExchangeRate>;
+
+    /// Convert a fiat amount into an amount of `currency`
+    ///
+    /// The result is rounded up to the currency's decimals, so the payer never
+    /// sends less than the fiat amount is worth at the quoted rate.
+    async fn quote(&self, amount: FiatAmount, currency: &Currency) -> Result<FiatQuote> {
+        let rate = self.get_rate(currency, amount.fiat).await?;
+        if rate.rate <= Decimal::ZERO {
+            return Err(Error::api_error(format!(
+                "{} returned a non-positive rate",
+                rate.source
+            )));
+        }
+
+        let crypto = (amount.amount / rate.rate)
+            .round_dp_with_strategy(currency.decimals() as u32, RoundingStrategy::AwayFromZero);
+
+        Ok(FiatQuote {
+            fiat_amount: amount,
+            rate,
+            amount: crypto,
+        })
+    }
+}
+
+/// Provider returning fixed, manually configured rates
+///
+/// Useful for tests and for merchants that set their own prices.
+#[derive(Debug, Clone, Default)]
+pub struct StaticRateProvider {
+    rates: HashMap<(String, Fiat), Decimal>,
+}
+
+impl StaticRateProvider {
+    /// Create a provider without any rates
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the price of one unit of `currency` in `fiat`
+    pub fn with_rate(mut self, currency: &Currency, fiat: Fiat, rate: Decimal) -> Self {
+        self.rates.insert((currency_key(currency), fiat), rate);
+        self
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl ExchangeRateProvider for StaticRateProvider {
+    async fn get_rate(&self, currency: &Currency, fiat: Fiat) -> Result<ExchangeRate> {
+        let rate = self
+            .rates
+            .get(&(currency_key(currency), fiat))
+            .copied()
+            .ok_or_else(|| {
+                Error::generic(format!("no {} rate configured for {:?}", fiat, currency))
+            })?;
+
+        Ok(ExchangeRate {
+            fiat,
+            rate,
+            source: "static".to_string(),
+            fetched_at: Utc::now(),
+        })
+    }
+}
+
+/// Provider wrapper caching rates for a fixed time
+///
+/// Price APIs have tight rate limits; a short TTL (a minute or so) keeps
+/// invoice creation fast without quoting stale prices.
+pub struct CachedRateProvider<P> {
+    inner: P,
+    cache: Cache<(String, Fiat), ExchangeRate>,
+}
+
+impl<P: ExchangeRateProvider> CachedRateProvider<P> {
+    /// Wrap a provider, caching each rate for `ttl`
+    pub fn new(inner: P, ttl: Duration) -> Self {
+        Self {
+            inner,
+            cache: Cache::builder()
+                .max_capacity(1000)
+                .time_to_live(ttl)
+                .build(),
+        }
+    }
+
+    /// Get the wrapped provider
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl<P: ExchangeRateProvider> ExchangeRateProvider for CachedRateProvider<P> {
+    async fn get_rate(&self, currency: &Currency, fiat: Fiat) -> Result<ExchangeRate> {
+        let key = (currency_key(currency), fiat);
+        if let Some(rate) = self.cache.get(&key).await {
+            return Ok(rate);
+        }
+
+        let rate = self.inner.get_rate(currency, fiat).await?;
+        self.cache.insert(key, rate.clone()).await;
+        Ok(rate)
+    }
+}
+
+/// Case-insensitive identity of a currency
+fn currency_key(currency: &Currency) -> String {
+    match currency {
+        Currency::Native => "eth".to_string(),
+        Currency::ERC20 {
+            contract_address, ..
+        } => contract_address.to_string(),
+    }
+}
+
+impl PaymentRequest {
+    /// Create a payment request for a fiat amount, paid in `currency`
+    ///
+    /// The fiat amount is converted at the provider's current rate. The
+    /// returned [`FiatQuote`] records the rate used; attach it to the payment
+    /// with [`Payment::with_fiat_quote`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use cryptopay::*;
+    /// # use cryptopay::pricing::{CoinGeckoProvider, FiatAmount};
+    /// # async fn example() -> Result<()> {
+    /// let provider = CoinGeckoProvider::new()?;
+    /// let (request, quote) = PaymentRequest::from_fiat(
+    ///     FiatAmount::usd(rust_decimal::Decimal::from(25)),
+    ///     Currency::Native,
+    ///     "0x742d35cc6634c0532925a3b844bc9e7595f0beb0".parse()?,
+    ///     12,
+    ///     &provider,
+    /// )
+    /// .await?;
+    ///
+    /// let payment = Payment::new(request.with_timeout(900)).with_fiat_quote(&quote);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn from_fiat<P: ExchangeRateProvider + ?Sized>(
+        amount: FiatAmount,
+        currency: Currency,
+        recipient_address: Address,
+        required_confirmations: u64,
+        provider: &P,
+    ) -> Result<(Self, FiatQuote)> {
+        let quote = provider.quote(amount, &currency).await?;
+        let request = Self::new(
+            quote.amount,
+            currency,
+            recipient_address,
+            required_confirmations,
+        );
+        Ok((request, quote))
+    }
+}
+
+impl Payment {
+    /// Record the fiat quote the payment was priced with in its metadata
+    ///
+    /// Other metadata fields are kept; a non-object metadata value is replaced.
+    pub fn with_fiat_quote(mut self, quote: &FiatQuote) -> Self {
+        let quote = serde_json::to_value(quote).expect("FiatQuote serializes to JSON");
+        match self.metadata.as_object_mut() {
+            Some(metadata) => {
+                metadata.insert(METADATA_KEY.to_string(), quote);
+            }
+            None => self.metadata = serde_json::json!({ METADATA_KEY: quote }),
+        }
+        self
+    }
+
+    /// Fiat quote recorded with [`with_fiat_quote`](Self::with_fiat_quote), if any
+    pub fn fiat_quote(&self) -> Option<FiatQuote> {
+        self.metadata
+            .get(METADATA_KEY)
+            .and_then(|quote| serde_json::from_value(quote.clone()).ok())
+    }
+}
+
+/// Parse a JSON number without going through `f64`
+fn decimal_from_json(value: &serde_json::Value) -> Option<Decimal> {
+    let text = match value {
+        serde_json::Value::Number(number) => number.to_string(),
+        serde_json::Value::String(text) => text.clone(),
+        _ => return None,
+    };
+    text.parse()
+        .ok()
+        .or_else(|| Decimal::from_scientific(&text).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[tokio::test]
+    async fn test_from_fiat_rounds_up_and_records_quote() {
+        let provider = StaticRateProvider::new()
+            .with_rate(&Currency::Native, Fiat::Usd, Decimal::from(3000))
+            .with_rate(
+                &Currency::usdc(),
+                Fiat::Eur,
+                Decimal::from_str("0.92").unwrap(),
+            );
+
+        let (request, quote) = PaymentRequest::from_fiat(
+            FiatAmount::usd(Decimal::from(100)),
+            Currency::Native,
+            "0x0000000000000000000000000000000000000001"
+                .parse()
+                .unwrap(),
+            12,
+            &provider,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            request.amount,
+            Decimal::from_str("0.033333333333333334").unwrap()
+        );
+        assert_eq!(quote.rate.rate, Decimal::from(3000));
+
+        let (request, _) = PaymentRequest::from_fiat(
+            FiatAmount::eur(Decimal::from(10)),
+            Currency::usdc(),
+            "0x0000000000000000000000000000000000000001"
+                .parse()
+                .unwrap(),
+            6,
+            &provider,
+        )
+        .await
+        .unwrap();
+        assert_eq!(request.amount, Decimal::from_str("10.869566").unwrap());
+
+        let payment = Payment::new(request)
+            .with_metadata(serde_json::json!({ "order": 42 }))
+            .with_fiat_quote(&quote);
+        assert_eq!(payment.metadata["order"], 42);
+        assert_eq!(payment.fiat_quote(), Some(quote));
+
+        assert!(provider
+            .quote(FiatAmount::eur(Decimal::ONE), &Currency::Native)
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn test_decimal_from_json() {
+        let value = serde_json::json!({ "usd": 3012.45, "tiny": 1.5e-7, "text": "0.99" });
+        assert_eq!(
+            decimal_from_json(&value["usd"]),
+            Decimal::from_str("3012.45").ok()
+        );
+        assert_eq!(
+            decimal_from_json(&value["tiny"]),
+            Decimal::from_str("0.00000015").ok()
+        );
+        assert_eq!(
+            decimal_from_json(&value["text"]),
+            Decimal::from_str("0.99").ok()
+        );
+        assert_eq!(decimal_from_json(&value["missing"]), None);
+    }
+}
@@ -0,0 +1,542 @@
+//! Validated address, transaction hash and amount types
+//!
+//! [`Address`] and [`TxHash`] are checked when they are created and stored in
+//! lowercase, so two values compare equal regardless of how they were typed.
+//! Both serialize as their `0x`-prefixed lowercase hex string.
+//!
+//! [`TokenAmount`] holds raw on-chain amounts as 256-bit integers, so values
+//! that overflow `u128` or `Decimal` are never truncated.
+
+use crate::error::{Error, Result};
+use crate::payment::utils::{is_valid_address, is_valid_tx_hash, to_checksum_address};
+pub use primitive_types::U256;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+/// Ethereum/BSC account or contract address
+///
+/// Parsing accepts all-lowercase and all-uppercase hex, and mixed-case input
+/// only if it carries a valid EIP-55 checksum.
+///
+/// # Example
+/// ```
+/// # use cryptopay::Address;
+/// let address: Address = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed".parse().unwrap();
+/// assert_eq!(address.as_str(), "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed");
+/// assert_eq!(address, "0x5AAEB6053F3E94C9B9A09F33669435E7EF1BEAED");
+/// assert_eq!(address.to_checksum(), "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "openapi",
+    derive(utoipa::ToSchema),
+    schema(value_type = String, example = "0x742d35cc6634c0532925a3b844bc9e7595f0beb0")
+)]
+pub struct Address(String);
+
+impl Address {
+    /// Parse and normalize an address
+    pub fn parse(address: &str) -> Result<Self> {
+        if !is_valid_address(address) {
+            return Err(Error::InvalidAddress(address.to_string()));
+        }
+        Ok(Self(address.to_ascii_lowercase()))
+    }
+
+    /// Lowercase `0x`-prefixed hex form
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Address of a built-in constant, which is known to be valid
+    pub(crate) fn known(address: &'static str) -> Self {
+        debug_assert!(is_valid_address(address), "invalid address {}", address);
+        Self(address.to_ascii_lowercase())
+    }
+
+    /// EIP-55 mixed-case checksum form
+    pub fn to_checksum(&self) -> String {
+        // Always valid: the address was validated on creation
+        to_checksum_address(&self.0).unwrap_or_else(|_| self.0.clone())
+    }
+}
+
+/// Transaction hash
+///
+/// # Example
+/// ```
+/// # use cryptopay::TxHash;
+/// let hash: TxHash = "0x5C504ED432CB51138BCF09AA5E8A410DD4A1E204EF84BFED1BE16DFBA1B22060"
+///     .parse()
+///     .unwrap();
+/// assert!(hash.as_str().starts_with("0x5c504ed4"));
+/// assert!("0x123".parse::<TxHash>().is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "openapi",
+    derive(utoipa::ToSchema),
+    schema(
+        value_type = String,
+        example = "0x5c504ed432cb51138bcf09aa5e8a410dd4a1e204ef84bfed1be16dfba1b22060"
+    )
+)]
+pub struct TxHash(String);
+
+impl TxHash {
+    /// Parse and normalize a transaction hash
+    pub fn parse(hash: &str) -> Result<Self> {
+        if !is_valid_tx_hash(hash) {
+            return Err(Error::InvalidTxHash(hash.to_string()));
+        }
+        Ok(Self(hash.to_ascii_lowercase()))
+    }
+
+    /// Lowercase `0x`-prefixed hex form
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+macro_rules! impl_hex_string {
+    ($name:ident) => {
+        impl FromStr for $name {
+            type Err = Error;
+
+            fn from_str(s: &str) -> Result<Self> {
+                Self::parse(s)
+            }
+        }
+
+        impl TryFrom<&str> for $name {
+            type Error = Error;
+
+            fn try_from(s: &str) -> Result<Self> {
+                Self::parse(s)
+            }
+        }
+
+        impl TryFrom<String> for $name {
+            type Error = Error;
+
+            fn try_from(s: String) -> Result<Self> {
+                Self::parse(&s)
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+
+        impl AsRef<str> for $name {
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        /// Case-insensitive comparison with unparsed input
+        impl PartialEq<str> for $name {
+            fn eq(&self, other: &str) -> bool {
+                self.0.eq_ignore_ascii_case(other)
+            }
+        }
+
+        impl PartialEq<&str> for $name {
+            fn eq(&self, other: &&str) -> bool {
+                self.0.eq_ignore_ascii_case(other)
+            }
+        }
+
+        impl PartialEq<String> for $name {
+            fn eq(&self, other: &String) -> bool {
+                self.0.eq_ignore_ascii_case(other)
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(
+                &self,
+                serializer: S,
+            ) -> std::result::Result<S::Ok, S::Error> {
+                serializer.serialize_str(&self.0)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(
+                deserializer: D,
+            ) -> std::result::Result<Self, D::Error> {
+                let s = String::deserialize(deserializer)?;
+                Self::parse(&s).map_err(serde::de::Error::custom)
+            }
+        }
+    };
+}
+
+impl_hex_string!(Address);
+impl_hex_string!(TxHash);
+
+/// Raw on-chain amount together with its currency's decimals
+///
+/// The raw value is the amount in the smallest unit (wei for native
+/// currency), stored as a `U256` like on chain. Conversions to and from
+/// `Decimal` are exact or fail; formatting never loses digits.
+///
+/// # Example
+/// ```
+/// # use cryptopay::TokenAmount;
+/// // 10^30 raw units of an 18-decimals token (overflows Decimal's mantissa)
+/// let amount = TokenAmount::from_raw_str("1000000000000000000000000000001", 18).unwrap();
+/// assert_eq!(amount.to_string(), "1000000000000.000000000000000001");
+/// assert!(amount.to_decimal().is_err());
+///
+/// let half = TokenAmount::from_decimal("0.5".parse().unwrap(), 18).unwrap();
+/// assert_eq!(half.raw().to_string(), "500000000000000000");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TokenAmount {
+    raw: U256,
+    decimals: u8,
+}
+
+impl TokenAmount {
+    /// Create an amount from raw units
+    pub fn new(raw: U256, decimals: u8) -> Self {
+        Self { raw, decimals }
+    }
+
+    /// Zero in a currency with the given decimals
+    pub fn zero(decimals: u8) -> Self {
+        Self::new(U256::zero(), decimals)
+    }
+
+    /// Parse raw units from a base-10 integer string, as returned by explorer APIs
+    pub fn from_raw_str(raw: &str, decimals: u8) -> Result<Self> {
+        let raw =
+            U256::from_dec_str(raw.trim()).map_err(|_| Error::InvalidAmount(raw.to_string()))?;
+        Ok(Self::new(raw, decimals))
+    }
+
+    /// Parse raw units from a `0x`-prefixed hex quantity or ABI-encoded word
+    ///
+    /// An empty quantity (`"0x"`) is zero.
+    pub fn from_hex(raw: &str, decimals: u8) -> Result<Self> {
+        let digits = raw.trim_start_matches("0x");
+        if digits.is_empty() {
+            return Ok(Self::zero(decimals));
+        }
+        let raw =
+            U256::from_str_radix(digits, 16).map_err(|_| Error::InvalidAmount(raw.to_string()))?;
+        Ok(Self::new(raw, decimals))
+    }
+
+    /// Convert a human-readable amount to raw units
+    ///
+    /// Fails for negative amounts, amounts with more fractional digits than
+    /// `decimals`, and amounts that don't fit in 256 bits.
+    pub fn from_decimal(amount: Decimal, decimals: u8) -> Result<Self> {
+        let invalid = || Error::InvalidAmount(format!("{} with {} decimals", amount, decimals));
+
+        let normalized = amount.normalize();
+        if normalized.is_sign_negative() && !normalized.is_zero() {
+            return Err(invalid());
+        }
+        let scale = normalized.scale();
+        if scale > decimals as u32 {
+            return Err(invalid());
+        }
+
+        let mantissa = U256::from(normalized.mantissa().unsigned_abs());
+        let raw = pow10(decimals as u32 - scale)
+            .and_then(|multiplier| mantissa.checked_mul(multiplier))
+            .ok_or_else(invalid)?;
+        Ok(Self::new(raw, decimals))
+    }
+
+    /// Raw amount in the smallest unit
+    pub fn raw(&self) -> U256 {
+        self.raw
+    }
+
+    /// Decimals of the currency
+    pub fn decimals(&self) -> u8 {
+        self.decimals
+    }
+
+    /// Check if the amount is zero
+    pub fn is_zero(&self) -> bool {
+        self.raw.is_zero()
+    }
+
+    /// Add two amounts of the same currency, `None` on overflow or differing decimals
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        if self.decimals != other.decimals {
+            return None;
+        }
+        Some(Self::new(self.raw.checked_add(other.raw)?, self.decimals))
+    }
+
+    /// Exact human-readable amount
+    ///
+    /// Fails if the amount needs more precision than `Decimal` offers
+    /// (28 significant digits).
+    pub fn to_decimal(&self) -> Result<Decimal> {
+        let formatted = self.to_string();
+        Decimal::from_str_exact(&formatted).map_err(|_| Error::InvalidAmount(formatted))
+    }
+
+    /// Human-readable amount, rounded to `Decimal` precision
+    ///
+    /// Saturates at `Decimal::MAX` for amounts beyond its range.
+    pub fn to_decimal_lossy(&self) -> Decimal {
+        Decimal::from_str(&self.to_string()).unwrap_or(Decimal::MAX)
+    }
+}
+
+impl fmt::Display for TokenAmount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = self.raw.to_string();
+        let decimals = self.decimals as usize;
+        if decimals == 0 {
+            return f.write_str(&digits);
+        }
+
+        let padded = format!("{:0>width$}", digits, width = decimals + 1);
+        let (whole, fractional) = padded.split_at(padded.len() - decimals);
+        let fractional = fractional.trim_end_matches('0');
+        if fractional.is_empty() {
+            f.write_str(whole)
+        } else {
+            write!(f, "{}.{}", whole, fractional)
+        }
+    }
+}
+
+/// `10^exponent`, `None` if it doesn't fit in 256 bits
+fn pow10(exponent: u32) -> Option<U256> {
+    U256::from(10u8).checked_pow(U256::from(exponent))
+}
+
+/// Serde helper for optional fields that explorers report as `""` when absent
+/// (e.g. the `to` of a contract creation)
+pub(crate) mod empty_as_none {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::fmt::Display;
+    use std::str::FromStr;
+
+    pub fn serialize<T, S>(value: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize,
+        S: Serializer,
+    {
+        match value {
+            Some(value) => value.serialize(serializer),
+            None => serializer.serialize_str(""),
+        }
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+    where
+        T: FromStr,
+        T::Err: Display,
+        D: Deserializer<'de>,
+    {
+        match Option::<String>::deserialize(deserializer)? {
+            Some(s) if !s.is_empty() => s.parse().map(Some).map_err(serde::de::Error::custom),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Serde helpers for explorer quantities (block numbers, gas, confirmations)
+///
+/// Values arrive as decimal strings from the account module and as `0x` hex
+/// from the proxy module; both are accepted, as are JSON numbers. Empty or
+/// malformed values deserialize to zero rather than failing the whole
+/// response. Values serialize back as decimal strings.
+pub(crate) mod quantity {
+    use super::U256;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::fmt::Display;
+
+    /// Integer types a quantity can deserialize into
+    pub trait Quantity: Sized + Default + Display {
+        fn from_radix(digits: &str, radix: u32) -> Option<Self>;
+    }
+
+    impl Quantity for u64 {
+        fn from_radix(digits: &str, radix: u32) -> Option<Self> {
+            u64::from_str_radix(digits, radix).ok()
+        }
+    }
+
+    impl Quantity for U256 {
+        fn from_radix(digits: &str, radix: u32) -> Option<Self> {
+            U256::from_str_radix(digits, radix).ok()
+        }
+    }
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    pub(crate) enum Raw {
+        Text(String),
+        Number(u64),
+    }
+
+    /// Parse a decimal or `0x` hex quantity
+    pub(crate) fn parse<T: Quantity>(value: &str) -> Option<T> {
+        let value = value.trim();
+        match value.strip_prefix("0x") {
+            Some(hex) => T::from_radix(hex, 16),
+            None => T::from_radix(value, 10),
+        }
+    }
+
+    pub(crate) fn parse_raw<T: Quantity>(raw: Option<Raw>) -> Option<T> {
+        match raw? {
+            Raw::Text(text) => parse(&text),
+            Raw::Number(number) => parse(&number.to_string()),
+        }
+    }
+
+    pub fn serialize<T: Display, S: Serializer>(
+        value: &T,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(value)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: Quantity,
+        D: Deserializer<'de>,
+    {
+        Ok(parse_raw(Option::<Raw>::deserialize(deserializer)?).unwrap_or_default())
+    }
+}
+
+/// Serde helper for unix timestamps (seconds, decimal or `0x` hex)
+///
+/// Missing or malformed timestamps deserialize to `None`, which serializes
+/// back as `""`.
+pub(crate) mod unix_timestamp {
+    use super::quantity::{parse_raw, Raw};
+    use chrono::{DateTime, Utc};
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        value: &Option<DateTime<Utc>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(timestamp) => serializer.collect_str(&timestamp.timestamp()),
+            None => serializer.serialize_str(""),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<DateTime<Utc>>, D::Error> {
+        let seconds: Option<u64> = parse_raw(Option::<Raw>::deserialize(deserializer)?);
+        Ok(seconds
+            .and_then(|seconds| i64::try_from(seconds).ok())
+            .and_then(|seconds| DateTime::from_timestamp(seconds, 0)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_amount_conversions() {
+        // Above u128::MAX
+        let raw = "10000000005000000000000000000000000000000";
+        let amount = TokenAmount::from_raw_str(raw, 18).unwrap();
+        assert_eq!(amount.raw().to_string(), raw);
+        assert_eq!(amount.to_string(), "10000000005000000000000");
+        assert_eq!(
+            amount.to_decimal().unwrap(),
+            Decimal::from(10_000_000_005_000_000_000_000i128)
+        );
+
+        // More digits than Decimal holds
+        let precise =
+            TokenAmount::from_raw_str("340282366920938463463374607431768211456", 18).unwrap();
+        assert_eq!(
+            precise.to_string(),
+            "340282366920938463463.374607431768211456"
+        );
+        assert!(precise.to_decimal().is_err());
+        assert_eq!(
+            precise.to_decimal_lossy(),
+            Decimal::from_str("340282366920938463463.37460743").unwrap()
+        );
+
+        let tiny = TokenAmount::new(U256::one(), 30);
+        assert_eq!(tiny.to_string(), "0.000000000000000000000000000001");
+        assert!(tiny.to_decimal().is_err());
+        assert_eq!(tiny.to_decimal_lossy(), Decimal::ZERO);
+        assert_eq!(TokenAmount::zero(6).to_string(), "0");
+
+        let max = TokenAmount::new(U256::MAX, 0);
+        assert_eq!(max.to_decimal_lossy(), Decimal::MAX);
+        assert!(max.checked_add(TokenAmount::new(U256::one(), 0)).is_none());
+
+        let hex = TokenAmount::from_hex(&format!("0x{:064x}", 1_500_000u64), 6).unwrap();
+        assert_eq!(hex.to_decimal().unwrap(), Decimal::new(15, 1));
+        assert_eq!(
+            TokenAmount::from_decimal(Decimal::new(15, 1), 6).unwrap(),
+            hex
+        );
+        assert_eq!(
+            TokenAmount::from_decimal(Decimal::from(1_000_000_000_000i64), 18)
+                .unwrap()
+                .raw(),
+            U256::from(10u8).pow(U256::from(30))
+        );
+        assert!(TokenAmount::from_decimal(Decimal::new(1, 7), 6).is_err());
+        assert!(TokenAmount::from_decimal(Decimal::NEGATIVE_ONE, 18).is_err());
+        assert!(TokenAmount::from_raw_str("12abc", 18).is_err());
+    }
+
+    #[test]
+    fn test_address_serde() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Tx {
+            from: Address,
+            #[serde(with = "empty_as_none")]
+            to: Option<Address>,
+        }
+
+        let tx: Tx = serde_json::from_str(
+            r#"{"from": "0xFB6916095CA1DF60BB79CE92CE3EA74C37C5D359", "to": ""}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            tx.from.as_str(),
+            "0xfb6916095ca1df60bb79ce92ce3ea74c37c5d359"
+        );
+        assert_eq!(tx.to, None);
+        assert_eq!(
+            serde_json::to_string(&tx).unwrap(),
+            r#"{"from":"0xfb6916095ca1df60bb79ce92ce3ea74c37c5d359","to":""}"#
+        );
+
+        // Mixed case with a broken checksum
+        assert!(serde_json::from_str::<Tx>(
+            r#"{"from": "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d35A", "to": ""}"#
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,286 @@
+//! Reconciliation of stored payments against on-chain history
+
+use crate::client::BscScanClient;
+use crate::error::Result;
+use crate::ledger::{fetch_deposits, same_currency, Deposit};
+use crate::payment::{Payment, PaymentStatus, PaymentVerifier};
+use crate::primitives::{Address, TxHash};
+use crate::storage::PaymentStorage;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::ops::RangeInclusive;
+use uuid::Uuid;
+
+/// Difference between what storage says and what the chain shows
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Discrepancy {
+    /// Payment is stored as confirmed, but its transaction no longer exists
+    MissingTransaction {
+        /// Payment ID
+        payment_id: Uuid,
+        /// Transaction hash recorded for the payment
+        tx_hash: String,
+    },
+
+    /// Inbound transfer that no stored payment accounts for
+    UnmatchedDeposit {
+        /// The transfer
+        deposit: Deposit,
+    },
+
+    /// Transaction recorded for a payment pays a different amount
+    AmountMismatch {
+        /// Payment ID
+        payment_id: Uuid,
+        /// Transaction hash recorded for the payment
+        tx_hash: String,
+        /// Requested amount in the currency that was received
+        expected: Decimal,
+        /// Amount actually received
+        actual: Decimal,
+    },
+}
+
+/// Result of a reconciliation run
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReconciliationReport {
+    /// Number of stored payments checked
+    pub payments_checked: usize,
+    /// Number of on-chain deposits checked
+    pub deposits_checked: usize,
+    /// Discrepancies found
+    pub discrepancies: Vec<Discrepancy>,
+}
+
+impl ReconciliationReport {
+    /// Check if storage and chain agree
+    pub fn is_clean(&self) -> bool {
+        self.discrepancies.is_empty()
+    }
+}
+
+/// Cross-checks stored payments against the chain
+pub struct Reconciler;
+
+impl Reconciler {
+    /// Reconcile the payments to `addresses` with their on-chain history in `blocks`
+    ///
+    /// Reports confirmed payments whose transaction is gone (e.g. after a
+    /// reorg), deposits in the block range that no payment claims, and
+    /// payments whose transaction pays a different amount than requested.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use cryptopay::*;
+    /// # use cryptopay::reconcile::Reconciler;
+    /// # async fn example(storage: MemoryStorage) -> Result<()> {
+    /// let client = BscScanClient::new("api-key")?;
+    /// let wallet: Address = "0x742d35cc6634c0532925a3b844bc9e7595f0beb0".parse()?;
+    /// let report = Reconciler::run(&storage, &client, &[wallet], 19_000_000..=19_100_000).await?;
+    ///
+    /// for discrepancy in &report.discrepancies {
+    ///     println!("{:?}", discrepancy);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn run<S>(
+        storage: &S,
+        client: &BscScanClient,
+        addresses: &[Address],
+        blocks: RangeInclusive<u64>,
+    ) -> Result<ReconciliationReport>
+    where
+        S: PaymentStorage + ?Sized,
+    {
+        let payments: Vec<Payment> = storage
+            .list_payments()
+            .await?
+            .into_iter()
+            .filter(|payment| addresses.contains(&payment.request.recipient_address))
+            .collect();
+
+        let mut deposits = Vec::new();
+        for address in addresses {
+            deposits.extend(fetch_deposits(client, address, *blocks.start(), *blocks.end()).await?);
+        }
+
+        let verifier = PaymentVerifier::new(client.clone());
+        let mut missing = HashSet::new();
+        for payment in &payments {
+            if let PaymentStatus::Confirmed { tx_hash, .. } = &payment.status {
+                if verifier
+                    .get_inclusion(&TxHash::parse(tx_hash)?)
+                    .await?
+                    .is_none()
+                {
+                    missing.insert(tx_hash.to_lowercase());
+                }
+            }
+        }
+
+        Ok(reconcile(&payments, deposits, &missing))
+    }
+}
+
+/// Compare payments with deposits, given the (lowercase) hashes of confirmed
+/// payment transactions that no longer exist
+fn reconcile(
+    payments: &[Payment],
+    deposits: Vec<Deposit>,
+    missing: &HashSet<String>,
+) -> ReconciliationReport {
+    let mut report = ReconciliationReport {
+        payments_checked: payments.len(),
+        deposits_checked: deposits.len(),
+        discrepancies: Vec::new(),
+    };
+    let mut claimed = HashSet::new();
+
+    for payment in payments {
+        let tx_hashes: Vec<&String> = match &payment.status {
+            PaymentStatus::Detected { tx_hash, .. } | PaymentStatus::Confirmed { tx_hash, .. } => {
+                vec![tx_hash]
+            }
+            PaymentStatus::PartiallyPaid { tx_hashes, .. } => tx_hashes.iter().collect(),
+            _ => Vec::new(),
+        };
+
+        for tx_hash in &tx_hashes {
+            claimed.insert(tx_hash.to_lowercase());
+        }
+
+        let PaymentStatus::Confirmed { tx_hash, .. } = &payment.status else {
+            continue;
+        };
+
+        if missing.contains(&tx_hash.to_lowercase()) {
+            report.discrepancies.push(Discrepancy::MissingTransaction {
+                payment_id: payment.id,
+                tx_hash: tx_hash.clone(),
+            });
+            continue;
+        }
+
+        // Compare against the option paid in, if the transfer is in the scanned range
+        let paid: Vec<&Deposit> = deposits
+            .iter()
+            .filter(|deposit| {
+                deposit.tx_hash.eq_ignore_ascii_case(tx_hash)
+                    && payment.request.recipient_address == deposit.details.to
+            })
+            .collect();
+
+        for option in payment.request.option_requests() {
+            let received: Vec<Decimal> = paid
+                .iter()
+                .filter(|deposit| same_currency(&deposit.details.currency, &option.currency))
+                .map(|deposit| deposit.details.amount)
+                .collect();
+            if received.is_empty() {
+                continue;
+            }
+
+            let actual: Decimal = received.into_iter().sum();
+            if !option.accepts_amount(actual) {
+                report.discrepancies.push(Discrepancy::AmountMismatch {
+                    payment_id: payment.id,
+                    tx_hash: tx_hash.clone(),
+                    expected: option.amount,
+                    actual,
+                });
+            }
+            break;
+        }
+    }
+
+    for deposit in deposits {
+        if !claimed.contains(&deposit.tx_hash.to_lowercase()) {
+            report
+                .discrepancies
+                .push(Discrepancy::UnmatchedDeposit { deposit });
+        }
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payment::{Currency, PaymentDetails, PaymentRequest};
+
+    const WALLET: &str = "0x742d35cc6634c0532925a3b844bc9e7595f0beb0";
+
+    fn confirmed(amount: i64, tx_hash: &str) -> Payment {
+        let mut payment = Payment::new(PaymentRequest::eth(
+            Decimal::from(amount),
+            WALLET.parse().unwrap(),
+            12,
+        ));
+        payment.update_status(PaymentStatus::Confirmed {
+            tx_hash: tx_hash.to_string(),
+            confirmations: 12,
+        });
+        payment
+    }
+
+    fn deposit(tx_hash: &str, amount: i64) -> Deposit {
+        Deposit {
+            tx_hash: tx_hash.to_string(),
+            symbol: "ETH".to_string(),
+            details: PaymentDetails {
+                currency: Currency::Native,
+                amount: Decimal::from(amount),
+                from: "0x00000000000000000000000000000000000000c1"
+                    .parse()
+                    .unwrap(),
+                to: WALLET.parse().unwrap(),
+                block_number: 100,
+                timestamp: None,
+                gas_cost: None,
+                funded_by: None,
+            },
+        }
+    }
+
+    #[test]
+    fn test_reconcile_reports_discrepancies() {
+        let ok = confirmed(1, "0xok");
+        let short = confirmed(5, "0xshort");
+        let gone = confirmed(2, "0xgone");
+        let missing = HashSet::from(["0xgone".to_string()]);
+
+        let report = reconcile(
+            &[ok, short.clone(), gone.clone()],
+            vec![
+                deposit("0xOK", 1),
+                deposit("0xshort", 4),
+                deposit("0xstray", 3),
+            ],
+            &missing,
+        );
+
+        assert_eq!(report.payments_checked, 3);
+        assert_eq!(report.deposits_checked, 3);
+        assert_eq!(
+            report.discrepancies,
+            vec![
+                Discrepancy::AmountMismatch {
+                    payment_id: short.id,
+                    tx_hash: "0xshort".to_string(),
+                    expected: Decimal::from(5),
+                    actual: Decimal::from(4),
+                },
+                Discrepancy::MissingTransaction {
+                    payment_id: gone.id,
+                    tx_hash: "0xgone".to_string(),
+                },
+                Discrepancy::UnmatchedDeposit {
+                    deposit: deposit("0xstray", 3),
+                },
+            ]
+        );
+    }
+}
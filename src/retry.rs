@@ -0,0 +1,164 @@
+//! Retries of transient failures
+//!
+//! [`RetryPolicy`] is shared by the explorer client, the gateway's webhook
+//! delivery, and the monitor pool's storage writes, so all of them back off
+//! the same way and agree on which errors are worth another attempt (see
+//! [`Error::is_retryable`]).
+
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::time::Duration;
+
+/// Exponential backoff for retrying transient errors
+///
+/// The delay starts at `initial_backoff_ms` and doubles with every attempt
+/// up to `max_backoff_ms`. A delay the error asks for
+/// ([`Error::retry_after`]) is waited at least.
+///
+/// # Example
+/// ```
+/// use cryptopay::RetryPolicy;
+/// use std::time::Duration;
+///
+/// let policy = RetryPolicy::new(5).initial_backoff(Duration::from_millis(200));
+/// assert_eq!(policy.backoff(0), Duration::from_millis(200));
+/// assert_eq!(policy.backoff(2), Duration::from_millis(800));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    /// Attempts after the first one (default: 3)
+    pub max_retries: u32,
+
+    /// Delay before the first retry in milliseconds (default: 500)
+    pub initial_backoff_ms: u64,
+
+    /// Upper bound of the delay in milliseconds (default: 30000)
+    pub max_backoff_ms: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff_ms: 500,
+            max_backoff_ms: 30_000,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Policy with `max_retries` retries and the default backoff
+    pub fn new(max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            ..Self::default()
+        }
+    }
+
+    /// Policy that never retries
+    pub fn none() -> Self {
+        Self::new(0)
+    }
+
+    /// Set the delay before the first retry
+    pub fn initial_backoff(mut self, backoff: Duration) -> Self {
+        self.initial_backoff_ms = backoff.as_millis() as u64;
+        self
+    }
+
+    /// Set the upper bound of the delay
+    pub fn max_backoff(mut self, backoff: Duration) -> Self {
+        self.max_backoff_ms = backoff.as_millis() as u64;
+        self
+    }
+
+    /// Delay before retry number `attempt` (starting at 0)
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let delay = self
+            .initial_backoff_ms
+            .saturating_mul(2u64.saturating_pow(attempt));
+        Duration::from_millis(delay.min(self.max_backoff_ms))
+    }
+
+    /// Run `operation`, retrying it while it fails with a retryable error
+    ///
+    /// Returns the first success, the first error that isn't retryable, or
+    /// the last error once the retries are used up.
+    pub async fn retry<T, F, Fut>(&self, mut operation: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut attempt = 0;
+        loop {
+            match operation().await {
+                Err(e) if attempt < self.max_retries && e.is_retryable() => {
+                    let delay = self.delay(attempt, &e);
+                    tracing::debug!(attempt, error = %e, delay_ms = delay.as_millis() as u64, "retrying");
+                    crate::rt::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    fn delay(&self, attempt: u32, error: &Error) -> Duration {
+        self.backoff(attempt)
+            .max(error.retry_after().unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[tokio::test]
+    async fn test_retries_transient_errors_only() {
+        let policy = RetryPolicy::new(2).initial_backoff(Duration::from_millis(1));
+        let calls = AtomicU32::new(0);
+
+        let result: Result<()> = policy
+            .retry(|| async {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Err(Error::api_error("HTTP 502 Bad Gateway: Unknown error"))
+            })
+            .await;
+        assert!(result.unwrap_err().is_retryable());
+        assert_eq!(calls.swap(0, Ordering::SeqCst), 3);
+
+        let result: Result<()> = policy
+            .retry(|| async {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Err(Error::InvalidConfig("bad".to_string()))
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(calls.swap(0, Ordering::SeqCst), 1);
+
+        let result = policy
+            .retry(|| async {
+                match calls.fetch_add(1, Ordering::SeqCst) {
+                    0 => Err(Error::api_error("HTTP 503 Service Unavailable: down")),
+                    _ => Ok(42),
+                }
+            })
+            .await;
+        assert_eq!(result.unwrap(), 42);
+    }
+
+    #[test]
+    fn test_backoff_is_capped() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.backoff(0), Duration::from_millis(500));
+        assert_eq!(policy.backoff(3), Duration::from_secs(4));
+        assert_eq!(policy.backoff(40), Duration::from_secs(30));
+        assert_eq!(
+            policy.delay(0, &Error::RateLimitExceeded { retry_after: None }),
+            Duration::from_secs(1)
+        );
+    }
+}
@@ -0,0 +1,23 @@
+//! Timer shims so the crate builds for `wasm32-unknown-unknown`
+//!
+//! Tokio's timer driver isn't available in the browser; there, delays use
+//! `setTimeout` through `gloo-timers` and instants come from `performance.now()`.
+
+use std::time::Duration;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+pub(crate) use web_time::Instant;
+
+/// Wait for `duration`
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) async fn sleep(duration: Duration) {
+    tokio::time::sleep(duration).await;
+}
+
+/// Wait for `duration`
+#[cfg(target_arch = "wasm32")]
+pub(crate) async fn sleep(duration: Duration) {
+    gloo_timers::future::sleep(duration).await;
+}
@@ -0,0 +1,417 @@
+//! Drop-in HTTP payment API (`server` feature)
+//!
+//! [`axum_router`] exposes a small REST API on top of a [`MonitorPool`]:
+//!
+//! | Method | Path                         | Description                            |
+//! |--------|------------------------------|----------------------------------------|
+//! | POST   | `/payments`                  | Create a payment and start monitoring  |
+//! | GET    | `/payments`                  | List payments                          |
+//! | GET    | `/payments/{id}`             | Get a payment and its status           |
+//! | POST   | `/payments/{id}/redeliver`   | Re-broadcast the payment's last update |
+//!
+//! Every request needs one of the configured API keys, either as
+//! `Authorization: Bearer <key>` or in an `X-Api-Key` header.
+//!
+//! With the `openapi` feature, [`openapi`] describes the API as an OpenAPI 3
+//! document, e.g. to generate client SDKs.
+
+use crate::error::Error;
+use crate::payment::{MonitorPool, Payment, PaymentRequest, PaymentUpdate};
+use axum::extract::{Path, Request, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::Deserialize;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Shared state of the router
+#[derive(Clone)]
+struct ApiState {
+    pool: Arc<MonitorPool>,
+    api_keys: Arc<Vec<String>>,
+}
+
+/// Body of `POST /payments`: a payment request plus optional metadata
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+struct CreatePayment {
+    #[serde(flatten)]
+    request: PaymentRequest,
+    #[serde(default)]
+    metadata: serde_json::Value,
+}
+
+/// Build the payment API router
+///
+/// Payments are stored in and monitored by `pool`. Requests without one of
+/// `api_keys` are rejected with `401 Unauthorized`; with no keys configured,
+/// every request is rejected.
+///
+/// # Example
+/// ```no_run
+/// # use cryptopay::*;
+/// # use cryptopay::payment::MonitorPool;
+/// # use std::sync::Arc;
+/// # use std::time::Duration;
+/// # async fn example() -> std::result::Result<(), Box<dyn std::error::Error>> {
+/// let monitor = PaymentMonitor::new(BscScanClient::new("api-key")?, Duration::from_secs(15));
+/// let pool = Arc::new(MonitorPool::new(monitor, Arc::new(MemoryStorage::new())));
+/// pool.resume().await?;
+///
+/// let app = cryptopay::server::axum_router(pool, ["secret-key"]);
+/// let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await?;
+/// axum::serve(listener, app).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn axum_router<I, K>(pool: Arc<MonitorPool>, api_keys: I) -> Router
+where
+    I: IntoIterator<Item = K>,
+    K: Into<String>,
+{
+    let state = ApiState {
+        pool,
+        api_keys: Arc::new(api_keys.into_iter().map(Into::into).collect()),
+    };
+
+    Router::new()
+        .route("/payments", post(create_payment).get(list_payments))
+        .route("/payments/{id}", get(get_payment))
+        .route("/payments/{id}/redeliver", post(redeliver))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            require_api_key,
+        ))
+        .with_state(state)
+}
+
+async fn require_api_key(State(state): State<ApiState>, request: Request, next: Next) -> Response {
+    if is_authorized(request.headers(), &state.api_keys) {
+        next.run(request).await
+    } else {
+        ApiError(
+            StatusCode::UNAUTHORIZED,
+            Error::Unauthorized("missing or invalid API key".to_string()),
+        )
+        .into_response()
+    }
+}
+
+/// Check the request's API key against the configured keys in constant time
+fn is_authorized(headers: &HeaderMap, api_keys: &[String]) -> bool {
+    let bearer = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    let key = bearer.or_else(|| {
+        headers
+            .get("x-api-key")
+            .and_then(|value| value.to_str().ok())
+    });
+
+    match key {
+        Some(key) => api_keys.iter().fold(false, |found, expected| {
+            found | constant_time_eq(key, expected)
+        }),
+        None => false,
+    }
+}
+
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0u8, |diff, (x, y)| diff | (x ^ y))
+            == 0
+}
+
+#[cfg_attr(feature = "openapi", utoipa::path(
+    post,
+    path = "/payments",
+    request_body = CreatePayment,
+    responses(
+        (status = 201, description = "Payment created and monitored", body = Payment),
+        (status = 400, description = "Invalid payment request", body = ErrorBody),
+    ),
+))]
+async fn create_payment(
+    State(state): State<ApiState>,
+    Json(body): Json<CreatePayment>,
+) -> Result<(StatusCode, Json<Payment>), ApiError> {
+    let payment = Payment::new(body.request).with_metadata(body.metadata);
+    state.pool.watch(payment.clone()).await?;
+    Ok((StatusCode::CREATED, Json(payment)))
+}
+
+#[cfg_attr(feature = "openapi", utoipa::path(
+    get,
+    path = "/payments",
+    responses((status = 200, description = "All payments", body = Vec<Payment>)),
+))]
+async fn list_payments(State(state): State<ApiState>) -> Result<Json<Vec<Payment>>, ApiError> {
+    Ok(Json(state.pool.storage().list_payments().await?))
+}
+
+#[cfg_attr(feature = "openapi", utoipa::path(
+    get,
+    path = "/payments/{id}",
+    params(("id" = Uuid, Path, description = "Payment ID")),
+    responses(
+        (status = 200, description = "The payment and its status", body = Payment),
+        (status = 404, description = "Unknown payment", body = ErrorBody),
+    ),
+))]
+async fn get_payment(
+    State(state): State<ApiState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Payment>, ApiError> {
+    match state.pool.storage().get_payment(id).await? {
+        Some(payment) => Ok(Json(payment)),
+        None => Err(Error::PaymentNotFound(id).into()),
+    }
+}
+
+#[cfg_attr(feature = "openapi", utoipa::path(
+    post,
+    path = "/payments/{id}/redeliver",
+    params(("id" = Uuid, Path, description = "Payment ID")),
+    responses(
+        (status = 202, description = "Last update re-broadcast", body = PaymentUpdate),
+        (status = 404, description = "Unknown payment", body = ErrorBody),
+    ),
+))]
+async fn redeliver(
+    State(state): State<ApiState>,
+    Path(id): Path<Uuid>,
+) -> Result<(StatusCode, Json<PaymentUpdate>), ApiError> {
+    let update = state.pool.redeliver(id).await?;
+    Ok((StatusCode::ACCEPTED, Json(update)))
+}
+
+/// OpenAPI document of the payment API (`openapi` feature)
+///
+/// Covers every route of [`axum_router`] with its request and response
+/// schemas, plus the `Authorization: Bearer` and `X-Api-Key` security schemes.
+///
+/// # Example
+/// ```
+/// let json = cryptopay::server::openapi().to_pretty_json().unwrap();
+/// assert!(json.contains("/payments/{id}/redeliver"));
+/// ```
+#[cfg(feature = "openapi")]
+pub fn openapi() -> utoipa::openapi::OpenApi {
+    use utoipa::OpenApi;
+
+    #[derive(OpenApi)]
+    #[openapi(
+        info(title = "cryptopay payment API"),
+        paths(create_payment, list_payments, get_payment, redeliver),
+        components(schemas(crate::payment::VerificationResult)),
+        modifiers(&SecuritySchemes),
+        security(("bearer" = []), ("api_key" = [])),
+    )]
+    struct ApiDoc;
+
+    ApiDoc::openapi()
+}
+
+#[cfg(feature = "openapi")]
+struct SecuritySchemes;
+
+#[cfg(feature = "openapi")]
+impl utoipa::Modify for SecuritySchemes {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        use utoipa::openapi::security::{
+            ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme,
+        };
+
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+        );
+        components.add_security_scheme(
+            "api_key",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new("X-Api-Key"))),
+        );
+    }
+}
+
+/// Error response body, as documented in the OpenAPI schema
+#[cfg(feature = "openapi")]
+#[derive(utoipa::ToSchema)]
+#[allow(dead_code)]
+struct ErrorBody {
+    error: ErrorDetails,
+}
+
+/// Serialized [`Error`]
+#[cfg(feature = "openapi")]
+#[derive(utoipa::ToSchema)]
+#[allow(dead_code)]
+struct ErrorDetails {
+    /// Stable error code, e.g. `CP3009`
+    code: String,
+    /// Human-readable message
+    message: String,
+    /// Error-specific fields
+    details: Option<serde_json::Value>,
+}
+
+/// Error response: status code plus a JSON `{"error": {"code", "message", "details"}}` body
+struct ApiError(StatusCode, Error);
+
+impl From<Error> for ApiError {
+    fn from(error: Error) -> Self {
+        let status = match &error {
+            Error::PaymentNotFound(_) => StatusCode::NOT_FOUND,
+            Error::InvalidAddress(_)
+            | Error::InvalidAmount(_)
+            | Error::InvalidConfig(_)
+            | Error::ChainMismatch { .. }
+            | Error::Serialization(_) => StatusCode::BAD_REQUEST,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        ApiError(status, error)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = serde_json::json!({ "error": self.1 });
+        (self.0, Json(body)).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::BscScanClient;
+    use crate::payment::PaymentMonitor;
+    use crate::storage::MemoryStorage;
+    use axum::body::Body;
+    use std::time::Duration;
+    use tower::ServiceExt;
+
+    fn router() -> Router {
+        let monitor = PaymentMonitor::new(
+            BscScanClient::new("test-key").unwrap(),
+            Duration::from_secs(60),
+        );
+        let pool = Arc::new(MonitorPool::new(monitor, Arc::new(MemoryStorage::new())));
+        axum_router(pool, ["secret"])
+    }
+
+    async fn send(
+        app: &Router,
+        method: &str,
+        uri: &str,
+        key: Option<&str>,
+        body: &str,
+    ) -> Response {
+        let mut request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(header::CONTENT_TYPE, "application/json");
+        if let Some(key) = key {
+            request = request.header("x-api-key", key);
+        }
+        app.clone()
+            .oneshot(request.body(Body::from(body.to_string())).unwrap())
+            .await
+            .unwrap()
+    }
+
+    async fn json(response: Response) -> serde_json::Value {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_payment_api() {
+        let app = router();
+
+        let response = send(&app, "GET", "/payments", None, "").await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = send(&app, "GET", "/payments", Some("wrong"), "").await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let body = r#"{
+            "amount": "0.5",
+            "currency": "Native",
+            "recipient_address": "0x742d35cc6634c0532925a3b844bc9e7595f0beb0",
+            "required_confirmations": 12,
+            "metadata": {"order": 42}
+        }"#;
+        let response = send(&app, "POST", "/payments", Some("secret"), body).await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let created = json(response).await;
+        assert_eq!(created["metadata"]["order"], 42);
+        let id = created["id"].as_str().unwrap();
+
+        let response = send(
+            &app,
+            "GET",
+            &format!("/payments/{}", id),
+            Some("secret"),
+            "",
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(json(response).await["status"], "Pending");
+
+        let uri = format!("/payments/{}/redeliver", id);
+        let response = send(&app, "POST", &uri, Some("secret"), "").await;
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        assert_eq!(json(response).await["redelivery"], true);
+
+        let uri = format!("/payments/{}", Uuid::new_v4());
+        let response = send(&app, "GET", &uri, Some("secret"), "").await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let error = json(response).await;
+        assert_eq!(error["error"]["code"], "CP3009");
+        assert_eq!(
+            error["error"]["details"]["id"],
+            uri.trim_start_matches("/payments/")
+        );
+    }
+
+    #[cfg(feature = "openapi")]
+    #[test]
+    fn test_openapi_document() {
+        let doc = serde_json::to_value(openapi()).unwrap();
+        assert_eq!(doc["openapi"], "3.1.0");
+
+        let paths = &doc["paths"];
+        for (path, method) in [
+            ("/payments", "post"),
+            ("/payments", "get"),
+            ("/payments/{id}", "get"),
+            ("/payments/{id}/redeliver", "post"),
+        ] {
+            assert!(paths[path][method].is_object(), "{} {}", method, path);
+        }
+        assert_eq!(
+            paths["/payments/{id}"]["get"]["responses"]["200"]["content"]["application/json"]
+                ["schema"]["$ref"],
+            "#/components/schemas/Payment"
+        );
+
+        let schemas = &doc["components"]["schemas"];
+        for name in [
+            "Payment",
+            "PaymentRequest",
+            "PaymentStatus",
+            "VerificationResult",
+            "CreatePayment",
+        ] {
+            assert!(schemas[name].is_object(), "{}", name);
+        }
+        assert!(doc["components"]["securitySchemes"]["api_key"].is_object());
+    }
+}
@@ -0,0 +1,147 @@
+//! Export and import of payments as NDJSON
+//!
+//! The format is one JSON object per line: a header naming the format and
+//! its version, then one [`Payment`] per line as it serializes. Payments
+//! carry their `schema_version` and are upgraded on import like on any read,
+//! so exports from older versions of the crate stay importable.
+//!
+//! ```text
+//! {"format":"cryptopay-payments","version":1}
+//! {"id":"...","request":{...},"status":"Pending",...,"schema_version":1}
+//! ```
+
+use crate::error::{Error, Result};
+use crate::payment::Payment;
+use crate::storage::PaymentStorage;
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, Write};
+
+/// Name of the export format in the header line
+const FORMAT: &str = "cryptopay-payments";
+
+/// Version of the export format written by [`export`]
+pub const EXPORT_VERSION: u32 = 1;
+
+/// First line of an export
+#[derive(Debug, Serialize, Deserialize)]
+struct Header {
+    format: String,
+    version: u32,
+}
+
+/// Write every payment in `storage` to `writer`, returning the number written
+///
+/// # Example
+/// ```no_run
+/// # use cryptopay::*;
+/// # async fn example(old: &dyn PaymentStorage, new: &dyn PaymentStorage) -> Result<()> {
+/// cryptopay::storage::export(old, std::fs::File::create("payments.ndjson")?).await?;
+///
+/// let file = std::io::BufReader::new(std::fs::File::open("payments.ndjson")?);
+/// cryptopay::storage::import(new, file).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn export<S, W>(storage: &S, mut writer: W) -> Result<usize>
+where
+    S: PaymentStorage + ?Sized,
+    W: Write,
+{
+    let header = Header {
+        format: FORMAT.to_string(),
+        version: EXPORT_VERSION,
+    };
+    serde_json::to_writer(&mut writer, &header)?;
+    writeln!(writer)?;
+
+    let payments = storage.list_payments().await?;
+    for payment in &payments {
+        serde_json::to_writer(&mut writer, payment)?;
+        writeln!(writer)?;
+    }
+    writer.flush()?;
+    Ok(payments.len())
+}
+
+/// Save every payment of an export read from `reader`, returning the number saved
+///
+/// Payments already in `storage` are replaced. Fails on the first line that
+/// can't be read, naming its line number; payments before it are saved.
+pub async fn import<S, R>(storage: &S, reader: R) -> Result<usize>
+where
+    S: PaymentStorage + ?Sized,
+    R: BufRead,
+{
+    let mut lines = reader.lines();
+
+    let header = lines
+        .next()
+        .transpose()?
+        .ok_or_else(|| Error::generic("empty payment export"))?;
+    let header: Header = serde_json::from_str(&header)
+        .map_err(|_| Error::generic("not a payment export: missing header"))?;
+    if header.format != FORMAT {
+        return Err(Error::generic(format!(
+            "not a payment export: format {}",
+            header.format
+        )));
+    }
+    if header.version > EXPORT_VERSION {
+        return Err(Error::generic(format!(
+            "payment export version {} is newer than the supported version {}",
+            header.version, EXPORT_VERSION
+        )));
+    }
+
+    let mut imported = 0;
+    for (index, line) in lines.enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let payment: Payment = serde_json::from_str(&line)
+            .map_err(|e| Error::generic(format!("line {}: {}", index + 2, e)))?;
+        storage.save_payment(&payment).await?;
+        imported += 1;
+    }
+    Ok(imported)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payment::{PaymentRequest, PaymentStatus};
+    use crate::storage::MemoryStorage;
+    use rust_decimal::Decimal;
+
+    #[tokio::test]
+    async fn test_export_import_roundtrip() {
+        let source = MemoryStorage::new();
+        let mut payment = Payment::new(PaymentRequest::eth(
+            Decimal::ONE,
+            "0x742d35cc6634c0532925a3b844bc9e7595f0beb0"
+                .parse()
+                .unwrap(),
+            12,
+        ))
+        .with_metadata(serde_json::json!({ "order": 42 }));
+        payment.update_status(PaymentStatus::Expired);
+        source.save_payment(&payment).await.unwrap();
+
+        let mut ndjson = Vec::new();
+        assert_eq!(export(&source, &mut ndjson).await.unwrap(), 1);
+        let text = String::from_utf8(ndjson.clone()).unwrap();
+        assert!(text.starts_with("{\"format\":\"cryptopay-payments\",\"version\":1}\n"));
+
+        let target = MemoryStorage::new();
+        assert_eq!(import(&target, ndjson.as_slice()).await.unwrap(), 1);
+        let imported = target.get_payment(payment.id).await.unwrap().unwrap();
+        assert_eq!(imported.status, PaymentStatus::Expired);
+        assert_eq!(imported.metadata["order"], 42);
+
+        let error = import(&target, "{\"id\":1}\n".as_bytes())
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("missing header"));
+    }
+}
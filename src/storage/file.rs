@@ -0,0 +1,211 @@
+//! File-backed payment storage
+
+use crate::error::{Error, Result};
+use crate::payment::{Payment, PaymentStatus, StatusTransition};
+use crate::storage::{PaymentStorage, WebhookStorage};
+use crate::webhook::{DeliveryStatus, WebhookDelivery};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+/// Payment storage kept as JSON files in a directory
+///
+/// Every payment is stored in `payments/<id>.json` and every queued webhook
+/// in `webhooks/<id>.json`. Records are written to a temporary file and
+/// renamed into place, so they survive a restart and a crash never leaves a
+/// half-written record. Only one process may use a directory at a time.
+#[derive(Debug)]
+pub struct FileStorage {
+    dir: PathBuf,
+    // Serializes read-modify-write cycles such as `update_status`
+    lock: Mutex<()>,
+}
+
+impl FileStorage {
+    /// Open the storage in `dir`, creating the directory if needed
+    pub fn open(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(dir.join("payments"))?;
+        std::fs::create_dir_all(dir.join("webhooks"))?;
+        Ok(Self {
+            dir,
+            lock: Mutex::new(()),
+        })
+    }
+
+    /// Directory the records are stored in
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path(&self, kind: &str, id: Uuid) -> PathBuf {
+        self.dir.join(kind).join(format!("{}.json", id))
+    }
+
+    fn write<T: Serialize>(&self, kind: &str, id: Uuid, record: &T) -> Result<()> {
+        let path = self.path(kind, id);
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_vec(record)?)?;
+        std::fs::rename(tmp, path)?;
+        Ok(())
+    }
+
+    fn read<T: DeserializeOwned>(&self, kind: &str, id: Uuid) -> Result<Option<T>> {
+        match std::fs::read(self.path(kind, id)) {
+            Ok(contents) => Ok(Some(serde_json::from_slice(&contents)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn read_all<T: DeserializeOwned>(&self, kind: &str) -> Result<Vec<T>> {
+        let mut records = Vec::new();
+        for entry in std::fs::read_dir(self.dir.join(kind))? {
+            let path = entry?.path();
+            if path
+                .extension()
+                .is_some_and(|extension| extension == "json")
+            {
+                records.push(serde_json::from_slice(&std::fs::read(path)?)?);
+            }
+        }
+        Ok(records)
+    }
+
+    fn remove(&self, kind: &str, id: Uuid) -> Result<bool> {
+        match std::fs::remove_file(self.path(kind, id)) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn webhooks_where(
+        &self,
+        filter: impl Fn(&WebhookDelivery) -> bool,
+    ) -> Result<Vec<WebhookDelivery>> {
+        let mut deliveries: Vec<WebhookDelivery> = self
+            .read_all("webhooks")?
+            .into_iter()
+            .filter(|delivery| filter(delivery))
+            .collect();
+        deliveries.sort_by_key(|delivery| delivery.created_at);
+        Ok(deliveries)
+    }
+}
+
+#[async_trait]
+impl PaymentStorage for FileStorage {
+    async fn save_payment(&self, payment: &Payment) -> Result<()> {
+        let _guard = self.lock.lock().await;
+        self.write("payments", payment.id, payment)
+    }
+
+    async fn get_payment(&self, id: Uuid) -> Result<Option<Payment>> {
+        self.read("payments", id)
+    }
+
+    async fn update_status(&self, id: Uuid, status: PaymentStatus) -> Result<StatusTransition> {
+        let _guard = self.lock.lock().await;
+        let mut payment: Payment = self
+            .read("payments", id)?
+            .ok_or(Error::PaymentNotFound(id))?;
+        let transition = payment.transition(status)?;
+        self.write("payments", id, &payment)?;
+        Ok(transition)
+    }
+
+    async fn list_payments(&self) -> Result<Vec<Payment>> {
+        let mut payments: Vec<Payment> = self.read_all("payments")?;
+        payments.sort_by_key(|payment| payment.created_at);
+        Ok(payments)
+    }
+
+    async fn delete_payment(&self, id: Uuid) -> Result<bool> {
+        let _guard = self.lock.lock().await;
+        self.remove("payments", id)
+    }
+}
+
+#[async_trait]
+impl WebhookStorage for FileStorage {
+    async fn save_webhook(&self, delivery: &WebhookDelivery) -> Result<()> {
+        let _guard = self.lock.lock().await;
+        self.write("webhooks", delivery.id, delivery)
+    }
+
+    async fn due_webhooks(&self, now: DateTime<Utc>) -> Result<Vec<WebhookDelivery>> {
+        self.webhooks_where(|delivery| {
+            delivery.status == DeliveryStatus::Pending && delivery.next_retry_at <= now
+        })
+    }
+
+    async fn dead_webhooks(&self) -> Result<Vec<WebhookDelivery>> {
+        self.webhooks_where(|delivery| delivery.status == DeliveryStatus::Dead)
+    }
+
+    async fn delete_webhook(&self, id: Uuid) -> Result<bool> {
+        let _guard = self.lock.lock().await;
+        self.remove("webhooks", id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payment::PaymentRequest;
+    use rust_decimal::Decimal;
+
+    #[tokio::test]
+    async fn test_payments_survive_reopening() {
+        let dir = std::env::temp_dir().join(format!("cryptopay-storage-{}", Uuid::new_v4()));
+        let payment = Payment::new(PaymentRequest::eth(
+            Decimal::from(1),
+            "0x0000000000000000000000000000000000000001"
+                .parse()
+                .unwrap(),
+            12,
+        ));
+
+        let storage = FileStorage::open(&dir).unwrap();
+        storage.save_payment(&payment).await.unwrap();
+        storage
+            .update_status(
+                payment.id,
+                PaymentStatus::Detected {
+                    confirmations: 1,
+                    tx_hash: "0xabc".to_string(),
+                },
+            )
+            .await
+            .unwrap();
+        drop(storage);
+
+        let storage = FileStorage::open(&dir).unwrap();
+        let pending = storage.list_pending().await.unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].id, payment.id);
+        assert!(matches!(
+            pending[0].status,
+            PaymentStatus::Detected {
+                confirmations: 1,
+                ..
+            }
+        ));
+        assert!(matches!(
+            storage
+                .update_status(Uuid::new_v4(), PaymentStatus::Expired)
+                .await,
+            Err(Error::PaymentNotFound(_))
+        ));
+
+        assert!(storage.delete_payment(payment.id).await.unwrap());
+        assert!(!storage.delete_payment(payment.id).await.unwrap());
+        assert!(storage.get_payment(payment.id).await.unwrap().is_none());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
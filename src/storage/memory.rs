@@ -0,0 +1,155 @@
+//! In-memory payment storage
+
+use crate::error::{Error, Result};
+use crate::payment::{Payment, PaymentStatus, StatusTransition};
+use crate::storage::{PaymentStorage, WebhookStorage};
+use crate::webhook::{DeliveryStatus, WebhookDelivery};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+/// Payment storage kept in process memory
+///
+/// Suitable for tests, prototypes, and single-process deployments that don't
+/// need payments to survive a restart.
+#[derive(Debug, Default)]
+pub struct MemoryStorage {
+    payments: RwLock<HashMap<Uuid, Payment>>,
+    webhooks: RwLock<HashMap<Uuid, WebhookDelivery>>,
+}
+
+impl MemoryStorage {
+    /// Create an empty storage
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl PaymentStorage for MemoryStorage {
+    async fn save_payment(&self, payment: &Payment) -> Result<()> {
+        self.payments
+            .write()
+            .await
+            .insert(payment.id, payment.clone());
+        Ok(())
+    }
+
+    async fn get_payment(&self, id: Uuid) -> Result<Option<Payment>> {
+        Ok(self.payments.read().await.get(&id).cloned())
+    }
+
+    async fn update_status(&self, id: Uuid, status: PaymentStatus) -> Result<StatusTransition> {
+        let mut payments = self.payments.write().await;
+        let payment = payments.get_mut(&id).ok_or(Error::PaymentNotFound(id))?;
+        Ok(payment.transition(status)?)
+    }
+
+    async fn list_payments(&self) -> Result<Vec<Payment>> {
+        let mut payments: Vec<Payment> = self.payments.read().await.values().cloned().collect();
+        payments.sort_by_key(|payment| payment.created_at);
+        Ok(payments)
+    }
+
+    async fn delete_payment(&self, id: Uuid) -> Result<bool> {
+        Ok(self.payments.write().await.remove(&id).is_some())
+    }
+}
+
+impl MemoryStorage {
+    async fn webhooks_where(
+        &self,
+        filter: impl Fn(&WebhookDelivery) -> bool,
+    ) -> Vec<WebhookDelivery> {
+        let mut deliveries: Vec<WebhookDelivery> = self
+            .webhooks
+            .read()
+            .await
+            .values()
+            .filter(|delivery| filter(delivery))
+            .cloned()
+            .collect();
+        deliveries.sort_by_key(|delivery| delivery.created_at);
+        deliveries
+    }
+}
+
+#[async_trait]
+impl WebhookStorage for MemoryStorage {
+    async fn save_webhook(&self, delivery: &WebhookDelivery) -> Result<()> {
+        self.webhooks
+            .write()
+            .await
+            .insert(delivery.id, delivery.clone());
+        Ok(())
+    }
+
+    async fn due_webhooks(&self, now: DateTime<Utc>) -> Result<Vec<WebhookDelivery>> {
+        Ok(self
+            .webhooks_where(|delivery| {
+                delivery.status == DeliveryStatus::Pending && delivery.next_retry_at <= now
+            })
+            .await)
+    }
+
+    async fn dead_webhooks(&self) -> Result<Vec<WebhookDelivery>> {
+        Ok(self
+            .webhooks_where(|delivery| delivery.status == DeliveryStatus::Dead)
+            .await)
+    }
+
+    async fn delete_webhook(&self, id: Uuid) -> Result<bool> {
+        Ok(self.webhooks.write().await.remove(&id).is_some())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payment::PaymentRequest;
+    use rust_decimal::Decimal;
+
+    #[tokio::test]
+    async fn test_memory_storage_roundtrip() {
+        let storage = MemoryStorage::new();
+        let payment = Payment::new(PaymentRequest::eth(
+            Decimal::from(1),
+            "0x0000000000000000000000000000000000000001"
+                .parse()
+                .unwrap(),
+            12,
+        ));
+
+        storage.save_payment(&payment).await.unwrap();
+        assert_eq!(storage.list_pending().await.unwrap().len(), 1);
+        assert_eq!(crate::storage::migrate_payments(&storage).await.unwrap(), 1);
+
+        let transition = storage
+            .update_status(payment.id, PaymentStatus::Expired)
+            .await
+            .unwrap();
+        assert_eq!(transition.payment_id, payment.id);
+        assert_eq!(transition.from, PaymentStatus::Pending);
+        assert_eq!(transition.to, PaymentStatus::Expired);
+        let stored = storage.get_payment(payment.id).await.unwrap().unwrap();
+        assert_eq!(stored.status, PaymentStatus::Expired);
+        assert!(storage.list_pending().await.unwrap().is_empty());
+
+        let late = PaymentStatus::Confirmed {
+            tx_hash: "0xabc".to_string(),
+            confirmations: 12,
+        };
+        assert!(matches!(
+            storage.update_status(payment.id, late).await,
+            Err(Error::InvalidTransition(_))
+        ));
+
+        assert!(storage.delete_payment(payment.id).await.unwrap());
+        assert!(storage
+            .update_status(payment.id, PaymentStatus::Pending)
+            .await
+            .is_err());
+    }
+}
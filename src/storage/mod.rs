@@ -0,0 +1,101 @@
+//! Payment storage backends
+//!
+//! [`PaymentStorage`] is the persistence interface used by the higher-level
+//! subsystems (invoices, sweepers, servers), and [`WebhookStorage`] keeps
+//! the webhook queue. [`MemoryStorage`] and the file-backed [`FileStorage`]
+//! are built in; other backends implement the traits.
+
+use crate::error::Result;
+use crate::payment::{Payment, PaymentStatus, StatusTransition};
+use crate::webhook::WebhookDelivery;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::ops::Range;
+use uuid::Uuid;
+
+mod export;
+mod file;
+mod memory;
+mod stats;
+
+pub use export::{export, import, EXPORT_VERSION};
+pub use file::FileStorage;
+pub use memory::MemoryStorage;
+pub use stats::{ConfirmationTimes, CurrencyTotals, DailyVolume, PaymentStats};
+
+/// Persistence interface for payment records
+#[async_trait]
+pub trait PaymentStorage: Send + Sync {
+    /// Insert a payment, or replace it if a payment with the same ID exists
+    async fn save_payment(&self, payment: &Payment) -> Result<()>;
+
+    /// Get a payment by ID
+    async fn get_payment(&self, id: Uuid) -> Result<Option<Payment>>;
+
+    /// Update the status of a stored payment
+    ///
+    /// Fails with [`Error::PaymentNotFound`](crate::Error::PaymentNotFound)
+    /// if no payment with this ID is stored, and with
+    /// [`Error::InvalidTransition`](crate::Error::InvalidTransition) if the
+    /// stored status can't move to `status` (see
+    /// [`Payment::transition`]). Use [`save_payment`](Self::save_payment) to
+    /// force a status. Returns the change, to publish as an
+    /// [`Event`](crate::events::Event).
+    async fn update_status(&self, id: Uuid, status: PaymentStatus) -> Result<StatusTransition>;
+
+    /// List all stored payments, oldest first
+    async fn list_payments(&self) -> Result<Vec<Payment>>;
+
+    /// List payments that are not finalized yet, oldest first
+    async fn list_pending(&self) -> Result<Vec<Payment>> {
+        let payments = self.list_payments().await?;
+        Ok(payments
+            .into_iter()
+            .filter(|payment| !payment.status.is_finalized())
+            .collect())
+    }
+
+    /// Delete a payment, returning whether it existed
+    async fn delete_payment(&self, id: Uuid) -> Result<bool>;
+
+    /// Statistics of the payments created in `range`, for reporting
+    ///
+    /// The default implementation aggregates [`list_payments`](Self::list_payments);
+    /// database backends may aggregate in SQL instead.
+    async fn stats(&self, range: Range<DateTime<Utc>>) -> Result<PaymentStats> {
+        let payments = self.list_payments().await?;
+        Ok(PaymentStats::from_payments(&payments, range))
+    }
+}
+
+/// Persistence interface for the webhook queue, see
+/// [`WebhookQueue`](crate::webhook::WebhookQueue)
+#[async_trait]
+pub trait WebhookStorage: Send + Sync {
+    /// Insert a queued notification, or replace it if one with the same ID exists
+    async fn save_webhook(&self, delivery: &WebhookDelivery) -> Result<()>;
+
+    /// List pending notifications whose `next_retry_at` is at or before `now`,
+    /// oldest first
+    async fn due_webhooks(&self, now: DateTime<Utc>) -> Result<Vec<WebhookDelivery>>;
+
+    /// List dead-lettered notifications, oldest first
+    async fn dead_webhooks(&self) -> Result<Vec<WebhookDelivery>>;
+
+    /// Delete a notification, returning whether it existed
+    async fn delete_webhook(&self, id: Uuid) -> Result<bool>;
+}
+
+/// Rewrite every stored payment in the current schema
+///
+/// Payments are upgraded to [`PAYMENT_SCHEMA_VERSION`](crate::payment::PAYMENT_SCHEMA_VERSION)
+/// when read, so this isn't required for reading old records; run it after
+/// an upgrade, e.g. from a deploy script, to stop depending on the upgrade
+/// steps for historical rows. Returns the number of payments written.
+pub async fn migrate_payments<S: PaymentStorage + ?Sized>(storage: &S) -> Result<usize> {
+    let payments = storage.list_payments().await?;
+    for payment in &payments {
+        storage.save_payment(payment).await?;
+    }
+    Ok(payments.len())
+}
@@ -0,0 +1,256 @@
+//! Payment statistics for reporting
+
+use crate::error::Result;
+use crate::ledger::csv_field;
+use crate::payment::{Currency, Payment};
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::ops::Range;
+
+/// Statistics of the payments created in a time range
+///
+/// Returned by [`PaymentStorage::stats`](crate::storage::PaymentStorage::stats).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PaymentStats {
+    /// Start of the range (inclusive)
+    pub from: DateTime<Utc>,
+    /// End of the range (exclusive)
+    pub to: DateTime<Utc>,
+    /// Number of payments created in the range
+    pub count: usize,
+    /// Number and requested amount per currency and status
+    pub totals: Vec<CurrencyTotals>,
+    /// Time from creation to confirmation of confirmed payments
+    pub confirmation_time: Option<ConfirmationTimes>,
+    /// Number and requested amount of confirmed payments per day and currency
+    pub daily: Vec<DailyVolume>,
+}
+
+/// Payments of one currency in one status
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CurrencyTotals {
+    /// `"native"` or the token contract address
+    pub currency: String,
+    /// Status name, see [`PaymentStatus::name`](crate::payment::PaymentStatus::name)
+    pub status: String,
+    /// Number of payments
+    pub count: usize,
+    /// Sum of the requested amounts
+    pub amount: Decimal,
+}
+
+/// Percentiles of the time to confirmation, in seconds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ConfirmationTimes {
+    /// Median
+    pub p50: u64,
+    /// 90th percentile
+    pub p90: u64,
+    /// 99th percentile
+    pub p99: u64,
+}
+
+/// Confirmed payments of one currency on one day (UTC, by creation)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DailyVolume {
+    /// Day
+    pub date: NaiveDate,
+    /// `"native"` or the token contract address
+    pub currency: String,
+    /// Number of confirmed payments
+    pub count: usize,
+    /// Sum of their requested amounts
+    pub amount: Decimal,
+}
+
+impl PaymentStats {
+    /// Compute the statistics of the `payments` created in `range`
+    pub fn from_payments<'a>(
+        payments: impl IntoIterator<Item = &'a Payment>,
+        range: Range<DateTime<Utc>>,
+    ) -> Self {
+        let mut count = 0;
+        let mut totals: BTreeMap<(String, &'static str), (usize, Decimal)> = BTreeMap::new();
+        let mut daily: BTreeMap<(NaiveDate, String), (usize, Decimal)> = BTreeMap::new();
+        let mut confirmation_secs = Vec::new();
+
+        for payment in payments {
+            if !range.contains(&payment.created_at) {
+                continue;
+            }
+            count += 1;
+
+            let currency = currency_key(&payment.request.currency);
+            let total = totals
+                .entry((currency.clone(), payment.status.name()))
+                .or_default();
+            total.0 += 1;
+            total.1 += payment.request.amount;
+
+            if payment.status.is_successful() {
+                let day = daily
+                    .entry((payment.created_at.date_naive(), currency))
+                    .or_default();
+                day.0 += 1;
+                day.1 += payment.request.amount;
+
+                // The last status change of a confirmed payment is its confirmation
+                let elapsed = payment.updated_at - payment.created_at;
+                confirmation_secs.push(elapsed.num_seconds().max(0) as u64);
+            }
+        }
+
+        confirmation_secs.sort_unstable();
+        let confirmation_time = (!confirmation_secs.is_empty()).then(|| ConfirmationTimes {
+            p50: percentile(&confirmation_secs, 50),
+            p90: percentile(&confirmation_secs, 90),
+            p99: percentile(&confirmation_secs, 99),
+        });
+
+        Self {
+            from: range.start,
+            to: range.end,
+            count,
+            totals: totals
+                .into_iter()
+                .map(|((currency, status), (count, amount))| CurrencyTotals {
+                    currency,
+                    status: status.to_string(),
+                    count,
+                    amount,
+                })
+                .collect(),
+            confirmation_time,
+            daily: daily
+                .into_iter()
+                .map(|((date, currency), (count, amount))| DailyVolume {
+                    date,
+                    currency,
+                    count,
+                    amount,
+                })
+                .collect(),
+        }
+    }
+
+    /// Write the totals as CSV, one row per currency and status
+    pub fn write_totals_csv<W: Write>(&self, mut writer: W) -> Result<()> {
+        writeln!(writer, "currency,status,count,amount")?;
+        for total in &self.totals {
+            writeln!(
+                writer,
+                "{},{},{},{}",
+                csv_field(&total.currency),
+                csv_field(&total.status),
+                total.count,
+                total.amount
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Write the daily volumes as CSV, one row per day and currency
+    pub fn write_daily_csv<W: Write>(&self, mut writer: W) -> Result<()> {
+        writeln!(writer, "date,currency,count,amount")?;
+        for day in &self.daily {
+            writeln!(
+                writer,
+                "{},{},{},{}",
+                day.date,
+                csv_field(&day.currency),
+                day.count,
+                day.amount
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Write the statistics as JSON
+    pub fn write_json<W: Write>(&self, writer: W) -> Result<()> {
+        serde_json::to_writer_pretty(writer, self)?;
+        Ok(())
+    }
+}
+
+fn currency_key(currency: &Currency) -> String {
+    match currency {
+        Currency::Native => "native".to_string(),
+        Currency::ERC20 {
+            contract_address, ..
+        } => contract_address.to_string(),
+    }
+}
+
+/// Nearest-rank percentile of sorted, non-empty `values`
+fn percentile(values: &[u64], percent: usize) -> u64 {
+    let rank = (values.len() * percent).div_ceil(100).max(1);
+    values[rank - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payment::{PaymentRequest, PaymentStatus};
+    use chrono::Duration;
+
+    #[test]
+    fn test_payment_stats() {
+        let start: DateTime<Utc> = "2024-01-01T00:00:00Z".parse().unwrap();
+        let recipient: crate::Address = "0x742d35cc6634c0532925a3b844bc9e7595f0beb0"
+            .parse()
+            .unwrap();
+        let payment = |amount: i64, offset_hours: i64, confirmed_after_secs: Option<i64>| {
+            let created_at = start + Duration::hours(offset_hours);
+            let mut payment = Payment::new_at(
+                PaymentRequest::eth(Decimal::from(amount), recipient.clone(), 12),
+                created_at,
+            );
+            if let Some(secs) = confirmed_after_secs {
+                payment.update_status_at(
+                    PaymentStatus::Confirmed {
+                        tx_hash: "0xabc".to_string(),
+                        confirmations: 12,
+                    },
+                    created_at + Duration::seconds(secs),
+                );
+            }
+            payment
+        };
+
+        let payments = [
+            payment(1, 1, Some(60)),
+            payment(2, 2, Some(120)),
+            payment(4, 30, Some(600)),
+            payment(8, 3, None),
+            payment(16, 100, Some(60)), // outside the range
+        ];
+        let stats = PaymentStats::from_payments(&payments, start..start + Duration::days(2));
+
+        assert_eq!(stats.count, 4);
+        assert_eq!(stats.totals.len(), 2);
+        assert_eq!(stats.totals[0].status, "confirmed");
+        assert_eq!(stats.totals[0].amount, Decimal::from(7));
+        assert_eq!(stats.totals[1].status, "pending");
+        assert_eq!(
+            stats.confirmation_time,
+            Some(ConfirmationTimes {
+                p50: 120,
+                p90: 600,
+                p99: 600
+            })
+        );
+        assert_eq!(stats.daily.len(), 2);
+        assert_eq!(stats.daily[0].count, 2);
+        assert_eq!(stats.daily[0].amount, Decimal::from(3));
+
+        let mut csv = Vec::new();
+        stats.write_daily_csv(&mut csv).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "date,currency,count,amount\n2024-01-01,native,2,3\n2024-01-02,native,1,4\n"
+        );
+    }
+}
@@ -0,0 +1,350 @@
+//! Recurring (subscription) payments
+//!
+//! A [`Subscription`] describes a fixed amount billed every period. Each
+//! period has to be paid before it ends; after that the payment is late, and
+//! once the grace window has passed as well the subscription lapses.
+//! [`SubscriptionScheduler`] creates the [`PaymentRequest`] for each period,
+//! watches for the payment, and reports [`SubscriptionEvent`]s.
+
+use crate::client::BscScanClient;
+use crate::error::{Error, Result};
+use crate::payment::{Currency, PaymentRequest, PaymentVerifier, VerificationResult};
+use crate::primitives::{Address, TxHash};
+use crate::rt::sleep;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Recurring payment plan
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Subscription {
+    /// Unique subscription ID
+    pub id: Uuid,
+
+    /// Amount billed per period (in token/ETH units, not wei)
+    pub amount: Decimal,
+
+    /// Currency type
+    pub currency: Currency,
+
+    /// Recipient address
+    pub recipient_address: Address,
+
+    /// Length of a billing period
+    pub period: Duration,
+
+    /// How long after a period ends a late payment is still accepted
+    pub grace_period: Duration,
+
+    /// Required number of confirmations
+    pub required_confirmations: u64,
+
+    /// Start of the first period
+    pub starts_at: DateTime<Utc>,
+
+    /// Number of periods to bill (`None` = until lapsed)
+    pub periods: Option<u64>,
+
+    /// If set, only payments from this address count
+    pub payer: Option<Address>,
+}
+
+impl Subscription {
+    /// Create a subscription starting now, with a grace period of one day
+    pub fn new(
+        amount: Decimal,
+        currency: Currency,
+        recipient_address: Address,
+        period: Duration,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            amount,
+            currency,
+            recipient_address,
+            period,
+            grace_period: Duration::from_secs(24 * 60 * 60),
+            required_confirmations: 12,
+            starts_at: Utc::now(),
+            periods: None,
+            payer: None,
+        }
+    }
+
+    /// Set the grace window after each period
+    pub fn with_grace_period(mut self, grace_period: Duration) -> Self {
+        self.grace_period = grace_period;
+        self
+    }
+
+    /// Set the required number of confirmations (default: 12)
+    pub fn with_confirmations(mut self, confirmations: u64) -> Self {
+        self.required_confirmations = confirmations;
+        self
+    }
+
+    /// Start the first period at the given time
+    pub fn starting_at(mut self, starts_at: DateTime<Utc>) -> Self {
+        self.starts_at = starts_at;
+        self
+    }
+
+    /// Bill a fixed number of periods
+    pub fn with_periods(mut self, periods: u64) -> Self {
+        self.periods = Some(periods);
+        self
+    }
+
+    /// Only accept payments from this address
+    pub fn with_payer(mut self, payer: Address) -> Self {
+        self.payer = Some(payer);
+        self
+    }
+
+    /// Start of period `index` (0-based)
+    pub fn period_start(&self, index: u64) -> DateTime<Utc> {
+        self.starts_at + self.period_duration() * index as i32
+    }
+
+    /// Time by which period `index` has to be paid (the end of the period)
+    pub fn due_at(&self, index: u64) -> DateTime<Utc> {
+        self.period_start(index + 1)
+    }
+
+    /// Time after which an unpaid period `index` lapses the subscription
+    pub fn lapses_at(&self, index: u64) -> DateTime<Utc> {
+        self.due_at(index) + to_chrono(self.grace_period)
+    }
+
+    /// Payment request for period `index`
+    pub fn request_for_period(&self, index: u64) -> PaymentRequest {
+        let request = PaymentRequest::new(
+            self.amount,
+            self.currency.clone(),
+            self.recipient_address.clone(),
+            self.required_confirmations,
+        )
+        .with_not_before(self.period_start(index));
+
+        match &self.payer {
+            Some(payer) => request.with_allowed_senders([payer.clone()]),
+            None => request,
+        }
+    }
+
+    /// Check the deadlines of an unpaid period at `now`
+    fn deadline(&self, index: u64, now: DateTime<Utc>) -> Deadline {
+        if now >= self.lapses_at(index) {
+            Deadline::Lapsed
+        } else if now >= self.due_at(index) {
+            Deadline::Late
+        } else {
+            Deadline::OnTime
+        }
+    }
+
+    fn period_duration(&self) -> chrono::Duration {
+        to_chrono(self.period)
+    }
+}
+
+fn to_chrono(duration: Duration) -> chrono::Duration {
+    chrono::Duration::from_std(duration).unwrap_or(chrono::Duration::MAX)
+}
+
+/// Where an unpaid period stands relative to its deadlines
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Deadline {
+    OnTime,
+    Late,
+    Lapsed,
+}
+
+/// Subscription lifecycle event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SubscriptionEvent {
+    /// Payment for a period confirmed
+    Paid {
+        /// Period index (0-based)
+        period: u64,
+        /// Transaction hash
+        tx_hash: TxHash,
+        /// Amount received
+        amount: Decimal,
+    },
+
+    /// Period ended without payment; the grace window has started
+    Late {
+        /// Period index (0-based)
+        period: u64,
+        /// When the payment was due
+        due_at: DateTime<Utc>,
+    },
+
+    /// Grace window ended without payment; the subscription is over
+    Lapsed {
+        /// Period index (0-based)
+        period: u64,
+    },
+}
+
+/// Drives subscriptions period by period
+pub struct SubscriptionScheduler {
+    verifier: PaymentVerifier,
+    poll_interval: Duration,
+}
+
+impl SubscriptionScheduler {
+    /// Create a new scheduler
+    pub fn new(client: BscScanClient, poll_interval: Duration) -> Self {
+        Self {
+            verifier: PaymentVerifier::new(client),
+            poll_interval,
+        }
+    }
+
+    /// Run a subscription until it lapses or all of its periods are paid
+    ///
+    /// Periods are handled in order, starting at `first_period` (use 0 for a
+    /// new subscription, or the next unpaid period when resuming). A period
+    /// is watched from its start, so paying ahead of time is not recognized.
+    /// A transfer is never counted for more than one period.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use cryptopay::*;
+    /// # use cryptopay::subscription::{Subscription, SubscriptionEvent, SubscriptionScheduler};
+    /// # use std::time::Duration;
+    /// # async fn example() -> Result<()> {
+    /// let client = BscScanClient::new("api-key")?;
+    /// let scheduler = SubscriptionScheduler::new(client, Duration::from_secs(60));
+    ///
+    /// let monthly = Subscription::new(
+    ///     rust_decimal::Decimal::from(10),
+    ///     Currency::usdc(),
+    ///     "0x742d35cc6634c0532925a3b844bc9e7595f0beb0".parse()?,
+    ///     Duration::from_secs(30 * 24 * 60 * 60),
+    /// )
+    /// .with_payer("0x0000000000000000000000000000000000c0ffee".parse()?);
+    ///
+    /// scheduler
+    ///     .run(&monthly, 0, |event| match event {
+    ///         SubscriptionEvent::Paid { period, .. } => println!("period {} paid", period),
+    ///         SubscriptionEvent::Late { period, .. } => println!("period {} is late", period),
+    ///         SubscriptionEvent::Lapsed { .. } => println!("subscription lapsed"),
+    ///     })
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn run<F>(
+        &self,
+        subscription: &Subscription,
+        first_period: u64,
+        callback: F,
+    ) -> Result<()>
+    where
+        F: Fn(SubscriptionEvent) + Send + Sync,
+    {
+        let mut period = first_period;
+        // Transfers before this time were already counted for an earlier period
+        let mut counted_until: Option<DateTime<Utc>> = None;
+
+        while !matches!(subscription.periods, Some(periods) if period >= periods) {
+            let start = subscription.period_start(period);
+            if let Ok(wait) = (start - Utc::now()).to_std() {
+                sleep(wait).await;
+            }
+
+            let mut request = subscription.request_for_period(period);
+            if let Some(counted_until) = counted_until {
+                request.not_before = Some(start.max(counted_until));
+            }
+
+            let mut late = false;
+            loop {
+                let result = self.verifier.verify_payment(&request).await?;
+                if let VerificationResult::Confirmed {
+                    tx_hash, details, ..
+                } = result
+                {
+                    counted_until = details
+                        .timestamp
+                        .map(|timestamp| timestamp + chrono::Duration::seconds(1));
+                    callback(SubscriptionEvent::Paid {
+                        period,
+                        tx_hash,
+                        amount: details.amount,
+                    });
+                    break;
+                }
+
+                match subscription.deadline(period, Utc::now()) {
+                    Deadline::Lapsed => {
+                        callback(SubscriptionEvent::Lapsed { period });
+                        return Ok(());
+                    }
+                    Deadline::Late if !late => {
+                        late = true;
+                        callback(SubscriptionEvent::Late {
+                            period,
+                            due_at: subscription.due_at(period),
+                        });
+                    }
+                    _ => {}
+                }
+
+                sleep(self.poll_interval).await;
+            }
+
+            period = period
+                .checked_add(1)
+                .ok_or_else(|| Error::generic("subscription period overflow"))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    fn address(suffix: &str) -> Address {
+        format!("0x{:0>40}", suffix).parse().unwrap()
+    }
+
+    #[test]
+    fn test_subscription_deadlines() {
+        let start = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+        let subscription =
+            Subscription::new(Decimal::from(10), Currency::usdc(), address("0a"), DAY * 30)
+                .starting_at(start)
+                .with_grace_period(DAY * 3)
+                .with_payer(address("C1"));
+
+        assert_eq!(
+            subscription.period_start(2),
+            Utc.with_ymd_and_hms(2026, 3, 2, 0, 0, 0).unwrap()
+        );
+        assert_eq!(
+            subscription.due_at(0),
+            Utc.with_ymd_and_hms(2026, 1, 31, 0, 0, 0).unwrap()
+        );
+
+        let request = subscription.request_for_period(1);
+        assert_eq!(request.not_before, Some(subscription.period_start(1)));
+        assert!(request.is_sender_allowed(&address("C1")));
+        assert!(!request.is_sender_allowed(&address("b2")));
+
+        let at = |month, day| Utc.with_ymd_and_hms(2026, month, day, 12, 0, 0).unwrap();
+        assert_eq!(subscription.deadline(0, at(1, 15)), Deadline::OnTime);
+        assert_eq!(subscription.deadline(0, at(2, 1)), Deadline::Late);
+        assert_eq!(subscription.deadline(0, at(2, 3)), Deadline::Lapsed);
+    }
+}
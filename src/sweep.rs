@@ -0,0 +1,324 @@
+//! Detection of deposit sweeps to a treasury address
+//!
+//! With per-invoice (e.g. HD-derived) deposit addresses, received funds are
+//! periodically forwarded ("swept") to a treasury address. [`SweepMonitor`]
+//! finds those forwarding transfers, follows their confirmations, and records
+//! them on the payments made to the deposit address.
+
+use crate::client::{BscScanClient, InternalTransaction, TokenTransfer, Transaction};
+use crate::error::Result;
+use crate::payment::{Currency, Payment, PaymentDetails};
+use crate::primitives::{Address, TxHash};
+use crate::rt::sleep;
+use crate::storage::PaymentStorage;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Metadata key under which sweeps are stored on the payment
+const METADATA_KEY: &str = "sweeps";
+
+/// Transfer forwarding funds from a deposit address to the treasury
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Sweep {
+    /// Deposit address the funds left
+    pub deposit_address: Address,
+    /// Treasury address the funds arrived at
+    pub treasury_address: Address,
+    /// Sweep transaction hash
+    pub tx_hash: String,
+    /// Swept currency
+    pub currency: Currency,
+    /// Swept amount
+    pub amount: Decimal,
+    /// Block the sweep was included in
+    pub block_number: u64,
+    /// Confirmations at the last check
+    pub confirmations: u64,
+    /// Block timestamp, if reported by the explorer
+    pub timestamp: Option<DateTime<Utc>>,
+}
+
+impl Sweep {
+    fn new(
+        deposit: &Address,
+        treasury: &Address,
+        tx_hash: &TxHash,
+        details: PaymentDetails,
+    ) -> Self {
+        Self {
+            deposit_address: deposit.clone(),
+            treasury_address: treasury.clone(),
+            tx_hash: tx_hash.to_string(),
+            currency: details.currency,
+            amount: details.amount,
+            block_number: details.block_number,
+            confirmations: 0,
+            timestamp: details.timestamp,
+        }
+    }
+
+    /// Identity of the swept transfer (a transaction may sweep several currencies)
+    fn same_transfer(&self, other: &Sweep) -> bool {
+        self.tx_hash.eq_ignore_ascii_case(&other.tx_hash) && self.currency == other.currency
+    }
+}
+
+impl Payment {
+    /// Sweeps recorded for this payment's deposit address
+    pub fn sweeps(&self) -> Vec<Sweep> {
+        self.metadata
+            .get(METADATA_KEY)
+            .and_then(|sweeps| serde_json::from_value(sweeps.clone()).ok())
+            .unwrap_or_default()
+    }
+
+    /// Check if a sweep with at least `confirmations` confirmations is recorded
+    pub fn is_swept(&self, confirmations: u64) -> bool {
+        self.sweeps()
+            .iter()
+            .any(|sweep| sweep.confirmations >= confirmations)
+    }
+
+    /// Add or update sweeps in the payment's metadata
+    fn record_sweeps(&mut self, sweeps: &[Sweep]) -> Result<()> {
+        let mut recorded = self.sweeps();
+        for sweep in sweeps {
+            match recorded.iter_mut().find(|r| r.same_transfer(sweep)) {
+                Some(existing) => *existing = sweep.clone(),
+                None => recorded.push(sweep.clone()),
+            }
+        }
+
+        let recorded = serde_json::to_value(recorded)?;
+        match self.metadata.as_object_mut() {
+            Some(metadata) => {
+                metadata.insert(METADATA_KEY.to_string(), recorded);
+            }
+            None => self.metadata = serde_json::json!({ METADATA_KEY: recorded }),
+        }
+        self.updated_at = Utc::now();
+        Ok(())
+    }
+}
+
+/// Watches deposit addresses for sweeps to a treasury address
+pub struct SweepMonitor {
+    client: BscScanClient,
+    treasury_address: Address,
+    required_confirmations: u64,
+    poll_interval: Duration,
+}
+
+impl SweepMonitor {
+    /// Create a monitor for sweeps to `treasury_address`
+    pub fn new(client: BscScanClient, treasury_address: Address) -> Self {
+        Self {
+            client,
+            treasury_address,
+            required_confirmations: 12,
+            poll_interval: Duration::from_secs(30),
+        }
+    }
+
+    /// Set the confirmations after which a sweep counts as final (default: 12)
+    pub fn required_confirmations(mut self, confirmations: u64) -> Self {
+        self.required_confirmations = confirmations;
+        self
+    }
+
+    /// Set the poll interval used by [`watch`](Self::watch) (default: 30s)
+    pub fn poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Find transfers from `deposit_address` to the treasury since `start_block`
+    ///
+    /// Covers native transactions, internal transactions (contract-based
+    /// forwarders), and ERC20 transfers.
+    pub async fn find_sweeps(
+        &self,
+        deposit_address: &Address,
+        start_block: u64,
+    ) -> Result<Vec<Sweep>> {
+        let end_block = self.client.latest_block_number().await?;
+        let swept = |details: &PaymentDetails| {
+            *deposit_address == details.from
+                && self.treasury_address == details.to
+                && details.amount > Decimal::ZERO
+        };
+        let mut sweeps = Vec::new();
+
+        let transactions: Vec<Transaction> = self
+            .client
+            .fetch_history("txlist", deposit_address, start_block, end_block)
+            .await?;
+        for tx in transactions.iter().filter(|tx| tx.is_successful()) {
+            let details = PaymentDetails::from_transaction(tx);
+            if swept(&details) {
+                sweeps.push(Sweep::new(
+                    deposit_address,
+                    &self.treasury_address,
+                    &tx.hash,
+                    details,
+                ));
+            }
+        }
+
+        let internal: Vec<InternalTransaction> = self
+            .client
+            .fetch_history("txlistinternal", deposit_address, start_block, end_block)
+            .await?;
+        for tx in internal.iter().filter(|tx| tx.is_error == "0") {
+            let details = PaymentDetails::from_internal_transaction(tx);
+            if swept(&details) {
+                sweeps.push(Sweep::new(
+                    deposit_address,
+                    &self.treasury_address,
+                    &tx.hash,
+                    details,
+                ));
+            }
+        }
+
+        let transfers: Vec<TokenTransfer> = self
+            .client
+            .fetch_history("tokentx", deposit_address, start_block, end_block)
+            .await?;
+        for transfer in &transfers {
+            let details = PaymentDetails::from_token_transfer(transfer);
+            if swept(&details) {
+                sweeps.push(Sweep::new(
+                    deposit_address,
+                    &self.treasury_address,
+                    &transfer.hash,
+                    details,
+                ));
+            }
+        }
+
+        for sweep in &mut sweeps {
+            sweep.confirmations = confirmations_at(end_block, sweep.block_number);
+        }
+        sweeps.sort_by_key(|sweep| sweep.block_number);
+        Ok(sweeps)
+    }
+
+    /// Record sweeps on every stored payment made to their deposit address
+    pub async fn mark_swept<S: PaymentStorage + ?Sized>(
+        &self,
+        storage: &S,
+        sweeps: &[Sweep],
+    ) -> Result<()> {
+        for mut payment in storage.list_payments().await? {
+            let relevant: Vec<Sweep> = sweeps
+                .iter()
+                .filter(|sweep| sweep.deposit_address == payment.request.recipient_address)
+                .cloned()
+                .collect();
+
+            if !relevant.is_empty() {
+                payment.record_sweeps(&relevant)?;
+                storage.save_payment(&payment).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Wait until `deposit_address` has been swept and the sweep is final
+    ///
+    /// Polls for sweeps since `start_block`, recording them in storage as they
+    /// are found and confirmed. Returns the sweeps once all of them have the
+    /// required confirmations.
+    pub async fn watch<S: PaymentStorage + ?Sized>(
+        &self,
+        storage: &S,
+        deposit_address: &Address,
+        start_block: u64,
+    ) -> Result<Vec<Sweep>> {
+        let mut recorded: Vec<Sweep> = Vec::new();
+
+        loop {
+            let sweeps = self.find_sweeps(deposit_address, start_block).await?;
+            if sweeps != recorded {
+                self.mark_swept(storage, &sweeps).await?;
+                recorded = sweeps;
+            }
+
+            let final_sweeps = !recorded.is_empty()
+                && recorded
+                    .iter()
+                    .all(|sweep| sweep.confirmations >= self.required_confirmations);
+            if final_sweeps {
+                return Ok(recorded);
+            }
+
+            sleep(self.poll_interval).await;
+        }
+    }
+}
+
+fn confirmations_at(head: u64, block: u64) -> u64 {
+    if head >= block {
+        head - block + 1
+    } else {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payment::PaymentRequest;
+    use crate::storage::MemoryStorage;
+
+    fn address(last: u8) -> Address {
+        format!("0x{:040x}", last).parse().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_mark_swept() {
+        let (deposit_address, treasury) = (address(1), address(2));
+        let storage = MemoryStorage::new();
+        let deposit = Payment::new(PaymentRequest::eth(
+            Decimal::ONE,
+            deposit_address.clone(),
+            12,
+        ));
+        let other = Payment::new(PaymentRequest::eth(Decimal::ONE, address(3), 12));
+        storage.save_payment(&deposit).await.unwrap();
+        storage.save_payment(&other).await.unwrap();
+
+        let mut sweep = Sweep {
+            deposit_address,
+            treasury_address: treasury.clone(),
+            tx_hash: "0xsweep".to_string(),
+            currency: Currency::Native,
+            amount: Decimal::new(99, 2),
+            block_number: 100,
+            confirmations: 1,
+            timestamp: None,
+        };
+        let monitor = SweepMonitor::new(BscScanClient::new("test-key").unwrap(), treasury);
+        monitor
+            .mark_swept(&storage, &[sweep.clone()])
+            .await
+            .unwrap();
+
+        sweep.confirmations = 12;
+        monitor
+            .mark_swept(&storage, &[sweep.clone()])
+            .await
+            .unwrap();
+
+        let stored = storage.get_payment(deposit.id).await.unwrap().unwrap();
+        assert_eq!(stored.sweeps(), vec![sweep]);
+        assert!(stored.is_swept(12));
+
+        let stored = storage.get_payment(other.id).await.unwrap().unwrap();
+        assert!(stored.sweeps().is_empty());
+        assert_eq!(confirmations_at(100, 101), 0);
+    }
+}
@@ -0,0 +1,343 @@
+//! Builders for fake explorer records
+
+use crate::client::types::{InternalTransaction, TokenTransfer, Transaction};
+use crate::primitives::{Address, TokenAmount, TxHash, U256};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Default sender of fake transfers
+pub const FAKE_SENDER: &str = "0x00000000000000000000000000000000000000a1";
+
+/// Default recipient of fake transfers
+pub const FAKE_RECIPIENT: &str = "0x742d35cc6634c0532925a3b844bc9e7595f0beb0";
+
+/// Default token contract of fake token transfers (USDT on Ethereum)
+pub const FAKE_TOKEN: &str = "0xdac17f958d2ee523a2206206994597c13d831ec7";
+
+static NEXT_HASH: AtomicU64 = AtomicU64::new(1);
+
+/// A transaction hash that is unique within the process
+pub fn fake_tx_hash() -> TxHash {
+    let id = NEXT_HASH.fetch_add(1, Ordering::Relaxed);
+    format!("0x{:064x}", id).parse().expect("valid hash")
+}
+
+fn address(value: &str) -> Address {
+    value
+        .parse()
+        .unwrap_or_else(|_| panic!("invalid address: {}", value))
+}
+
+fn raw_amount(amount: &str, decimals: u8) -> String {
+    let amount: Decimal = amount
+        .parse()
+        .unwrap_or_else(|_| panic!("invalid amount: {}", amount));
+    TokenAmount::from_decimal(amount, decimals)
+        .unwrap_or_else(|e| panic!("{}", e))
+        .raw()
+        .to_string()
+}
+
+/// Start building a native transaction from [`FAKE_SENDER`] to [`FAKE_RECIPIENT`]
+///
+/// # Example
+/// ```
+/// use cryptopay::testing::fake_transaction;
+///
+/// let tx = fake_transaction().value_eth("0.1").confirmations(12).build();
+/// assert_eq!(tx.value, "100000000000000000");
+/// assert!(tx.is_successful());
+/// ```
+pub fn fake_transaction() -> TransactionBuilder {
+    TransactionBuilder {
+        tx: Transaction {
+            block_number: 1_000,
+            time_stamp: Some(Utc::now()),
+            hash: fake_tx_hash(),
+            nonce: 0,
+            block_hash: String::new(),
+            transaction_index: 0,
+            from: address(FAKE_SENDER),
+            to: Some(address(FAKE_RECIPIENT)),
+            value: "0".to_string(),
+            gas: 21_000,
+            gas_price: U256::from(1_000_000_000u64),
+            is_error: "0".to_string(),
+            txreceipt_status: "1".to_string(),
+            input: "0x".to_string(),
+            contract_address: String::new(),
+            cumulative_gas_used: 21_000,
+            gas_used: 21_000,
+            confirmations: 1,
+            method_id: String::new(),
+            function_name: String::new(),
+            extra: Default::default(),
+        },
+    }
+}
+
+/// Start building an internal transaction from [`FAKE_SENDER`] to [`FAKE_RECIPIENT`]
+pub fn fake_internal_transaction() -> InternalTransactionBuilder {
+    InternalTransactionBuilder {
+        tx: InternalTransaction {
+            block_number: 1_000,
+            time_stamp: Some(Utc::now()),
+            hash: fake_tx_hash(),
+            from: address(FAKE_SENDER),
+            to: Some(address(FAKE_RECIPIENT)),
+            value: "0".to_string(),
+            contract_address: String::new(),
+            input: String::new(),
+            tx_type: "call".to_string(),
+            gas: 2_300,
+            gas_used: 0,
+            trace_id: "0".to_string(),
+            is_error: "0".to_string(),
+            err_code: String::new(),
+            extra: Default::default(),
+        },
+    }
+}
+
+/// Start building a [`FAKE_TOKEN`] (6 decimals) transfer from [`FAKE_SENDER`] to [`FAKE_RECIPIENT`]
+pub fn fake_token_transfer() -> TokenTransferBuilder {
+    TokenTransferBuilder {
+        transfer: TokenTransfer {
+            block_number: 1_000,
+            time_stamp: Some(Utc::now()),
+            hash: fake_tx_hash(),
+            nonce: 0,
+            block_hash: String::new(),
+            from: address(FAKE_SENDER),
+            contract_address: address(FAKE_TOKEN),
+            to: address(FAKE_RECIPIENT),
+            value: "0".to_string(),
+            token_name: "Tether USD".to_string(),
+            token_symbol: "USDT".to_string(),
+            token_decimal: "6".to_string(),
+            transaction_index: 0,
+            gas: 65_000,
+            gas_price: U256::from(1_000_000_000u64),
+            gas_used: 65_000,
+            cumulative_gas_used: 65_000,
+            input: "deprecated".to_string(),
+            confirmations: 1,
+            extra: Default::default(),
+        },
+    }
+}
+
+/// Builder for fake [`Transaction`]s, see [`fake_transaction`]
+///
+/// Setters panic on malformed addresses, hashes, and amounts.
+#[derive(Debug, Clone)]
+pub struct TransactionBuilder {
+    tx: Transaction,
+}
+
+impl TransactionBuilder {
+    /// Set the transaction hash
+    pub fn hash(mut self, hash: &str) -> Self {
+        self.tx.hash = hash.parse().expect("valid transaction hash");
+        self
+    }
+
+    /// Set the sender
+    pub fn from(mut self, from: &str) -> Self {
+        self.tx.from = address(from);
+        self
+    }
+
+    /// Set the recipient
+    pub fn to(mut self, to: &str) -> Self {
+        self.tx.to = Some(address(to));
+        self
+    }
+
+    /// Set the value in the native currency, e.g. `"0.1"`
+    pub fn value_eth(mut self, value: &str) -> Self {
+        self.tx.value = raw_amount(value, 18);
+        self
+    }
+
+    /// Set the value in wei
+    pub fn value_wei(mut self, wei: u128) -> Self {
+        self.tx.value = wei.to_string();
+        self
+    }
+
+    /// Set the block number
+    pub fn block(mut self, block_number: u64) -> Self {
+        self.tx.block_number = block_number;
+        self
+    }
+
+    /// Set the confirmations reported by the explorer
+    pub fn confirmations(mut self, confirmations: u64) -> Self {
+        self.tx.confirmations = confirmations;
+        self
+    }
+
+    /// Set the block timestamp
+    pub fn timestamp(mut self, timestamp: DateTime<Utc>) -> Self {
+        self.tx.time_stamp = Some(timestamp);
+        self
+    }
+
+    /// Set gas used and gas price (in wei)
+    pub fn gas(mut self, gas_used: u64, gas_price_wei: u64) -> Self {
+        self.tx.gas_used = gas_used;
+        self.tx.gas_price = U256::from(gas_price_wei);
+        self
+    }
+
+    /// Set the call data
+    pub fn input(mut self, input: &str) -> Self {
+        self.tx.input = input.to_string();
+        self
+    }
+
+    /// Mark the transaction as reverted
+    pub fn failed(mut self) -> Self {
+        self.tx.is_error = "1".to_string();
+        self.tx.txreceipt_status = "0".to_string();
+        self
+    }
+
+    /// Finish the transaction
+    pub fn build(self) -> Transaction {
+        self.tx
+    }
+}
+
+impl From<TransactionBuilder> for Transaction {
+    fn from(builder: TransactionBuilder) -> Self {
+        builder.build()
+    }
+}
+
+/// Builder for fake [`InternalTransaction`]s, see [`fake_internal_transaction`]
+#[derive(Debug, Clone)]
+pub struct InternalTransactionBuilder {
+    tx: InternalTransaction,
+}
+
+impl InternalTransactionBuilder {
+    /// Set the hash of the parent transaction
+    pub fn hash(mut self, hash: &str) -> Self {
+        self.tx.hash = hash.parse().expect("valid transaction hash");
+        self
+    }
+
+    /// Set the sending contract
+    pub fn from(mut self, from: &str) -> Self {
+        self.tx.from = address(from);
+        self
+    }
+
+    /// Set the recipient
+    pub fn to(mut self, to: &str) -> Self {
+        self.tx.to = Some(address(to));
+        self
+    }
+
+    /// Set the value in the native currency, e.g. `"0.1"`
+    pub fn value_eth(mut self, value: &str) -> Self {
+        self.tx.value = raw_amount(value, 18);
+        self
+    }
+
+    /// Set the block number
+    pub fn block(mut self, block_number: u64) -> Self {
+        self.tx.block_number = block_number;
+        self
+    }
+
+    /// Mark the call as reverted
+    pub fn failed(mut self) -> Self {
+        self.tx.is_error = "1".to_string();
+        self
+    }
+
+    /// Finish the internal transaction
+    pub fn build(self) -> InternalTransaction {
+        self.tx
+    }
+}
+
+impl From<InternalTransactionBuilder> for InternalTransaction {
+    fn from(builder: InternalTransactionBuilder) -> Self {
+        builder.build()
+    }
+}
+
+/// Builder for fake [`TokenTransfer`]s, see [`fake_token_transfer`]
+#[derive(Debug, Clone)]
+pub struct TokenTransferBuilder {
+    transfer: TokenTransfer,
+}
+
+impl TokenTransferBuilder {
+    /// Set the transaction hash
+    pub fn hash(mut self, hash: &str) -> Self {
+        self.transfer.hash = hash.parse().expect("valid transaction hash");
+        self
+    }
+
+    /// Set the sender
+    pub fn from(mut self, from: &str) -> Self {
+        self.transfer.from = address(from);
+        self
+    }
+
+    /// Set the recipient
+    pub fn to(mut self, to: &str) -> Self {
+        self.transfer.to = address(to);
+        self
+    }
+
+    /// Set the token contract, symbol, and decimals
+    pub fn token(mut self, contract_address: &str, symbol: &str, decimals: u8) -> Self {
+        self.transfer.contract_address = address(contract_address);
+        self.transfer.token_name = symbol.to_string();
+        self.transfer.token_symbol = symbol.to_string();
+        self.transfer.token_decimal = decimals.to_string();
+        self
+    }
+
+    /// Set the amount in token units, e.g. `"25.5"`, using the token's decimals
+    pub fn amount(mut self, amount: &str) -> Self {
+        self.transfer.value = raw_amount(amount, self.transfer.decimals());
+        self
+    }
+
+    /// Set the block number
+    pub fn block(mut self, block_number: u64) -> Self {
+        self.transfer.block_number = block_number;
+        self
+    }
+
+    /// Set the confirmations reported by the explorer
+    pub fn confirmations(mut self, confirmations: u64) -> Self {
+        self.transfer.confirmations = confirmations;
+        self
+    }
+
+    /// Set the block timestamp
+    pub fn timestamp(mut self, timestamp: DateTime<Utc>) -> Self {
+        self.transfer.time_stamp = Some(timestamp);
+        self
+    }
+
+    /// Finish the token transfer
+    pub fn build(self) -> TokenTransfer {
+        self.transfer
+    }
+}
+
+impl From<TokenTransferBuilder> for TokenTransfer {
+    fn from(builder: TokenTransferBuilder) -> Self {
+        builder.build()
+    }
+}
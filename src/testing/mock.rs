@@ -0,0 +1,327 @@
+//! In-process mock of the Etherscan API
+
+use crate::client::types::{InternalTransaction, TokenTransfer, Transaction};
+use crate::client::BscScanClient;
+use crate::config::ClientConfig;
+use crate::primitives::Address;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use wiremock::matchers::method;
+use wiremock::{Mock, MockServer, Request, Respond, ResponseTemplate};
+
+/// Programmable Etherscan server for tests
+///
+/// Serves `account/txlist`, `account/txlistinternal`, `account/tokentx`,
+/// `account/balance`, `block/getblocknobytime`, and `proxy/eth_blockNumber`
+/// from in-memory state, honouring the address, contract, block range, sort,
+/// and paging parameters. Block lookups by time go by the timestamps of the
+/// stored history. Any other call can be answered with [`respond`](Self::respond).
+/// State can be changed while clients are using the server, e.g. to let a
+/// payment arrive between two monitor polls.
+///
+/// # Example
+/// ```
+/// use cryptopay::testing::{fake_transaction, MockEtherscan, FAKE_RECIPIENT};
+/// use cryptopay::{PaymentRequest, PaymentVerifier, VerificationResult};
+///
+/// # tokio_test::block_on(async {
+/// let mock = MockEtherscan::start().await;
+/// mock.add_transaction(fake_transaction().value_eth("0.1").confirmations(12));
+///
+/// let verifier = PaymentVerifier::new(mock.client()).scan_internal_transactions(false);
+/// let request = PaymentRequest::eth("0.1".parse().unwrap(), FAKE_RECIPIENT.parse().unwrap(), 12);
+/// let result = verifier.verify_payment(&request).await.unwrap();
+/// assert!(matches!(result, VerificationResult::Confirmed { .. }));
+/// # });
+/// ```
+pub struct MockEtherscan {
+    server: MockServer,
+    state: Arc<Mutex<MockState>>,
+}
+
+#[derive(Default)]
+struct MockState {
+    block_number: u64,
+    transactions: Vec<Transaction>,
+    internal_transactions: Vec<InternalTransaction>,
+    token_transfers: Vec<TokenTransfer>,
+    balances: HashMap<Address, u128>,
+    overrides: HashMap<(String, String), Value>,
+    requests: usize,
+}
+
+impl MockEtherscan {
+    /// Start a mock server on a random local port
+    pub async fn start() -> Self {
+        let server = MockServer::start().await;
+        let state = Arc::new(Mutex::new(MockState {
+            block_number: 1_000,
+            ..MockState::default()
+        }));
+
+        Mock::given(method("GET"))
+            .respond_with(Responder {
+                state: state.clone(),
+            })
+            .mount(&server)
+            .await;
+
+        Self { server, state }
+    }
+
+    /// Base URL of the server
+    pub fn uri(&self) -> String {
+        self.server.uri()
+    }
+
+    /// Client configuration pointing at the server, without caching or rate limiting
+    pub fn config(&self) -> ClientConfig {
+        ClientConfig::builder()
+            .api_key("mock-key")
+            .base_url(self.uri())
+            .rate_limit(10_000)
+            .cache_ttl(0)
+            .block_number_ttl(0)
+            .build()
+            .expect("valid mock config")
+    }
+
+    /// Client querying the server
+    pub fn client(&self) -> BscScanClient {
+        BscScanClient::with_config(self.config()).expect("valid mock client")
+    }
+
+    /// Add a transaction to the history of its sender and recipient
+    pub fn add_transaction(&self, tx: impl Into<Transaction>) {
+        self.state().transactions.push(tx.into());
+    }
+
+    /// Add an internal transaction to the history of its sender and recipient
+    pub fn add_internal_transaction(&self, tx: impl Into<InternalTransaction>) {
+        self.state().internal_transactions.push(tx.into());
+    }
+
+    /// Add a token transfer to the history of its sender and recipient
+    pub fn add_token_transfer(&self, transfer: impl Into<TokenTransfer>) {
+        self.state().token_transfers.push(transfer.into());
+    }
+
+    /// Remove all transactions, internal transactions, and token transfers
+    pub fn clear_history(&self) {
+        let mut state = self.state();
+        state.transactions.clear();
+        state.internal_transactions.clear();
+        state.token_transfers.clear();
+    }
+
+    /// Set the native balance of an address (in wei)
+    pub fn set_balance(&self, address: &str, wei: u128) {
+        let address = address.parse().expect("valid address");
+        self.state().balances.insert(address, wei);
+    }
+
+    /// Set the latest block number (defaults to 1000)
+    pub fn set_block_number(&self, block_number: u64) {
+        self.state().block_number = block_number;
+    }
+
+    /// Answer `module`/`action` with `result`, overriding the built-in handling
+    ///
+    /// `proxy` results are wrapped in a JSON-RPC envelope, everything else
+    /// in an explorer `{"status": "1", "message": "OK"}` envelope.
+    pub fn respond(&self, module: &str, action: &str, result: impl Serialize) {
+        let result = serde_json::to_value(result).expect("serializable result");
+        self.state()
+            .overrides
+            .insert((module.to_string(), action.to_string()), result);
+    }
+
+    /// Number of requests received so far
+    pub fn request_count(&self) -> usize {
+        self.state().requests
+    }
+
+    fn state(&self) -> MutexGuard<'_, MockState> {
+        lock(&self.state)
+    }
+}
+
+fn lock(state: &Mutex<MockState>) -> MutexGuard<'_, MockState> {
+    state
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+struct Responder {
+    state: Arc<Mutex<MockState>>,
+}
+
+impl Respond for Responder {
+    fn respond(&self, request: &Request) -> ResponseTemplate {
+        let params: HashMap<String, String> = request.url.query_pairs().into_owned().collect();
+        let param = |name: &str| params.get(name).map(String::as_str).unwrap_or_default();
+        let (module, action) = (param("module"), param("action"));
+
+        let mut state = lock(&self.state);
+        state.requests += 1;
+
+        if let Some(result) = state
+            .overrides
+            .get(&(module.to_string(), action.to_string()))
+        {
+            return ok(module, result.clone());
+        }
+
+        let body = match (module, action) {
+            ("account", "txlist") => list(&state.transactions, &params, |tx| {
+                (tx.block_number, &tx.from, tx.to.as_ref())
+            }),
+            ("account", "txlistinternal") => list(&state.internal_transactions, &params, |tx| {
+                (tx.block_number, &tx.from, tx.to.as_ref())
+            }),
+            ("account", "tokentx") => {
+                let contract = param("contractaddress").parse::<Address>().ok();
+                let transfers: Vec<&TokenTransfer> = state
+                    .token_transfers
+                    .iter()
+                    .filter(|transfer| {
+                        contract
+                            .as_ref()
+                            .is_none_or(|c| *c == transfer.contract_address)
+                    })
+                    .collect();
+                list(&transfers, &params, |transfer| {
+                    (transfer.block_number, &transfer.from, Some(&transfer.to))
+                })
+            }
+            ("account", "balance") => {
+                let balance = param("address")
+                    .parse::<Address>()
+                    .ok()
+                    .and_then(|address| state.balances.get(&address).copied())
+                    .unwrap_or(0);
+                explorer("1", "OK", json!(balance.to_string()))
+            }
+            ("block", "getblocknobytime") => {
+                let block = block_by_time(&state, param("timestamp"), param("closest"));
+                explorer("1", "OK", json!(block.to_string()))
+            }
+            ("proxy", "eth_blockNumber") => rpc(json!(format!("0x{:x}", state.block_number))),
+            ("proxy", _) => rpc(Value::Null),
+            _ => explorer("0", "NOTOK", json!("Error! Unsupported by MockEtherscan")),
+        };
+
+        ResponseTemplate::new(200).set_body_json(body)
+    }
+}
+
+/// Block closest to a unix timestamp, judged by the blocks of the stored history
+fn block_by_time(state: &MockState, timestamp: &str, closest: &str) -> u64 {
+    let Some(time) = timestamp
+        .parse()
+        .ok()
+        .and_then(|secs| DateTime::<Utc>::from_timestamp(secs, 0))
+    else {
+        return 0;
+    };
+    let blocks = state
+        .transactions
+        .iter()
+        .map(|tx| (tx.block_number, tx.time_stamp))
+        .chain(
+            state
+                .internal_transactions
+                .iter()
+                .map(|tx| (tx.block_number, tx.time_stamp)),
+        )
+        .chain(
+            state
+                .token_transfers
+                .iter()
+                .map(|transfer| (transfer.block_number, transfer.time_stamp)),
+        )
+        .filter_map(|(block, time_stamp)| Some((block, time_stamp?)));
+
+    if closest == "after" {
+        blocks
+            .filter(|(_, time_stamp)| *time_stamp >= time)
+            .map(|(block, _)| block)
+            .min()
+            .unwrap_or(state.block_number)
+    } else {
+        blocks
+            .filter(|(_, time_stamp)| *time_stamp <= time)
+            .map(|(block, _)| block)
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// Filter, sort, and page a history list the way the explorer does
+fn list<T, F>(entries: &[T], params: &HashMap<String, String>, index: F) -> Value
+where
+    T: Serialize,
+    F: Fn(&T) -> (u64, &Address, Option<&Address>),
+{
+    let number = |name: &str, default: u64| {
+        params
+            .get(name)
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(default)
+    };
+    let address = params
+        .get("address")
+        .and_then(|value| value.parse::<Address>().ok());
+    let (start, end) = (number("startblock", 0), number("endblock", u64::MAX));
+
+    let mut matching: Vec<&T> = entries
+        .iter()
+        .filter(|entry| {
+            let (block, from, to) = index(entry);
+            let involved = address
+                .as_ref()
+                .is_none_or(|address| address == from || Some(address) == to);
+            involved && (start..=end).contains(&block)
+        })
+        .collect();
+    matching.sort_by_key(|entry| index(entry).0);
+    if params.get("sort").map(String::as_str) == Some("desc") {
+        matching.reverse();
+    }
+
+    let offset = number("offset", 0) as usize;
+    if offset > 0 {
+        let page = number("page", 1).max(1) as usize;
+        matching = matching
+            .into_iter()
+            .skip((page - 1) * offset)
+            .take(offset)
+            .collect();
+    }
+
+    if matching.is_empty() {
+        explorer("0", "No transactions found", json!([]))
+    } else {
+        explorer("1", "OK", json!(matching))
+    }
+}
+
+fn ok(module: &str, result: Value) -> ResponseTemplate {
+    let body = if module == "proxy" {
+        rpc(result)
+    } else {
+        explorer("1", "OK", result)
+    };
+    ResponseTemplate::new(200).set_body_json(body)
+}
+
+fn explorer(status: &str, message: &str, result: Value) -> Value {
+    json!({ "status": status, "message": message, "result": result })
+}
+
+fn rpc(result: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": 1, "result": result })
+}
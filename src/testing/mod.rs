@@ -0,0 +1,87 @@
+//! Testing utilities (`test-utils` feature)
+//!
+//! Lets applications unit-test their payment flows without hitting
+//! Etherscan: [`MockEtherscan`] is a local HTTP server answering explorer
+//! calls from programmable in-memory state, and the `fake_*` builders create
+//! transactions and transfers to put into it.
+//!
+//! ```toml
+//! [dev-dependencies]
+//! cryptopay = { version = "0.1", features = ["test-utils"] }
+//! ```
+
+mod fixtures;
+mod mock;
+
+pub use fixtures::{
+    fake_internal_transaction, fake_token_transfer, fake_transaction, fake_tx_hash,
+    InternalTransactionBuilder, TokenTransferBuilder, TransactionBuilder, FAKE_RECIPIENT,
+    FAKE_SENDER, FAKE_TOKEN,
+};
+pub use mock::MockEtherscan;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::endpoints::AccountEndpoints;
+    use crate::payment::{Currency, PaymentRequest, PaymentVerifier, VerificationResult};
+    use rust_decimal::Decimal;
+
+    #[tokio::test]
+    async fn test_mock_serves_filtered_history() {
+        let mock = MockEtherscan::start().await;
+        let other = "0x00000000000000000000000000000000000000b2";
+        mock.add_transaction(fake_transaction().value_eth("1").block(10));
+        mock.add_transaction(fake_transaction().value_eth("2").block(30));
+        mock.add_transaction(fake_transaction().value_eth("3").block(20));
+        mock.add_transaction(fake_transaction().to(other).block(40));
+
+        let client = mock.client();
+        let recipient = FAKE_RECIPIENT.parse().unwrap();
+        let txs = client
+            .get_transactions(&recipient, 0, 99_999_999, 1, 2, "desc")
+            .await
+            .unwrap();
+        let blocks: Vec<u64> = txs.iter().map(|tx| tx.block_number).collect();
+        assert_eq!(blocks, vec![30, 20]);
+        assert_eq!(txs[0].value_native(), Decimal::from(2));
+
+        let txs = client
+            .get_transactions(&other.parse().unwrap(), 0, 35, 1, 100, "asc")
+            .await
+            .unwrap();
+        assert!(txs.is_empty());
+
+        mock.set_balance(FAKE_RECIPIENT, 5);
+        assert_eq!(client.get_balance(&recipient).await.unwrap().wei, "5");
+        assert_eq!(mock.request_count(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_verify_token_payment_against_mock() {
+        let mock = MockEtherscan::start().await;
+        mock.add_token_transfer(fake_token_transfer().amount("25").confirmations(3));
+
+        let request = PaymentRequest::token(
+            Decimal::from(25),
+            FAKE_TOKEN.parse().unwrap(),
+            6,
+            FAKE_RECIPIENT.parse().unwrap(),
+            12,
+        );
+        assert_eq!(
+            request.currency,
+            Currency::erc20(FAKE_TOKEN.parse().unwrap(), 6)
+        );
+
+        let verifier = PaymentVerifier::new(mock.client());
+        let result = verifier.verify_payment(&request).await.unwrap();
+        assert!(matches!(
+            result,
+            VerificationResult::Pending {
+                confirmations: 3,
+                ..
+            }
+        ));
+    }
+}
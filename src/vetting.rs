@@ -0,0 +1,318 @@
+//! Risk checks for token contracts
+//!
+//! Payment requests name the token contract they accept, so a typo or a
+//! look-alike token can make a gateway accept worthless transfers.
+//! [`TokenVetting`] checks a contract before it is used: that it is a
+//! contract at all, answers the ERC20 `decimals()` call, has verified source,
+//! is not brand new, and actually sees transfers.
+
+use crate::client::{BscScanClient, ContractEndpoints, TokenEndpoints, TokenTransfer};
+use crate::error::{Error, Result};
+use crate::payment::{Currency, PaymentRequest};
+use crate::primitives::Address;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::Duration;
+
+/// Reason a token contract looks suspicious
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RiskFinding {
+    /// No code is deployed at the address
+    NotAContract,
+    /// The contract doesn't answer `decimals()` like an ERC20 token
+    NonStandard,
+    /// The source code isn't verified on the explorer
+    UnverifiedSource,
+    /// The contract was deployed recently
+    RecentlyCreated {
+        /// Creation time
+        created_at: DateTime<Utc>,
+    },
+    /// Fewer transfers than expected of an established token
+    LowActivity {
+        /// Transfers found (up to the configured minimum)
+        transfers: usize,
+    },
+}
+
+impl fmt::Display for RiskFinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RiskFinding::NotAContract => f.write_str("no contract at the address"),
+            RiskFinding::NonStandard => f.write_str("not a standard ERC20 token"),
+            RiskFinding::UnverifiedSource => f.write_str("source code not verified"),
+            RiskFinding::RecentlyCreated { created_at } => {
+                write!(f, "created recently ({})", created_at.to_rfc3339())
+            }
+            RiskFinding::LowActivity { transfers } => {
+                write!(f, "only {} transfers", transfers)
+            }
+        }
+    }
+}
+
+/// Result of vetting a token contract
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenRisk {
+    /// Vetted contract
+    pub contract: Address,
+    /// Whether code is deployed at the address
+    pub is_contract: bool,
+    /// Whether the source is verified on the explorer
+    pub verified: bool,
+    /// Contract name from the verified source
+    pub contract_name: Option<String>,
+    /// Creation time, if the explorer reports it
+    pub created_at: Option<DateTime<Utc>>,
+    /// Transfers found, up to the configured minimum
+    pub recent_transfers: usize,
+    /// Everything that looked suspicious; empty if the token passed
+    pub findings: Vec<RiskFinding>,
+}
+
+impl TokenRisk {
+    /// Check whether the token passed every check
+    pub fn passed(&self) -> bool {
+        self.findings.is_empty()
+    }
+}
+
+/// Vets token contracts before payments in them are accepted
+///
+/// # Example
+/// ```no_run
+/// # use cryptopay::*;
+/// # use cryptopay::vetting::TokenVetting;
+/// # use std::time::Duration;
+/// # async fn example(client: BscScanClient) -> Result<()> {
+/// let vetting = TokenVetting::new(client).min_age(Duration::from_secs(30 * 86400));
+///
+/// let risk = vetting.check(&"0xdac17f958d2ee523a2206206994597c13d831ec7".parse()?).await?;
+/// for finding in &risk.findings {
+///     println!("{}: {}", risk.contract, finding);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct TokenVetting {
+    client: BscScanClient,
+    min_age: Duration,
+    min_transfers: usize,
+    require_verified: bool,
+}
+
+impl TokenVetting {
+    /// Create a vetting with the default thresholds
+    pub fn new(client: BscScanClient) -> Self {
+        Self {
+            client,
+            min_age: Duration::from_secs(7 * 24 * 60 * 60),
+            min_transfers: 10,
+            require_verified: true,
+        }
+    }
+
+    /// Flag contracts younger than this (default: 7 days)
+    pub fn min_age(mut self, age: Duration) -> Self {
+        self.min_age = age;
+        self
+    }
+
+    /// Flag tokens with fewer transfers than this (default: 10; 0 disables the check)
+    pub fn min_transfers(mut self, transfers: usize) -> Self {
+        self.min_transfers = transfers;
+        self
+    }
+
+    /// Flag contracts without verified source (default: true)
+    pub fn require_verified(mut self, require: bool) -> Self {
+        self.require_verified = require;
+        self
+    }
+
+    /// Vet a token contract
+    ///
+    /// Findings are part of the report; errors are only returned if the
+    /// explorer can't be queried.
+    pub async fn check(&self, contract: &Address) -> Result<TokenRisk> {
+        let mut risk = TokenRisk {
+            contract: contract.clone(),
+            is_contract: self.client.is_contract(contract).await?,
+            verified: false,
+            contract_name: None,
+            created_at: None,
+            recent_transfers: 0,
+            findings: Vec::new(),
+        };
+        if !risk.is_contract {
+            // Nothing else to learn about an account without code
+            risk.findings.push(RiskFinding::NotAContract);
+            return Ok(risk);
+        }
+
+        if self.client.get_erc20_decimals(contract).await.is_err() {
+            risk.findings.push(RiskFinding::NonStandard);
+        }
+
+        let source = self.client.get_contract_source(contract).await?;
+        risk.verified = source.is_verified();
+        if risk.verified {
+            risk.contract_name = Some(source.contract_name).filter(|name| !name.is_empty());
+        } else if self.require_verified {
+            risk.findings.push(RiskFinding::UnverifiedSource);
+        }
+
+        risk.created_at = self.client.get_contract_creation(contract).await?.timestamp;
+        if let Some(created_at) = risk.created_at {
+            let min_age = chrono::Duration::from_std(self.min_age).unwrap_or(chrono::Duration::MAX);
+            if Utc::now().signed_duration_since(created_at) < min_age {
+                risk.findings
+                    .push(RiskFinding::RecentlyCreated { created_at });
+            }
+        }
+
+        if self.min_transfers > 0 {
+            risk.recent_transfers = self.recent_transfers(contract).await?;
+            if risk.recent_transfers < self.min_transfers {
+                risk.findings.push(RiskFinding::LowActivity {
+                    transfers: risk.recent_transfers,
+                });
+            }
+        }
+
+        Ok(risk)
+    }
+
+    /// Vet every token a payment request accepts
+    ///
+    /// Fails with [`Error::InvalidConfig`] naming the first token with findings.
+    pub async fn check_request(&self, request: &PaymentRequest) -> Result<()> {
+        let currencies = std::iter::once(&request.currency)
+            .chain(request.alternatives.iter().map(|option| &option.currency));
+        for currency in currencies {
+            let Currency::ERC20 {
+                contract_address, ..
+            } = currency
+            else {
+                continue;
+            };
+            let risk = self.check(contract_address).await?;
+            if !risk.passed() {
+                let findings: Vec<String> = risk.findings.iter().map(ToString::to_string).collect();
+                return Err(Error::InvalidConfig(format!(
+                    "token {} failed vetting: {}",
+                    risk.contract,
+                    findings.join(", ")
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Latest transfers of the token, up to `min_transfers`
+    async fn recent_transfers(&self, contract: &Address) -> Result<usize> {
+        let offset = self.min_transfers.to_string();
+        let params = [
+            ("contractaddress", contract.as_str()),
+            ("page", "1"),
+            ("offset", offset.as_str()),
+            ("sort", "desc"),
+        ];
+        let transfers: Vec<TokenTransfer> =
+            self.client.request("account", "tokentx", &params).await?;
+        Ok(transfers.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ClientConfig;
+    use wiremock::matchers::query_param;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn ok(result: serde_json::Value) -> ResponseTemplate {
+        ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "status": "1", "message": "OK", "result": result
+        }))
+    }
+
+    fn rpc(result: &str) -> ResponseTemplate {
+        ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "jsonrpc": "2.0", "id": 1, "result": result
+        }))
+    }
+
+    #[tokio::test]
+    async fn test_flags_new_unverified_token() {
+        let server = MockServer::start().await;
+        Mock::given(query_param("action", "eth_getCode"))
+            .respond_with(rpc("0x6080"))
+            .mount(&server)
+            .await;
+        Mock::given(query_param("action", "eth_call"))
+            .respond_with(rpc(&format!("0x{:064x}", 18)))
+            .mount(&server)
+            .await;
+        Mock::given(query_param("action", "getsourcecode"))
+            .respond_with(ok(serde_json::json!([{
+                "SourceCode": "",
+                "ABI": "Contract source code not verified",
+                "ContractName": "",
+            }])))
+            .mount(&server)
+            .await;
+        let created_at = Utc::now() - chrono::Duration::days(1);
+        Mock::given(query_param("action", "getcontractcreation"))
+            .respond_with(ok(serde_json::json!([{
+                "contractAddress": "0x6b175474e89094c44da98b954eedeac495271d0f",
+                "contractCreator": "0x742d35cc6634c0532925a3b844bc9e7595f0beb0",
+                "txHash": format!("0x{:064x}", 1),
+                "blockNumber": "19000000",
+                "timestamp": created_at.timestamp().to_string(),
+            }])))
+            .mount(&server)
+            .await;
+        Mock::given(query_param("action", "tokentx"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "status": "0", "message": "No transactions found", "result": []
+            })))
+            .mount(&server)
+            .await;
+
+        let config = ClientConfig::builder()
+            .api_key("key")
+            .base_url(server.uri())
+            .build()
+            .unwrap();
+        let vetting = TokenVetting::new(BscScanClient::with_config(config).unwrap());
+
+        let contract: Address = "0x6b175474e89094c44da98b954eedeac495271d0f"
+            .parse()
+            .unwrap();
+        let risk = vetting.check(&contract).await.unwrap();
+        assert!(risk.is_contract);
+        assert_eq!(
+            risk.findings,
+            vec![
+                RiskFinding::UnverifiedSource,
+                RiskFinding::RecentlyCreated {
+                    created_at: risk.created_at.unwrap()
+                },
+                RiskFinding::LowActivity { transfers: 0 },
+            ]
+        );
+
+        let request = PaymentRequest::new(
+            rust_decimal::Decimal::ONE,
+            Currency::erc20(contract.clone(), 18),
+            "0x742d35cc6634c0532925a3b844bc9e7595f0beb0"
+                .parse()
+                .unwrap(),
+            12,
+        );
+        let error = vetting.check_request(&request).await.unwrap_err();
+        assert!(error.to_string().contains("source code not verified"));
+    }
+}
@@ -0,0 +1,442 @@
+//! Webhook notifications
+//!
+//! [`WebhookQueue`] persists outbound notifications in a [`WebhookStorage`]
+//! before sending them. With a durable storage such as
+//! [`FileStorage`](crate::storage::FileStorage), notifications survive a crash
+//! of the process; [`MemoryStorage`](crate::storage::MemoryStorage) only keeps
+//! them until it is dropped.
+//! Failed deliveries are retried with exponential backoff by their
+//! [`RetryPolicy`] and moved to the dead letters once the retries are used up.
+//!
+//! Notifications can be authenticated with [`sign`] and [`verify`]: the
+//! [`SIGNATURE_HEADER`] carries a timestamp and an HMAC-SHA256 of
+//! `"<timestamp>.<body>"`, so receivers can reject forged and replayed
+//! requests.
+//!
+//! ```text
+//! Cryptopay-Signature: t=<unix seconds>,v1=<hex HMAC-SHA256>
+//! ```
+
+use crate::clock::{Clock, SystemClock};
+use crate::config::Secret;
+use crate::error::{Error, Result};
+use crate::events::{Event, EventBus};
+use crate::retry::RetryPolicy;
+use crate::storage::WebhookStorage;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// HTTP header carrying the signature of a notification
+pub const SIGNATURE_HEADER: &str = "Cryptopay-Signature";
+
+/// Signature header value for `payload`, timestamped now
+///
+/// # Example
+/// ```
+/// use cryptopay::webhook;
+/// use std::time::Duration;
+///
+/// let body = br#"{"status":"Confirmed"}"#;
+/// let header = webhook::sign(body, "whsec_test");
+/// assert!(webhook::verify(&header, body, "whsec_test", Duration::from_secs(300)).is_ok());
+/// assert!(webhook::verify(&header, body, "other", Duration::from_secs(300)).is_err());
+/// ```
+pub fn sign(payload: impl AsRef<[u8]>, secret: impl AsRef<[u8]>) -> String {
+    sign_at(payload, secret, Utc::now())
+}
+
+/// Signature header value for `payload`, timestamped `timestamp`
+pub fn sign_at(
+    payload: impl AsRef<[u8]>,
+    secret: impl AsRef<[u8]>,
+    timestamp: DateTime<Utc>,
+) -> String {
+    let timestamp = timestamp.timestamp();
+    let signature = mac(secret.as_ref(), timestamp, payload.as_ref()).finalize();
+    format!("t={},v1={}", timestamp, hex::encode(signature.into_bytes()))
+}
+
+/// Check a signature header against `payload`
+///
+/// Fails with [`Error::Unauthorized`] if the header is malformed, no `v1`
+/// signature matches, or the timestamp is more than `tolerance` away from
+/// now. The header may carry several `v1` signatures, e.g. while a secret
+/// is rotated; one matching is enough.
+pub fn verify(
+    header: &str,
+    payload: impl AsRef<[u8]>,
+    secret: impl AsRef<[u8]>,
+    tolerance: Duration,
+) -> Result<()> {
+    verify_at(header, payload, secret, tolerance, Utc::now())
+}
+
+/// [`verify`] relative to `now` instead of the current time
+pub fn verify_at(
+    header: &str,
+    payload: impl AsRef<[u8]>,
+    secret: impl AsRef<[u8]>,
+    tolerance: Duration,
+    now: DateTime<Utc>,
+) -> Result<()> {
+    let mut timestamp = None;
+    let mut signatures = Vec::new();
+    for part in header.split(',') {
+        match part.trim().split_once('=') {
+            Some(("t", value)) => timestamp = value.parse::<i64>().ok(),
+            Some(("v1", value)) => signatures.extend(hex::decode(value).ok()),
+            _ => {}
+        }
+    }
+    let timestamp =
+        timestamp.ok_or_else(|| Error::Unauthorized("malformed webhook signature".to_string()))?;
+
+    let age = (now.timestamp() - timestamp).unsigned_abs();
+    if age > tolerance.as_secs() {
+        return Err(Error::Unauthorized(
+            "webhook signature timestamp outside the tolerance".to_string(),
+        ));
+    }
+
+    let mac = mac(secret.as_ref(), timestamp, payload.as_ref());
+    if signatures
+        .iter()
+        .any(|signature| mac.clone().verify_slice(signature).is_ok())
+    {
+        Ok(())
+    } else {
+        Err(Error::Unauthorized(
+            "webhook signature mismatch".to_string(),
+        ))
+    }
+}
+
+fn mac(secret: &[u8], timestamp: i64, payload: &[u8]) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(payload);
+    mac
+}
+
+/// State of a queued notification
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DeliveryStatus {
+    /// Waiting for its first or next attempt
+    Pending,
+    /// Retries used up; kept for inspection and manual replay
+    Dead,
+}
+
+/// Outbound notification in the webhook queue
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebhookDelivery {
+    /// Delivery ID
+    pub id: Uuid,
+    /// URL receiving the `POST`
+    pub url: String,
+    /// JSON body
+    pub payload: serde_json::Value,
+    /// Failed attempts so far
+    pub attempts: u32,
+    /// Earliest time of the next attempt
+    pub next_retry_at: DateTime<Utc>,
+    /// Error of the last failed attempt
+    pub last_error: Option<String>,
+    /// Backoff and number of retries
+    pub retry: RetryPolicy,
+    /// Pending or dead-lettered
+    pub status: DeliveryStatus,
+    /// Time the notification was queued
+    pub created_at: DateTime<Utc>,
+}
+
+/// Persistent queue and delivery worker for webhook notifications
+///
+/// Delivered notifications are removed from the storage. Notifications to
+/// URLs registered with [`sign_with`](Self::sign_with) carry a
+/// [`SIGNATURE_HEADER`].
+///
+/// # Example
+/// ```no_run
+/// # use cryptopay::*;
+/// # use cryptopay::webhook::WebhookQueue;
+/// # use std::sync::Arc;
+/// # use std::time::Duration;
+/// # async fn example() -> Result<()> {
+/// let queue = WebhookQueue::new(Arc::new(MemoryStorage::new()));
+/// queue
+///     .enqueue(
+///         "https://shop.example/hooks",
+///         serde_json::json!({ "status": "Confirmed" }),
+///         RetryPolicy::default(),
+///     )
+///     .await?;
+///
+/// queue.run(Duration::from_secs(1)).await; // deliver until dropped
+/// # Ok(())
+/// # }
+/// ```
+pub struct WebhookQueue {
+    storage: Arc<dyn WebhookStorage>,
+    http: reqwest::Client,
+    clock: Arc<dyn Clock>,
+    secrets: HashMap<String, Secret<String>>,
+    events: Option<EventBus>,
+}
+
+impl WebhookQueue {
+    /// Create a queue persisting to `storage`
+    pub fn new(storage: Arc<dyn WebhookStorage>) -> Self {
+        Self {
+            storage,
+            http: reqwest::Client::new(),
+            clock: Arc::new(SystemClock),
+            secrets: HashMap::new(),
+            events: None,
+        }
+    }
+
+    /// Send notifications with `client`
+    pub fn http_client(mut self, client: reqwest::Client) -> Self {
+        self.http = client;
+        self
+    }
+
+    /// Use `clock` for retry times and between delivery rounds
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Sign notifications to `url` with `secret`, see [`sign`]
+    ///
+    /// Secrets are kept in memory only, never in the queue's storage.
+    pub fn sign_with(mut self, url: impl Into<String>, secret: impl Into<Secret<String>>) -> Self {
+        self.secrets.insert(url.into(), secret.into());
+        self
+    }
+
+    /// Publish delivered notifications to `events`
+    pub fn events(mut self, events: EventBus) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Queue a notification for delivery as soon as possible
+    pub async fn enqueue(
+        &self,
+        url: impl Into<String>,
+        payload: serde_json::Value,
+        retry: RetryPolicy,
+    ) -> Result<WebhookDelivery> {
+        let now = self.clock.now();
+        let delivery = WebhookDelivery {
+            id: Uuid::new_v4(),
+            url: url.into(),
+            payload,
+            attempts: 0,
+            next_retry_at: now,
+            last_error: None,
+            retry,
+            status: DeliveryStatus::Pending,
+            created_at: now,
+        };
+        self.storage.save_webhook(&delivery).await?;
+        Ok(delivery)
+    }
+
+    /// Attempt every notification that is due, returning the number delivered
+    pub async fn deliver_due(&self) -> Result<usize> {
+        let mut delivered = 0;
+        for mut delivery in self.storage.due_webhooks(self.clock.now()).await? {
+            match self.send(&delivery).await {
+                Ok(()) => {
+                    self.storage.delete_webhook(delivery.id).await?;
+                    if let Some(events) = &self.events {
+                        events.publish(Event::WebhookDelivered {
+                            id: delivery.id,
+                            url: delivery.url.clone(),
+                            attempts: delivery.attempts,
+                        });
+                    }
+                    delivered += 1;
+                }
+                Err(error) => {
+                    delivery.attempts += 1;
+                    delivery.last_error = Some(error);
+                    if delivery.attempts > delivery.retry.max_retries {
+                        tracing::warn!(url = %delivery.url, id = %delivery.id, attempts = delivery.attempts, "webhook delivery failed; dead-lettered");
+                        delivery.status = DeliveryStatus::Dead;
+                    } else {
+                        let backoff = delivery.retry.backoff(delivery.attempts - 1);
+                        delivery.next_retry_at = self.clock.now()
+                            + chrono::Duration::from_std(backoff).unwrap_or_default();
+                    }
+                    self.storage.save_webhook(&delivery).await?;
+                }
+            }
+        }
+        Ok(delivered)
+    }
+
+    /// Deliver due notifications every `interval` until the future is dropped
+    pub async fn run(&self, interval: Duration) {
+        loop {
+            if let Err(e) = self.deliver_due().await {
+                tracing::warn!(error = %e, "webhook queue failed");
+            }
+            self.clock.sleep(interval).await;
+        }
+    }
+
+    /// Notifications whose retries are used up
+    pub async fn dead_letters(&self) -> Result<Vec<WebhookDelivery>> {
+        self.storage.dead_webhooks().await
+    }
+
+    /// Queue a dead-lettered notification again with a fresh set of retries
+    pub async fn replay(&self, mut delivery: WebhookDelivery) -> Result<()> {
+        delivery.status = DeliveryStatus::Pending;
+        delivery.attempts = 0;
+        delivery.next_retry_at = self.clock.now();
+        self.storage.save_webhook(&delivery).await
+    }
+
+    /// `POST` a notification, returning the error message on failure
+    async fn send(&self, delivery: &WebhookDelivery) -> std::result::Result<(), String> {
+        let body = serde_json::to_vec(&delivery.payload).map_err(|e| e.to_string())?;
+        let mut request = self
+            .http
+            .post(&delivery.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json");
+        if let Some(secret) = self.secrets.get(&delivery.url) {
+            request = request.header(
+                SIGNATURE_HEADER,
+                sign_at(&body, secret.expose(), self.clock.now()),
+            );
+        }
+        request
+            .body(body)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::storage::{FileStorage, MemoryStorage};
+    use wiremock::matchers::{body_json, header_exists, method};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_retries_then_dead_letters() {
+        let server = MockServer::start().await;
+        let payload = serde_json::json!({ "status": "Confirmed" });
+        Mock::given(method("POST"))
+            .and(body_json(&payload))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let clock = MockClock::new("2024-01-01T00:00:00Z".parse().unwrap());
+        let storage = Arc::new(MemoryStorage::new());
+        let queue = WebhookQueue::new(storage.clone()).clock(Arc::new(clock.clone()));
+        let policy = RetryPolicy::new(1).initial_backoff(Duration::from_secs(10));
+        queue.enqueue(server.uri(), payload, policy).await.unwrap();
+
+        assert_eq!(queue.deliver_due().await.unwrap(), 0);
+        // Not due again before the backoff has passed
+        assert_eq!(queue.deliver_due().await.unwrap(), 0);
+        assert!(queue.dead_letters().await.unwrap().is_empty());
+
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(queue.deliver_due().await.unwrap(), 0);
+        let dead = queue.dead_letters().await.unwrap();
+        assert_eq!(dead.len(), 1);
+        assert_eq!(dead[0].attempts, 2);
+        assert!(dead[0].last_error.is_some());
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        let now: DateTime<Utc> = "2024-01-01T00:00:00Z".parse().unwrap();
+        let body = br#"{"status":"Confirmed"}"#;
+        let tolerance = Duration::from_secs(300);
+        let header = sign_at(body, "secret", now);
+        assert!(header.starts_with("t=1704067200,v1="));
+
+        assert!(verify_at(&header, body, "secret", tolerance, now).is_ok());
+        assert!(verify_at(&header, b"{}", "secret", tolerance, now).is_err());
+        let late = now + chrono::Duration::seconds(301);
+        assert!(matches!(
+            verify_at(&header, body, "secret", tolerance, late),
+            Err(Error::Unauthorized(_))
+        ));
+        assert!(verify_at("v1=00", body, "secret", tolerance, now).is_err());
+
+        // Any of several signatures may match, e.g. during secret rotation
+        let rotated = format!("{},v1={}", sign_at(body, "old", now), &header[16..]);
+        assert!(verify_at(&rotated, body, "secret", tolerance, now).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_delivered_notifications_are_removed() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(header_exists(SIGNATURE_HEADER))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let storage = Arc::new(MemoryStorage::new());
+        let queue = WebhookQueue::new(storage.clone()).sign_with(server.uri(), "secret");
+        queue
+            .enqueue(server.uri(), serde_json::json!({}), RetryPolicy::default())
+            .await
+            .unwrap();
+
+        assert_eq!(queue.deliver_due().await.unwrap(), 1);
+        assert_eq!(queue.deliver_due().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_queue_is_drained_after_a_restart() {
+        let server = MockServer::start().await;
+        let payload = serde_json::json!({ "status": "Confirmed" });
+        Mock::given(method("POST"))
+            .and(body_json(&payload))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let dir = std::env::temp_dir().join(format!("cryptopay-webhooks-{}", Uuid::new_v4()));
+        let queue = WebhookQueue::new(Arc::new(FileStorage::open(&dir).unwrap()));
+        queue
+            .enqueue(server.uri(), payload, RetryPolicy::default())
+            .await
+            .unwrap();
+        drop(queue);
+
+        let queue = WebhookQueue::new(Arc::new(FileStorage::open(&dir).unwrap()));
+        assert_eq!(queue.deliver_due().await.unwrap(), 1);
+        assert_eq!(queue.deliver_due().await.unwrap(), 0);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}